@@ -403,6 +403,85 @@ char*           dc_get_blobdir               (const dc_context_t* context);
  *                    to not mess up with non-delivery-reports or read-receipts.
  *                    0=no limit (default).
  *                    Changes affect future messages only.
+ * - `trusted_mailinglist_domains` = Comma-separated list of domains, e.g. `example.org,example.com`.
+ *                    Mailing lists from these domains or their subdomains
+ *                    are accepted automatically and are not shown as contact requests.
+ * - `auto_accept_known_contact_requests` = 1=accept contact requests from known contacts automatically,
+ *                    0=show them as contact requests as usual (default).
+ *                    Contacts are known if they were added by the user or replied to a known message;
+ *                    first-time senders are always shown as contact requests.
+ * - `delete_mdns_from_server` = 1=delete read receipts and delivery status notifications from the server
+ *                    after they were processed,
+ *                    0=leave them on the server (default).
+ * - `mute_new_mailinglists` = 1=mute newly created mailing lists,
+ *                    so they do not cause notifications and are not unarchived by new messages,
+ *                    0=do not mute new mailing lists (default).
+ * - `imap_connection_count` = Number of IMAP connections used to scan folders that are not watched,
+ *                    1 to 4, default 1. Additional connections are opened only during the scan
+ *                    and may speed up fetching from accounts with many folders.
+ * - `trash_retention_secs` = Minimum time in seconds since deletion of messages whose tombstones are removed
+ *                    when purging the trash; younger tombstones are kept to detect duplicates,
+ *                    default 604800 (one week).
+ * - `known_aliases` = Additional addresses of the user, separated by spaces,
+ *                    e.g. addresses the provider forwards to this account.
+ *                    Messages from or to these addresses are assigned to chats
+ *                    as if they were from or to the configured address.
+ * - `show_delivered_to_alias` = 1=append the address a classic email was delivered to
+ *                    to the sender name shown in the chat, e.g. "Bob (via alias@example.org)",
+ *                    0=do not show the address (default).
+ *                    Useful if mail for several aliases ends up in one mailbox.
+ * - `emit_folder_stats` = 1=emit #DC_EVENT_IMAP_FOLDER_STATS after each scan of an IMAP folder,
+ *                    0=do not emit the event (default).
+ * - `auto_accept_verified` = 1=accept the contact request chat with a contact
+ *                    as soon as the contact becomes verified,
+ *                    0=keep such chats as contact requests (default).
+ * - `smtp_helo`    = Hostname to send in the SMTP EHLO command,
+ *                    e.g. to match the PTR record of the sending host.
+ *                    If unset, the default hostname of the SMTP client is used.
+ * - `adhoc_group_min_origin` = Minimum origin of the sender of a classical email
+ *                    for creating an ad-hoc group, messages from senders with a lower origin
+ *                    are assigned to the 1:1 chat instead.
+ *                    0=create ad-hoc groups for all senders (default).
+ * - `authserv_id` = Comma-separated list of the `authserv-id`s of the own provider
+ *                    in `Authentication-Results` headers, only their verdicts are trusted.
+ *                    Learned from messages sent by the user if unset.
+ * - `prevent_rename_headers` = Comma-separated list of headers marking classic emails
+ *                    as sent automatically; the display name of such messages
+ *                    is shown for the message only and does not rename the contact.
+ *                    Defaults to `X-Auto-Response-Suppress`.
+ * - `disable_adhoc_groups` = 1=assign classic emails with multiple recipients to the 1:1 chat
+ *                    with the sender instead of creating ad-hoc groups,
+ *                    0=create ad-hoc groups (default).
+ * - `catch_all_chat` = 1=assign classic emails sent to unconfigured addresses of the own domain,
+ *                    e.g. when using a catch-all domain, to a single "Catch-All" chat,
+ *                    0=assign them to the chat with the sender (default).
+ *                    Enable `show_delivered_to_alias` to show the original recipient address
+ *                    next to the sender name.
+ * - `max_blob_size_bytes` = maximum size in bytes of a single received attachment, 0=no limit.
+ *                    Larger attachments are not stored and replaced by a text telling that the attachment was too large.
+ *                    Defaults to 104857600 (100 MiB).
+ * - `default_ephemeral_timer` = ephemeral timer in seconds applied to newly created chats, 0=disabled (default).
+ *                    The timer is announced to the chat members with the first outgoing message,
+ *                    timer changes received later still override it.
+ * - `block_remote_content` = 1=remove remote content as images, backgrounds and stylesheets,
+ *                    often used as tracking pixels, from dc_get_msg_html() (default),
+ *                    0=return remote content as is.
+ *                    Changing the option also affects messages received before.
+ *                    Use dc_msg_is_remote_content_blocked() and dc_get_msg_html_with_remote_content()
+ *                    to offer loading the remote content.
+ * - `blob_layout` = DC_BLOB_LAYOUT_FLAT (0) =
+ *                    store all attachments directly in the blob directory (default),
+ *                    DC_BLOB_LAYOUT_PER_CHAT (1) =
+ *                    store attachments of received messages in a subdirectory per chat.
+ *                    Changing the layout does not move existing attachments.
+ * - `auto_continue_key_transfer` = 1=import Autocrypt Setup Messages sent by another device
+ *                    automatically using the code set as `key_transfer_setup_code`,
+ *                    0=the user has to call dc_continue_key_transfer() (default).
+ * - `key_transfer_setup_code` = setup code expected for Autocrypt Setup Messages
+ *                    if `auto_continue_key_transfer` is enabled.
+ *                    Setting a string that is not a setup code fails.
+ *                    The code is removed once a key was imported with it.
+ *                    The key is write-only and not included in backups.
  * - `ui.*`         = All keys prefixed by `ui.` can be used by the user-interfaces for system-specific purposes.
  *                    The prefix should be followed by the system and maybe subsystem,
  *                    e.g. `ui.desktop.foo`, `ui.desktop.linux.bar`, `ui.android.foo`, `ui.dc40.bar`, `ui.bot.simplebot.baz`.
@@ -712,6 +791,24 @@ void            dc_stop_io(dc_context_t* context);
 void            dc_maybe_network             (dc_context_t* context);
 
 
+/**
+ * Measure the roundtrip time to the configured IMAP server.
+ *
+ * A NOOP command is sent over the existing inbox connection,
+ * so IO must be started using dc_start_io().
+ * On success, #DC_EVENT_PING_RESULT is emitted.
+ * If the server does not answer within 30 seconds or the connection fails,
+ * the network is considered to be lost and a reconnect is triggered.
+ *
+ * The function may take a while, so it should be called from a background thread.
+ *
+ * @memberof dc_context_t
+ * @param context The context as created by dc_context_new().
+ * @return Roundtrip time in milliseconds, -1 on errors.
+ */
+int64_t         dc_ping                      (dc_context_t* context);
+
+
 
 /**
  * Save a keypair as the default keys for the user.
@@ -1018,6 +1115,24 @@ uint32_t dc_send_videochat_invitation (dc_context_t* context, uint32_t chat_id);
 int dc_send_webxdc_status_update (dc_context_t* context, uint32_t msg_id, const char* json, const char* descr);
 
 
+/**
+ * Send an ephemeral status update to the other members of a webxdc instance.
+ *
+ * Unlike dc_send_webxdc_status_update(), the update is neither stored on this device
+ * nor by the recipients and is not included in dc_get_webxdc_status_updates().
+ * Recipients are informed by #DC_EVENT_WEBXDC_EPHEMERAL_UPDATE instead.
+ * This is meant for transient state, e.g. cursor positions.
+ * Only the `payload` of the update is sent, the instance must not be a draft.
+ *
+ * @memberof dc_context_t
+ * @param context The context object.
+ * @param msg_id The ID of the message with the webxdc instance.
+ * @param json program-readable data, the actual payload
+ * @return 1=success, 0=error
+ */
+int dc_send_webxdc_ephemeral_update (dc_context_t* context, uint32_t msg_id, const char* json);
+
+
 /**
  * Get webxdc status updates.
  * The status updates may be sent by yourself or by other members using dc_send_webxdc_status_update().
@@ -1225,6 +1340,23 @@ int             dc_get_fresh_msg_cnt         (dc_context_t* context, uint32_t ch
 int             dc_estimate_deletion_cnt    (dc_context_t* context, int from_server, int64_t seconds);
 
 
+/**
+ * Schedule deletion of messages from the server
+ * that are older than allowed by the dc_set_config()-option `delete_server_after`.
+ * Changing `delete_server_after` only affects new messages,
+ * this function can be called afterwards to apply the setting to existing messages as well.
+ *
+ * Only messages that are stored on the device are deleted,
+ * messages that are not fully downloaded are skipped.
+ * While the function is running, #DC_EVENT_SERVER_DELETION_PROGRESS events are emitted.
+ *
+ * @memberof dc_context_t
+ * @param context The context object as returned from dc_context_new().
+ * @return Number of messages scheduled for deletion, -1 on errors.
+ */
+int             dc_schedule_retroactive_server_deletion (dc_context_t* context);
+
+
 /**
  * Returns the message IDs of all _fresh_ messages of any chat.
  * Typically used for implementing notification summaries
@@ -1597,6 +1729,8 @@ int             dc_remove_contact_from_chat  (dc_context_t* context, uint32_t ch
  *
  * Sends out #DC_EVENT_CHAT_MODIFIED and #DC_EVENT_MSGS_CHANGED if a status message was sent.
  *
+ * Fails if the group has an admin other than the user.
+ *
  * @memberof dc_context_t
  * @param chat_id The chat ID to set the name for. Must be a group chat.
  * @param name New name of the group.
@@ -1605,6 +1739,24 @@ int             dc_remove_contact_from_chat  (dc_context_t* context, uint32_t ch
  */
 int             dc_set_chat_name             (dc_context_t* context, uint32_t chat_id, const char* name);
 
+
+/**
+ * Set the welcome message of a group.
+ *
+ * The welcome message is sent as a one-to-one message
+ * to each member added to the group afterwards using dc_add_contact_to_chat() on this device.
+ * Members added by other members do not get the welcome message.
+ *
+ * Sends out #DC_EVENT_CHAT_MODIFIED.
+ *
+ * @memberof dc_context_t
+ * @param context The context object.
+ * @param chat_id The chat ID to set the welcome message for. Must be a group chat.
+ * @param text The welcome message. NULL or an empty string removes the welcome message.
+ * @return 1=success, 0=error
+ */
+int             dc_set_chat_welcome_message  (dc_context_t* context, uint32_t chat_id, const char* text);
+
 /**
  * Set the chat's ephemeral message timer.
  *
@@ -1737,6 +1889,24 @@ char*           dc_get_msg_info              (dc_context_t* context, uint32_t ms
 char*           dc_get_msg_html              (dc_context_t* context, uint32_t msg_id);
 
 
+/**
+ * Get uncut message, if available, including remote content.
+ *
+ * Same as dc_get_msg_html(), however,
+ * remote content is not removed even if the config-option `block_remote_content` is set.
+ * The UI should call this function only after the user requested to load remote content,
+ * see dc_msg_is_remote_content_blocked().
+ *
+ * @memberof dc_context_t
+ * @param context The context object.
+ * @param msg_id The message ID for which the uncut text should be loaded.
+ * @return Uncut text as HTML.
+ *     In case of errors, NULL is returned.
+ *     The result must be released using dc_str_unref().
+ */
+char*           dc_get_msg_html_with_remote_content (dc_context_t* context, uint32_t msg_id);
+
+
 /**
   * Asks the core to start downloading a message fully.
   * This function is typically called when the user hits the "Download" button
@@ -2020,6 +2190,54 @@ void            dc_block_contact             (dc_context_t* context, uint32_t co
 char*           dc_get_contact_encrinfo      (dc_context_t* context, uint32_t contact_id);
 
 
+/**
+ * Create a vCard for a contact.
+ * The vCard contains the display name, the email address
+ * and, if known, the public key of the contact.
+ *
+ * To share the contact, write the vCard to a `.vcf` file
+ * and send it as a message of type #DC_MSG_VCARD.
+ *
+ * @memberof dc_context_t
+ * @param context The context object.
+ * @param contact_id The ID of the contact to create the vCard for.
+ *     If DC_CONTACT_ID_SELF is given, a vCard of the own profile is created.
+ * @return vCard, must be released using dc_str_unref() after usage.
+ *     NULL on errors.
+ */
+char*           dc_make_vcard                (dc_context_t* context, uint32_t contact_id);
+
+
+/**
+ * Create a vCard containing all members of a chat except yourself,
+ * see dc_make_vcard() for details.
+ *
+ * @memberof dc_context_t
+ * @param context The context object.
+ * @param chat_id The ID of the chat to create the vCard for.
+ * @return vCard with one record per member, must be released using dc_str_unref() after usage.
+ *     NULL on errors.
+ */
+char*           dc_make_chat_members_vcard   (dc_context_t* context, uint32_t chat_id);
+
+
+/**
+ * Import contacts from a vCard,
+ * e.g. from the file of a message of type #DC_MSG_VCARD.
+ * The contacts are added as if created by dc_create_contact().
+ * Records without email address are ignored.
+ *
+ * May result in #DC_EVENT_CONTACTS_CHANGED events.
+ *
+ * @memberof dc_context_t
+ * @param context The context object.
+ * @param vcard The vCard, may contain several records.
+ * @return An array containing the IDs of the imported contacts.
+ *     Must be freed using dc_array_unref() when no longer used.
+ */
+dc_array_t*     dc_import_vcard              (dc_context_t* context, const char* vcard);
+
+
 /**
  * Delete a contact. The contact is deleted from the local device. It may happen that this is not
  * possible as the contact is in use. In this case, the contact can be blocked.
@@ -2054,6 +2272,7 @@ dc_contact_t*   dc_get_contact               (dc_context_t* context, uint32_t co
 
 #define         DC_IMEX_EXPORT_SELF_KEYS      1 // param1 is a directory where the keys are written to
 #define         DC_IMEX_IMPORT_SELF_KEYS      2 // param1 is a directory where the keys are searched in and read from
+#define         DC_IMEX_IMPORT_SELF_KEYS_ALLOW_EXPIRED 3 // like DC_IMEX_IMPORT_SELF_KEYS, but expired keys are imported as well
 #define         DC_IMEX_EXPORT_BACKUP        11 // param1 is a directory where the backup is written to, param2 is a passphrase to encrypt the backup
 #define         DC_IMEX_IMPORT_BACKUP        12 // param1 is the file with the backup to import, param2 is the backup's passphrase
 
@@ -2083,6 +2302,12 @@ dc_contact_t*   dc_get_contact               (dc_context_t* context, uint32_t co
  *
  * - **DC_IMEX_IMPORT_SELF_KEYS** (2) - Import private keys found in the directory given as `param1`.
  *   The last imported key is made the default keys unless its name contains the string `legacy`. Public keys are not imported.
+ *   If a key is already expired, it is not imported and the import fails
+ *   unless other keys were imported.
+ *
+ * - **DC_IMEX_IMPORT_SELF_KEYS_ALLOW_EXPIRED** (3) - Like DC_IMEX_IMPORT_SELF_KEYS,
+ *   but expired keys are imported as well.
+ *   This can be offered to the user if importing with DC_IMEX_IMPORT_SELF_KEYS failed.
  *
  * While dc_imex() returns immediately, the started job may take a while,
  * you can stop it using dc_stop_ongoing_process(). During execution of the job,
@@ -2229,6 +2454,25 @@ char*           dc_initiate_key_transfer     (dc_context_t* context);
 int             dc_continue_key_transfer     (dc_context_t* context, uint32_t msg_id, const char* setup_code);
 
 
+/**
+ * Replace the own key by a newly generated one.
+ *
+ * The old key is kept to decrypt existing messages.
+ * Messages sent afterwards advertise the new key to the recipients,
+ * however, contacts that have verified the old key need to verify the new one again.
+ *
+ * Key generation may take a while,
+ * so it is recommended to call this function in a separate thread.
+ *
+ * @memberof dc_context_t
+ * @param context The context object.
+ * @return The fingerprint of the new key as hex string.
+ *     Must be released using dc_str_unref() after usage.
+ *     On errors, NULL is returned.
+ */
+char*           dc_rotate_self_key           (dc_context_t* context);
+
+
 /**
  * Signal an ongoing process to stop.
  *
@@ -3270,6 +3514,17 @@ int             dc_chat_get_type             (const dc_chat_t* chat);
 char*           dc_chat_get_mailinglist_addr (const dc_chat_t* chat);
 
 
+/**
+ * Get the welcome message of a group, see dc_set_chat_welcome_message().
+ *
+ * @memberof dc_chat_t
+ * @param chat The chat object.
+ * @return The welcome message. Must be released using dc_str_unref() after usage.
+ *     NULL if no welcome message is set.
+ */
+char*           dc_chat_get_welcome_message  (const dc_chat_t* chat);
+
+
 /**
  * Get name of a chat. For one-to-one chats, this is the name of the contact.
  * For group chats, this is the name given e.g. to dc_create_group_chat() or
@@ -3410,6 +3665,19 @@ int             dc_chat_is_device_talk       (const dc_chat_t* chat);
 int             dc_chat_can_send              (const dc_chat_t* chat);
 
 
+/**
+ * Get the reason why messages cannot be sent to a given chat.
+ * The UI may use this to show a specific explanation
+ * instead of just hiding the input controls, cmp. dc_chat_can_send().
+ *
+ * @memberof dc_chat_t
+ * @param chat The chat object.
+ * @return One of @ref DC_SEND_RESTRICTION,
+ *     DC_SEND_RESTRICTION_UNRESTRICTED if messages can be sent to the chat.
+ */
+int             dc_chat_get_send_restriction  (const dc_chat_t* chat);
+
+
 /**
  * Check if a chat is protected.
  * Protected chats contain only verified members and encryption is always enabled.
@@ -3423,6 +3691,19 @@ int             dc_chat_can_send              (const dc_chat_t* chat);
 int             dc_chat_is_protected         (const dc_chat_t* chat);
 
 
+/**
+ * Get the contact who created a group.
+ * The UI may show this as "Created by [name]" in the group profile.
+ *
+ * @memberof dc_chat_t
+ * @param chat The chat object.
+ * @return The contact ID of the creator, DC_CONTACT_ID_SELF for groups created on this device.
+ *     0 if the creator is unknown, e.g. for groups created with older versions;
+ *     the UI may show "Unknown" then.
+ */
+uint32_t        dc_chat_get_creator_id       (const dc_chat_t* chat);
+
+
 /**
  * Check if locations are sent to the chat
  * at the time the object was created using dc_get_chat().
@@ -3558,6 +3839,10 @@ int             dc_msg_get_viewtype           (const dc_msg_t* msg);
  *   Use dc_marknoticed_chat() to mark messages as being noticed.
  * - @ref DC_STATE_IN_SEEN - Incoming message, really _seen_ by the user.
  *   Marked as read on IMAP and MDN may be sent. Use dc_markseen_msgs() to mark messages as being seen.
+ * - @ref DC_STATE_IN_EXPIRED - Incoming ephemeral message whose timer has expired.
+ *   UIs may show a "This message has expired" placeholder,
+ *   the message is deleted about 30 seconds later.
+ *   Outgoing ephemeral messages are deleted right away.
  *
  * Outgoing message states:
  * - @ref DC_STATE_OUT_PREPARING - For files which need time to be prepared before they can be sent,
@@ -3946,6 +4231,21 @@ char*           dc_msg_get_summarytext        (const dc_msg_t* msg, int approx_c
 char*           dc_msg_get_override_sender_name(const dc_msg_t* msg);
 
 
+/**
+ * Get the language of a received message
+ * as announced by the sender in the `Content-Language` header, e.g. `de` or `en-US`.
+ * If the header lists several languages, the first one is returned.
+ *
+ * The UI may use this to offer translating messages that are not in the user's language.
+ *
+ * @memberof dc_msg_t
+ * @param msg The message object.
+ * @return The language tag or NULL if the language is unknown.
+ *     The returned string must be released using dc_str_unref().
+ */
+char*           dc_msg_get_language          (const dc_msg_t* msg);
+
+
 
 /**
  * Check if a message has a deviating timestamp.
@@ -4205,6 +4505,22 @@ int dc_msg_get_videochat_type (const dc_msg_t* msg);
 int dc_msg_has_html (dc_msg_t* msg);
 
 
+/**
+ * Check if remote content is removed from the full HTML version of a message.
+ *
+ * This is the case if the config-option `block_remote_content` is currently set
+ * and the HTML-part of the message references remote content as images,
+ * backgrounds or stylesheets, which are often used as tracking pixels.
+ * In this case, the UI may offer a button "Load remote content"
+ * that shows the message using dc_get_msg_html_with_remote_content().
+ *
+ * @memberof dc_msg_t
+ * @param msg The message object.
+ * @return 1=remote content is removed from dc_get_msg_html(), 0=no remote content is removed.
+ */
+int dc_msg_is_remote_content_blocked (dc_msg_t* msg);
+
+
 /**
   * Check if the message is completely downloaded
   * or if some further action is needed.
@@ -4230,6 +4546,20 @@ int dc_msg_has_html (dc_msg_t* msg);
 int dc_msg_get_download_state (const dc_msg_t* msg);
 
 
+/**
+  * Get the full size of a message that is not downloaded completely.
+  * UIs may show the size on the download button,
+  * e.g. "Download (2.3 MB)", if dc_msg_get_download_state()
+  * does not return @ref DC_DOWNLOAD_DONE.
+  *
+  * @memberof dc_msg_t
+  * @param msg The message object.
+  * @return The full size of the message in bytes.
+  *     0 if the message is downloaded completely or the size is unknown.
+  */
+uint32_t dc_msg_get_download_size (const dc_msg_t* msg);
+
+
 /**
  * Set the text of a message object.
  * This does not alter any information in the database; this may be done by dc_send_msg() later.
@@ -4971,6 +5301,18 @@ int64_t          dc_lot_get_timestamp     (const dc_lot_t* lot);
 #define DC_MSG_WEBXDC    80
 
 
+/**
+ * Message containing one or more contacts in vCard format.
+ * The file is set via dc_msg_set_file()
+ * and retrieved via dc_msg_get_file().
+ *
+ * Use dc_import_vcard() to add the contacts to the contact list,
+ * UIs typically offer this when the message is tapped.
+ * To create a vCard, use dc_make_vcard() or dc_make_chat_members_vcard().
+ */
+#define DC_MSG_VCARD     90
+
+
 /**
  * @}
  */
@@ -5007,6 +5349,11 @@ int64_t          dc_lot_get_timestamp     (const dc_lot_t* lot);
  */
 #define         DC_STATE_IN_SEEN             16
 
+/**
+ * Expired ephemeral message. See dc_msg_get_state() for details.
+ */
+#define         DC_STATE_IN_EXPIRED          17
+
 /**
  * Outgoing message being prepared. See dc_msg_get_state() for details.
  */
@@ -5356,6 +5703,18 @@ int dc_event_get_data1_int(dc_event_t* event);
 int dc_event_get_data2_int(dc_event_t* event);
 
 
+/**
+ * Get additional data associated with an event object.
+ * Only few events have a third data item, see the @ref DC_EVENT constants.
+ * See also dc_event_get_data1_int() and dc_event_get_data2_int().
+ *
+ * @memberof dc_event_t
+ * @param event Event object as returned from dc_get_next_event().
+ * @return "data3" as a signed integer, at least 32bit, 0 if the event has no third data item.
+ */
+int dc_event_get_data3_int(dc_event_t* event);
+
+
 /**
  * Get data associated with an event object.
  * The meaning of the data depends on the event ID
@@ -5581,15 +5940,33 @@ void dc_event_unref(dc_event_t* event);
 
 
 /**
- * A single message is read by the receiver. State changed from @ref DC_STATE_OUT_DELIVERED to
+ * A single message is read by a receiver.
+ *
+ * The first read receipt changes the state from @ref DC_STATE_OUT_DELIVERED to
  * @ref DC_STATE_OUT_MDN_RCVD.
+ * In groups, this event is emitted once for every member who sent a read receipt,
+ * also if the state did not change.
+ * The readers are listed by dc_get_msg_info().
  *
  * @param data1 (int) chat_id
  * @param data2 (int) msg_id
+ * @param data3 (int) contact_id of the reader, use dc_event_get_data3_int() to get it
  */
 #define DC_EVENT_MSG_READ                 2015
 
 
+/**
+ * The state of a single message changed, see dc_msg_get_state().
+ * This is emitted in addition to events as #DC_EVENT_MSG_DELIVERED or #DC_EVENT_MSG_READ
+ * and allows the UI to animate the transition between the states.
+ *
+ * @param data1 (int) msg_id
+ * @param data2 (int) Old and new state as @ref DC_STATE constants:
+ *     The old state is `data2 >> 16`, the new state is `data2 & 0xffff`.
+ */
+#define DC_EVENT_MESSAGE_STATE_CHANGED    2016
+
+
 /**
  * Chat changed. The name or the image of a chat group was changed or members were added or removed.
  * Or the verify state of a chat has changed.
@@ -5609,6 +5986,17 @@ void dc_event_unref(dc_event_t* event);
  */
 #define DC_EVENT_CHAT_EPHEMERAL_TIMER_MODIFIED 2021
 
+/**
+ * A single chatlist item may have changed,
+ * e.g. because a message was received or a contact request was accepted.
+ *
+ * UIs may reload just this item instead of the whole chatlist.
+ *
+ * @param data1 (int) chat_id
+ * @param data2 0
+ */
+#define DC_EVENT_CHATLIST_ITEM_CHANGED    2022
+
 
 /**
  * Contact(s) created, renamed, verified, blocked or deleted.
@@ -5694,6 +6082,16 @@ void dc_event_unref(dc_event_t* event);
 #define DC_EVENT_SECUREJOIN_JOINER_PROGRESS       2061
 
 
+/**
+ * Inform about the progress of scheduling old messages for deletion from the server,
+ * started by dc_schedule_retroactive_server_deletion().
+ *
+ * @param data1 (int) 0=error, 1-999=progress in permille, 1000=success and done
+ * @param data2 0
+ */
+#define DC_EVENT_SERVER_DELETION_PROGRESS         2070
+
+
 /**
  * The connectivity to the server changed.
  * This means that you should refresh the connectivity view
@@ -5706,6 +6104,16 @@ void dc_event_unref(dc_event_t* event);
 #define DC_EVENT_CONNECTIVITY_CHANGED             2100
 
 
+/**
+ * Inform about the result of an IMAP roundtrip measurement,
+ * started by dc_ping().
+ *
+ * @param data1 (int) Roundtrip time of an IMAP NOOP command in milliseconds.
+ * @param data2 0
+ */
+#define DC_EVENT_PING_RESULT                      2105
+
+
 /**
  * The user's avatar changed.
  * You can get the new avatar file with `dc_get_config(context, "selfavatar")`.
@@ -5730,13 +6138,57 @@ void dc_event_unref(dc_event_t* event);
 #define DC_EVENT_WEBXDC_STATUS_UPDATE                2120
 
 
+/**
+ * An ephemeral status update for a webxdc instance was received,
+ * see dc_send_webxdc_ephemeral_update().
+ *
+ * The update is not stored, so the UI has to pass the payload to the webxdc instance directly.
+ *
+ * @param data1 (int) msg_id
+ * @param data2 (char*) payload as JSON
+ */
+#define DC_EVENT_WEBXDC_EPHEMERAL_UPDATE             2121
+
+
+/**
+ * Statistics of an IMAP folder after it was scanned for new messages.
+ *
+ * The event is only emitted if the config option `emit_folder_stats` is enabled.
+ *
+ * @param data1 (int) Number of messages in the folder.
+ * @param data2 (char*) Name of the folder.
+ */
+#define DC_EVENT_IMAP_FOLDER_STATS                   2130
+
+
+/**
+ * Progress of a secure-join handshake with named stages,
+ * emitted on both the inviter and the joiner side
+ * in addition to #DC_EVENT_SECUREJOIN_INVITER_PROGRESS and #DC_EVENT_SECUREJOIN_JOINER_PROGRESS.
+ *
+ * @param data1 (int) The ID of the contact the handshake is done with.
+ * @param data2 (int) The stage, one of the @ref DC_SECUREJOIN_STAGE constants.
+ */
+#define DC_EVENT_SECUREJOIN_PROGRESS                 2140
+
+
+/**
+ * The number of outgoing messages waiting to be sent has changed,
+ * eg. because a message was sent, failed or was deleted.
+ *
+ * @param data1 (int) Number of pending outgoing messages.
+ * @param data2 0
+ */
+#define DC_EVENT_OUTBOX_CHANGED                      2150
+
+
 /**
  * @}
  */
 
 
 #define DC_EVENT_DATA1_IS_STRING(e)  0    // not used anymore 
-#define DC_EVENT_DATA2_IS_STRING(e)  ((e)==DC_EVENT_CONFIGURE_PROGRESS || (e)==DC_EVENT_IMEX_FILE_WRITTEN || ((e)>=100 && (e)<=499))
+#define DC_EVENT_DATA2_IS_STRING(e)  ((e)==DC_EVENT_CONFIGURE_PROGRESS || (e)==DC_EVENT_IMEX_FILE_WRITTEN || (e)==DC_EVENT_IMAP_FOLDER_STATS || (e)==DC_EVENT_WEBXDC_EPHEMERAL_UPDATE || ((e)>=100 && (e)<=499))
 
 
 /*
@@ -5754,6 +6206,13 @@ void dc_event_unref(dc_event_t* event);
 #define DC_MEDIA_QUALITY_WORSE    1
 
 
+/*
+ * Values for dc_get|set_config("blob_layout")
+ */
+#define DC_BLOB_LAYOUT_FLAT     0
+#define DC_BLOB_LAYOUT_PER_CHAT 1
+
+
 /*
  * Values for dc_get|set_config("key_gen_type")
  */
@@ -5817,6 +6276,104 @@ void dc_event_unref(dc_event_t* event);
  */
 
 
+/**
+ * @defgroup DC_SEND_RESTRICTION DC_SEND_RESTRICTION
+ *
+ * These constants describe why messages cannot be sent to a chat,
+ * see dc_chat_get_send_restriction().
+ *
+ * @addtogroup DC_SEND_RESTRICTION
+ * @{
+ */
+
+/**
+ * Messages can be sent to the chat.
+ */
+#define         DC_SEND_RESTRICTION_UNRESTRICTED                    0
+
+/**
+ * The chat is a special chat such as the trash or the archive link.
+ */
+#define         DC_SEND_RESTRICTION_SPECIAL_CHAT                    10
+
+/**
+ * The chat is the device chat, see dc_chat_is_device_talk().
+ */
+#define         DC_SEND_RESTRICTION_DEVICE_CHAT                     20
+
+/**
+ * The chat is a contact request that has to be accepted first,
+ * see dc_chat_is_contact_request().
+ */
+#define         DC_SEND_RESTRICTION_CONTACT_REQUEST                 30
+
+/**
+ * The chat is a mailing list without List-Post header,
+ * e.g. a notification-only sender.
+ */
+#define         DC_SEND_RESTRICTION_MAILINGLIST_WITHOUT_LIST_POST   40
+
+/**
+ * The chat is a mailing list using a different List-Post header in each message,
+ * so it is unknown where replies should go to.
+ */
+#define         DC_SEND_RESTRICTION_MAILINGLIST_VARYING_LIST_POST   50
+
+/**
+ * The user is not a member of the group, e.g. because they left it.
+ */
+#define         DC_SEND_RESTRICTION_NOT_MEMBER                      60
+
+/**
+ * The chat is blocked, see dc_block_chat().
+ */
+#define         DC_SEND_RESTRICTION_BLOCKED                         70
+
+/**
+ * @}
+ */
+
+
+/**
+ * @defgroup DC_SECUREJOIN_STAGE DC_SECUREJOIN_STAGE
+ *
+ * These constants describe the stage of a secure-join handshake,
+ * see #DC_EVENT_SECUREJOIN_PROGRESS.
+ *
+ * @addtogroup DC_SECUREJOIN_STAGE
+ * @{
+ */
+
+/**
+ * The handshake failed.
+ */
+#define         DC_SECUREJOIN_STAGE_FAILED              0
+
+/**
+ * The inviter received the request of the joiner.
+ */
+#define         DC_SECUREJOIN_STAGE_REQUEST_RECEIVED    1
+
+/**
+ * The key of the peer was verified.
+ */
+#define         DC_SECUREJOIN_STAGE_AUTH_VERIFIED       2
+
+/**
+ * The inviter added the joiner to the group.
+ */
+#define         DC_SECUREJOIN_STAGE_MEMBER_ADDED_SENT   3
+
+/**
+ * The handshake finished successfully.
+ */
+#define         DC_SECUREJOIN_STAGE_COMPLETED           4
+
+/**
+ * @}
+ */
+
+
 /**
  * @defgroup DC_CHAT_VISIBILITY DC_CHAT_VISIBILITY
  *
@@ -6445,6 +7002,78 @@ void dc_event_unref(dc_event_t* event);
 /// Used in a device message that explains AEAP.
 #define DC_STR_AEAP_EXPLANATION_AND_LINK  123
 
+/// "Unknown group"
+///
+/// Used as the name of a placeholder group
+/// created for messages to an unknown group that could not be decrypted.
+#define DC_STR_UNKNOWN_GROUP              124
+
+/// "Backup from %1$s imported: %2$s chats, %3$s messages."
+///
+/// Used as a device message after a backup was imported.
+///
+/// `%1$s` will be replaced by the date of the backup,
+/// `%2$s` by the number of chats and `%3$s` by the number of messages.
+#define DC_STR_BACKUP_IMPORTED            125
+
+/// "Your encryption key changed from %1$s to %2$s."
+///
+/// Used as a device message after the own key was changed.
+///
+/// `%1$s` and `%2$s` will be replaced by the truncated fingerprints of the old and the new key.
+#define DC_STR_SELF_KEY_CHANGED           126
+
+/// "Your encryption keys were exported to %1$s."
+///
+/// Used as a device message after the own keys were exported.
+///
+/// `%1$s` will be replaced by the export directory.
+#define DC_STR_SELF_KEYS_EXPORTED         127
+
+/// "Group image change could not be applied."
+///
+/// Used in status messages when a group image change was received
+/// but the image was missing or broken, e.g. because it was removed by an attachment filter.
+#define DC_STR_GROUP_IMAGE_CHANGE_FAILED  128
+
+/// "Your encryption key expires on %1$s. Import or generate a new key to stay reachable."
+///
+/// Used as a device message after importing an own key that expires soon.
+///
+/// `%1$s` will be replaced by the expiration date.
+#define DC_STR_SELF_KEY_EXPIRES_SOON      129
+
+/// "[Some parts of this message are not shown because it is too complex.]"
+///
+/// Used as the last part of a received message
+/// that has too many MIME parts or nesting levels to be shown completely.
+#define DC_STR_MSG_PARTS_TRUNCATED        130
+
+/// "Contact"
+///
+/// Used in summaries of messages of type #DC_MSG_VCARD.
+#define DC_STR_CONTACT                    131
+
+/// "Catch-All"
+///
+/// Used as the name of the chat collecting messages to unconfigured addresses,
+/// see the `catch_all_chat` option of dc_set_config().
+#define DC_STR_CATCH_ALL_CHAT             132
+
+/// "Attachment too large: %1$s"
+///
+/// Used as message text if a received attachment exceeds the `max_blob_size_bytes` option of dc_set_config().
+///
+/// `%1$s` will be replaced by the size of the attachment, e.g. "2.1 GiB".
+#define DC_STR_ATTACHMENT_TOO_LARGE       133
+
+/// "⚠️ The clock of %1$s seems to be wrong, the times of their messages may be inaccurate."
+///
+/// Added as info message to a chat if several messages of a contact were dated in the future.
+///
+/// `%1$s` will be replaced by the name of the contact.
+#define DC_STR_SENDER_CLOCK_WRONG         134
+
 /**
  * @}
  */
@@ -30,13 +30,14 @@
 use tokio::runtime::Runtime;
 use tokio::sync::RwLock;
 
-use deltachat::chat::{ChatId, ChatVisibility, MuteDuration, ProtectionStatus};
+use deltachat::chat::{CantSendReason, ChatId, ChatVisibility, MuteDuration, ProtectionStatus};
 use deltachat::constants::DC_MSG_ID_LAST_SPECIAL;
 use deltachat::contact::{Contact, ContactId, Origin};
 use deltachat::context::Context;
 use deltachat::ephemeral::Timer as EphemeralTimer;
 use deltachat::key::DcKey;
 use deltachat::message::MsgId;
+use deltachat::securejoin::SecurejoinStage;
 use deltachat::stock_str::StockMessage;
 use deltachat::webxdc::StatusUpdateSerial;
 use deltachat::*;
@@ -492,18 +493,26 @@ fn render_info(
         EventType::MsgDelivered { .. } => 2010,
         EventType::MsgFailed { .. } => 2012,
         EventType::MsgRead { .. } => 2015,
+        EventType::MessageStateChanged { .. } => 2016,
         EventType::ChatModified(_) => 2020,
         EventType::ChatEphemeralTimerModified { .. } => 2021,
+        EventType::ChatlistItemChanged(_) => 2022,
         EventType::ContactsChanged(_) => 2030,
         EventType::LocationChanged(_) => 2035,
         EventType::ConfigureProgress { .. } => 2041,
         EventType::ImexProgress(_) => 2051,
         EventType::ImexFileWritten(_) => 2052,
+        EventType::ServerDeletionProgress(_) => 2070,
         EventType::SecurejoinInviterProgress { .. } => 2060,
         EventType::SecurejoinJoinerProgress { .. } => 2061,
         EventType::ConnectivityChanged => 2100,
+        EventType::PingResult { .. } => 2105,
         EventType::SelfavatarChanged => 2110,
         EventType::WebxdcStatusUpdate { .. } => 2120,
+        EventType::WebxdcEphemeralUpdate { .. } => 2121,
+        EventType::ImapFolderStats { .. } => 2130,
+        EventType::SecurejoinProgress { .. } => 2140,
+        EventType::OutboxChanged { .. } => 2150,
     }
 }
 
@@ -536,20 +545,27 @@ fn render_info(
         | EventType::MsgFailed { chat_id, .. }
         | EventType::MsgRead { chat_id, .. }
         | EventType::ChatModified(chat_id)
+        | EventType::ChatlistItemChanged(chat_id)
         | EventType::ChatEphemeralTimerModified { chat_id, .. } => chat_id.to_u32() as libc::c_int,
         EventType::ContactsChanged(id) | EventType::LocationChanged(id) => {
             let id = id.unwrap_or_default();
             id.to_u32() as libc::c_int
         }
-        EventType::ConfigureProgress { progress, .. } | EventType::ImexProgress(progress) => {
-            *progress as libc::c_int
-        }
+        EventType::ConfigureProgress { progress, .. }
+        | EventType::ImexProgress(progress)
+        | EventType::ServerDeletionProgress(progress) => *progress as libc::c_int,
         EventType::ImexFileWritten(_) => 0,
         EventType::SecurejoinInviterProgress { contact_id, .. }
         | EventType::SecurejoinJoinerProgress { contact_id, .. } => {
             contact_id.to_u32() as libc::c_int
         }
-        EventType::WebxdcStatusUpdate { msg_id, .. } => msg_id.to_u32() as libc::c_int,
+        EventType::WebxdcStatusUpdate { msg_id, .. }
+        | EventType::WebxdcEphemeralUpdate { msg_id, .. } => msg_id.to_u32() as libc::c_int,
+        EventType::PingResult { latency_ms } => *latency_ms as libc::c_int,
+        EventType::ImapFolderStats { msg_count, .. } => *msg_count as libc::c_int,
+        EventType::SecurejoinProgress { contact_id, .. } => contact_id.to_u32() as libc::c_int,
+        EventType::OutboxChanged { pending_count } => *pending_count as libc::c_int,
+        EventType::MessageStateChanged { msg_id, .. } => msg_id.to_u32() as libc::c_int,
     }
 }
 
@@ -579,15 +595,31 @@ fn render_info(
         | EventType::ConfigureProgress { .. }
         | EventType::ImexProgress(_)
         | EventType::ImexFileWritten(_)
+        | EventType::ServerDeletionProgress(_)
         | EventType::MsgsNoticed(_)
         | EventType::ConnectivityChanged
-        | EventType::SelfavatarChanged => 0,
-        EventType::ChatModified(_) => 0,
+        | EventType::PingResult { .. }
+        | EventType::SelfavatarChanged
+        | EventType::WebxdcEphemeralUpdate { .. } => 0,
+        EventType::ChatModified(_) | EventType::ChatlistItemChanged(_) => 0,
+        EventType::ImapFolderStats { .. } | EventType::OutboxChanged { .. } => 0,
+        EventType::SecurejoinProgress { stage, .. } => match stage {
+            SecurejoinStage::Failed { .. } => 0,
+            SecurejoinStage::RequestReceived => 1,
+            SecurejoinStage::AuthVerified => 2,
+            SecurejoinStage::MemberAddedSent => 3,
+            SecurejoinStage::Completed => 4,
+        },
         EventType::MsgsChanged { msg_id, .. }
         | EventType::IncomingMsg { msg_id, .. }
         | EventType::MsgDelivered { msg_id, .. }
         | EventType::MsgFailed { msg_id, .. }
         | EventType::MsgRead { msg_id, .. } => msg_id.to_u32() as libc::c_int,
+        EventType::MessageStateChanged {
+            old_state,
+            new_state,
+            ..
+        } => ((*old_state as libc::c_int) << 16) | *new_state as libc::c_int,
         EventType::SecurejoinInviterProgress { progress, .. }
         | EventType::SecurejoinJoinerProgress { progress, .. } => *progress as libc::c_int,
         EventType::ChatEphemeralTimerModified { timer, .. } => timer.to_u32() as libc::c_int,
@@ -598,6 +630,19 @@ fn render_info(
     }
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn dc_event_get_data3_int(event: *mut dc_event_t) -> libc::c_int {
+    if event.is_null() {
+        eprintln!("ignoring careless call to dc_event_get_data3_int()");
+        return 0;
+    }
+
+    match &(*event).typ {
+        EventType::MsgRead { contact_id, .. } => contact_id.to_u32() as libc::c_int,
+        _ => 0,
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn dc_event_get_data2_str(event: *mut dc_event_t) -> *mut libc::c_char {
     if event.is_null() {
@@ -628,13 +673,19 @@ fn render_info(
         | EventType::MsgDelivered { .. }
         | EventType::MsgFailed { .. }
         | EventType::MsgRead { .. }
+        | EventType::MessageStateChanged { .. }
         | EventType::ChatModified(_)
+        | EventType::ChatlistItemChanged(_)
         | EventType::ContactsChanged(_)
         | EventType::LocationChanged(_)
         | EventType::ImexProgress(_)
+        | EventType::ServerDeletionProgress(_)
         | EventType::SecurejoinInviterProgress { .. }
         | EventType::SecurejoinJoinerProgress { .. }
+        | EventType::SecurejoinProgress { .. }
+        | EventType::OutboxChanged { .. }
         | EventType::ConnectivityChanged
+        | EventType::PingResult { .. }
         | EventType::SelfavatarChanged
         | EventType::WebxdcStatusUpdate { .. }
         | EventType::ChatEphemeralTimerModified { .. } => ptr::null_mut(),
@@ -649,6 +700,14 @@ fn render_info(
             let data2 = file.to_c_string().unwrap_or_default();
             data2.into_raw()
         }
+        EventType::ImapFolderStats { folder, .. } => {
+            let data2 = folder.to_c_string().unwrap_or_default();
+            data2.into_raw()
+        }
+        EventType::WebxdcEphemeralUpdate { payload, .. } => {
+            let data2 = payload.to_c_string().unwrap_or_default();
+            data2.into_raw()
+        }
     }
 }
 
@@ -727,6 +786,22 @@ fn render_info(
     block_on(async move { ctx.maybe_network().await })
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn dc_ping(context: *mut dc_context_t) -> i64 {
+    if context.is_null() {
+        eprintln!("ignoring careless call to dc_ping()");
+        return -1;
+    }
+    let ctx = &*context;
+
+    block_on(async move {
+        ctx.ping()
+            .await
+            .log_err(ctx, "Failed to ping IMAP server")
+            .unwrap_or(-1)
+    })
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn dc_preconfigure_keypair(
     context: *mut dc_context_t,
@@ -955,6 +1030,23 @@ fn render_info(
     .is_ok() as libc::c_int
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn dc_send_webxdc_ephemeral_update(
+    context: *mut dc_context_t,
+    msg_id: u32,
+    json: *const libc::c_char,
+) -> libc::c_int {
+    if context.is_null() {
+        eprintln!("ignoring careless call to dc_send_webxdc_ephemeral_update()");
+        return 0;
+    }
+    let ctx = &*context;
+
+    block_on(ctx.send_webxdc_ephemeral_update(MsgId::new(msg_id), &to_string_lossy(json)))
+        .log_err(ctx, "Failed to send ephemeral webxdc update")
+        .is_ok() as libc::c_int
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn dc_get_webxdc_status_updates(
     context: *mut dc_context_t,
@@ -1147,6 +1239,23 @@ fn render_info(
     })
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn dc_schedule_retroactive_server_deletion(
+    context: *mut dc_context_t,
+) -> libc::c_int {
+    if context.is_null() {
+        eprintln!("ignoring careless call to dc_schedule_retroactive_server_deletion()");
+        return -1;
+    }
+    let ctx = &*context;
+    block_on(async move {
+        ctx.schedule_retroactive_server_deletion()
+            .await
+            .log_err(ctx, "Failed to schedule retroactive server deletion")
+            .map_or(-1, |cnt| cnt as libc::c_int)
+    })
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn dc_get_fresh_msgs(
     context: *mut dc_context_t,
@@ -1575,6 +1684,30 @@ fn from_prim<S, T>(s: S) -> Option<T>
     })
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn dc_set_chat_welcome_message(
+    context: *mut dc_context_t,
+    chat_id: u32,
+    text: *const libc::c_char,
+) -> libc::c_int {
+    if context.is_null() || chat_id <= constants::DC_CHAT_ID_LAST_SPECIAL.to_u32() {
+        eprintln!("ignoring careless call to dc_set_chat_welcome_message()");
+        return 0;
+    }
+    let ctx = &*context;
+
+    block_on(async move {
+        chat::set_welcome_message(
+            ctx,
+            ChatId::new(chat_id),
+            to_opt_string_lossy(text).as_deref(),
+        )
+        .await
+        .map(|_| 1)
+        .unwrap_or_log_default(ctx, "Failed to set welcome message")
+    })
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn dc_set_chat_profile_image(
     context: *mut dc_context_t,
@@ -1730,6 +1863,22 @@ fn from_prim<S, T>(s: S) -> Option<T>
         .strdup()
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn dc_get_msg_html_with_remote_content(
+    context: *mut dc_context_t,
+    msg_id: u32,
+) -> *mut libc::c_char {
+    if context.is_null() {
+        eprintln!("ignoring careless call to dc_get_msg_html_with_remote_content()");
+        return ptr::null_mut();
+    }
+    let ctx = &*context;
+
+    block_on(MsgId::new(msg_id).get_html_with_remote_content(ctx))
+        .unwrap_or_log_default(ctx, "Failed get_msg_html_with_remote_content")
+        .strdup()
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn dc_get_mime_headers(
     context: *mut dc_context_t,
@@ -1886,7 +2035,7 @@ fn from_prim<S, T>(s: S) -> Option<T>
         return 0;
     }
 
-    contact::may_be_valid_addr(&to_string_lossy(addr)) as libc::c_int
+    tools::is_valid_email_addr(&to_string_lossy(addr)) as libc::c_int
 }
 
 #[no_mangle]
@@ -2058,6 +2207,67 @@ fn from_prim<S, T>(s: S) -> Option<T>
     })
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn dc_make_vcard(
+    context: *mut dc_context_t,
+    contact_id: u32,
+) -> *mut libc::c_char {
+    if context.is_null() {
+        eprintln!("ignoring careless call to dc_make_vcard()");
+        return ptr::null_mut();
+    }
+    let ctx = &*context;
+
+    block_on(async move {
+        let contact = Contact::get_by_id(ctx, ContactId::new(contact_id)).await?;
+        contact.export_to_vcard(ctx).await
+    })
+    .log_err(ctx, "Cannot make vCard")
+    .map(|vcard| vcard.strdup())
+    .unwrap_or_else(|_| ptr::null_mut())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn dc_make_chat_members_vcard(
+    context: *mut dc_context_t,
+    chat_id: u32,
+) -> *mut libc::c_char {
+    if context.is_null() {
+        eprintln!("ignoring careless call to dc_make_chat_members_vcard()");
+        return ptr::null_mut();
+    }
+    let ctx = &*context;
+
+    block_on(ChatId::new(chat_id).export_members_vcard(ctx))
+        .log_err(ctx, "Cannot make vCard of chat members")
+        .map(|vcard| String::from_utf8_lossy(&vcard).strdup())
+        .unwrap_or_else(|_| ptr::null_mut())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn dc_import_vcard(
+    context: *mut dc_context_t,
+    vcard: *const libc::c_char,
+) -> *mut dc_array::dc_array_t {
+    if context.is_null() || vcard.is_null() {
+        eprintln!("ignoring careless call to dc_import_vcard()");
+        return ptr::null_mut();
+    }
+    let ctx = &*context;
+
+    block_on(async move {
+        Box::into_raw(Box::new(dc_array_t::from(
+            Contact::import_vcard(ctx, &to_string_lossy(vcard))
+                .await
+                .log_err(ctx, "Cannot import vCard")
+                .unwrap_or_default()
+                .iter()
+                .map(|id| id.to_u32())
+                .collect::<Vec<u32>>(),
+        )))
+    })
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn dc_delete_contact(
     context: *mut dc_context_t,
@@ -2201,6 +2411,25 @@ fn from_prim<S, T>(s: S) -> Option<T>
     })
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn dc_rotate_self_key(context: *mut dc_context_t) -> *mut libc::c_char {
+    if context.is_null() {
+        eprintln!("ignoring careless call to dc_rotate_self_key()");
+        return ptr::null_mut();
+    }
+    let ctx = &*context;
+
+    block_on(async move {
+        match rotate_self_key(ctx).await {
+            Ok(fingerprint) => fingerprint.hex().strdup(),
+            Err(err) => {
+                error!(ctx, "dc_rotate_self_key(): {:#}", err);
+                ptr::null_mut()
+            }
+        }
+    })
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn dc_stop_ongoing_process(context: *mut dc_context_t) {
     if context.is_null() {
@@ -2792,6 +3021,20 @@ pub struct ChatWrapper {
     ffi_chat.chat.get_mailinglist_addr().strdup()
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn dc_chat_get_welcome_message(chat: *mut dc_chat_t) -> *mut libc::c_char {
+    if chat.is_null() {
+        eprintln!("ignoring careless call to dc_chat_get_welcome_message()");
+        return ptr::null_mut();
+    }
+    let ffi_chat = &*chat;
+    ffi_chat
+        .chat
+        .get_welcome_message()
+        .map(|text| text.strdup())
+        .unwrap_or_else(ptr::null_mut)
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn dc_chat_get_profile_image(chat: *mut dc_chat_t) -> *mut libc::c_char {
     if chat.is_null() {
@@ -2892,6 +3135,21 @@ pub struct ChatWrapper {
         .unwrap_or_default() as libc::c_int
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn dc_chat_get_send_restriction(chat: *mut dc_chat_t) -> libc::c_int {
+    if chat.is_null() {
+        eprintln!("ignoring careless call to dc_chat_get_send_restriction()");
+        return 0;
+    }
+    let ffi_chat = &*chat;
+    let ctx = &*ffi_chat.context;
+    block_on(ffi_chat.chat.why_cant_send(ctx))
+        .log_err(ctx, "why_cant_send failed")
+        .map_or(CantSendReason::SpecialChat as libc::c_int, |reason| {
+            reason.map_or(0, |reason| reason as libc::c_int)
+        })
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn dc_chat_is_protected(chat: *mut dc_chat_t) -> libc::c_int {
     if chat.is_null() {
@@ -2902,6 +3160,20 @@ pub struct ChatWrapper {
     ffi_chat.chat.is_protected() as libc::c_int
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn dc_chat_get_creator_id(chat: *mut dc_chat_t) -> u32 {
+    if chat.is_null() {
+        eprintln!("ignoring careless call to dc_chat_get_creator_id()");
+        return 0;
+    }
+    let ffi_chat = &*chat;
+    ffi_chat
+        .chat
+        .get_creator_id()
+        .map(|id| id.to_u32())
+        .unwrap_or_default()
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn dc_chat_is_sending_locations(chat: *mut dc_chat_t) -> libc::c_int {
     if chat.is_null() {
@@ -3086,6 +3358,16 @@ pub struct MessageWrapper {
     ffi_msg.message.download_state() as libc::c_int
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn dc_msg_get_download_size(msg: *mut dc_msg_t) -> u32 {
+    if msg.is_null() {
+        eprintln!("ignoring careless call to dc_msg_get_download_size()");
+        return 0;
+    }
+    let ffi_msg = &*msg;
+    ffi_msg.message.download_size().unwrap_or_default()
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn dc_msg_get_timestamp(msg: *mut dc_msg_t) -> i64 {
     if msg.is_null() {
@@ -3368,6 +3650,17 @@ pub struct MessageWrapper {
     ffi_msg.message.get_override_sender_name().strdup()
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn dc_msg_get_language(msg: *mut dc_msg_t) -> *mut libc::c_char {
+    if msg.is_null() {
+        eprintln!("ignoring careless call to dc_msg_get_language()");
+        return ptr::null_mut();
+    }
+    let ffi_msg = &*msg;
+
+    ffi_msg.message.language().strdup()
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn dc_msg_has_deviating_timestamp(msg: *mut dc_msg_t) -> libc::c_int {
     if msg.is_null() {
@@ -3458,6 +3751,20 @@ pub struct MessageWrapper {
     ffi_msg.message.has_html().into()
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn dc_msg_is_remote_content_blocked(msg: *mut dc_msg_t) -> libc::c_int {
+    if msg.is_null() {
+        eprintln!("ignoring careless call to dc_msg_is_remote_content_blocked()");
+        return 0;
+    }
+    let ffi_msg = &*msg;
+    let ctx = &*ffi_msg.context;
+    block_on(ffi_msg.message.is_remote_content_blocked(ctx))
+        .log_err(ctx, "Cannot check if remote content is blocked")
+        .unwrap_or_default()
+        .into()
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn dc_msg_get_videochat_url(msg: *mut dc_msg_t) -> *mut libc::c_char {
     if msg.is_null() {
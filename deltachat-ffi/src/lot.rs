@@ -199,6 +199,7 @@ pub enum LotState {
     MsgInFresh = 10,
     MsgInNoticed = 13,
     MsgInSeen = 16,
+    MsgInExpired = 17,
     MsgOutPreparing = 18,
     MsgOutDraft = 19,
     MsgOutPending = 20,
@@ -221,6 +222,7 @@ fn from(s: MessageState) -> Self {
             InFresh => LotState::MsgInFresh,
             InNoticed => LotState::MsgInNoticed,
             InSeen => LotState::MsgInSeen,
+            InExpired => LotState::MsgInExpired,
             OutPreparing => LotState::MsgOutPreparing,
             OutDraft => LotState::MsgOutDraft,
             OutPending => LotState::MsgOutPending,
@@ -19,16 +19,29 @@ pub fn event_to_json_rpc_notification(event: Event) -> Value {
         | EventType::ErrorSelfNotInGroup(txt) => (json!(txt), Value::Null),
         EventType::ImexFileWritten(path) => (json!(path.to_str()), Value::Null),
         // single number
-        EventType::MsgsNoticed(chat_id) | EventType::ChatModified(chat_id) => {
-            (json!(chat_id), Value::Null)
+        EventType::MsgsNoticed(chat_id)
+        | EventType::ChatModified(chat_id)
+        | EventType::ChatlistItemChanged(chat_id) => (json!(chat_id), Value::Null),
+        EventType::ImexProgress(progress) | EventType::ServerDeletionProgress(progress) => {
+            (json!(progress), Value::Null)
         }
-        EventType::ImexProgress(progress) => (json!(progress), Value::Null),
         // both fields contain numbers
         EventType::MsgsChanged { chat_id, msg_id }
         | EventType::IncomingMsg { chat_id, msg_id }
         | EventType::MsgDelivered { chat_id, msg_id }
         | EventType::MsgFailed { chat_id, msg_id }
-        | EventType::MsgRead { chat_id, msg_id } => (json!(chat_id), json!(msg_id)),
+        | EventType::MsgRead {
+            chat_id, msg_id, ..
+        } => (json!(chat_id), json!(msg_id)),
+        EventType::MessageStateChanged {
+            chat_id,
+            msg_id,
+            old_state,
+            new_state,
+        } => (
+            json!(msg_id),
+            json!({ "chatId": chat_id, "oldState": old_state, "newState": new_state }),
+        ),
         EventType::ChatEphemeralTimerModified { chat_id, timer } => (json!(chat_id), json!(timer)),
         EventType::SecurejoinInviterProgress {
             contact_id,
@@ -55,11 +68,30 @@ pub fn event_to_json_rpc_notification(event: Event) -> Value {
             },
         ),
         EventType::ConnectivityChanged => (Value::Null, Value::Null),
+        EventType::PingResult { latency_ms } => (json!(latency_ms), Value::Null),
         EventType::SelfavatarChanged => (Value::Null, Value::Null),
         EventType::WebxdcStatusUpdate {
             msg_id,
             status_update_serial,
         } => (json!(msg_id), json!(status_update_serial)),
+        EventType::WebxdcEphemeralUpdate { msg_id, payload } => (json!(msg_id), json!(payload)),
+        EventType::ImapFolderStats {
+            folder, msg_count, ..
+        } => (json!(folder), json!(msg_count)),
+        EventType::SecurejoinProgress {
+            contact_id,
+            chat_id,
+            stage,
+        } => (
+            json!(contact_id),
+            json!({ "chatId": chat_id, "stage": stage }),
+        ),
+        EventType::OutboxChanged { pending_count } => (json!(pending_count), Value::Null),
+    };
+    // Only few events carry a third value.
+    let field3 = match &event.typ {
+        EventType::MsgRead { contact_id, .. } => json!(contact_id),
+        _ => Value::Null,
     };
 
     let id: EventTypeName = event.typ.into();
@@ -67,7 +99,8 @@ pub fn event_to_json_rpc_notification(event: Event) -> Value {
         "id": id,
         "contextId": event.id,
         "field1": field1,
-        "field2": field2
+        "field2": field2,
+        "field3": field3
     })
 }
 
@@ -90,18 +123,26 @@ pub enum EventTypeName {
     MsgDelivered,
     MsgFailed,
     MsgRead,
+    MessageStateChanged,
     ChatModified,
     ChatEphemeralTimerModified,
+    ChatlistItemChanged,
     ContactsChanged,
     LocationChanged,
     ConfigureProgress,
     ImexProgress,
     ImexFileWritten,
+    ServerDeletionProgress,
     SecurejoinInviterProgress,
     SecurejoinJoinerProgress,
     ConnectivityChanged,
+    PingResult,
     SelfavatarChanged,
     WebxdcStatusUpdate,
+    WebxdcEphemeralUpdate,
+    ImapFolderStats,
+    SecurejoinProgress,
+    OutboxChanged,
 }
 
 impl From<EventType> for EventTypeName {
@@ -125,18 +166,26 @@ fn from(event: EventType) -> Self {
             EventType::MsgDelivered { .. } => MsgDelivered,
             EventType::MsgFailed { .. } => MsgFailed,
             EventType::MsgRead { .. } => MsgRead,
+            EventType::MessageStateChanged { .. } => MessageStateChanged,
             EventType::ChatModified(_) => ChatModified,
             EventType::ChatEphemeralTimerModified { .. } => ChatEphemeralTimerModified,
+            EventType::ChatlistItemChanged(_) => ChatlistItemChanged,
             EventType::ContactsChanged(_) => ContactsChanged,
             EventType::LocationChanged(_) => LocationChanged,
             EventType::ConfigureProgress { .. } => ConfigureProgress,
             EventType::ImexProgress(_) => ImexProgress,
             EventType::ImexFileWritten(_) => ImexFileWritten,
+            EventType::ServerDeletionProgress(_) => ServerDeletionProgress,
             EventType::SecurejoinInviterProgress { .. } => SecurejoinInviterProgress,
             EventType::SecurejoinJoinerProgress { .. } => SecurejoinJoinerProgress,
             EventType::ConnectivityChanged => ConnectivityChanged,
+            EventType::PingResult { .. } => PingResult,
             EventType::SelfavatarChanged => SelfavatarChanged,
             EventType::WebxdcStatusUpdate { .. } => WebxdcStatusUpdate,
+            EventType::WebxdcEphemeralUpdate { .. } => WebxdcEphemeralUpdate,
+            EventType::ImapFolderStats { .. } => ImapFolderStats,
+            EventType::SecurejoinProgress { .. } => SecurejoinProgress,
+            EventType::OutboxChanged { .. } => OutboxChanged,
         }
     }
 }
@@ -3,11 +3,12 @@
     chat::{get_chat_media, get_chat_msgs, ChatId},
     chatlist::Chatlist,
     config::Config,
-    contact::{may_be_valid_addr, Contact, ContactId},
+    contact::{Contact, ContactId},
     context::get_info,
     message::{Message, MsgId, Viewtype},
     provider::get_provider_info,
     qr,
+    tools::is_valid_email_addr,
     webxdc::StatusUpdateSerial,
 };
 use std::collections::BTreeMap;
@@ -71,7 +72,7 @@ impl CommandApi {
 
     /// Check if an email address is valid.
     async fn check_email_validity(&self, email: String) -> bool {
-        may_be_valid_addr(&email)
+        is_valid_email_addr(&email)
     }
 
     /// Get general system info.
@@ -465,7 +466,7 @@ async fn contacts_create_contact(
         name: Option<String>,
     ) -> Result<u32> {
         let ctx = self.get_context(account_id).await?;
-        if !may_be_valid_addr(&email) {
+        if !is_valid_email_addr(&email) {
             bail!(anyhow!(
                 "provided email address is not a valid email address"
             ))
@@ -567,6 +568,24 @@ async fn contacts_get_contacts_by_ids(
         }
         Ok(contacts)
     }
+
+    /// Returns a vCard containing the given contacts.
+    async fn make_vcard(&self, account_id: u32, contacts: Vec<u32>) -> Result<String> {
+        let ctx = self.get_context(account_id).await?;
+        let mut vcard = String::new();
+        for id in contacts {
+            let contact = Contact::get_by_id(&ctx, ContactId::new(id)).await?;
+            vcard += &contact.export_to_vcard(&ctx).await?;
+        }
+        Ok(vcard)
+    }
+
+    /// Imports contacts from a vCard and returns the IDs of the imported contacts.
+    async fn import_vcard(&self, account_id: u32, vcard: String) -> Result<Vec<u32>> {
+        let ctx = self.get_context(account_id).await?;
+        let contacts = Contact::import_vcard(&ctx, &vcard).await?;
+        Ok(contacts.into_iter().map(|c| c.to_u32()).collect())
+    }
     // ---------------------------------------------
     //                   chat
     // ---------------------------------------------
@@ -618,6 +637,17 @@ async fn webxdc_send_status_update(
             .await
     }
 
+    async fn webxdc_send_ephemeral_update(
+        &self,
+        account_id: u32,
+        instance_msg_id: u32,
+        update_str: String,
+    ) -> Result<()> {
+        let ctx = self.get_context(account_id).await?;
+        ctx.send_webxdc_ephemeral_update(MsgId::new(instance_msg_id), &update_str)
+            .await
+    }
+
     async fn webxdc_get_status_updates(
         &self,
         account_id: u32,
@@ -33,6 +33,7 @@ pub struct FullChat {
     is_muted: bool,
     ephemeral_timer: u32, //TODO look if there are more important properties in newer core versions
     can_send: bool,
+    creator_id: Option<u32>,
 }
 
 impl FullChat {
@@ -87,6 +88,7 @@ pub async fn try_from_dc_chat_id(context: &Context, chat_id: u32) -> Result<Self
             is_muted: chat.is_muted(),
             ephemeral_timer,
             can_send,
+            creator_id: chat.get_creator_id().map(|id| id.to_u32()),
         })
     }
 }
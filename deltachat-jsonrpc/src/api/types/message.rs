@@ -163,6 +163,9 @@ pub enum MessageViewtype {
 
     /// Message is an webxdc instance.
     Webxdc,
+
+    /// Message containing one or more contacts in vCard format.
+    Vcard,
 }
 
 impl From<Viewtype> for MessageViewtype {
@@ -179,6 +182,7 @@ fn from(viewtype: Viewtype) -> Self {
             Viewtype::File => MessageViewtype::File,
             Viewtype::VideochatInvitation => MessageViewtype::VideochatInvitation,
             Viewtype::Webxdc => MessageViewtype::Webxdc,
+            Viewtype::Vcard => MessageViewtype::Vcard,
         }
     }
 }
@@ -197,6 +201,7 @@ fn from(viewtype: MessageViewtype) -> Self {
             MessageViewtype::File => Viewtype::File,
             MessageViewtype::VideochatInvitation => Viewtype::VideochatInvitation,
             MessageViewtype::Webxdc => Viewtype::Webxdc,
+            MessageViewtype::Vcard => Viewtype::Vcard,
         }
     }
 }
@@ -292,6 +292,20 @@ export class RawClient {
     return (this._transport.request('contacts_get_contacts_by_ids', [accountId, ids] as RPC.Params)) as Promise<Record<T.U32,T.Contact>>;
   }
 
+  /**
+   * Returns a vCard containing the given contacts.
+   */
+  public makeVcard(accountId: T.U32, contacts: (T.U32)[]): Promise<string> {
+    return (this._transport.request('make_vcard', [accountId, contacts] as RPC.Params)) as Promise<string>;
+  }
+
+  /**
+   * Imports contacts from a vCard and returns the IDs of the imported contacts.
+   */
+  public importVcard(accountId: T.U32, vcard: string): Promise<(T.U32)[]> {
+    return (this._transport.request('import_vcard', [accountId, vcard] as RPC.Params)) as Promise<(T.U32)[]>;
+  }
+
   /**
    * Returns all message IDs of the given types in a chat.
    * Typically used to show a gallery.
@@ -310,6 +324,11 @@ export class RawClient {
   }
 
 
+  public webxdcSendEphemeralUpdate(accountId: T.U32, instanceMsgId: T.U32, updateStr: string): Promise<null> {
+    return (this._transport.request('webxdc_send_ephemeral_update', [accountId, instanceMsgId, updateStr] as RPC.Params)) as Promise<null>;
+  }
+
+
   public webxdcGetStatusUpdates(accountId: T.U32, instanceMsgId: T.U32, lastKnownSerial: T.U32): Promise<string> {
     return (this._transport.request('webxdc_get_status_updates', [accountId, instanceMsgId, lastKnownSerial] as RPC.Params)) as Promise<string>;
   }
@@ -129,6 +129,7 @@ export type FullChat = {
   isMuted: boolean;
   ephemeralTimer: U32;
   canSend: boolean;
+  creatorId: U32 | null;
 };
 export type Viewtype =
   | "Unknown"
@@ -175,7 +176,11 @@ export type Viewtype =
   /**
    * Message is an webxdc instance.
    */
-  | "Webxdc";
+  | "Webxdc"
+  /**
+   * Message containing one or more contacts in vCard format.
+   */
+  | "Vcard";
 export type I32 = number;
 export type U64 = number;
 export type Message = {
@@ -10,6 +10,7 @@ export type DeltaChatEvent = {
   contextId: number;
   field1: any;
   field2: any;
+  field3: any;
 };
 export type Events = Record<
   EventTypeName | "ALL",
@@ -19,8 +19,10 @@ module.exports = {
   2010: 'DC_EVENT_MSG_DELIVERED',
   2012: 'DC_EVENT_MSG_FAILED',
   2015: 'DC_EVENT_MSG_READ',
+  2016: 'DC_EVENT_MESSAGE_STATE_CHANGED',
   2020: 'DC_EVENT_CHAT_MODIFIED',
   2021: 'DC_EVENT_CHAT_EPHEMERAL_TIMER_MODIFIED',
+  2022: 'DC_EVENT_CHATLIST_ITEM_CHANGED',
   2030: 'DC_EVENT_CONTACTS_CHANGED',
   2035: 'DC_EVENT_LOCATION_CHANGED',
   2041: 'DC_EVENT_CONFIGURE_PROGRESS',
@@ -28,7 +30,13 @@ module.exports = {
   2052: 'DC_EVENT_IMEX_FILE_WRITTEN',
   2060: 'DC_EVENT_SECUREJOIN_INVITER_PROGRESS',
   2061: 'DC_EVENT_SECUREJOIN_JOINER_PROGRESS',
+  2070: 'DC_EVENT_SERVER_DELETION_PROGRESS',
   2100: 'DC_EVENT_CONNECTIVITY_CHANGED',
+  2105: 'DC_EVENT_PING_RESULT',
   2110: 'DC_EVENT_SELFAVATAR_CHANGED',
-  2120: 'DC_EVENT_WEBXDC_STATUS_UPDATE'
+  2120: 'DC_EVENT_WEBXDC_STATUS_UPDATE',
+  2121: 'DC_EVENT_WEBXDC_EPHEMERAL_UPDATE',
+  2130: 'DC_EVENT_IMAP_FOLDER_STATS',
+  2140: 'DC_EVENT_SECUREJOIN_PROGRESS',
+  2150: 'DC_EVENT_OUTBOX_CHANGED'
 }
@@ -1,6 +1,8 @@
 // Generated!
 
 export enum C {
+  DC_BLOB_LAYOUT_FLAT = 0,
+  DC_BLOB_LAYOUT_PER_CHAT = 1,
   DC_CERTCK_ACCEPT_INVALID_CERTIFICATES = 3,
   DC_CERTCK_AUTO = 0,
   DC_CERTCK_STRICT = 1,
@@ -28,6 +30,7 @@ export enum C {
   DC_DOWNLOAD_DONE = 0,
   DC_DOWNLOAD_FAILURE = 20,
   DC_DOWNLOAD_IN_PROGRESS = 1000,
+  DC_EVENT_CHATLIST_ITEM_CHANGED = 2022,
   DC_EVENT_CHAT_EPHEMERAL_TIMER_MODIFIED = 2021,
   DC_EVENT_CHAT_MODIFIED = 2020,
   DC_EVENT_CONFIGURE_PROGRESS = 2041,
@@ -37,6 +40,7 @@ export enum C {
   DC_EVENT_ERROR = 400,
   DC_EVENT_ERROR_SELF_NOT_IN_GROUP = 410,
   DC_EVENT_IMAP_CONNECTED = 102,
+  DC_EVENT_IMAP_FOLDER_STATS = 2130,
   DC_EVENT_IMAP_MESSAGE_DELETED = 104,
   DC_EVENT_IMAP_MESSAGE_MOVED = 105,
   DC_EVENT_IMEX_FILE_WRITTEN = 2052,
@@ -44,18 +48,24 @@ export enum C {
   DC_EVENT_INCOMING_MSG = 2005,
   DC_EVENT_INFO = 100,
   DC_EVENT_LOCATION_CHANGED = 2035,
+  DC_EVENT_MESSAGE_STATE_CHANGED = 2016,
   DC_EVENT_MSGS_CHANGED = 2000,
   DC_EVENT_MSGS_NOTICED = 2008,
   DC_EVENT_MSG_DELIVERED = 2010,
   DC_EVENT_MSG_FAILED = 2012,
   DC_EVENT_MSG_READ = 2015,
   DC_EVENT_NEW_BLOB_FILE = 150,
+  DC_EVENT_OUTBOX_CHANGED = 2150,
+  DC_EVENT_PING_RESULT = 2105,
   DC_EVENT_SECUREJOIN_INVITER_PROGRESS = 2060,
   DC_EVENT_SECUREJOIN_JOINER_PROGRESS = 2061,
+  DC_EVENT_SECUREJOIN_PROGRESS = 2140,
   DC_EVENT_SELFAVATAR_CHANGED = 2110,
+  DC_EVENT_SERVER_DELETION_PROGRESS = 2070,
   DC_EVENT_SMTP_CONNECTED = 101,
   DC_EVENT_SMTP_MESSAGE_SENT = 103,
   DC_EVENT_WARNING = 300,
+  DC_EVENT_WEBXDC_EPHEMERAL_UPDATE = 2121,
   DC_EVENT_WEBXDC_STATUS_UPDATE = 2120,
   DC_GCL_ADD_ALLDONE_HINT = 4,
   DC_GCL_ADD_SELF = 2,
@@ -69,6 +79,7 @@ export enum C {
   DC_IMEX_EXPORT_SELF_KEYS = 1,
   DC_IMEX_IMPORT_BACKUP = 12,
   DC_IMEX_IMPORT_SELF_KEYS = 2,
+  DC_IMEX_IMPORT_SELF_KEYS_ALLOW_EXPIRED = 3,
   DC_INFO_PROTECTION_DISABLED = 12,
   DC_INFO_PROTECTION_ENABLED = 11,
   DC_KEY_GEN_DEFAULT = 0,
@@ -87,6 +98,7 @@ export enum C {
   DC_MSG_IMAGE = 20,
   DC_MSG_STICKER = 23,
   DC_MSG_TEXT = 10,
+  DC_MSG_VCARD = 90,
   DC_MSG_VIDEO = 50,
   DC_MSG_VIDEOCHAT_INVITATION = 70,
   DC_MSG_VOICE = 41,
@@ -109,6 +121,19 @@ export enum C {
   DC_QR_WEBRTC_INSTANCE = 260,
   DC_QR_WITHDRAW_VERIFYCONTACT = 500,
   DC_QR_WITHDRAW_VERIFYGROUP = 502,
+  DC_SECUREJOIN_STAGE_AUTH_VERIFIED = 2,
+  DC_SECUREJOIN_STAGE_COMPLETED = 4,
+  DC_SECUREJOIN_STAGE_FAILED = 0,
+  DC_SECUREJOIN_STAGE_MEMBER_ADDED_SENT = 3,
+  DC_SECUREJOIN_STAGE_REQUEST_RECEIVED = 1,
+  DC_SEND_RESTRICTION_BLOCKED = 70,
+  DC_SEND_RESTRICTION_CONTACT_REQUEST = 30,
+  DC_SEND_RESTRICTION_DEVICE_CHAT = 20,
+  DC_SEND_RESTRICTION_MAILINGLIST_VARYING_LIST_POST = 50,
+  DC_SEND_RESTRICTION_MAILINGLIST_WITHOUT_LIST_POST = 40,
+  DC_SEND_RESTRICTION_NOT_MEMBER = 60,
+  DC_SEND_RESTRICTION_SPECIAL_CHAT = 10,
+  DC_SEND_RESTRICTION_UNRESTRICTED = 0,
   DC_SHOW_EMAILS_ACCEPTED_CONTACTS = 1,
   DC_SHOW_EMAILS_ALL = 2,
   DC_SHOW_EMAILS_OFF = 0,
@@ -116,6 +141,7 @@ export enum C {
   DC_SOCKET_PLAIN = 3,
   DC_SOCKET_SSL = 1,
   DC_SOCKET_STARTTLS = 2,
+  DC_STATE_IN_EXPIRED = 17,
   DC_STATE_IN_FRESH = 10,
   DC_STATE_IN_NOTICED = 13,
   DC_STATE_IN_SEEN = 16,
@@ -137,6 +163,7 @@ export enum C {
   DC_STR_CONFIGURATION_FAILED = 84,
   DC_STR_CONNECTED = 107,
   DC_STR_CONNTECTING = 108,
+  DC_STR_CONTACT = 131,
   DC_STR_CONTACT_NOT_VERIFIED = 36,
   DC_STR_CONTACT_SETUP_CHANGED = 37,
   DC_STR_CONTACT_VERIFIED = 35,
@@ -245,8 +272,10 @@ export const EventId2EventName: { [key: number]: string } = {
   2010: 'DC_EVENT_MSG_DELIVERED',
   2012: 'DC_EVENT_MSG_FAILED',
   2015: 'DC_EVENT_MSG_READ',
+  2016: 'DC_EVENT_MESSAGE_STATE_CHANGED',
   2020: 'DC_EVENT_CHAT_MODIFIED',
   2021: 'DC_EVENT_CHAT_EPHEMERAL_TIMER_MODIFIED',
+  2022: 'DC_EVENT_CHATLIST_ITEM_CHANGED',
   2030: 'DC_EVENT_CONTACTS_CHANGED',
   2035: 'DC_EVENT_LOCATION_CHANGED',
   2041: 'DC_EVENT_CONFIGURE_PROGRESS',
@@ -254,7 +283,13 @@ export const EventId2EventName: { [key: number]: string } = {
   2052: 'DC_EVENT_IMEX_FILE_WRITTEN',
   2060: 'DC_EVENT_SECUREJOIN_INVITER_PROGRESS',
   2061: 'DC_EVENT_SECUREJOIN_JOINER_PROGRESS',
+  2070: 'DC_EVENT_SERVER_DELETION_PROGRESS',
   2100: 'DC_EVENT_CONNECTIVITY_CHANGED',
+  2105: 'DC_EVENT_PING_RESULT',
   2110: 'DC_EVENT_SELFAVATAR_CHANGED',
   2120: 'DC_EVENT_WEBXDC_STATUS_UPDATE',
+  2121: 'DC_EVENT_WEBXDC_EPHEMERAL_UPDATE',
+  2130: 'DC_EVENT_IMAP_FOLDER_STATS',
+  2140: 'DC_EVENT_SECUREJOIN_PROGRESS',
+  2150: 'DC_EVENT_OUTBOX_CHANGED',
 }
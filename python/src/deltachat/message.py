@@ -441,6 +441,10 @@ def is_webxdc(self):
         """return True if it's a Webxdc message."""
         return self._view_type == const.DC_MSG_WEBXDC
 
+    def is_vcard(self):
+        """return True if it's a vCard message."""
+        return self._view_type == const.DC_MSG_VCARD
+
     def is_file(self):
         """return True if it's a file message."""
         return self._view_type == const.DC_MSG_FILE
@@ -462,6 +466,7 @@ def mark_seen(self):
     "sticker": const.DC_MSG_STICKER,
     "videochat": const.DC_MSG_VIDEOCHAT_INVITATION,
     "webxdc": const.DC_MSG_WEBXDC,
+    "vcard": const.DC_MSG_VCARD,
 }
 
 
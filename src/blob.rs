@@ -4,23 +4,28 @@
 use std::ffi::OsStr;
 use std::fmt;
 use std::io::Cursor;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, SystemTime};
 
-use anyhow::{format_err, Context as _, Error, Result};
+use anyhow::{bail, ensure, format_err, Context as _, Error, Result};
 use image::{DynamicImage, ImageFormat};
 use num_traits::FromPrimitive;
 use tokio::io::AsyncWriteExt;
 use tokio::{fs, io};
 
+use crate::chat::ChatId;
 use crate::config::Config;
 use crate::constants::{
-    MediaQuality, BALANCED_AVATAR_SIZE, BALANCED_IMAGE_SIZE, WORSE_AVATAR_SIZE, WORSE_IMAGE_SIZE,
+    BlobLayout, MediaQuality, BALANCED_AVATAR_SIZE, BALANCED_IMAGE_SIZE, WORSE_AVATAR_SIZE,
+    WORSE_IMAGE_SIZE,
 };
 use crate::context::Context;
 use crate::events::EventType;
 use crate::log::LogExt;
 use crate::message;
 use crate::message::Viewtype;
+use crate::sql;
+use crate::tools;
 
 /// Represents a file in the blob directory.
 ///
@@ -40,15 +45,37 @@ impl<'a> BlobObject<'a> {
     /// Creates a new file in the blob directory.  The name will be
     /// derived from the platform-agnostic basename of the suggested
     /// name, followed by a random number and followed by a possible
-    /// extension.  The `data` will be written into the file without
-    /// race-conditions.
+    /// extension.  If the suggested name has no extension, it is
+    /// derived from the content type sniffed from `data`.  The `data`
+    /// will be written into the file without race-conditions.
     pub async fn create(
         context: &'a Context,
         suggested_name: &str,
         data: &[u8],
     ) -> Result<BlobObject<'a>> {
+        let max_blob_size = context.get_config_u64(Config::MaxBlobSizeBytes).await?;
+        if max_blob_size > 0 && data.len() as u64 > max_blob_size {
+            bail!(
+                "{} has {} bytes, exceeding the limit of {} bytes",
+                suggested_name,
+                data.len(),
+                max_blob_size
+            );
+        }
         let blobdir = context.get_blobdir();
-        let (stem, ext) = BlobObject::sanitise_name(suggested_name);
+        let (stem, mut ext) = BlobObject::sanitise_name(suggested_name);
+        let content_type = tools::detect_content_type_from_bytes("", suggested_name, data);
+        if ext.is_empty() {
+            if let Some(detected) = tools::get_extension_for_content_type(content_type) {
+                ext = format!(".{}", detected);
+            }
+        }
+        // UTF-16 text cannot be decoded without its BOM, so only the UTF-8 BOM is removed.
+        let data = if content_type.starts_with("text/") && data.starts_with(tools::UTF8_BOM) {
+            tools::strip_bom(data)
+        } else {
+            data
+        };
         let (name, mut file) = BlobObject::create_new_file(context, blobdir, &stem, &ext).await?;
         file.write_all(data).await.context("file write failure")?;
 
@@ -88,7 +115,8 @@ async fn create_new_file(
                 Err(err) => {
                     if attempt >= MAX_ATTEMPT {
                         return Err(err).context("failed to create file");
-                    } else if attempt == 1 && !dir.exists() {
+                    } else if !dir.exists() {
+                        // The directory may also be removed concurrently by housekeeping.
                         fs::create_dir_all(dir).await.ok_or_log(context);
                     } else {
                         name = format!("{}-{}{}", stem, rand::random::<u32>(), ext);
@@ -130,13 +158,41 @@ pub async fn create_and_copy(context: &'a Context, src: &Path) -> Result<BlobObj
         Ok(blob)
     }
 
+    /// Moves the blob into a subdirectory of the blob directory, see [`blob_subdir`].
+    ///
+    /// The file name is kept unless it is already used in the subdirectory,
+    /// the returned [BlobObject] refers to the new location.
+    pub(crate) async fn move_to_subdir(
+        &self,
+        context: &'a Context,
+        subdir: &str,
+    ) -> Result<BlobObject<'a>> {
+        ensure!(
+            name_from_rel_path(Path::new(subdir)).as_deref() == Some(subdir),
+            "not an acceptable blob subdirectory: {}",
+            subdir
+        );
+        let (stem, ext) = BlobObject::sanitise_name(self.as_file_name());
+        let dir = context.get_blobdir().join(subdir);
+        let (name, _file) = BlobObject::create_new_file(context, &dir, &stem, &ext).await?;
+        let blob = BlobObject {
+            blobdir: context.get_blobdir(),
+            name: format!("$BLOBDIR/{}/{}", subdir, name),
+        };
+        if let Err(err) = fs::rename(self.to_abs_path(), blob.to_abs_path()).await {
+            fs::remove_file(blob.to_abs_path()).await.ok();
+            return Err(err).context("failed to move blob");
+        }
+        Ok(blob)
+    }
+
     /// Creates a blob from a file, possibly copying it to the blobdir.
     ///
     /// If the source file is not a path to into the blob directory
     /// the file will be copied into the blob directory first.  If the
     /// source file is already in the blobdir it will not be copied
-    /// and only be created if it is a valid blobname, that is no
-    /// subdirectory is used and [BlobObject::sanitise_name] does not
+    /// and only be created if it is a valid blobname, that is at most
+    /// one subdirectory is used and [BlobObject::sanitise_name] does not
     /// modify the filename.
     ///
     /// Paths into the blob directory may be either defined by an absolute path
@@ -153,19 +209,16 @@ pub async fn new_from_path(context: &'a Context, src: &Path) -> Result<BlobObjec
 
     /// Returns a [BlobObject] for an existing blob from a path.
     ///
-    /// The path must designate a file directly in the blobdir and
-    /// must use a valid blob name.  That is after sanitisation the
-    /// name must still be the same, that means it must be valid UTF-8
-    /// and not have any special characters in it.
+    /// The path must designate a file in the blobdir or in one of its
+    /// subdirectories and must use a valid blob name.  That is after
+    /// sanitisation the name must still be the same, that means it must
+    /// be valid UTF-8 and not have any special characters in it.
     pub fn from_path(context: &'a Context, path: &Path) -> Result<BlobObject<'a>> {
         let rel_path = path
             .strip_prefix(context.get_blobdir())
             .context("wrong blobdir")?;
-        if !BlobObject::is_acceptible_blob_name(rel_path) {
-            return Err(format_err!("wrong name"));
-        }
-        let name = rel_path.to_str().context("wrong name")?;
-        BlobObject::from_name(context, name.to_string())
+        let name = name_from_rel_path(rel_path).context("wrong name")?;
+        BlobObject::from_name(context, name)
     }
 
     /// Returns a [BlobObject] for an existing blob.
@@ -179,9 +232,8 @@ pub fn from_name(context: &'a Context, name: String) -> Result<BlobObject<'a>> {
             true => name.splitn(2, '/').last().unwrap().to_string(),
             false => name,
         };
-        if !BlobObject::is_acceptible_blob_name(&name) {
-            return Err(format_err!("not an acceptable blob name: {}", &name));
-        }
+        let name = name_from_rel_path(Path::new(&name))
+            .with_context(|| format!("not an acceptable blob name: {}", &name))?;
         Ok(BlobObject {
             blobdir: context.get_blobdir(),
             name: format!("$BLOBDIR/{}", name),
@@ -213,7 +265,7 @@ pub fn as_file_name(&self) -> &str {
 
     /// The path relative in the blob directory.
     pub fn as_rel_path(&self) -> &Path {
-        Path::new(self.as_file_name())
+        Path::new(self.name.strip_prefix("$BLOBDIR/").unwrap_or(&self.name))
     }
 
     /// Returns the extension of the blob.
@@ -501,15 +553,162 @@ fn encoded_img_exceeds_bytes(
     Ok(false)
 }
 
+/// Returns the blob name for a path relative to the blob directory.
+///
+/// The path may contain at most one subdirectory, as used by [`BlobLayout::PerChat`],
+/// and all its components must be acceptable blob names.
+pub(crate) fn name_from_rel_path(rel_path: &Path) -> Option<String> {
+    let mut parts = Vec::new();
+    for component in rel_path.components() {
+        match component {
+            Component::Normal(part) if BlobObject::is_acceptible_blob_name(part) => {
+                parts.push(part.to_str()?)
+            }
+            _ => return None,
+        }
+    }
+    match parts.len() {
+        1 | 2 => Some(parts.join("/")),
+        _ => None,
+    }
+}
+
+/// Returns the subdirectory of the blob directory for attachments received in `chat_id`
+/// or `None` if the configured [`BlobLayout`] stores them directly in the blob directory.
+pub(crate) async fn blob_subdir(context: &Context, chat_id: ChatId) -> Result<Option<String>> {
+    let layout =
+        BlobLayout::from_i32(context.get_config_int(Config::BlobLayout).await?).unwrap_or_default();
+    Ok(match layout {
+        BlobLayout::Flat => None,
+        BlobLayout::PerChat => Some(format!("chat-{}", chat_id.to_u32())),
+    })
+}
+
+/// Returns true if `name` is a subdirectory created by [`blob_subdir`], i.e. `chat-<digits>`.
+///
+/// Other directories in the blob directory are not created by us and are left untouched.
+fn is_blob_subdir(name: &str) -> bool {
+    name.strip_prefix("chat-").map_or(false, |chat_id| {
+        !chat_id.is_empty() && chat_id.bytes().all(|b| b.is_ascii_digit())
+    })
+}
+
+/// Lists the files in the blob directory and in the subdirectories created by [`blob_subdir`].
+///
+/// Returns the blob names, i.e. the paths relative to the blob directory as stored
+/// in the database without the `$BLOBDIR/` prefix, together with the directory entries.
+pub(crate) async fn read_blobdir(context: &Context) -> Result<Vec<(String, fs::DirEntry)>> {
+    let blobdir = context.get_blobdir();
+    let mut entries = Vec::new();
+    let mut dir_handle = fs::read_dir(blobdir)
+        .await
+        .with_context(|| format!("cannot open {}", blobdir.display()))?;
+    while let Some(entry) = dir_handle.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if !entry.file_type().await?.is_dir() {
+            entries.push((name, entry));
+            continue;
+        }
+        if !is_blob_subdir(&name) {
+            continue;
+        }
+        let mut subdir_handle = fs::read_dir(entry.path())
+            .await
+            .with_context(|| format!("cannot open {}", entry.path().display()))?;
+        while let Some(entry) = subdir_handle.next_entry().await? {
+            let file_name = entry.file_name();
+            entries.push((format!("{}/{}", name, file_name.to_string_lossy()), entry));
+        }
+    }
+    Ok(entries)
+}
+
+/// Removes the empty subdirectories created by [`blob_subdir`],
+/// e.g. the ones left by [`BlobLayout::PerChat`] after all blobs of a chat were deleted.
+pub(crate) async fn remove_empty_subdirs(context: &Context) -> Result<()> {
+    let blobdir = context.get_blobdir();
+    let mut dir_handle = fs::read_dir(blobdir)
+        .await
+        .with_context(|| format!("cannot open {}", blobdir.display()))?;
+    while let Some(entry) = dir_handle.next_entry().await? {
+        if !is_blob_subdir(&entry.file_name().to_string_lossy()) {
+            continue;
+        }
+        // Removing a directory fails if it is not empty.
+        if entry.file_type().await?.is_dir() && fs::remove_dir(entry.path()).await.is_ok() {
+            info!(
+                context,
+                "Removed empty blob subdirectory {:?}.",
+                entry.file_name()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Blobs younger than this are not collected by [`gc_orphans`],
+/// they may belong to a message that is just being created or received.
+const GC_ORPHANS_MIN_AGE: Duration = Duration::from_secs(60);
+
+/// Deletes blobs that are not referenced by any message, chat, contact or config.
+///
+/// [`message::delete_msgs`] only deletes the files of the deleted messages,
+/// this also catches e.g. previews and files of deleted chats without waiting for housekeeping.
+/// Recently created or modified blobs are kept.
+///
+/// Returns the number of deleted blobs.
+pub async fn gc_orphans(context: &Context) -> Result<usize> {
+    gc_orphans_older_than(context, GC_ORPHANS_MIN_AGE).await
+}
+
+async fn gc_orphans_older_than(context: &Context, min_age: Duration) -> Result<usize> {
+    let files_in_use = sql::get_files_in_use(context).await?;
+    let keep_files_newer_than = SystemTime::now()
+        .checked_sub(min_age)
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+
+    let mut deleted = 0;
+    for (name, entry) in read_blobdir(context).await? {
+        if sql::is_blob_in_use(&files_in_use, &name) {
+            continue;
+        }
+
+        let metadata = match entry.metadata().await {
+            Ok(metadata) if metadata.is_file() => metadata,
+            _ => continue,
+        };
+        let recently_created = metadata
+            .created()
+            .map_or(false, |t| t > keep_files_newer_than);
+        let recently_modified = metadata
+            .modified()
+            .map_or(false, |t| t > keep_files_newer_than);
+        if recently_created || recently_modified {
+            continue;
+        }
+
+        info!(context, "Deleting orphaned blob {:?}", name);
+        if tools::delete_file(context, entry.path()).await {
+            deleted += 1;
+        }
+    }
+
+    Ok(deleted)
+}
+
 #[cfg(test)]
 mod tests {
     use fs::File;
 
+    use std::collections::HashSet;
+
     use anyhow::Result;
     use image::{GenericImageView, Pixel};
 
     use crate::chat::{self, create_group_chat, ProtectionStatus};
     use crate::message::Message;
+    use crate::param::Param;
+    use crate::sql;
     use crate::test_utils::{self, TestContext};
 
     use super::*;
@@ -534,6 +733,20 @@ async fn test_create() {
         assert_eq!(blob.to_abs_path(), t.get_blobdir().join("foo"));
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_create_sniffed_ext() {
+        let t = TestContext::new().await;
+        let blob = BlobObject::create(&t, "foo", b"\x89PNG\r\n\x1a\n")
+            .await
+            .unwrap();
+        assert_eq!(blob.as_name(), "$BLOBDIR/foo.png");
+
+        let blob = BlobObject::create(&t, "bar.bin", b"%PDF-1.4")
+            .await
+            .unwrap();
+        assert_eq!(blob.as_name(), "$BLOBDIR/bar.bin");
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_lowercase_ext() {
         let t = TestContext::new().await;
@@ -541,6 +754,47 @@ async fn test_lowercase_ext() {
         assert_eq!(blob.as_name(), "$BLOBDIR/foo.txt");
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_create_strips_utf8_bom() {
+        let t = TestContext::new().await;
+        let blob = BlobObject::create(&t, "foo.txt", b"\xEF\xBB\xBFhello")
+            .await
+            .unwrap();
+        assert_eq!(fs::read(blob.to_abs_path()).await.unwrap(), b"hello");
+
+        // The BOM is kept for non-text files and for UTF-16 text.
+        let blob = BlobObject::create(&t, "foo.pdf", b"\xEF\xBB\xBFhello")
+            .await
+            .unwrap();
+        assert_eq!(
+            fs::read(blob.to_abs_path()).await.unwrap(),
+            b"\xEF\xBB\xBFhello"
+        );
+        let blob = BlobObject::create(&t, "foo.html", b"\xFF\xFE<\x00")
+            .await
+            .unwrap();
+        assert_eq!(
+            fs::read(blob.to_abs_path()).await.unwrap(),
+            b"\xFF\xFE<\x00"
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_create_size_limit() {
+        let t = TestContext::new().await;
+        t.set_config(Config::MaxBlobSizeBytes, Some("5"))
+            .await
+            .unwrap();
+        assert!(BlobObject::create(&t, "foo.bin", b"hello").await.is_ok());
+        assert!(BlobObject::create(&t, "bar.bin", b"hello!").await.is_err());
+        assert!(!t.get_blobdir().join("bar.bin").exists());
+
+        t.set_config(Config::MaxBlobSizeBytes, Some("0"))
+            .await
+            .unwrap();
+        assert!(BlobObject::create(&t, "bar.bin", b"hello!").await.is_ok());
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_as_file_name() {
         let t = TestContext::new().await;
@@ -990,4 +1244,138 @@ async fn test_increation_not_blobdir() -> Result<()> {
 
         Ok(())
     }
+
+    #[test]
+    fn test_name_from_rel_path() {
+        assert_eq!(
+            name_from_rel_path(Path::new("foo.txt")),
+            Some("foo.txt".to_string())
+        );
+        assert_eq!(
+            name_from_rel_path(Path::new("chat-10/foo.txt")),
+            Some("chat-10/foo.txt".to_string())
+        );
+        assert_eq!(name_from_rel_path(Path::new("a/b/foo.txt")), None);
+        assert_eq!(name_from_rel_path(Path::new("../foo.txt")), None);
+        assert_eq!(name_from_rel_path(Path::new("/foo.txt")), None);
+        assert_eq!(name_from_rel_path(Path::new("")), None);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_per_chat_layout() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let bob = TestContext::new_bob().await;
+        bob.set_config(Config::BlobLayout, Some("1")).await?;
+
+        let chat = alice.create_chat(&bob).await;
+        let file = alice.get_blobdir().join("hello.txt");
+        fs::write(&file, b"hello").await?;
+        let mut msg = Message::new(Viewtype::File);
+        msg.set_file(file.to_str().unwrap(), None);
+        let sent = alice.send_msg(chat.id, &mut msg).await;
+
+        let msg = bob.recv_msg(&sent).await;
+        let path = msg.get_file(&bob).unwrap();
+        assert_eq!(
+            path.parent().unwrap(),
+            bob.get_blobdir()
+                .join(format!("chat-{}", msg.chat_id.to_u32()))
+        );
+        assert_eq!(fs::read(&path).await?, b"hello");
+        assert!(msg
+            .param
+            .get(Param::File)
+            .unwrap()
+            .starts_with("$BLOBDIR/chat-"));
+
+        // Blobs in subdirectories are deleted together with their message,
+        // housekeeping removes the empty subdirectory.
+        message::delete_msgs(&bob, &[msg.id]).await?;
+        assert!(!path.exists());
+        assert!(path.parent().unwrap().exists());
+        sql::remove_unused_files(&bob).await?;
+        assert!(!path.parent().unwrap().exists());
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_foreign_subdirs_untouched() -> Result<()> {
+        let t = TestContext::new().await;
+        let blobdir = t.get_blobdir();
+        for dir in ["chat-12", "chat-13", "chat-", "chat-1x", "other", "empty"] {
+            fs::create_dir(blobdir.join(dir)).await?;
+        }
+        for file in [
+            "chat-12/foo.txt",
+            "chat-/foo.txt",
+            "chat-1x/foo.txt",
+            "other/foo.txt",
+        ] {
+            fs::write(blobdir.join(file), b"foo").await?;
+        }
+
+        let names: Vec<String> = read_blobdir(&t)
+            .await?
+            .into_iter()
+            .map(|(name, _)| name)
+            .filter(|name| name.contains('/'))
+            .collect();
+        assert_eq!(names, vec!["chat-12/foo.txt".to_string()]);
+
+        remove_empty_subdirs(&t).await?;
+        assert!(blobdir.join("chat-12").exists());
+        assert!(!blobdir.join("chat-13").exists());
+        assert!(blobdir.join("empty").exists());
+
+        sql::remove_stray_files(&t, &HashSet::new()).await?;
+        for file in ["chat-/foo.txt", "chat-1x/foo.txt", "other/foo.txt"] {
+            assert!(blobdir.join(file).exists());
+        }
+        assert!(!blobdir.join("chat-12/foo.txt").exists());
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_gc_orphans() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let bob = TestContext::new_bob().await;
+        let chat = alice.create_chat(&bob).await;
+
+        let blob = BlobObject::create(&alice, "kept.txt", b"hello").await?;
+        let mut msg = Message::new(Viewtype::File);
+        msg.set_file(blob.as_name(), None);
+        alice.send_msg(chat.id, &mut msg).await;
+
+        let blobdir = alice.get_blobdir();
+        fs::create_dir(blobdir.join("chat-5")).await?;
+        let orphans = [
+            blobdir.join("orphan.txt"),
+            blobdir.join("chat-5/orphan.txt"),
+        ];
+        for orphan in &orphans {
+            fs::write(orphan, b"orphan").await?;
+        }
+
+        // The blobs are too new to be collected.
+        assert_eq!(gc_orphans(&alice).await?, 0);
+        assert!(orphans.iter().all(|orphan| orphan.exists()));
+
+        assert!(gc_orphans_older_than(&alice, Duration::ZERO).await? >= orphans.len());
+        assert!(orphans.iter().all(|orphan| !orphan.exists()));
+        assert!(blob.to_abs_path().exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_blob_subdir() {
+        assert!(is_blob_subdir("chat-1"));
+        assert!(is_blob_subdir("chat-1234"));
+        assert!(!is_blob_subdir("chat-"));
+        assert!(!is_blob_subdir("chat-12a"));
+        assert!(!is_blob_subdir("Chat-12"));
+        assert!(!is_blob_subdir("other"));
+    }
 }
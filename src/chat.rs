@@ -8,6 +8,7 @@
 
 use anyhow::{bail, ensure, Context as _, Result};
 use deltachat_derive::{FromSql, ToSql};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
 use serde::{Deserialize, Serialize};
 
 use crate::aheader::EncryptPreference;
@@ -20,12 +21,14 @@
 };
 use crate::contact::{Contact, ContactId, Origin, VerifiedStatus};
 use crate::context::Context;
-use crate::ephemeral::Timer as EphemeralTimer;
+use crate::ephemeral::{stock_ephemeral_timer_changed, Timer as EphemeralTimer};
 use crate::events::EventType;
 use crate::html::new_html_mimepart;
-use crate::message::{self, Message, MessageState, MsgId, Viewtype};
+use crate::message::{
+    self, get_blob_bytes, Message, MessageState, MessengerMessage, MsgId, Viewtype,
+};
 use crate::mimefactory::MimeFactory;
-use crate::mimeparser::SystemMessage;
+use crate::mimeparser::{get_list_post, SystemMessage};
 use crate::param::{Param, Params};
 use crate::peerstate::{Peerstate, PeerstateVerifiedStatus};
 use crate::receive_imf::ReceivedMsg;
@@ -34,7 +37,8 @@
 use crate::stock_str;
 use crate::tools::{
     create_id, create_outgoing_rfc724_mid, create_smeared_timestamp, create_smeared_timestamps,
-    get_abs_path, gm2local_offset, improve_single_line_input, time, IsNoneOrEmpty,
+    get_abs_path, get_filebytes, gm2local_offset, improve_single_line_input, time,
+    timestamp_to_str, IsNoneOrEmpty,
 };
 use crate::webxdc::WEBXDC_SUFFIX;
 use crate::{location, sql};
@@ -81,6 +85,34 @@ fn default() -> Self {
     }
 }
 
+/// Reason why messages can not be sent to a chat, see [`Chat::why_cant_send`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive, ToPrimitive)]
+#[repr(u32)]
+pub enum CantSendReason {
+    /// The chat is a special chat such as the trash or the archive link.
+    SpecialChat = 10,
+
+    /// The chat is the device chat.
+    DeviceChat = 20,
+
+    /// The chat is a contact request that has to be accepted first.
+    ContactRequest = 30,
+
+    /// The chat is a mailing list without `List-Post` header,
+    /// e.g. a notification-only sender.
+    MailinglistWithoutListPost = 40,
+
+    /// The chat is a mailing list using a different `List-Post` header in each message,
+    /// so it is unknown where replies should go to.
+    MailinglistVaryingListPost = 50,
+
+    /// The user is not a member of the group, e.g. because they left it.
+    NotMember = 60,
+
+    /// The chat is blocked.
+    Blocked = 70,
+}
+
 /// Chat ID, including reserved IDs.
 ///
 /// Some chat IDs are reserved to identify special chat types.  This
@@ -224,6 +256,7 @@ pub(crate) async fn create_multiuser_record(
         create_protected: ProtectionStatus,
         param: Option<String>,
     ) -> Result<Self> {
+        let created_timestamp = create_smeared_timestamp(context).await;
         let row_id =
             context.sql.insert(
                 "INSERT INTO chats (type, name, grpid, blocked, created_timestamp, protected, param) VALUES(?, ?, ?, ?, ?, ?, ?);",
@@ -232,13 +265,16 @@ pub(crate) async fn create_multiuser_record(
                     grpname,
                     grpid,
                     create_blocked,
-                    create_smeared_timestamp(context).await,
+                    created_timestamp,
                     create_protected,
                     param.unwrap_or_default(),
                 ],
             ).await?;
 
         let chat_id = ChatId::new(u32::try_from(row_id)?);
+        chat_id
+            .apply_default_ephemeral_timer(context, created_timestamp)
+            .await?;
         info!(
             context,
             "Created group/mailinglist '{}' grpid={} as {}", grpname, grpid, chat_id
@@ -247,6 +283,34 @@ pub(crate) async fn create_multiuser_record(
         Ok(chat_id)
     }
 
+    /// Sets the creator of a group, see [`Chat::get_creator_id`].
+    pub(crate) async fn set_creator_id(
+        self,
+        context: &Context,
+        creator_id: ContactId,
+    ) -> Result<()> {
+        context
+            .sql
+            .execute(
+                "UPDATE chats SET creator_id=? WHERE id=?;",
+                paramsv![creator_id, self],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Sets the admin of a group, see [`Chat::get_admin_id`].
+    pub(crate) async fn set_admin_id(self, context: &Context, admin_id: ContactId) -> Result<()> {
+        context
+            .sql
+            .execute(
+                "UPDATE chats SET admin_contact_id=? WHERE id=?;",
+                paramsv![admin_id, self],
+            )
+            .await?;
+        Ok(())
+    }
+
     pub async fn set_selfavatar_timestamp(self, context: &Context, timestamp: i64) -> Result<()> {
         context
             .sql
@@ -343,6 +407,7 @@ pub async fn accept(self, context: &Context) -> Result<()> {
 
         if self.set_blocked(context, Blocked::Not).await? {
             context.emit_event(EventType::ChatModified(self));
+            context.emit_event(EventType::ChatlistItemChanged(self));
         }
 
         Ok(())
@@ -727,6 +792,49 @@ pub async fn get_msg_cnt(self, context: &Context) -> Result<usize> {
         Ok(count as usize)
     }
 
+    /// Returns the storage used by the chat
+    /// as `(message bytes, attachment bytes, message count)`, see [`get_storage_usage`].
+    pub async fn get_storage_usage(self, context: &Context) -> Result<(u64, u64, u32)> {
+        update_blob_bytes(context, Some(self)).await?;
+        let usage = context
+            .sql
+            .query_row(
+                "SELECT SUM(bytes), SUM(IFNULL(blob_bytes, 0)), COUNT(*)
+                   FROM msgs
+                  WHERE chat_id=?;",
+                paramsv![self],
+                |row| {
+                    let msg_bytes: Option<i64> = row.get(0)?;
+                    let blob_bytes: Option<i64> = row.get(1)?;
+                    let msg_count: u32 = row.get(2)?;
+                    Ok((
+                        u64::try_from(msg_bytes.unwrap_or_default()).unwrap_or_default(),
+                        u64::try_from(blob_bytes.unwrap_or_default()).unwrap_or_default(),
+                        msg_count,
+                    ))
+                },
+            )
+            .await?;
+        Ok(usage)
+    }
+
+    /// Exports all chat members except SELF as a multi-record vCard,
+    /// see [`Contact::export_to_vcard`].
+    ///
+    /// The result can be written to a `.vcf` file
+    /// and sent as a message of type [`Viewtype::Vcard`].
+    pub async fn export_members_vcard(self, context: &Context) -> Result<Vec<u8>> {
+        let mut vcard = String::new();
+        for contact_id in get_chat_contacts(context, self).await? {
+            if contact_id == ContactId::SELF {
+                continue;
+            }
+            let contact = Contact::load_from_db(context, contact_id).await?;
+            vcard += &contact.export_to_vcard(context).await?;
+        }
+        Ok(vcard.into_bytes())
+    }
+
     pub async fn get_fresh_msg_cnt(self, context: &Context) -> Result<usize> {
         // this function is typically used to show a badge counter beside _each_ chatlist item.
         // to make this as fast as possible, esp. on older devices, we added an combined index over the rows used for querying.
@@ -752,6 +860,18 @@ pub async fn get_fresh_msg_cnt(self, context: &Context) -> Result<usize> {
         Ok(count as usize)
     }
 
+    /// Returns the number of messages in a chat,
+    /// e.g. to display it in the chat header.
+    pub async fn get_message_count(self, context: &Context) -> Result<u32> {
+        Ok(u32::try_from(self.get_msg_cnt(context).await?)?)
+    }
+
+    /// Returns the number of unread messages in a chat,
+    /// same as [`ChatId::get_fresh_msg_cnt`].
+    pub async fn get_unread_count(self, context: &Context) -> Result<u32> {
+        Ok(u32::try_from(self.get_fresh_msg_cnt(context).await?)?)
+    }
+
     pub(crate) async fn get_param(self, context: &Context) -> Result<Params> {
         let res: Option<String> = context
             .sql
@@ -807,12 +927,18 @@ async fn get_parent_mime_headers(
         if let Some((rfc724_mid, mime_in_reply_to, mime_references, error)) = self
             .parent_query(
                 context,
-                "rfc724_mid, mime_in_reply_to, mime_references, error",
+                "rfc724_mid, mime_in_reply_to, mime_references, error, param",
                 |row: &rusqlite::Row| {
                     let rfc724_mid: String = row.get(0)?;
                     let mime_in_reply_to: String = row.get(1)?;
                     let mime_references: String = row.get(2)?;
                     let error: String = row.get(3)?;
+                    let param: Params = row.get::<_, String>(4)?.parse().unwrap_or_default();
+                    // Reply to the Message-ID the sender used, not to a synthetic one.
+                    let rfc724_mid = param
+                        .get(Param::OriginalRfc724Mid)
+                        .map(|mid| mid.to_string())
+                        .unwrap_or(rfc724_mid);
                     Ok((rfc724_mid, mime_in_reply_to, mime_references, error))
                 },
             )
@@ -949,6 +1075,26 @@ pub(crate) async fn set_gossiped_timestamp(
     }
 }
 
+/// Returns `Message-ID`, `In-Reply-To` and `References` of the quoted message
+/// for use as parent headers of a reply to it.
+async fn get_quote_mime_headers(
+    context: &Context,
+    quote: &Message,
+) -> Result<Option<(String, String, String)>> {
+    context
+        .sql
+        .query_row_optional(
+            "SELECT mime_in_reply_to, mime_references FROM msgs WHERE id=?",
+            paramsv![quote.id],
+            |row| {
+                let mime_in_reply_to: String = row.get(0)?;
+                let mime_references: String = row.get(1)?;
+                Ok((quote.rfc724_mid.clone(), mime_in_reply_to, mime_references))
+            },
+        )
+        .await
+}
+
 impl std::fmt::Display for ChatId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if self.is_trash() {
@@ -1006,6 +1152,7 @@ pub struct Chat {
     is_sending_locations: bool,
     pub mute_duration: MuteDuration,
     protected: ProtectionStatus,
+    creator_id: Option<ContactId>,
 }
 
 impl Chat {
@@ -1015,7 +1162,8 @@ pub async fn load_from_db(context: &Context, chat_id: ChatId) -> Result<Self> {
             .sql
             .query_row(
                 "SELECT c.type, c.name, c.grpid, c.param, c.archived,
-                    c.blocked, c.locations_send_until, c.muted_until, c.protected
+                    c.blocked, c.locations_send_until, c.muted_until, c.protected,
+                    c.creator_id
              FROM chats c
              WHERE c.id=?;",
                 paramsv![chat_id],
@@ -1031,6 +1179,7 @@ pub async fn load_from_db(context: &Context, chat_id: ChatId) -> Result<Self> {
                         is_sending_locations: row.get(6)?,
                         mute_duration: row.get(7)?,
                         protected: row.get(8)?,
+                        creator_id: row.get(9)?,
                     };
                     Ok(c)
                 },
@@ -1084,12 +1233,35 @@ pub fn is_mailing_list(&self) -> bool {
 
     /// Returns true if user can send messages to this chat.
     pub async fn can_send(&self, context: &Context) -> Result<bool> {
-        let cannot_send = self.id.is_special()
-            || self.is_device_talk()
-            || self.is_contact_request()
-            || (self.is_mailing_list() && self.param.get(Param::ListPost).is_none_or_empty())
-            || !self.is_self_in_chat(context).await?;
-        Ok(!cannot_send)
+        Ok(self.why_cant_send(context).await?.is_none())
+    }
+
+    /// Returns the reason why messages can not be sent to this chat,
+    /// `None` if they can be sent.
+    pub async fn why_cant_send(&self, context: &Context) -> Result<Option<CantSendReason>> {
+        let reason = if self.id.is_special() {
+            Some(CantSendReason::SpecialChat)
+        } else if self.is_device_talk() {
+            Some(CantSendReason::DeviceChat)
+        } else if self.is_contact_request() {
+            Some(CantSendReason::ContactRequest)
+        } else if self.blocked == Blocked::Yes {
+            Some(CantSendReason::Blocked)
+        } else if self.is_mailing_list() {
+            // An empty `ListPost` is set by `apply_mailinglist_changes()`
+            // when the `List-Post` header changes between messages.
+            match self.param.get(Param::ListPost) {
+                None => Some(CantSendReason::MailinglistWithoutListPost),
+                Some("") => Some(CantSendReason::MailinglistVaryingListPost),
+                Some(_) => None,
+            }
+        } else if !self.is_self_in_chat(context).await? {
+            // Set by `apply_group_changes()` when we were removed from the group.
+            Some(CantSendReason::NotMember)
+        } else {
+            None
+        };
+        Ok(reason)
     }
 
     /// Checks if the user is part of a chat
@@ -1103,6 +1275,49 @@ pub(crate) async fn is_self_in_chat(&self, context: &Context) -> Result<bool> {
         }
     }
 
+    /// Returns the contact who created the group,
+    /// [`ContactId::SELF`] for groups created on this device.
+    ///
+    /// Unlike the admin, see [`Chat::get_admin_id`], the creator is also recorded for ad-hoc groups.
+    /// Returns `None` for groups created before creators were recorded
+    /// and for groups joined via QR code.
+    pub fn get_creator_id(&self) -> Option<ContactId> {
+        self.creator_id
+    }
+
+    /// Returns the admin of a group,
+    /// i.e. the sender of the message that created the group on this device
+    /// or [`ContactId::SELF`] for groups created with [`create_group_chat`].
+    ///
+    /// Returns `None` for groups created before admins were recorded
+    /// and for ad-hoc groups, which have no admin as all members may rename them.
+    pub async fn get_admin_id(&self, context: &Context) -> Result<Option<ContactId>> {
+        let admin_id = context
+            .sql
+            .query_get_value::<Option<ContactId>>(
+                "SELECT admin_contact_id FROM chats WHERE id=?;",
+                paramsv![self.id],
+            )
+            .await?;
+        Ok(admin_id.flatten())
+    }
+
+    /// Returns true if the user may rename the chat,
+    /// i.e. if the user is the admin or the chat has no admin.
+    pub async fn can_edit_name(&self, context: &Context) -> Result<bool> {
+        self.is_name_editable_by(context, ContactId::SELF).await
+    }
+
+    /// Returns true if name changes of `contact_id` are accepted.
+    pub(crate) async fn is_name_editable_by(
+        &self,
+        context: &Context,
+        contact_id: ContactId,
+    ) -> Result<bool> {
+        let admin_id = self.get_admin_id(context).await?;
+        Ok(admin_id.is_none() || admin_id == Some(contact_id))
+    }
+
     pub async fn update_param(&mut self, context: &Context) -> Result<()> {
         context
             .sql
@@ -1134,6 +1349,26 @@ pub fn get_mailinglist_addr(&self) -> &str {
         self.param.get(Param::ListPost).unwrap_or_default()
     }
 
+    /// Returns mailing list description from the `List-Id` header, if any.
+    pub fn get_mailinglist_description(&self) -> Option<&str> {
+        self.param.get(Param::ListDescription)
+    }
+
+    /// Returns mailing list help URL from the `List-Help` header, if any.
+    pub fn get_mailinglist_help_url(&self) -> Option<&str> {
+        self.param.get(Param::ListHelp)
+    }
+
+    /// Returns mailing list archive URL from the `List-Archive` header, if any.
+    pub fn get_mailinglist_archive_url(&self) -> Option<&str> {
+        self.param.get(Param::ListArchive)
+    }
+
+    /// Returns the welcome message sent to new members of the group, if any.
+    pub fn get_welcome_message(&self) -> Option<&str> {
+        self.param.get(Param::WelcomeMessage)
+    }
+
     /// Returns profile image path for the chat.
     pub async fn get_profile_image(&self, context: &Context) -> Result<Option<PathBuf>> {
         if let Some(image_rel) = self.param.get(Param::ProfileImage) {
@@ -1317,8 +1552,24 @@ async fn prepare_msg_raw(
         // as self-talks are mainly used to transfer data between devices,
         // we do not set In-Reply-To/References in this case.
         if !self.is_self_talk() {
-            if let Some((parent_rfc724_mid, parent_in_reply_to, parent_references)) =
-                self.id.get_parent_mime_headers(context).await?
+            // In mailing lists and classic email threads, a quoted reply is threaded below the
+            // quoted message rather than below the latest message of the chat,
+            // so that list archives and other MUAs show it at the right place.
+            let quote = match msg.quoted_message(context).await? {
+                Some(quote)
+                    if self.typ == Chattype::Mailinglist
+                        || quote.is_dc_message == MessengerMessage::No =>
+                {
+                    Some(quote)
+                }
+                _ => None,
+            };
+            let is_quote_reply = quote.is_some();
+            let parent_headers = match quote {
+                Some(quote) => get_quote_mime_headers(context, &quote).await?,
+                None => self.id.get_parent_mime_headers(context).await?,
+            };
+            if let Some((parent_rfc724_mid, parent_in_reply_to, parent_references)) = parent_headers
             {
                 // "In-Reply-To:" is not changed if it is set manually.
                 // This does not affect "References:" header, it will contain "default parent" (the
@@ -1343,6 +1594,9 @@ async fn prepare_msg_raw(
                     new_references = format!("{} {}", parent_in_reply_to, parent_rfc724_mid);
                 } else if !parent_in_reply_to.is_empty() {
                     new_references = parent_in_reply_to;
+                } else if is_quote_reply && !parent_rfc724_mid.is_empty() {
+                    // the quoted message starts the thread, reference it.
+                    new_references = parent_rfc724_mid;
                 } else {
                     // as a fallback, use our Message-ID, see reasoning below.
                     new_references = new_rfc724_mid.clone();
@@ -1391,7 +1645,7 @@ async fn prepare_msg_raw(
 
         let new_mime_headers = if msg.has_html() {
             let html = if msg.param.exists(Param::Forwarded) {
-                msg.get_id().get_html(context).await?
+                msg.get_id().get_html_with_remote_content(context).await?
             } else {
                 msg.param.get(Param::SendHtml).map(|s| s.to_string())
             };
@@ -1402,6 +1656,7 @@ async fn prepare_msg_raw(
 
         // add message to the database
 
+        let blob_bytes = get_blob_bytes(context, &msg.param).await;
         if let Some(update_msg_id) = update_msg_id {
             context
                 .sql
@@ -1410,7 +1665,8 @@ async fn prepare_msg_raw(
                      SET rfc724_mid=?, chat_id=?, from_id=?, to_id=?, timestamp=?, type=?,
                          state=?, txt=?, subject=?, param=?,
                          hidden=?, mime_in_reply_to=?, mime_references=?, mime_modified=?,
-                         mime_headers=?, location_id=?, ephemeral_timer=?, ephemeral_timestamp=?
+                         mime_headers=?, location_id=?, ephemeral_timer=?, ephemeral_timestamp=?,
+                         blob_bytes=?
                      WHERE id=?;",
                     paramsv![
                         new_rfc724_mid,
@@ -1431,6 +1687,7 @@ async fn prepare_msg_raw(
                         location_id as i32,
                         ephemeral_timer,
                         ephemeral_timestamp,
+                        blob_bytes as i64,
                         update_msg_id
                     ],
                 )
@@ -1458,8 +1715,9 @@ async fn prepare_msg_raw(
                         mime_headers,
                         location_id,
                         ephemeral_timer,
-                        ephemeral_timestamp)
-                        VALUES (?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?);",
+                        ephemeral_timestamp,
+                        blob_bytes)
+                        VALUES (?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?);",
                     paramsv![
                         new_rfc724_mid,
                         self.id,
@@ -1478,12 +1736,16 @@ async fn prepare_msg_raw(
                         new_mime_headers.unwrap_or_default(),
                         location_id as i32,
                         ephemeral_timer,
-                        ephemeral_timestamp
+                        ephemeral_timestamp,
+                        blob_bytes as i64
                     ],
                 )
                 .await?;
             msg.id = MsgId::new(u32::try_from(raw_id)?);
         }
+        if msg.state.is_pending() {
+            message::emit_outbox_changed(context).await?;
+        }
         context.interrupt_ephemeral_task().await;
         Ok(msg.id)
     }
@@ -1590,6 +1852,149 @@ pub struct ChatInfo {
     // - [ ] email
 }
 
+/// A single entry of the chatlist with everything needed to display it.
+///
+/// Use [`get_chatlist_item`] to refresh a single entry
+/// on [`EventType::ChatlistItemChanged`] instead of reloading the whole chatlist.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatlistItem {
+    /// The chat ID.
+    pub chat_id: ChatId,
+
+    /// The name of the chat.
+    pub name: String,
+
+    /// Summary of the last message or draft, "No messages" if the chat is empty.
+    pub preview_text: String,
+
+    /// Timestamp of the last message or draft, 0 if the chat is empty.
+    pub preview_ts: i64,
+
+    /// Number of fresh messages in the chat.
+    pub unread_count: u32,
+
+    /// Absolute path to the avatar of the chat, if any.
+    pub avatar_path: Option<String>,
+
+    /// Whether the chat is a contact request.
+    pub is_contact_request: bool,
+
+    /// Whether the chat is archived.
+    pub is_archived: bool,
+
+    /// Whether the chat is muted.
+    pub is_muted: bool,
+}
+
+/// Loads a single chatlist entry.
+///
+/// In contrast to [`crate::chatlist::Chatlist::try_load`],
+/// this only loads the given chat, so UIs can update a single entry
+/// when receiving [`EventType::ChatlistItemChanged`].
+pub async fn get_chatlist_item(context: &Context, chat_id: ChatId) -> Result<ChatlistItem> {
+    ensure!(!chat_id.is_special(), "Invalid chat-id {}.", chat_id);
+
+    let (mut item, typ, chat_param, contact_param, lastmsg) = context
+        .sql
+        .query_row(
+            "SELECT c.name, c.type, c.param, c.blocked, c.archived, c.muted_until,
+                    (SELECT COUNT(*) FROM msgs WHERE state=?1 AND hidden=0 AND chat_id=c.id),
+                    ct.param,
+                    m.id, m.from_id, m.timestamp, m.timestamp_sent, m.type, m.state, m.txt, m.param
+             FROM chats c
+             LEFT JOIN contacts ct
+                    ON c.type=?2
+                   AND ct.id=(SELECT contact_id FROM chats_contacts WHERE chat_id=c.id LIMIT 1)
+             LEFT JOIN msgs m
+                    ON m.id=(
+                           SELECT id
+                             FROM msgs
+                            WHERE chat_id=c.id
+                              AND (hidden=0 OR state=?3)
+                              ORDER BY timestamp DESC, id DESC LIMIT 1)
+             WHERE c.id=?4;",
+            paramsv![
+                MessageState::InFresh,
+                Chattype::Single,
+                MessageState::OutDraft,
+                chat_id
+            ],
+            |row| {
+                let typ: Chattype = row.get(1)?;
+                let chat_param: Params = row.get::<_, String>(2)?.parse().unwrap_or_default();
+                let blocked: Blocked = row.get::<_, Option<_>>(3)?.unwrap_or_default();
+                let visibility: ChatVisibility = row.get(4)?;
+                let mute_duration: MuteDuration = row.get(5)?;
+                let contact_param: Option<Params> = row
+                    .get::<_, Option<String>>(7)?
+                    .map(|param| param.parse().unwrap_or_default());
+                let lastmsg = match row.get::<_, Option<MsgId>>(8)? {
+                    Some(id) => Some(Message {
+                        id,
+                        chat_id,
+                        from_id: row.get(9)?,
+                        timestamp_sort: row.get(10)?,
+                        timestamp_sent: row.get(11)?,
+                        viewtype: row.get(12)?,
+                        state: row.get(13)?,
+                        text: Some(row.get::<_, Option<String>>(14)?.unwrap_or_default()),
+                        param: row
+                            .get::<_, Option<String>>(15)?
+                            .unwrap_or_default()
+                            .parse()
+                            .unwrap_or_default(),
+                        ..Default::default()
+                    }),
+                    None => None,
+                };
+                let item = ChatlistItem {
+                    chat_id,
+                    name: row.get(0)?,
+                    preview_text: String::new(),
+                    preview_ts: 0,
+                    unread_count: row.get(6)?,
+                    avatar_path: None,
+                    is_contact_request: blocked == Blocked::Request,
+                    is_archived: visibility == ChatVisibility::Archived,
+                    is_muted: match mute_duration {
+                        MuteDuration::NotMuted => false,
+                        MuteDuration::Forever => true,
+                        MuteDuration::Until(when) => when > SystemTime::now(),
+                    },
+                };
+                Ok((item, typ, chat_param, contact_param, lastmsg))
+            },
+        )
+        .await
+        .with_context(|| format!("Failed loading chatlist item for {}", chat_id))?;
+
+    if let Some(lastmsg) = lastmsg.filter(|msg| msg.from_id != ContactId::UNDEFINED) {
+        item.preview_text = lastmsg.summary_text(context).await;
+        item.preview_ts = lastmsg.get_timestamp();
+    } else {
+        item.preview_text = stock_str::no_messages(context).await;
+    }
+
+    let image_rel = match chat_param.get(Param::ProfileImage) {
+        Some(image_rel) => Some(image_rel.to_string()),
+        None => match typ {
+            Chattype::Single => contact_param
+                .and_then(|param| param.get(Param::ProfileImage).map(|s| s.to_string())),
+            Chattype::Broadcast => Some(get_broadcast_icon(context).await?),
+            Chattype::Undefined | Chattype::Group | Chattype::Mailinglist => None,
+        },
+    };
+    item.avatar_path = image_rel
+        .filter(|image_rel| !image_rel.is_empty())
+        .map(|image_rel| {
+            get_abs_path(context, &image_rel)
+                .to_string_lossy()
+                .into_owned()
+        });
+
+    Ok(item)
+}
+
 pub(crate) async fn update_saved_messages_icon(context: &Context) -> Result<()> {
     // if there is no saved-messages chat, there is nothing to update. this is no error.
     if let Some(chat_id) = ChatId::lookup_by_contact(context, ContactId::SELF).await? {
@@ -1789,7 +2194,11 @@ pub async fn get_for_contact(
         match contact_id {
             ContactId::SELF => update_saved_messages_icon(context).await?,
             ContactId::DEVICE => update_device_icon(context).await?,
-            _ => (),
+            _ => {
+                chat_id
+                    .apply_default_ephemeral_timer(context, created_timestamp)
+                    .await?
+            }
         }
 
         Ok(Self {
@@ -1827,6 +2236,10 @@ async fn prepare_msg_blob(context: &Context, msg: &mut Message) -> Result<()> {
             }
         }
         msg.param.set(Param::File, blob.as_name());
+        if !msg.is_increation() {
+            let bytes = get_filebytes(context, blob.to_abs_path()).await;
+            msg.param.set_i64(Param::Bytes, bytes as i64);
+        }
 
         if msg.viewtype == Viewtype::File || msg.viewtype == Viewtype::Image {
             // Correct the type, take care not to correct already very special
@@ -1980,6 +2393,10 @@ pub async fn send_msg_sync(context: &Context, chat_id: ChatId, msg: &mut Message
 }
 
 async fn send_msg_inner(context: &Context, chat_id: ChatId, msg: &mut Message) -> Result<MsgId> {
+    if !chat_id.is_unset() && msg.param.get_cmd() != SystemMessage::EphemeralTimerChanged {
+        announce_default_ephemeral_timer(context, chat_id).await?;
+    }
+
     if prepare_send_msg(context, chat_id, msg).await?.is_some() {
         context.emit_msgs_changed(msg.chat_id, msg.id);
 
@@ -1993,8 +2410,62 @@ async fn send_msg_inner(context: &Context, chat_id: ChatId, msg: &mut Message) -
     Ok(msg.id)
 }
 
+/// Sends the timer change message for an ephemeral timer
+/// set by [`Config::DefaultEphemeralTimer`] when the chat was created.
+///
+/// The message is queued before the first outgoing message of the chat,
+/// the caller has to interrupt the SMTP loop.
+async fn announce_default_ephemeral_timer(context: &Context, chat_id: ChatId) -> Result<()> {
+    let mut chat = Chat::load_from_db(context, chat_id).await?;
+    if !chat.param.exists(Param::EphemeralTimerUnannounced) {
+        return Ok(());
+    }
+    if !are_members_chat_capable(context, chat_id).await? {
+        // Classic email clients do not understand the timer,
+        // keep the default unannounced until all members use a messenger.
+        return Ok(());
+    }
+    chat.param.remove(Param::EphemeralTimerUnannounced);
+    chat.update_param(context).await?;
+
+    let timer = chat_id.get_ephemeral_timer(context).await?;
+    if timer == EphemeralTimer::Disabled || chat.is_self_talk() || !chat.can_send(context).await? {
+        return Ok(());
+    }
+
+    let mut msg = Message::new(Viewtype::Text);
+    msg.text = Some(stock_ephemeral_timer_changed(context, timer, ContactId::SELF).await);
+    msg.param.set_cmd(SystemMessage::EphemeralTimerChanged);
+    if prepare_send_msg(context, chat_id, &mut msg)
+        .await?
+        .is_some()
+    {
+        context.emit_msgs_changed(chat_id, msg.id);
+    }
+    Ok(())
+}
+
+/// Returns true if all members of the chat except the user are known to use a messenger,
+/// i.e. they sent an Autocrypt key or a chat message.
+async fn are_members_chat_capable(context: &Context, chat_id: ChatId) -> Result<bool> {
+    let count = context
+        .sql
+        .count(
+            "SELECT COUNT(*) FROM chats_contacts cc
+             LEFT JOIN contacts c ON c.id=cc.contact_id
+             WHERE cc.chat_id=? AND cc.contact_id!=?
+             AND NOT EXISTS (SELECT 1 FROM msgs m WHERE m.from_id=cc.contact_id AND m.msgrmsg=?)
+             AND NOT EXISTS (SELECT 1 FROM acpeerstates p WHERE p.addr=c.addr COLLATE NOCASE)",
+            paramsv![chat_id, ContactId::SELF, MessengerMessage::Yes],
+        )
+        .await?;
+    Ok(count == 0)
+}
+
 /// Returns rowid from `smtp` table.
-async fn prepare_send_msg(
+///
+/// The caller has to interrupt the SMTP loop if a row was created.
+pub(crate) async fn prepare_send_msg(
     context: &Context,
     chat_id: ChatId,
     msg: &mut Message,
@@ -2012,7 +2483,9 @@ async fn prepare_send_msg(
             chat_id.is_unset() || chat_id == msg.chat_id,
             "Inconsistent chat ID"
         );
-        message::update_msg_state(context, msg.id, MessageState::OutPending).await?;
+        msg.id
+            .update_state(context, MessageState::OutPending)
+            .await?;
     }
     let row_id = create_send_msg_job(context, msg.id).await?;
     Ok(row_id)
@@ -2429,9 +2902,10 @@ pub async fn get_chat_media(
     msg_type3: Viewtype,
 ) -> Result<Vec<MsgId>> {
     // TODO This query could/should be converted to `AND type IN (?, ?, ?)`.
-    let list = context
+    let mut list = Vec::new();
+    context
         .sql
-        .query_map(
+        .query_stream(
             "SELECT id
                FROM msgs
               WHERE chat_id=?
@@ -2451,115 +2925,642 @@ pub async fn get_chat_media(
                     msg_type
                 },
             ],
-            |row| row.get::<_, MsgId>(0),
-            |ids| Ok(ids.flatten().collect()),
+            |row| {
+                list.push(row.get::<_, MsgId>(0)?);
+                Ok(())
+            },
         )
         .await?;
     Ok(list)
 }
 
-/// Indicates the direction over which to iterate.
-#[derive(Debug, Clone, PartialEq, Eq)]
-#[repr(i32)]
-pub enum Direction {
-    Forward = 1,
-    Backward = -1,
-}
-
-pub async fn get_next_media(
+/// Returns the number of non-text messages per sender and viewtype in a chat.
+pub async fn get_media_count_per_member(
     context: &Context,
-    curr_msg_id: MsgId,
-    direction: Direction,
-    msg_type: Viewtype,
-    msg_type2: Viewtype,
-    msg_type3: Viewtype,
-) -> Result<Option<MsgId>> {
-    let mut ret: Option<MsgId> = None;
-
-    if let Ok(msg) = Message::load_from_db(context, curr_msg_id).await {
-        let list: Vec<MsgId> = get_chat_media(
-            context,
-            msg.chat_id,
-            if msg_type != Viewtype::Unknown {
-                msg_type
-            } else {
-                msg.viewtype
+    chat_id: ChatId,
+) -> Result<HashMap<ContactId, HashMap<Viewtype, u32>>> {
+    let mut counts: HashMap<ContactId, HashMap<Viewtype, u32>> = HashMap::new();
+    context
+        .sql
+        .query_stream(
+            "SELECT from_id, type, COUNT(*)
+               FROM msgs
+              WHERE chat_id=? AND type!=?
+              GROUP BY from_id, type;",
+            paramsv![chat_id, Viewtype::Text],
+            |row| {
+                let from_id: ContactId = row.get(0)?;
+                let viewtype: Viewtype = row.get(1)?;
+                let count: u32 = row.get(2)?;
+                counts.entry(from_id).or_default().insert(viewtype, count);
+                Ok(())
             },
-            msg_type2,
-            msg_type3,
         )
         .await?;
-        for (i, msg_id) in list.iter().enumerate() {
-            if curr_msg_id == *msg_id {
-                match direction {
-                    Direction::Forward => {
-                        if i + 1 < list.len() {
-                            ret = list.get(i + 1).copied();
-                        }
-                    }
-                    Direction::Backward => {
-                        if i >= 1 {
-                            ret = list.get(i - 1).copied();
-                        }
-                    }
-                }
-                break;
-            }
-        }
-    }
-    Ok(ret)
+    Ok(counts)
 }
 
-/// Returns a vector of contact IDs for given chat ID.
-pub async fn get_chat_contacts(context: &Context, chat_id: ChatId) -> Result<Vec<ContactId>> {
-    // Normal chats do not include SELF.  Group chats do (as it may happen that one is deleted from a
-    // groupchat but the chats stays visible, moreover, this makes displaying lists easier)
-
-    let list = context
+/// Returns the total size in bytes of the messages each sender contributed to a chat.
+///
+/// Sizes are only recorded for received messages,
+/// so messages sent from this device count as zero bytes.
+pub async fn get_total_media_size_per_member(
+    context: &Context,
+    chat_id: ChatId,
+) -> Result<HashMap<ContactId, u64>> {
+    let mut sizes = HashMap::new();
+    context
         .sql
-        .query_map(
-            "SELECT cc.contact_id
-               FROM chats_contacts cc
-               LEFT JOIN contacts c
-                      ON c.id=cc.contact_id
-              WHERE cc.chat_id=?
-              ORDER BY c.id=1, LOWER(c.name||c.addr), c.id;",
+        .query_stream(
+            "SELECT from_id, SUM(bytes)
+               FROM msgs
+              WHERE chat_id=?
+              GROUP BY from_id;",
             paramsv![chat_id],
-            |row| row.get::<_, ContactId>(0),
-            |ids| ids.collect::<Result<Vec<_>, _>>().map_err(Into::into),
+            |row| {
+                let from_id: ContactId = row.get(0)?;
+                let bytes: i64 = row.get(1)?;
+                sizes.insert(from_id, u64::try_from(bytes).unwrap_or_default());
+                Ok(())
+            },
         )
         .await?;
-
-    Ok(list)
+    Ok(sizes)
 }
 
-/// Creates a group chat with a given `name`.
-pub async fn create_group_chat(
-    context: &Context,
-    protect: ProtectionStatus,
-    chat_name: &str,
+/// Returns the storage used by each chat
+/// as `(chat_id, message bytes, attachment bytes, message count)` tuples.
+///
+/// Message bytes are the sizes of the received messages as downloaded from the server,
+/// attachment bytes are the sizes of the files in the blob directory.
+/// Missing attachment files count as zero bytes.
+pub async fn get_storage_usage(context: &Context) -> Result<Vec<(ChatId, u64, u64, u32)>> {
+    update_blob_bytes(context, None).await?;
+    let usage = context
+        .sql
+        .query_map(
+            "SELECT chat_id, SUM(bytes), SUM(IFNULL(blob_bytes, 0)), COUNT(*)
+               FROM msgs
+              WHERE chat_id>?
+              GROUP BY chat_id;",
+            paramsv![DC_CHAT_ID_LAST_SPECIAL],
+            |row| {
+                let chat_id: ChatId = row.get(0)?;
+                let msg_bytes: i64 = row.get(1)?;
+                let blob_bytes: i64 = row.get(2)?;
+                let msg_count: u32 = row.get(3)?;
+                Ok((
+                    chat_id,
+                    u64::try_from(msg_bytes).unwrap_or_default(),
+                    u64::try_from(blob_bytes).unwrap_or_default(),
+                    msg_count,
+                ))
+            },
+            |rows| rows.collect::<Result<Vec<_>, _>>().map_err(Into::into),
+        )
+        .await?;
+    Ok(usage)
+}
+
+/// Stores the attachment sizes of messages which do not have them yet,
+/// e.g. because they were added before sizes were recorded.
+///
+/// Only messages of `chat_id` are updated if it is given, otherwise the messages of all chats.
+async fn update_blob_bytes(context: &Context, chat_id: Option<ChatId>) -> Result<()> {
+    let (condition, chat_id) = match chat_id {
+        Some(chat_id) => ("chat_id=?", chat_id),
+        None => ("chat_id>?", DC_CHAT_ID_LAST_SPECIAL),
+    };
+    let msgs = context
+        .sql
+        .query_map(
+            &format!(
+                "SELECT id, param FROM msgs WHERE blob_bytes IS NULL AND {};",
+                condition
+            ),
+            paramsv![chat_id],
+            |row| {
+                let msg_id: MsgId = row.get(0)?;
+                let param: String = row.get(1)?;
+                Ok((msg_id, param))
+            },
+            |rows| rows.collect::<Result<Vec<_>, _>>().map_err(Into::into),
+        )
+        .await?;
+    if msgs.is_empty() {
+        return Ok(());
+    }
+
+    let mut updates = Vec::with_capacity(msgs.len());
+    for (msg_id, param) in msgs {
+        let param: Params = param.parse().unwrap_or_default();
+        updates.push((msg_id, get_blob_bytes(context, &param).await));
+    }
+    context
+        .sql
+        .transaction(move |transaction| {
+            let mut stmt = transaction.prepare("UPDATE msgs SET blob_bytes=? WHERE id=?;")?;
+            for (msg_id, blob_bytes) in updates {
+                stmt.execute(paramsv![blob_bytes as i64, msg_id])?;
+            }
+            Ok(())
+        })
+        .await
+}
+
+/// Style sheet of chats exported by [`export_html`].
+const EXPORT_HTML_STYLE: &str = "body { font-family: sans-serif; background: #eee; margin: 0 auto; max-width: 50em; padding: 1em; }
+.msg { background: #fff; border-radius: 1em; margin: 0.5em 20% 0.5em 0; padding: 0.5em 1em; }
+.msg.out { background: #dfd; margin: 0.5em 0 0.5em 20%; }
+.sender { font-weight: bold; }
+.time { color: #888; font-size: small; }
+blockquote { border-left: 3px solid #888; color: #555; margin: 0.5em 0; padding-left: 0.5em; }
+img { max-width: 100%; }
+.info { color: #555; font-size: small; margin: 1em; text-align: center; }
+.info.protection { color: #070; font-weight: bold; }
+";
+
+/// Exports a chat as a self-contained HTML archive to the directory `dir`.
+///
+/// The messages are written to `dir/index.html`, attachments are copied to `dir/files`.
+/// Images are shown inline, other attachments are linked.
+/// Hidden messages are not exported.
+///
+/// Returns the path of `index.html`.
+pub async fn export_html(context: &Context, chat_id: ChatId, dir: &Path) -> Result<PathBuf> {
+    let chat = Chat::load_from_db(context, chat_id).await?;
+    let files_dir = dir.join("files");
+    tokio::fs::create_dir_all(&files_dir)
+        .await
+        .with_context(|| format!("Cannot create {}", files_dir.display()))?;
+
+    let mut body = String::new();
+    for item in get_chat_msgs(context, chat_id, 0).await? {
+        if let ChatItem::Message { msg_id } = item {
+            let msg = Message::load_from_db(context, msg_id).await?;
+            body += &export_msg_html(context, &msg, &files_dir).await?;
+        }
+    }
+
+    let name = escaper::encode_minimal(chat.get_name());
+    let html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n{}</style>\n</head>\n<body>\n<h1>{}</h1>\n{}</body>\n</html>\n",
+        name, EXPORT_HTML_STYLE, name, body
+    );
+    let file_name = dir.join("index.html");
+    tokio::fs::write(&file_name, html)
+        .await
+        .with_context(|| format!("Cannot write {}", file_name.display()))?;
+    context.emit_event(EventType::ImexFileWritten(file_name.clone()));
+    Ok(file_name)
+}
+
+/// Returns the HTML of a single message for [`export_html`],
+/// copying its attachment to `files_dir`.
+async fn export_msg_html(context: &Context, msg: &Message, files_dir: &Path) -> Result<String> {
+    let text = escaper::encode_minimal(msg.get_text().as_deref().unwrap_or_default())
+        .replace('\n', "<br>\n");
+    let time = timestamp_to_str(msg.get_timestamp());
+
+    if msg.is_info() {
+        let class = match msg.get_info_type() {
+            SystemMessage::ChatProtectionEnabled | SystemMessage::ChatProtectionDisabled => {
+                "info protection"
+            }
+            _ => "info",
+        };
+        return Ok(format!(
+            "<div class=\"{}\"><span class=\"time\">{}</span> {}</div>\n",
+            class, time, text
+        ));
+    }
+
+    let mut html = if msg.get_from_id() == ContactId::SELF {
+        "<div class=\"msg out\">\n".to_string()
+    } else {
+        let contact = Contact::load_from_db(context, msg.get_from_id()).await?;
+        format!(
+            "<div class=\"msg in\">\n<div class=\"sender\">{}</div>\n",
+            escaper::encode_minimal(&msg.get_sender_name(&contact))
+        )
+    };
+    if let Some(quote) = msg.quoted_text() {
+        html += &format!(
+            "<blockquote>{}</blockquote>\n",
+            escaper::encode_minimal(&quote).replace('\n', "<br>\n")
+        );
+    }
+    if let (Some(path), Some(filename)) = (msg.get_file(context), msg.get_filename()) {
+        // Prefix the message ID as different messages may have attachments with the same name.
+        let target_name = format!("{}-{}", msg.id.to_u32(), filename);
+        let target = files_dir.join(&target_name);
+        tokio::fs::copy(&path, &target)
+            .await
+            .with_context(|| format!("Cannot copy {} to {}", path.display(), target.display()))?;
+        let href = format!(
+            "files/{}",
+            utf8_percent_encode(&target_name, NON_ALPHANUMERIC)
+        );
+        match msg.get_viewtype() {
+            Viewtype::Image | Viewtype::Gif | Viewtype::Sticker => {
+                html += &format!("<img src=\"{}\" alt=\"\">\n", href)
+            }
+            _ => {
+                html += &format!(
+                    "<a href=\"{}\" download>{}</a>\n",
+                    href,
+                    escaper::encode_minimal(&filename)
+                )
+            }
+        }
+    }
+    if !text.is_empty() {
+        html += &format!("<div class=\"text\">{}</div>\n", text);
+    }
+    html += &format!("<div class=\"time\">{}</div>\n</div>\n", time);
+    Ok(html)
+}
+
+/// Returns the messages of the chat saved from ephemeral deletion
+/// by [`MsgId::save_from_ephemeral`], oldest first.
+pub async fn get_saved_msgs(context: &Context, chat_id: ChatId) -> Result<Vec<MsgId>> {
+    let mut list = Vec::new();
+    context
+        .sql
+        .query_stream(
+            "SELECT id, param
+               FROM msgs
+              WHERE chat_id=?
+                AND param GLOB '*4=1*'
+              ORDER BY timestamp, id;",
+            paramsv![chat_id],
+            |row| {
+                let msg_id: MsgId = row.get(0)?;
+                let param: Params = row.get::<_, String>(1)?.parse().unwrap_or_default();
+                if param.get_bool(Param::Saved).unwrap_or_default() {
+                    list.push(msg_id);
+                }
+                Ok(())
+            },
+        )
+        .await?;
+    Ok(list)
+}
+
+/// Returns up to `limit` chats in the normal chatlist together with the timestamp
+/// of their last message, the chats with the oldest last message first.
+///
+/// Archived, pinned and blocked chats are not returned.
+/// For chats without messages, the creation timestamp is used.
+/// This can be used to suggest chats to archive.
+pub async fn get_oldest_active_chats(context: &Context, limit: u32) -> Result<Vec<(ChatId, i64)>> {
+    let list = context
+        .sql
+        .query_map(
+            "SELECT c.id, COALESCE(MAX(m.timestamp), c.created_timestamp) AS last_timestamp
+               FROM chats c
+               LEFT JOIN msgs m ON m.chat_id=c.id
+              WHERE c.id>9
+                AND c.archived=?
+                AND c.blocked=?
+              GROUP BY c.id
+              ORDER BY last_timestamp, c.id
+              LIMIT ?;",
+            paramsv![ChatVisibility::Normal, Blocked::Not, limit],
+            |row| {
+                let chat_id: ChatId = row.get(0)?;
+                let timestamp: i64 = row.get(1)?;
+                Ok((chat_id, timestamp))
+            },
+            |rows| {
+                rows.collect::<std::result::Result<Vec<_>, _>>()
+                    .map_err(Into::into)
+            },
+        )
+        .await?;
+    Ok(list)
+}
+
+/// Indicates the direction over which to iterate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[repr(i32)]
+pub enum Direction {
+    Forward = 1,
+    Backward = -1,
+}
+
+pub async fn get_next_media(
+    context: &Context,
+    curr_msg_id: MsgId,
+    direction: Direction,
+    msg_type: Viewtype,
+    msg_type2: Viewtype,
+    msg_type3: Viewtype,
+) -> Result<Option<MsgId>> {
+    let mut ret: Option<MsgId> = None;
+
+    if let Ok(msg) = Message::load_from_db(context, curr_msg_id).await {
+        let list: Vec<MsgId> = get_chat_media(
+            context,
+            msg.chat_id,
+            if msg_type != Viewtype::Unknown {
+                msg_type
+            } else {
+                msg.viewtype
+            },
+            msg_type2,
+            msg_type3,
+        )
+        .await?;
+        for (i, msg_id) in list.iter().enumerate() {
+            if curr_msg_id == *msg_id {
+                match direction {
+                    Direction::Forward => {
+                        if i + 1 < list.len() {
+                            ret = list.get(i + 1).copied();
+                        }
+                    }
+                    Direction::Backward => {
+                        if i >= 1 {
+                            ret = list.get(i - 1).copied();
+                        }
+                    }
+                }
+                break;
+            }
+        }
+    }
+    Ok(ret)
+}
+
+/// Where a reply to a message in a chat should be shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplyKind {
+    /// The reply belongs to the 1:1 chat with the sender.
+    PrivateReply,
+
+    /// The reply belongs to the chat of the parent message.
+    GroupReply,
+}
+
+/// Classifies a reply to a message in `parent_chat_id`
+/// as a private reply or a reply to the parent chat.
+///
+/// Replies addressed only to us, or sent by us to a single recipient,
+/// are private replies.  There is one exception: classical MUA replies
+/// to two-member groups are assigned to the group, as they have no
+/// `Chat-Group-ID` header that would allow sorting them into the
+/// correct chat otherwise.
+pub async fn classify_reply(
+    context: &Context,
+    to_ids: &[ContactId],
+    from_id: ContactId,
+    has_chat_version: bool,
+    parent_chat_id: ChatId,
+) -> Result<ReplyKind> {
+    let private_message =
+        (to_ids == [ContactId::SELF]) || (from_id == ContactId::SELF && to_ids.len() == 1);
+    if !private_message {
+        return Ok(ReplyKind::GroupReply);
+    }
+
+    if !has_chat_version {
+        let chat_contacts = get_chat_contacts(context, parent_chat_id).await?;
+        if chat_contacts.len() == 2 && chat_contacts.contains(&ContactId::SELF) {
+            return Ok(ReplyKind::GroupReply);
+        }
+    }
+
+    Ok(ReplyKind::PrivateReply)
+}
+
+/// Returns a vector of contact IDs for given chat ID.
+pub async fn get_chat_contacts(context: &Context, chat_id: ChatId) -> Result<Vec<ContactId>> {
+    // Normal chats do not include SELF.  Group chats do (as it may happen that one is deleted from a
+    // groupchat but the chats stays visible, moreover, this makes displaying lists easier)
+
+    let list = context
+        .sql
+        .query_map(
+            "SELECT cc.contact_id
+               FROM chats_contacts cc
+               LEFT JOIN contacts c
+                      ON c.id=cc.contact_id
+              WHERE cc.chat_id=?
+              ORDER BY c.id=1, LOWER(c.name||c.addr), c.id;",
+            paramsv![chat_id],
+            |row| row.get::<_, ContactId>(0),
+            |ids| ids.collect::<Result<Vec<_>, _>>().map_err(Into::into),
+        )
+        .await?;
+
+    Ok(list)
+}
+
+/// Returns the contact IDs of the chat members at the given `timestamp`.
+///
+/// The membership is reconstructed from the current member list
+/// by undoing the member additions and removals recorded as info messages after `timestamp`.
+/// Members added without such an info message, e.g. on group creation,
+/// are considered members since the creation of the chat.
+pub async fn members_at(
+    context: &Context,
+    chat_id: ChatId,
+    timestamp: i64,
+) -> Result<Vec<ContactId>> {
+    let mut members = get_chat_contacts(context, chat_id).await?;
+
+    let params = context
+        .sql
+        .query_map(
+            "SELECT param FROM msgs
+              WHERE chat_id=? AND timestamp>?
+                AND (param GLOB 'S=[45]*' OR param GLOB '*' || char(10) || 'S=[45]*')
+              ORDER BY timestamp DESC, id DESC",
+            paramsv![chat_id, timestamp],
+            |row| row.get::<_, String>(0),
+            |rows| rows.collect::<Result<Vec<_>, _>>().map_err(Into::into),
+        )
+        .await?;
+    for param in params {
+        let param: Params = param.parse().unwrap_or_default();
+        let cmd = param.get_cmd();
+        if cmd != SystemMessage::MemberAddedToGroup && cmd != SystemMessage::MemberRemovedFromGroup
+        {
+            continue;
+        }
+        let contact_id = match param.get(Param::Arg) {
+            Some(addr) => Contact::lookup_id_by_addr(context, addr, Origin::Unknown).await?,
+            None => None,
+        };
+        if let Some(contact_id) = contact_id {
+            if cmd == SystemMessage::MemberAddedToGroup {
+                members.retain(|member| *member != contact_id);
+            } else if !members.contains(&contact_id) {
+                members.push(contact_id);
+            }
+        }
+    }
+
+    Ok(members)
+}
+
+/// Returns the chats both `contact_id1` and `contact_id2` are members of, ordered by chat ID.
+///
+/// If one of the contacts is [`ContactId::SELF`],
+/// all chats with the other contact are returned, including the one-to-one chat,
+/// but excluding groups the user is no longer a member of.
+/// Blocked chats are not returned.
+pub async fn get_shared_chats(
+    context: &Context,
+    contact_id1: ContactId,
+    contact_id2: ContactId,
+) -> Result<Vec<ChatId>> {
+    let (contact_id1, contact_id2) = if contact_id2 == ContactId::SELF {
+        (contact_id2, contact_id1)
+    } else {
+        (contact_id1, contact_id2)
+    };
+
+    // SELF is not stored as a member of one-to-one chats.
+    let list = if contact_id1 == ContactId::SELF {
+        context
+            .sql
+            .query_map(
+                "SELECT c.id
+                   FROM chats c
+                   JOIN chats_contacts cc ON cc.chat_id=c.id
+                  WHERE cc.contact_id=?
+                    AND c.id>?
+                    AND c.blocked=0
+                    AND (c.type!=? OR EXISTS (
+                         SELECT 1 FROM chats_contacts
+                          WHERE chat_id=c.id AND contact_id=?))
+                  ORDER BY c.id",
+                paramsv![
+                    contact_id2,
+                    DC_CHAT_ID_LAST_SPECIAL,
+                    Chattype::Group,
+                    ContactId::SELF
+                ],
+                |row| row.get::<_, ChatId>(0),
+                |ids| ids.collect::<Result<Vec<_>, _>>().map_err(Into::into),
+            )
+            .await?
+    } else {
+        context
+            .sql
+            .query_map(
+                "SELECT cc1.chat_id
+                   FROM chats_contacts cc1
+                   JOIN chats_contacts cc2 ON cc1.chat_id=cc2.chat_id
+                   JOIN chats c ON c.id=cc1.chat_id
+                  WHERE cc1.contact_id=?
+                    AND cc2.contact_id=?
+                    AND c.id>?
+                    AND c.blocked=0
+                  ORDER BY c.id",
+                paramsv![contact_id1, contact_id2, DC_CHAT_ID_LAST_SPECIAL],
+                |row| row.get::<_, ChatId>(0),
+                |ids| ids.collect::<Result<Vec<_>, _>>().map_err(Into::into),
+            )
+            .await?
+    };
+
+    Ok(list)
+}
+
+/// Tries to make a mailing list writable again
+/// after it was made read-only because of a varying `List-Post` header.
+///
+/// The `List-Post` headers of the two most recent messages in the chat are compared
+/// and if they contain the same address, it is used for sending to the mailing list again.
+/// This requires the headers of these messages to be stored,
+/// see [`Config::SaveMimeHeaders`].
+///
+/// Returns true if the `List-Post` address was restored.
+pub async fn try_restore_mailinglist_post(context: &Context, chat_id: ChatId) -> Result<bool> {
+    let mut chat = Chat::load_from_db(context, chat_id).await?;
+    if chat.typ != Chattype::Mailinglist || chat.param.get(Param::ListPost) != Some("") {
+        return Ok(false);
+    }
+
+    let msg_ids = context
+        .sql
+        .query_map(
+            "SELECT id FROM msgs
+              WHERE chat_id=? AND hidden=0
+              ORDER BY timestamp DESC, id DESC
+              LIMIT 2",
+            paramsv![chat_id],
+            |row| row.get::<_, MsgId>(0),
+            |ids| ids.collect::<Result<Vec<_>, _>>().map_err(Into::into),
+        )
+        .await?;
+    let mut list_posts = Vec::with_capacity(msg_ids.len());
+    for msg_id in msg_ids {
+        let headers = message::get_mime_headers(context, msg_id).await?;
+        if headers.is_empty() {
+            info!(
+                context,
+                "Cannot restore List-Post of chat {}, headers of message {} are not stored.",
+                chat_id,
+                msg_id
+            );
+            return Ok(false);
+        }
+        let (headers, _) = mailparse::parse_headers(&headers)?;
+        list_posts.push(get_list_post(&headers));
+    }
+
+    match list_posts.as_slice() {
+        [Some(list_post), Some(previous_list_post)] if list_post == previous_list_post => {
+            info!(
+                context,
+                "Restoring List-Post {} of chat {}.", list_post, chat_id
+            );
+            chat.param.set(Param::ListPost, list_post);
+            chat.update_param(context).await?;
+            context.emit_event(EventType::ChatModified(chat_id));
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// Creates a group chat with a given `name`.
+pub async fn create_group_chat(
+    context: &Context,
+    protect: ProtectionStatus,
+    chat_name: &str,
 ) -> Result<ChatId> {
     let chat_name = improve_single_line_input(chat_name);
     ensure!(!chat_name.is_empty(), "Invalid chat name");
 
     let grpid = create_id();
 
+    let created_timestamp = create_smeared_timestamp(context).await;
     let row_id = context
         .sql
         .insert(
             "INSERT INTO chats
-        (type, name, grpid, param, created_timestamp)
-        VALUES(?, ?, ?, \'U=1\', ?);",
+        (type, name, grpid, param, created_timestamp, creator_id, admin_contact_id)
+        VALUES(?, ?, ?, \'U=1\', ?, ?, ?);",
             paramsv![
                 Chattype::Group,
                 chat_name,
                 grpid,
-                create_smeared_timestamp(context).await,
+                created_timestamp,
+                ContactId::SELF,
+                ContactId::SELF,
             ],
         )
         .await?;
 
     let chat_id = ChatId::new(u32::try_from(row_id)?);
+    chat_id
+        .apply_default_ephemeral_timer(context, created_timestamp)
+        .await?;
     if !is_contact_in_chat(context, chat_id, ContactId::SELF).await? {
         add_to_chat_contacts_table(context, chat_id, ContactId::SELF).await?;
     }
@@ -2602,21 +3603,20 @@ async fn find_unused_broadcast_list_name(context: &Context) -> Result<String> {
 pub async fn create_broadcast_list(context: &Context) -> Result<ChatId> {
     let chat_name = find_unused_broadcast_list_name(context).await?;
     let grpid = create_id();
+    let created_timestamp = create_smeared_timestamp(context).await;
     let row_id = context
         .sql
         .insert(
             "INSERT INTO chats
         (type, name, grpid, param, created_timestamp)
         VALUES(?, ?, ?, \'U=1\', ?);",
-            paramsv![
-                Chattype::Broadcast,
-                chat_name,
-                grpid,
-                create_smeared_timestamp(context).await,
-            ],
+            paramsv![Chattype::Broadcast, chat_name, grpid, created_timestamp,],
         )
         .await?;
     let chat_id = ChatId::new(u32::try_from(row_id)?);
+    chat_id
+        .apply_default_ephemeral_timer(context, created_timestamp)
+        .await?;
 
     context.emit_msgs_changed_without_ids();
     Ok(chat_id)
@@ -2718,6 +3718,7 @@ pub(crate) async fn add_contact_to_chat_ex(
         return Ok(false);
     }
 
+    let mut newly_added = false;
     if is_contact_in_chat(context, chat_id, contact_id).await? {
         if !from_handshake {
             return Ok(true);
@@ -2737,6 +3738,7 @@ pub(crate) async fn add_contact_to_chat_ex(
             return Ok(false);
         }
         add_to_chat_contacts_table(context, chat_id, contact_id).await?;
+        newly_added = true;
     }
     if chat.typ == Chattype::Group && chat.is_promoted() {
         msg.viewtype = Viewtype::Text;
@@ -2748,10 +3750,67 @@ pub(crate) async fn add_contact_to_chat_ex(
         msg.param.set_int(Param::Arg2, from_handshake.into());
         msg.id = send_msg(context, chat_id, &mut msg).await?;
     }
+    if newly_added {
+        if let Err(err) = send_welcome_message(context, &chat, contact_id).await {
+            warn!(context, "Failed to send welcome message: {:#}", err);
+        }
+    }
     context.emit_event(EventType::ChatModified(chat_id));
     Ok(true)
 }
 
+/// Sets the welcome message of a group.
+///
+/// The welcome message is sent as a 1:1 message to each member added to the group later
+/// on this device. Members added by others do not get it.
+/// `None` or an empty text removes the welcome message.
+pub async fn set_welcome_message(
+    context: &Context,
+    chat_id: ChatId,
+    msg: Option<&str>,
+) -> Result<()> {
+    ensure!(!chat_id.is_special(), "Invalid chat ID");
+    let mut chat = Chat::load_from_db(context, chat_id).await?;
+    ensure!(
+        chat.typ == Chattype::Group,
+        "Welcome messages can only be set for groups"
+    );
+    ensure!(
+        chat.is_self_in_chat(context).await?,
+        "Cannot set welcome message; self not in group"
+    );
+    match msg.filter(|msg| !msg.is_empty()) {
+        Some(msg) => chat.param.set(Param::WelcomeMessage, msg),
+        None => chat.param.remove(Param::WelcomeMessage),
+    };
+    chat.update_param(context).await?;
+    context.emit_event(EventType::ChatModified(chat_id));
+    Ok(())
+}
+
+/// Sends the welcome message of the group `chat`, if any,
+/// to the newly added member `contact_id` in the 1:1 chat.
+pub(crate) async fn send_welcome_message(
+    context: &Context,
+    chat: &Chat,
+    contact_id: ContactId,
+) -> Result<()> {
+    if chat.typ != Chattype::Group || contact_id.is_special() {
+        return Ok(());
+    }
+    let text = match chat.get_welcome_message() {
+        Some(text) => text.to_string(),
+        None => return Ok(()),
+    };
+    info!(
+        context,
+        "Sending welcome message of {} to {}.", chat.id, contact_id
+    );
+    let chat_id = ChatId::create_for_contact(context, contact_id).await?;
+    send_text_msg(context, chat_id, text).await?;
+    Ok(())
+}
+
 pub(crate) async fn shall_attach_selfavatar(context: &Context, chat_id: ChatId) -> Result<bool> {
     // versions before 12/2019 already allowed to set selfavatar, however, it was never sent to others.
     // to avoid sending out previously set selfavatars unexpectedly we added this additional check.
@@ -2930,6 +3989,27 @@ async fn set_group_explicitly_left(context: &Context, grpid: &str) -> Result<()>
     Ok(())
 }
 
+/// Accepts the 1:1 chat with a contact that has just become verified
+/// if the chat is still a contact request and [`Config::AutoAcceptVerified`] is enabled.
+pub(crate) async fn accept_verified_contact_request(
+    context: &Context,
+    contact_id: ContactId,
+) -> Result<()> {
+    if !context.get_config_bool(Config::AutoAcceptVerified).await? {
+        return Ok(());
+    }
+    if let Some(chat) = ChatIdBlocked::lookup_by_contact(context, contact_id).await? {
+        if chat.blocked == Blocked::Request {
+            info!(
+                context,
+                "Accepting contact request {} of verified contact {}.", chat.id, contact_id
+            );
+            chat.id.accept(context).await?;
+        }
+    }
+    Ok(())
+}
+
 pub(crate) async fn is_group_explicitly_left(context: &Context, grpid: &str) -> Result<bool> {
     let exists = context
         .sql
@@ -2963,6 +4043,8 @@ pub async fn set_chat_name(context: &Context, chat_id: ChatId, new_name: &str) -
             context.emit_event(EventType::ErrorSelfNotInGroup(
                 "Cannot set chat name; self not in group".into(),
             ));
+        } else if !chat.can_edit_name(context).await? {
+            bail!("Cannot set chat name; only the group admin may rename the group");
         } else {
             context
                 .sql
@@ -3166,7 +4248,9 @@ pub async fn resend_msgs(context: &Context, msg_ids: &[MsgId]) -> Result<()> {
             }
             match msg.get_state() {
                 MessageState::OutFailed | MessageState::OutDelivered | MessageState::OutMdnRcvd => {
-                    message::update_msg_state(context, msg.id, MessageState::OutPending).await?
+                    msg.id
+                        .update_state(context, MessageState::OutPending)
+                        .await?
                 }
                 _ => bail!("unexpected message state"),
             }
@@ -3883,36 +4967,260 @@ async fn test_leave_group() -> Result<()> {
         bob_chat_id.accept(&bob).await?;
         remove_contact_from_chat(&bob, bob_chat_id, ContactId::SELF).await?;
 
-        let leave_msg = bob.pop_sent_msg().await;
-        alice.recv_msg(&leave_msg).await;
+        let leave_msg = bob.pop_sent_msg().await;
+        alice.recv_msg(&leave_msg).await;
+
+        assert_eq!(get_chat_contacts(&alice, alice_chat_id).await?.len(), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_add_remove_contact_for_single() {
+        let ctx = TestContext::new_alice().await;
+        let bob = Contact::create(&ctx, "", "bob@f.br").await.unwrap();
+        let chat_id = ChatId::create_for_contact(&ctx, bob).await.unwrap();
+        let chat = Chat::load_from_db(&ctx, chat_id).await.unwrap();
+        assert_eq!(chat.typ, Chattype::Single);
+        assert_eq!(get_chat_contacts(&ctx, chat.id).await.unwrap().len(), 1);
+
+        // adding or removing contacts from one-to-one-chats result in an error
+        let claire = Contact::create(&ctx, "", "claire@foo.de").await.unwrap();
+        let added = add_contact_to_chat_ex(&ctx, chat.id, claire, false).await;
+        assert!(added.is_err());
+        assert_eq!(get_chat_contacts(&ctx, chat.id).await.unwrap().len(), 1);
+
+        let removed = remove_contact_from_chat(&ctx, chat.id, claire).await;
+        assert!(removed.is_err());
+        assert_eq!(get_chat_contacts(&ctx, chat.id).await.unwrap().len(), 1);
+
+        let removed = remove_contact_from_chat(&ctx, chat.id, ContactId::SELF).await;
+        assert!(removed.is_err());
+        assert_eq!(get_chat_contacts(&ctx, chat.id).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_members_at() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let bob = TestContext::new_bob().await;
+        let fiona = TestContext::new_fiona().await;
+        let alice_bob_id = alice.add_or_lookup_contact(&bob).await.id;
+        let alice_fiona_id = alice.add_or_lookup_contact(&fiona).await.id;
+
+        let alice_chat_id = create_group_chat(&alice, ProtectionStatus::Unprotected, "foo").await?;
+        add_contact_to_chat(&alice, alice_chat_id, alice_bob_id).await?;
+        let sent = alice.send_text(alice_chat_id, "Hi").await;
+        let bob_chat_id = bob.recv_msg(&sent).await.chat_id;
+        let bob_alice_id = bob.add_or_lookup_contact(&alice).await.id;
+        let bob_fiona_id = bob.add_or_lookup_contact(&fiona).await.id;
+
+        add_contact_to_chat(&alice, alice_chat_id, alice_fiona_id).await?;
+        let alice_added = alice.get_last_msg_in(alice_chat_id).await;
+        let bob_added = bob.recv_msg(&alice.pop_sent_msg().await).await;
+
+        remove_contact_from_chat(&alice, alice_chat_id, alice_fiona_id).await?;
+        let alice_removed = alice.get_last_msg_in(alice_chat_id).await;
+        let bob_removed = bob.recv_msg(&alice.pop_sent_msg().await).await;
+        assert!(alice_added.timestamp_sort < alice_removed.timestamp_sort);
+        assert!(bob_added.timestamp_sort < bob_removed.timestamp_sort);
+
+        let mut members = members_at(&alice, alice_chat_id, alice_added.timestamp_sort).await?;
+        members.sort_by_key(|id| id.to_u32());
+        let mut expected = vec![ContactId::SELF, alice_bob_id, alice_fiona_id];
+        expected.sort_by_key(|id| id.to_u32());
+        assert_eq!(members, expected);
+        assert!(
+            !members_at(&alice, alice_chat_id, alice_added.timestamp_sort - 1)
+                .await?
+                .contains(&alice_fiona_id)
+        );
+        assert_eq!(
+            members_at(&alice, alice_chat_id, alice_removed.timestamp_sort).await?,
+            get_chat_contacts(&alice, alice_chat_id).await?
+        );
+
+        // Bob reconstructs the membership from received info messages.
+        let mut members = members_at(&bob, bob_chat_id, bob_added.timestamp_sort).await?;
+        members.sort_by_key(|id| id.to_u32());
+        let mut expected = vec![ContactId::SELF, bob_alice_id, bob_fiona_id];
+        expected.sort_by_key(|id| id.to_u32());
+        assert_eq!(members, expected);
+        assert!(!members_at(&bob, bob_chat_id, bob_removed.timestamp_sort)
+            .await?
+            .contains(&bob_fiona_id));
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_get_shared_chats() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        let bob = Contact::create(&t, "", "bob@example.net").await?;
+        let fiona = Contact::create(&t, "", "fiona@example.net").await?;
+        let claire = Contact::create(&t, "", "claire@example.org").await?;
+
+        let bob_chat = ChatId::create_for_contact(&t, bob).await?;
+        let group1 = create_group_chat(&t, ProtectionStatus::Unprotected, "one").await?;
+        add_contact_to_chat(&t, group1, bob).await?;
+        add_contact_to_chat(&t, group1, fiona).await?;
+        let group2 = create_group_chat(&t, ProtectionStatus::Unprotected, "two").await?;
+        add_contact_to_chat(&t, group2, bob).await?;
+        let group3 = create_group_chat(&t, ProtectionStatus::Unprotected, "three").await?;
+        add_contact_to_chat(&t, group3, fiona).await?;
+        add_contact_to_chat(&t, group3, bob).await?;
+
+        assert_eq!(
+            get_shared_chats(&t, bob, fiona).await?,
+            vec![group1, group3]
+        );
+        assert_eq!(
+            get_shared_chats(&t, fiona, bob).await?,
+            vec![group1, group3]
+        );
+        assert_eq!(get_shared_chats(&t, bob, claire).await?, vec![]);
+        assert_eq!(
+            get_shared_chats(&t, ContactId::SELF, bob).await?,
+            vec![bob_chat, group1, group2, group3]
+        );
+        assert_eq!(
+            get_shared_chats(&t, fiona, ContactId::SELF).await?,
+            vec![group1, group3]
+        );
+
+        // Groups the user left and blocked chats are not shared.
+        remove_contact_from_chat(&t, group2, ContactId::SELF).await?;
+        group3.set_blocked(&t, Blocked::Yes).await?;
+        assert_eq!(
+            get_shared_chats(&t, ContactId::SELF, bob).await?,
+            vec![bob_chat, group1]
+        );
+        assert_eq!(get_shared_chats(&t, bob, fiona).await?, vec![group1]);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_try_restore_mailinglist_post() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        t.set_config(Config::ShowEmails, Some("2")).await?;
+        t.set_config_bool(Config::SaveMimeHeaders, true).await?;
+
+        let list_msg = |i: u32, list_post: &str| {
+            format!(
+                "From: Sender <sender@example.net>\n\
+                 To: list@example.net\n\
+                 Subject: Message {i}\n\
+                 Message-ID: <{i}@example.net>\n\
+                 List-ID: Some list <list.example.net>\n\
+                 List-Post: <mailto:{list_post}>\n\
+                 Precedence: list\n\
+                 Date: Sun, 22 Mar 2020 22:3{i}:57 +0000\n\
+                 \n\
+                 hello {i}\n",
+                i = i,
+                list_post = list_post
+            )
+        };
+
+        receive_imf(&t, list_msg(1, "a@example.net").as_bytes(), false).await?;
+        let chat_id = t.get_last_msg().await.chat_id;
+        chat_id.accept(&t).await?;
+        let chat = Chat::load_from_db(&t, chat_id).await?;
+        assert_eq!(chat.get_mailinglist_addr(), "a@example.net");
+        assert!(!try_restore_mailinglist_post(&t, chat_id).await?);
 
-        assert_eq!(get_chat_contacts(&alice, alice_chat_id).await?.len(), 1);
+        // The List-Post header changes, the mailing list becomes read-only.
+        receive_imf(&t, list_msg(2, "b@example.net").as_bytes(), false).await?;
+        let chat = Chat::load_from_db(&t, chat_id).await?;
+        assert_eq!(chat.get_mailinglist_addr(), "");
+        assert!(!try_restore_mailinglist_post(&t, chat_id).await?);
+
+        // Headers of the most recent message are not stored.
+        t.set_config_bool(Config::SaveMimeHeaders, false).await?;
+        receive_imf(&t, list_msg(3, "b@example.net").as_bytes(), false).await?;
+        assert!(!try_restore_mailinglist_post(&t, chat_id).await?);
+        t.set_config_bool(Config::SaveMimeHeaders, true).await?;
+        receive_imf(&t, list_msg(4, "b@example.net").as_bytes(), false).await?;
+        assert!(!try_restore_mailinglist_post(&t, chat_id).await?);
+
+        // The two most recent messages use the same List-Post header.
+        receive_imf(&t, list_msg(5, "b@example.net").as_bytes(), false).await?;
+        let chat = Chat::load_from_db(&t, chat_id).await?;
+        assert_eq!(chat.get_mailinglist_addr(), "");
+        assert_eq!(
+            chat.why_cant_send(&t).await?,
+            Some(CantSendReason::MailinglistVaryingListPost)
+        );
+        assert!(try_restore_mailinglist_post(&t, chat_id).await?);
+        let chat = Chat::load_from_db(&t, chat_id).await?;
+        assert_eq!(chat.get_mailinglist_addr(), "b@example.net");
+        assert_eq!(chat.why_cant_send(&t).await?, None);
+
+        // Further messages with the same List-Post header keep the mailing list writable.
+        receive_imf(&t, list_msg(6, "b@example.net").as_bytes(), false).await?;
+        let chat = Chat::load_from_db(&t, chat_id).await?;
+        assert_eq!(chat.get_mailinglist_addr(), "b@example.net");
 
         Ok(())
     }
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
-    async fn test_add_remove_contact_for_single() {
-        let ctx = TestContext::new_alice().await;
-        let bob = Contact::create(&ctx, "", "bob@f.br").await.unwrap();
-        let chat_id = ChatId::create_for_contact(&ctx, bob).await.unwrap();
-        let chat = Chat::load_from_db(&ctx, chat_id).await.unwrap();
-        assert_eq!(chat.typ, Chattype::Single);
-        assert_eq!(get_chat_contacts(&ctx, chat.id).await.unwrap().len(), 1);
+    async fn test_welcome_message() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let bob = TestContext::new_bob().await;
+        let fiona = TestContext::new_fiona().await;
 
-        // adding or removing contacts from one-to-one-chats result in an error
-        let claire = Contact::create(&ctx, "", "claire@foo.de").await.unwrap();
-        let added = add_contact_to_chat_ex(&ctx, chat.id, claire, false).await;
-        assert!(added.is_err());
-        assert_eq!(get_chat_contacts(&ctx, chat.id).await.unwrap().len(), 1);
+        let alice_bob_chat = alice.create_chat(&bob).await;
+        assert!(
+            set_welcome_message(&alice, alice_bob_chat.id, Some("Welcome!"))
+                .await
+                .is_err()
+        );
 
-        let removed = remove_contact_from_chat(&ctx, chat.id, claire).await;
-        assert!(removed.is_err());
-        assert_eq!(get_chat_contacts(&ctx, chat.id).await.unwrap().len(), 1);
+        // Members added by ourself get the welcome message.
+        let alice_chat_id = create_group_chat(&alice, ProtectionStatus::Unprotected, "grp").await?;
+        set_welcome_message(&alice, alice_chat_id, Some("Welcome!")).await?;
+        let chat = Chat::load_from_db(&alice, alice_chat_id).await?;
+        assert_eq!(chat.get_welcome_message(), Some("Welcome!"));
 
-        let removed = remove_contact_from_chat(&ctx, chat.id, ContactId::SELF).await;
-        assert!(removed.is_err());
-        assert_eq!(get_chat_contacts(&ctx, chat.id).await.unwrap().len(), 1);
+        let bob_id = alice.add_or_lookup_contact(&bob).await.id;
+        add_contact_to_chat(&alice, alice_chat_id, bob_id).await?;
+        let msg = alice.get_last_msg_in(alice_bob_chat.id).await;
+        assert_eq!(msg.get_text().as_deref(), Some("Welcome!"));
+        let sent = alice.pop_sent_msg().await;
+        let msg = bob.recv_msg(&sent).await;
+        assert_eq!(msg.get_text().as_deref(), Some("Welcome!"));
+
+        // Adding a member again does not send the welcome message again.
+        let msg_cnt = alice_bob_chat.id.get_msg_cnt(&alice).await?;
+        add_contact_to_chat(&alice, alice_chat_id, bob_id).await?;
+        assert_eq!(alice_bob_chat.id.get_msg_cnt(&alice).await?, msg_cnt);
+
+        // Members added by others do not get the welcome message of Bob,
+        // otherwise every member would send its own welcome message.
+        let sent = alice.send_text(alice_chat_id, "hi").await;
+        let bob_chat_id = bob.recv_msg(&sent).await.chat_id;
+        set_welcome_message(&bob, bob_chat_id, Some("Welcome from Bob!")).await?;
+        set_welcome_message(&alice, alice_chat_id, None).await?;
+
+        let fiona_id = alice.add_or_lookup_contact(&fiona).await.id;
+        add_contact_to_chat(&alice, alice_chat_id, fiona_id).await?;
+        let sent = alice.pop_sent_msg().await;
+        assert!(sent.payload().contains("Chat-Group-Member-Added"));
+        bob.recv_msg(&sent).await;
+        let bob_fiona_id = bob.add_or_lookup_contact(&fiona).await.id;
+        assert!(is_contact_in_chat(&bob, bob_chat_id, bob_fiona_id).await?);
+        assert!(ChatId::lookup_by_contact(&bob, bob_fiona_id)
+            .await?
+            .is_none());
+        assert_eq!(
+            bob.sql
+                .count("SELECT COUNT(*) FROM smtp", paramsv![])
+                .await?,
+            0
+        );
+
+        Ok(())
     }
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -4780,6 +6088,63 @@ async fn test_marknoticed_chat() -> Result<()> {
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_get_chatlist_item() -> Result<()> {
+        let t = TestContext::new_alice().await;
+
+        receive_imf(
+            &t,
+            b"From: bob@example.org\n\
+                 To: alice@example.org\n\
+                 Message-ID: <1@example.org>\n\
+                 Chat-Version: 1.0\n\
+                 Date: Sun, 22 Mar 2021 19:37:57 +0000\n\
+                 \n\
+                 hello\n",
+            false,
+        )
+        .await?;
+        let chat_id = t.get_last_msg().await.chat_id;
+        let event = t
+            .evtracker
+            .get_matching(|evt| matches!(evt, EventType::ChatlistItemChanged(_)))
+            .await;
+        assert_eq!(event, EventType::ChatlistItemChanged(chat_id));
+
+        let item = get_chatlist_item(&t, chat_id).await?;
+        assert_eq!(item.chat_id, chat_id);
+        assert_eq!(item.name, "bob@example.org");
+        assert_eq!(item.preview_text, "hello");
+        assert_eq!(item.preview_ts, 1616441877);
+        assert_eq!(item.unread_count, 1);
+        assert_eq!(item.avatar_path, None);
+        assert!(item.is_contact_request);
+        assert!(!item.is_archived);
+        assert!(!item.is_muted);
+
+        chat_id.accept(&t).await?;
+        t.evtracker
+            .get_matching(|evt| matches!(evt, EventType::ChatlistItemChanged(id) if *id == chat_id))
+            .await;
+        set_muted(&t, chat_id, MuteDuration::Forever).await?;
+        chat_id.set_visibility(&t, ChatVisibility::Archived).await?;
+        let item = get_chatlist_item(&t, chat_id).await?;
+        assert!(!item.is_contact_request);
+        assert!(item.is_archived);
+        assert!(item.is_muted);
+
+        // The saved messages chat has an avatar but no messages.
+        let self_chat = t.get_self_chat().await;
+        let item = get_chatlist_item(&t, self_chat.id).await?;
+        assert_eq!(item.preview_text, stock_str::no_messages(&t).await);
+        assert_eq!(item.preview_ts, 0);
+        assert!(item.avatar_path.is_some());
+
+        assert!(get_chatlist_item(&t, DC_CHAT_ID_TRASH).await.is_err());
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_contact_request_fresh_messages() -> Result<()> {
         let t = TestContext::new_alice().await;
@@ -5355,6 +6720,289 @@ async fn test_can_send_group() -> Result<()> {
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_why_cant_send() -> Result<()> {
+        let t = TestContext::new_alice().await;
+
+        let chat = Chat::load_from_db(&t, DC_CHAT_ID_TRASH).await?;
+        assert_eq!(
+            chat.why_cant_send(&t).await?,
+            Some(CantSendReason::SpecialChat)
+        );
+
+        t.update_device_chats().await?;
+        let chat_id = ChatId::get_for_contact(&t, ContactId::DEVICE).await?;
+        let chat = Chat::load_from_db(&t, chat_id).await?;
+        assert_eq!(
+            chat.why_cant_send(&t).await?,
+            Some(CantSendReason::DeviceChat)
+        );
+
+        let chat_id = create_group_chat(&t, ProtectionStatus::Unprotected, "foo").await?;
+        let chat = Chat::load_from_db(&t, chat_id).await?;
+        assert_eq!(chat.why_cant_send(&t).await?, None);
+        remove_contact_from_chat(&t, chat_id, ContactId::SELF).await?;
+        let chat = Chat::load_from_db(&t, chat_id).await?;
+        assert_eq!(
+            chat.why_cant_send(&t).await?,
+            Some(CantSendReason::NotMember)
+        );
+        assert!(!chat.can_send(&t).await?);
+
+        let chat_id = t
+            .create_chat_with_contact("Bob", "bob@example.net")
+            .await
+            .id;
+        chat_id.block(&t).await?;
+        let chat = Chat::load_from_db(&t, chat_id).await?;
+        assert_eq!(chat.why_cant_send(&t).await?, Some(CantSendReason::Blocked));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_group_admin_name_change() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let bob = TestContext::new_bob().await;
+        let fiona = TestContext::new_fiona().await;
+
+        let alice_chat_id = create_group_chat(&alice, ProtectionStatus::Unprotected, "foo").await?;
+        let alice_chat = Chat::load_from_db(&alice, alice_chat_id).await?;
+        assert_eq!(
+            alice_chat.get_admin_id(&alice).await?,
+            Some(ContactId::SELF)
+        );
+        assert!(alice_chat.can_edit_name(&alice).await?);
+        for contact_id in [
+            alice.add_or_lookup_contact(&bob).await.id,
+            alice.add_or_lookup_contact(&fiona).await.id,
+        ] {
+            add_contact_to_chat(&alice, alice_chat_id, contact_id).await?;
+        }
+        let sent = alice.send_text(alice_chat_id, "Hi").await;
+
+        let bob_chat_id = bob.recv_msg(&sent).await.chat_id;
+        bob_chat_id.accept(&bob).await?;
+        let bob_chat = Chat::load_from_db(&bob, bob_chat_id).await?;
+        assert_eq!(
+            bob_chat.get_admin_id(&bob).await?,
+            Some(bob.add_or_lookup_contact(&alice).await.id)
+        );
+        assert!(!bob_chat.can_edit_name(&bob).await?);
+        let fiona_chat_id = fiona.recv_msg(&sent).await.chat_id;
+
+        assert!(set_chat_name(&bob, bob_chat_id, "bar").await.is_err());
+        assert_eq!(Chat::load_from_db(&bob, bob_chat_id).await?.name, "foo");
+
+        // Name changes by other members are ignored,
+        // e.g. if they come from a device that did not record the admin.
+        bob.sql
+            .execute(
+                "UPDATE chats SET admin_contact_id=NULL WHERE id=?",
+                paramsv![bob_chat_id],
+            )
+            .await?;
+        set_chat_name(&bob, bob_chat_id, "bar").await?;
+        fiona.recv_msg(&bob.pop_sent_msg().await).await;
+        assert_eq!(Chat::load_from_db(&fiona, fiona_chat_id).await?.name, "foo");
+
+        set_chat_name(&alice, alice_chat_id, "baz").await?;
+        fiona.recv_msg(&alice.pop_sent_msg().await).await;
+        assert_eq!(Chat::load_from_db(&fiona, fiona_chat_id).await?.name, "baz");
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_group_creator() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let bob = TestContext::new_bob().await;
+
+        let alice_chat_id = create_group_chat(&alice, ProtectionStatus::Unprotected, "foo").await?;
+        let alice_chat = Chat::load_from_db(&alice, alice_chat_id).await?;
+        assert_eq!(alice_chat.get_creator_id(), Some(ContactId::SELF));
+        add_contact_to_chat(
+            &alice,
+            alice_chat_id,
+            alice.add_or_lookup_contact(&bob).await.id,
+        )
+        .await?;
+        let sent = alice.send_text(alice_chat_id, "Hi").await;
+
+        let bob_chat_id = bob.recv_msg(&sent).await.chat_id;
+        let bob_chat = Chat::load_from_db(&bob, bob_chat_id).await?;
+        assert_eq!(
+            bob_chat.get_creator_id(),
+            Some(bob.add_or_lookup_contact(&alice).await.id)
+        );
+
+        // Groups created before the migration have no creator.
+        alice
+            .sql
+            .execute("UPDATE chats SET creator_id=NULL;", paramsv![])
+            .await?;
+        let alice_chat = Chat::load_from_db(&alice, alice_chat_id).await?;
+        assert_eq!(alice_chat.get_creator_id(), None);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_why_cant_send_removed_from_group() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let bob = TestContext::new_bob().await;
+        let alice_chat_id = create_group_chat(&alice, ProtectionStatus::Unprotected, "foo").await?;
+        add_contact_to_chat(
+            &alice,
+            alice_chat_id,
+            alice.add_or_lookup_contact(&bob).await.id,
+        )
+        .await?;
+        let sent = alice.send_text(alice_chat_id, "Hi").await;
+        let bob_chat_id = bob.recv_msg(&sent).await.chat_id;
+        bob_chat_id.accept(&bob).await?;
+        let bob_chat = Chat::load_from_db(&bob, bob_chat_id).await?;
+        assert_eq!(bob_chat.why_cant_send(&bob).await?, None);
+
+        remove_contact_from_chat(
+            &alice,
+            alice_chat_id,
+            alice.add_or_lookup_contact(&bob).await.id,
+        )
+        .await?;
+        bob.recv_msg(&alice.pop_sent_msg().await).await;
+        let bob_chat = Chat::load_from_db(&bob, bob_chat_id).await?;
+        assert_eq!(
+            bob_chat.why_cant_send(&bob).await?,
+            Some(CantSendReason::NotMember)
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_classify_reply() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let bob = TestContext::new_bob().await;
+        let fiona = TestContext::new_fiona().await;
+        let bob_id = alice.add_or_lookup_contact(&bob).await.id;
+        let fiona_id = alice.add_or_lookup_contact(&fiona).await.id;
+
+        let chat_id = create_group_chat(&alice, ProtectionStatus::Unprotected, "foo").await?;
+        add_contact_to_chat(&alice, chat_id, bob_id).await?;
+
+        // Classical MUA reply to a two-member group goes to the group.
+        assert_eq!(
+            classify_reply(&alice, &[ContactId::SELF], bob_id, false, chat_id).await?,
+            ReplyKind::GroupReply
+        );
+
+        // Delta Chat reply without group ID is a private reply.
+        assert_eq!(
+            classify_reply(&alice, &[ContactId::SELF], bob_id, true, chat_id).await?,
+            ReplyKind::PrivateReply
+        );
+
+        // Outgoing reply to a single recipient is private as well.
+        assert_eq!(
+            classify_reply(&alice, &[bob_id], ContactId::SELF, true, chat_id).await?,
+            ReplyKind::PrivateReply
+        );
+
+        // Replies to several recipients are never private.
+        assert_eq!(
+            classify_reply(&alice, &[ContactId::SELF, fiona_id], bob_id, true, chat_id).await?,
+            ReplyKind::GroupReply
+        );
+
+        // Classical reply to a bigger group is private.
+        add_contact_to_chat(&alice, chat_id, fiona_id).await?;
+        assert_eq!(
+            classify_reply(&alice, &[ContactId::SELF], bob_id, false, chat_id).await?,
+            ReplyKind::PrivateReply
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_get_storage_usage() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let bob = TestContext::new_bob().await;
+        let alice_chat = alice.create_chat(&bob).await;
+
+        let file = alice.get_blobdir().join("file.txt");
+        tokio::fs::write(&file, b"hello world").await?;
+        let mut msg = Message::new(Viewtype::File);
+        msg.set_file(file.to_str().unwrap(), None);
+        let sent = alice.send_msg(alice_chat.id, &mut msg).await;
+        let (_, alice_blob_bytes, _) = alice_chat.id.get_storage_usage(&alice).await?;
+        assert_eq!(alice_blob_bytes, 11);
+
+        let msg = bob.recv_msg(&sent).await;
+        let file_bytes = tokio::fs::metadata(msg.get_file(&bob).unwrap())
+            .await?
+            .len();
+        let (msg_bytes, blob_bytes, msg_count) = msg.chat_id.get_storage_usage(&bob).await?;
+        assert!(msg_bytes > 0);
+        assert_eq!(blob_bytes, file_bytes);
+        assert_eq!(msg_count, 1);
+        assert!(get_storage_usage(&bob).await?.contains(&(
+            msg.chat_id,
+            msg_bytes,
+            blob_bytes,
+            msg_count
+        )));
+
+        // Sizes not recorded yet are filled in, missing files count as zero bytes.
+        bob.sql
+            .execute("UPDATE msgs SET blob_bytes=NULL;", paramsv![])
+            .await?;
+        tokio::fs::remove_file(msg.get_file(&bob).unwrap()).await?;
+        let (_, blob_bytes, msg_count) = msg.chat_id.get_storage_usage(&bob).await?;
+        assert_eq!(blob_bytes, 0);
+        assert_eq!(msg_count, 1);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_get_media_per_member() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let bob = TestContext::new_bob().await;
+        let alice_chat = alice.create_chat(&bob).await;
+        let bob_chat = bob.create_chat(&alice).await;
+        let alice_id = bob.add_or_lookup_contact(&alice).await.id;
+
+        let file = alice.get_blobdir().join("image.png");
+        tokio::fs::write(&file, include_bytes!("../test-data/image/avatar64x64.png")).await?;
+        let mut msg = Message::new(Viewtype::Image);
+        msg.set_file(file.to_str().unwrap(), None);
+        bob.recv_msg(&alice.send_msg(alice_chat.id, &mut msg).await)
+            .await;
+
+        let file = alice.get_blobdir().join("file.txt");
+        tokio::fs::write(&file, b"hello").await?;
+        let mut msg = Message::new(Viewtype::File);
+        msg.set_file(file.to_str().unwrap(), None);
+        bob.recv_msg(&alice.send_msg(alice_chat.id, &mut msg).await)
+            .await;
+
+        bob.recv_msg(&alice.send_text(alice_chat.id, "hi").await)
+            .await;
+        bob.send_text(bob_chat.id, "hello").await;
+
+        let counts = get_media_count_per_member(&bob, bob_chat.id).await?;
+        assert_eq!(counts.len(), 1);
+        let alice_counts = counts.get(&alice_id).unwrap();
+        assert_eq!(alice_counts.len(), 2);
+        assert_eq!(alice_counts.get(&Viewtype::Image), Some(&1));
+        assert_eq!(alice_counts.get(&Viewtype::File), Some(&1));
+
+        let sizes = get_total_media_size_per_member(&bob, bob_chat.id).await?;
+        assert!(*sizes.get(&alice_id).unwrap() > 250);
+        assert_eq!(sizes.get(&ContactId::SELF), Some(&0));
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_broadcast() -> Result<()> {
         // create two context, send two messages so both know the other
@@ -5486,4 +7134,114 @@ async fn test_chat_get_encryption_info() -> Result<()> {
 
         Ok(())
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_get_oldest_active_chats() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        let bob_chat = t.create_chat_with_contact("Bob", "bob@example.net").await;
+        let fiona_chat = t
+            .create_chat_with_contact("Fiona", "fiona@example.net")
+            .await;
+        let claire_chat = t
+            .create_chat_with_contact("Claire", "claire@example.org")
+            .await;
+        t.send_text(bob_chat.id, "hi bob").await;
+        t.send_text(fiona_chat.id, "hi fiona").await;
+        t.send_text(claire_chat.id, "hi claire").await;
+        t.send_text(bob_chat.id, "hi again").await;
+
+        let ours = |chats: Vec<(ChatId, i64)>| -> Vec<ChatId> {
+            chats
+                .into_iter()
+                .map(|(chat_id, _)| chat_id)
+                .filter(|chat_id| [bob_chat.id, fiona_chat.id, claire_chat.id].contains(chat_id))
+                .collect()
+        };
+
+        let chats = get_oldest_active_chats(&t, 10).await?;
+        assert!(chats.windows(2).all(|w| w[0].1 <= w[1].1));
+        assert_eq!(
+            ours(chats),
+            vec![fiona_chat.id, claire_chat.id, bob_chat.id]
+        );
+
+        // Archived and pinned chats are not suggested.
+        fiona_chat
+            .id
+            .set_visibility(&t, ChatVisibility::Archived)
+            .await?;
+        claire_chat
+            .id
+            .set_visibility(&t, ChatVisibility::Pinned)
+            .await?;
+        let chats = get_oldest_active_chats(&t, 10).await?;
+        assert_eq!(ours(chats), vec![bob_chat.id]);
+
+        assert!(get_oldest_active_chats(&t, 0).await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_export_html() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let bob = TestContext::new_bob().await;
+        let bob_chat = bob.create_chat(&alice).await;
+        let received = alice
+            .recv_msg(&bob.send_text(bob_chat.id, "Hello <b>Alice</b>").await)
+            .await;
+        let chat_id = alice.create_chat(&bob).await.id;
+        assert_eq!(received.chat_id, chat_id);
+
+        let mut msg = Message::new(Viewtype::Text);
+        msg.set_text(Some("Hi & bye".to_string()));
+        msg.set_quote(&alice, Some(&received)).await?;
+        alice.send_msg(chat_id, &mut msg).await;
+
+        let file = alice.get_blobdir().join("image.png");
+        tokio::fs::write(&file, include_bytes!("../test-data/image/avatar64x64.png")).await?;
+        let mut msg = Message::new(Viewtype::Image);
+        msg.set_file(file.to_str().unwrap(), None);
+        alice.send_msg(chat_id, &mut msg).await;
+        let image = Message::load_from_db(&alice, msg.id).await?;
+
+        add_info_msg(&alice, chat_id, "Some info", time()).await?;
+        add_info_msg_with_cmd(
+            &alice,
+            chat_id,
+            "Protection enabled",
+            SystemMessage::ChatProtectionEnabled,
+            time(),
+            None,
+            None,
+            None,
+        )
+        .await?;
+        let deleted = alice.send_text(chat_id, "Deleted text").await;
+        message::delete_msgs(&alice, &[deleted.sender_msg_id]).await?;
+
+        let dir = tempfile::tempdir()?;
+        let index = export_html(&alice, chat_id, dir.path()).await?;
+        assert_eq!(index, dir.path().join("index.html"));
+        let html = tokio::fs::read_to_string(&index).await?;
+        assert!(html.contains("<div class=\"sender\">bob@example.net</div>"));
+        assert!(html.contains("<div class=\"text\">Hello &lt;b&gt;Alice&lt;/b&gt;</div>"));
+        assert!(html.contains("<blockquote>Hello &lt;b&gt;Alice&lt;/b&gt;</blockquote>"));
+        assert!(html.contains("<div class=\"text\">Hi &amp; bye</div>"));
+        assert!(html.contains("<img src=\"files/"));
+        assert!(html.contains("<div class=\"info\">"));
+        assert!(html.contains("Some info"));
+        assert!(html.contains("<div class=\"info protection\">"));
+        assert!(!html.contains("Deleted text"));
+
+        let image_file = dir.path().join("files").join(format!(
+            "{}-{}",
+            image.id.to_u32(),
+            image.get_filename().unwrap()
+        ));
+        assert_eq!(
+            tokio::fs::read(image_file).await?,
+            tokio::fs::read(image.get_file(&alice).unwrap()).await?
+        );
+        Ok(())
+    }
 }
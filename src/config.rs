@@ -9,6 +9,7 @@
 use crate::contact::addr_cmp;
 use crate::context::Context;
 use crate::events::EventType;
+use crate::imex::{self, SetupCodeValidity};
 use crate::mimefactory::RECOMMENDED_FILE_SIZE;
 use crate::provider::{get_provider_by_id, Provider};
 use crate::tools::{get_abs_path, improve_single_line_input, EmailAddress};
@@ -184,6 +185,154 @@ pub enum Config {
     /// In a future versions, this switch may be removed.
     #[strum(props(default = "0"))]
     SendSyncMsgs,
+
+    /// Comma-separated list of trusted mailing list domains, e.g. `example.org,example.com`.
+    ///
+    /// Mailing lists whose List-Id or From domain equals one of these domains or is a subdomain
+    /// of one are accepted automatically instead of being shown as contact requests.
+    TrustedMailinglistDomains,
+
+    /// Accept contact requests from known contacts automatically.
+    ///
+    /// A contact is known if its origin is at least `Origin::IncomingReplyTo`,
+    /// first-time senders still end up in a contact request.
+    #[strum(props(default = "0"))]
+    AutoAcceptKnownContactRequests,
+
+    /// Delete read receipts (MDNs) and delivery status notifications (DSNs)
+    /// from the server once they are processed.
+    #[strum(props(default = "0"))]
+    DeleteMdnsFromServer,
+
+    /// Mute newly created mailing lists forever,
+    /// so they neither trigger notifications nor get unarchived by new messages.
+    #[strum(props(default = "0"))]
+    MuteNewMailinglists,
+
+    /// Number of IMAP connections used to scan folders that are not watched, 1 to 4.
+    ///
+    /// The connection watching the inbox is always used,
+    /// additional connections are opened for the duration of the scan.
+    #[strum(props(default = "1"))]
+    ImapConnectionCount,
+
+    /// Minimum time in seconds since deletion of trashed messages removed by `purge_trash()`.
+    ///
+    /// Younger trashed messages are kept to detect duplicates of recently deleted messages.
+    #[strum(props(default = "604800"))]
+    TrashRetentionSecs,
+
+    /// Additional addresses of the user separated by spaces,
+    /// e.g. addresses the provider forwards to this account.
+    ///
+    /// Unlike `SecondaryAddrs`, which are managed by the core,
+    /// these addresses are set by the user.
+    /// Messages from or to them are assigned to chats as if they were from or to self.
+    KnownAliases,
+
+    /// Append the address a classic email was delivered to
+    /// to the sender name shown in the chat,
+    /// so that emails received via different aliases can be told apart.
+    #[strum(props(default = "0"))]
+    ShowDeliveredToAlias,
+
+    /// Emit `EventType::ImapFolderStats` after each scan of an IMAP folder.
+    #[strum(props(default = "0"))]
+    EmitFolderStats,
+
+    /// Accept the contact request chat with a contact
+    /// as soon as the contact becomes verified.
+    #[strum(props(default = "0"))]
+    AutoAcceptVerified,
+
+    /// Minimum origin, as numeric `Origin` value, the sender of a classical email needs
+    /// so that an ad-hoc group is created for it.
+    ///
+    /// Messages from senders with a lower origin are assigned to the 1:1 chat instead.
+    /// The default of 0 allows ad-hoc groups for all senders.
+    #[strum(props(default = "0"))]
+    AdhocGroupMinOrigin,
+
+    /// Hostname to send in the SMTP EHLO command.
+    ///
+    /// Some servers require it to match the PTR record of the sending host.
+    /// If unset, the default hostname of the SMTP client is used.
+    SmtpHelo,
+
+    /// Number of recently emitted events to keep for [`Context::get_event_log`].
+    ///
+    /// The default of 0 disables the event log.
+    #[strum(props(default = "0"))]
+    EventLogSize,
+
+    /// Comma-separated list of headers marking classic emails as sent automatically.
+    ///
+    /// The From display name of such messages does not rename the contact,
+    /// but is only shown for the message, as for messages with `Auto-Submitted` header.
+    #[strum(props(default = "X-Auto-Response-Suppress"))]
+    PreventRenameHeaders,
+
+    /// If set to "1", classic emails with multiple recipients do not create ad-hoc groups,
+    /// but are assigned to the 1:1 chat with the sender.
+    ///
+    /// Groups with a group ID, e.g. created by Delta Chat, are not affected.
+    #[strum(props(default = "0"))]
+    DisableAdhocGroups,
+
+    /// If set to "1", classic emails sent to addresses of the own domain
+    /// that are not configured as self addresses, e.g. when using a catch-all domain,
+    /// are assigned to a single "Catch-All" chat instead of the chat with the sender.
+    ///
+    /// The original recipient address is stored as [`crate::param::Param::DeliveredTo`].
+    #[strum(props(default = "0"))]
+    CatchAllChat,
+
+    /// ID of the chat created for [`Config::CatchAllChat`], unset until a message is caught.
+    CatchAllChatId,
+
+    /// Comma-separated list of the `authserv-id`s used by the own provider
+    /// in `Authentication-Results` headers, e.g. `mx.example.org`.
+    ///
+    /// Only verdicts of these servers are trusted, other headers may be forged by the sender.
+    /// If unset, the `authserv-id` is learned from messages sent and signed by the user.
+    AuthservId,
+
+    /// Maximum size in bytes of a single received attachment, 0 for no limit.
+    ///
+    /// Larger attachments are not stored
+    /// and replaced by a text part telling that the attachment was too large.
+    #[strum(props(default = "104857600"))]
+    MaxBlobSizeBytes,
+
+    /// Ephemeral timer in seconds applied to newly created chats, 0 to disable.
+    #[strum(props(default = "0"))]
+    DefaultEphemeralTimer,
+
+    /// Whether remote images are removed from the HTML returned by `MsgId::get_html()`.
+    ///
+    /// Remote images are often used as tracking pixels.
+    #[strum(props(default = "1"))]
+    BlockRemoteContent,
+
+    /// Layout of the blob directory, see [`crate::constants::BlobLayout`].
+    ///
+    /// Changing the layout only affects attachments received afterwards,
+    /// existing blobs are not moved.
+    #[strum(props(default = "0"))] // also change BlobLayout.default() on changes
+    BlobLayout,
+
+    /// If set to "1", Autocrypt Setup Messages sent by another device of the user
+    /// are imported automatically using [`Config::KeyTransferSetupCode`].
+    #[strum(props(default = "0"))]
+    AutoContinueKeyTransfer,
+
+    /// Setup code expected for Autocrypt Setup Messages
+    /// if [`Config::AutoContinueKeyTransfer`] is enabled.
+    ///
+    /// The code is removed once a key was imported with it.
+    /// It is write-only, [`Context::get_config`] always returns `None`,
+    /// and it is not exported to backups.
+    KeyTransferSetupCode,
 }
 
 impl Context {
@@ -201,6 +350,7 @@ pub async fn get_config(&self, key: Config) -> Result<Option<String>> {
             Config::SysVersion => Some((&*DC_VERSION_STR).clone()),
             Config::SysMsgsizeMaxRecommended => Some(format!("{}", RECOMMENDED_FILE_SIZE)),
             Config::SysConfigKeys => Some(get_config_keys_string()),
+            Config::KeyTransferSetupCode => None,
             _ => self.sql.get_raw_config(key).await?,
         };
 
@@ -305,10 +455,25 @@ pub async fn set_config(&self, key: Config, value: Option<&str>) -> Result<()> {
                 self.interrupt_ephemeral_task().await;
                 ret?
             }
+            Config::EventLogSize => {
+                self.sql.set_raw_config(key, value).await?;
+                self.update_event_log_size().await?;
+            }
             Config::Displayname => {
                 let value = value.map(improve_single_line_input);
                 self.sql.set_raw_config(key, value.as_deref()).await?;
             }
+            Config::KeyTransferSetupCode => {
+                let value = value.filter(|value| !value.is_empty());
+                ensure!(
+                    value.map_or(true, |value| {
+                        imex::is_valid_setup_code(value) != SetupCodeValidity::BadFormat
+                    }),
+                    "Invalid setup code."
+                );
+                let value = value.map(imex::normalize_setup_code);
+                self.sql.set_raw_config(key, value.as_deref()).await?;
+            }
             _ => {
                 self.sql.set_raw_config(key, value).await?;
             }
@@ -352,6 +517,11 @@ pub(crate) async fn is_self_addr(&self, addr: &str) -> Result<bool> {
                 .get_secondary_self_addrs()
                 .await?
                 .iter()
+                .any(|a| addr_cmp(addr, a))
+            || self
+                .get_known_aliases()
+                .await?
+                .iter()
                 .any(|a| addr_cmp(addr, a)))
     }
 
@@ -409,6 +579,18 @@ pub(crate) async fn get_secondary_self_addrs(&self) -> Result<Vec<String>> {
             .collect())
     }
 
+    /// Returns the addresses set in [`Config::KnownAliases`].
+    pub(crate) async fn get_known_aliases(&self) -> Result<Vec<String>> {
+        let aliases = self
+            .get_config(Config::KnownAliases)
+            .await?
+            .unwrap_or_default();
+        Ok(aliases
+            .split_ascii_whitespace()
+            .map(|s| s.to_string())
+            .collect())
+    }
+
     /// Returns the primary self address.
     /// Returns an error if no self addr is configured.
     pub async fn get_primary_self_addr(&self) -> Result<String> {
@@ -536,6 +718,23 @@ async fn test_self_addrs() -> Result<()> {
             vec!["alice@alice.com", "Alice@Example.Org"]
         );
 
+        // Known aliases are self addresses, but not stored as secondary addresses
+        alice
+            .set_config(
+                Config::KnownAliases,
+                Some("me@forward.example  alice@old.example"),
+            )
+            .await?;
+        assert!(alice.is_self_addr("Me@Forward.example").await?);
+        assert!(alice.is_self_addr("alice@old.example").await?);
+        assert!(!alice.is_self_addr("forward.example").await?);
+        assert_eq!(
+            alice.get_all_self_addrs().await?,
+            vec!["alice@alice.com", "Alice@Example.Org"]
+        );
+        alice.set_config(Config::KnownAliases, None).await?;
+        assert!(!alice.is_self_addr("me@forward.example").await?);
+
         // Test switching back
         alice.set_primary_self_addr("alice@example.org").await?;
         assert_eq!(
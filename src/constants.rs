@@ -65,6 +65,24 @@ fn default() -> Self {
     }
 }
 
+/// How attachments of received messages are organized in the blob directory.
+#[derive(
+    Debug, Display, Clone, Copy, PartialEq, Eq, FromPrimitive, ToPrimitive, FromSql, ToSql,
+)]
+#[repr(u8)]
+pub enum BlobLayout {
+    /// All blobs are stored directly in the blob directory.
+    Flat = 0,
+    /// Attachments of received messages are stored in a subdirectory per chat.
+    PerChat = 1,
+}
+
+impl Default for BlobLayout {
+    fn default() -> Self {
+        BlobLayout::Flat // also change Config.BlobLayout props(default) on changes
+    }
+}
+
 #[derive(
     Debug, Display, Clone, Copy, PartialEq, Eq, FromPrimitive, ToPrimitive, FromSql, ToSql,
 )]
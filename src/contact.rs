@@ -1,21 +1,24 @@
 //! Contacts module
 
+use std::collections::HashSet;
 use std::convert::{TryFrom, TryInto};
 use std::fmt;
 use std::path::PathBuf;
 
 use anyhow::{bail, ensure, Context as _, Result};
 use deltachat_derive::{FromSql, ToSql};
+use mailparse::SingleInfo;
 use once_cell::sync::Lazy;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
 use crate::aheader::EncryptPreference;
-use crate::chat::ChatId;
+use crate::chat::{ChatId, ChatIdBlocked};
 use crate::color::str_to_color;
 use crate::config::Config;
 use crate::constants::{Blocked, Chattype, DC_GCL_ADD_SELF, DC_GCL_VERIFIED_ONLY};
 use crate::context::Context;
+use crate::dehtml::sanitize_html;
 use crate::events::EventType;
 use crate::key::{DcKey, SignedPublicKey};
 use crate::login_param::LoginParam;
@@ -24,7 +27,7 @@
 use crate::param::{Param, Params};
 use crate::peerstate::{Peerstate, PeerstateVerifiedStatus};
 use crate::sql::{self, params_iter};
-use crate::tools::{get_abs_path, improve_single_line_input, EmailAddress};
+use crate::tools::{get_abs_path, improve_single_line_input, truncate, EmailAddress};
 use crate::{chat, stock_str};
 
 /// Contact ID, including reserved IDs.
@@ -42,7 +45,7 @@ impl ContactId {
     pub const SELF: ContactId = ContactId::new(1);
     pub const INFO: ContactId = ContactId::new(2);
     pub const DEVICE: ContactId = ContactId::new(5);
-    const LAST_SPECIAL: ContactId = ContactId::new(9);
+    pub(crate) const LAST_SPECIAL: ContactId = ContactId::new(9);
 
     /// Address to go with [`ContactId::DEVICE`].
     ///
@@ -148,6 +151,9 @@ pub struct Contact {
     /// Time when the contact was seen last time, Unix time in seconds.
     last_seen: i64,
 
+    /// The header the last seen timestamp was taken from.
+    last_seen_source: LastSeenSource,
+
     /// The origin/source of the contact.
     pub origin: Origin,
 
@@ -222,6 +228,30 @@ pub enum Origin {
     ManuallyCreated = 0x0400_0000,
 }
 
+/// Source of the [`Contact::last_seen`] timestamp.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, FromPrimitive, ToPrimitive, FromSql, ToSql,
+)]
+#[repr(u32)]
+pub enum LastSeenSource {
+    /// The contact was never seen or was seen by an older version
+    /// that did not record the source.
+    Unknown = 0,
+
+    /// Timestamp was taken from the `Date:` header of the message.
+    Date = 1,
+
+    /// `Date:` header was missing or in the future,
+    /// so the time the message was received was used instead.
+    Received = 2,
+}
+
+impl Default for LastSeenSource {
+    fn default() -> Self {
+        LastSeenSource::Unknown
+    }
+}
+
 impl Default for Origin {
     fn default() -> Self {
         Origin::Unknown
@@ -267,7 +297,7 @@ pub async fn load_from_db(context: &Context, contact_id: ContactId) -> Result<Se
             .sql
             .query_row(
                 "SELECT c.name, c.addr, c.origin, c.blocked, c.last_seen,
-                c.authname, c.param, c.status
+                c.authname, c.param, c.status, c.last_seen_source
                FROM contacts c
               WHERE c.id=?;",
                 paramsv![contact_id],
@@ -280,6 +310,7 @@ pub async fn load_from_db(context: &Context, contact_id: ContactId) -> Result<Se
                     let authname: String = row.get(5)?;
                     let param: String = row.get(6)?;
                     let status: Option<String> = row.get(7)?;
+                    let last_seen_source: LastSeenSource = row.get(8)?;
                     let contact = Self {
                         id: contact_id,
                         name,
@@ -287,6 +318,7 @@ pub async fn load_from_db(context: &Context, contact_id: ContactId) -> Result<Se
                         addr,
                         blocked: blocked.unwrap_or_default(),
                         last_seen,
+                        last_seen_source,
                         origin,
                         param: param.parse().unwrap_or_default(),
                         status: status.unwrap_or_default(),
@@ -323,6 +355,11 @@ pub fn last_seen(&self) -> i64 {
         self.last_seen
     }
 
+    /// Returns where the last seen timestamp was taken from.
+    pub fn last_seen_source(&self) -> LastSeenSource {
+        self.last_seen_source
+    }
+
     /// Check if a contact is blocked.
     pub async fn is_blocked_load(context: &Context, id: ContactId) -> Result<bool> {
         let blocked = Self::load_from_db(context, id).await?.blocked;
@@ -674,6 +711,24 @@ pub async fn add_address_book(context: &Context, addr_book: &str) -> Result<usiz
         Ok(modify_cnt)
     }
 
+    /// Imports contacts from a vCard string as created by [`Contact::export_to_vcard`]
+    /// or by other applications.
+    ///
+    /// The vCard may contain several records; records without `EMAIL` property are ignored.
+    /// The contacts are added as if created manually by the user, see [`Contact::create`].
+    ///
+    /// Returns the IDs of the imported contacts.
+    pub async fn import_vcard(context: &Context, vcard: &str) -> Result<Vec<ContactId>> {
+        let mut contact_ids = Vec::new();
+        for (name, addr) in parse_vcard(vcard) {
+            match Contact::create(context, &name, &addr).await {
+                Ok(contact_id) => contact_ids.push(contact_id),
+                Err(err) => warn!(context, "Cannot import vCard contact {}: {:#}", addr, err),
+            }
+        }
+        Ok(contact_ids)
+    }
+
     /// Returns known and unblocked contacts.
     ///
     /// To get information about a single contact, see get_contact().
@@ -970,6 +1025,184 @@ pub async fn delete(context: &Context, contact_id: ContactId) -> Result<()> {
         bail!("Could not delete contact with ongoing chats");
     }
 
+    /// Merges the duplicate contact `merge_id` into the contact `keep_id`.
+    ///
+    /// Both contacts must have the same address, ignoring case;
+    /// [`find_duplicate_candidates`] returns such contacts.
+    /// Messages, chat memberships, locations and read receipts are reassigned to `keep_id`,
+    /// names and parameters not set for `keep_id` are taken over from `merge_id`.
+    /// If there are 1:1 chats with both contacts,
+    /// the messages are moved to the chat with `keep_id` and the other chat is deleted.
+    /// Of several peerstates for the address, the best verified one is kept.
+    /// Finally, `merge_id` is deleted.
+    pub async fn merge(context: &Context, keep_id: ContactId, merge_id: ContactId) -> Result<()> {
+        ensure!(
+            !keep_id.is_special() && !merge_id.is_special(),
+            "Can not merge special contacts"
+        );
+        ensure!(keep_id != merge_id, "Can not merge contact with itself");
+        let keep = Contact::load_from_db(context, keep_id).await?;
+        let merge = Contact::load_from_db(context, merge_id).await?;
+        ensure!(
+            addr_cmp(&keep.addr, &merge.addr),
+            "Can not merge contacts with different addresses"
+        );
+
+        let chat_ids = context
+            .sql
+            .query_map(
+                "SELECT chat_id FROM chats_contacts WHERE contact_id=?
+                 UNION SELECT chat_id FROM msgs WHERE from_id=? OR to_id=?",
+                paramsv![merge_id, merge_id, merge_id],
+                |row| row.get::<_, ChatId>(0),
+                |ids| {
+                    ids.collect::<std::result::Result<Vec<_>, _>>()
+                        .map_err(Into::into)
+                },
+            )
+            .await?;
+
+        // If there are two 1:1 chats, the one with `merge_id` is merged into the other one.
+        // The resulting chat is accepted if any of them was accepted.
+        let merge_chats = match (
+            ChatIdBlocked::lookup_by_contact(context, keep_id).await?,
+            ChatIdBlocked::lookup_by_contact(context, merge_id).await?,
+        ) {
+            (Some(keep_chat), Some(merge_chat)) => Some((
+                keep_chat.id,
+                merge_chat.id,
+                keep_chat.blocked == Blocked::Request && merge_chat.blocked == Blocked::Not,
+            )),
+            _ => None,
+        };
+
+        // `addr` is compared case-insensitively, so this returns peerstates of both contacts.
+        let peerstate_id = context
+            .sql
+            .query_row_optional(
+                "SELECT id FROM acpeerstates WHERE addr=?
+                 ORDER BY IFNULL(verified_key_fingerprint, '')!='' DESC, last_seen DESC
+                 LIMIT 1",
+                paramsv![keep.addr],
+                |row| row.get::<_, i64>(0),
+            )
+            .await?;
+
+        let mut param = keep.param.clone();
+        param.merge_missing(&merge.param);
+        let name = if keep.name.is_empty() {
+            merge.name
+        } else {
+            keep.name
+        };
+        let authname = if keep.authname.is_empty() {
+            merge.authname
+        } else {
+            keep.authname
+        };
+        let status = if keep.status.is_empty() {
+            merge.status
+        } else {
+            keep.status
+        };
+        let origin = std::cmp::max(keep.origin, merge.origin);
+        let last_seen = std::cmp::max(keep.last_seen, merge.last_seen);
+        let addr = keep.addr;
+
+        context
+            .sql
+            .transaction(move |transaction| {
+                if let Some((keep_chat_id, merge_chat_id, accept)) = merge_chats {
+                    transaction.execute(
+                        "UPDATE msgs SET chat_id=? WHERE chat_id=?",
+                        paramsv![keep_chat_id, merge_chat_id],
+                    )?;
+                    if accept {
+                        transaction.execute(
+                            "UPDATE chats SET blocked=? WHERE id=?",
+                            paramsv![Blocked::Not, keep_chat_id],
+                        )?;
+                    }
+                    transaction.execute(
+                        "DELETE FROM chats_contacts WHERE chat_id=?",
+                        paramsv![merge_chat_id],
+                    )?;
+                    transaction.execute("DELETE FROM chats WHERE id=?", paramsv![merge_chat_id])?;
+                }
+                transaction.execute(
+                    "UPDATE msgs SET from_id=? WHERE from_id=?",
+                    paramsv![keep_id, merge_id],
+                )?;
+                transaction.execute(
+                    "UPDATE msgs SET to_id=? WHERE to_id=?",
+                    paramsv![keep_id, merge_id],
+                )?;
+                transaction.execute(
+                    "DELETE FROM chats_contacts WHERE contact_id=?
+                     AND chat_id IN (SELECT chat_id FROM chats_contacts WHERE contact_id=?)",
+                    paramsv![merge_id, keep_id],
+                )?;
+                transaction.execute(
+                    "UPDATE chats_contacts SET contact_id=? WHERE contact_id=?",
+                    paramsv![keep_id, merge_id],
+                )?;
+                transaction.execute(
+                    "UPDATE msgs_mdns SET contact_id=? WHERE contact_id=?",
+                    paramsv![keep_id, merge_id],
+                )?;
+                transaction.execute(
+                    "UPDATE smtp_mdns SET from_id=? WHERE from_id=?",
+                    paramsv![keep_id, merge_id],
+                )?;
+                transaction.execute(
+                    "UPDATE locations SET from_id=? WHERE from_id=?",
+                    paramsv![keep_id, merge_id],
+                )?;
+                if let Some(peerstate_id) = peerstate_id {
+                    transaction.execute(
+                        "DELETE FROM acpeerstates WHERE addr=? AND id!=?",
+                        paramsv![addr, peerstate_id],
+                    )?;
+                    transaction.execute(
+                        "UPDATE acpeerstates SET addr=? WHERE id=?",
+                        paramsv![addr, peerstate_id],
+                    )?;
+                }
+                transaction.execute(
+                    "UPDATE contacts
+                     SET name=?, authname=?, status=?, origin=?, last_seen=?, param=?
+                     WHERE id=?",
+                    paramsv![
+                        name,
+                        authname,
+                        status,
+                        origin,
+                        last_seen,
+                        param.to_string(),
+                        keep_id
+                    ],
+                )?;
+                transaction.execute("DELETE FROM contacts WHERE id=?", paramsv![merge_id])?;
+                Ok(())
+            })
+            .await?;
+
+        info!(context, "Merged contact {} into {}.", merge_id, keep_id);
+        context.emit_event(EventType::ContactsChanged(Some(keep_id)));
+        if merge_chats.is_some() {
+            context.emit_event(EventType::ChatlistChanged);
+            context.emit_msgs_changed_without_ids();
+        }
+        for chat_id in chat_ids {
+            let deleted =
+                matches!(merge_chats, Some((_, merge_chat_id, _)) if merge_chat_id == chat_id);
+            if !chat_id.is_special() && !deleted {
+                context.emit_event(EventType::ChatModified(chat_id));
+            }
+        }
+        Ok(())
+    }
+
     /// Get a single contact object.  For a list, see eg. get_contacts().
     ///
     /// For contact ContactId::SELF (1), the function returns sth.
@@ -1077,6 +1310,38 @@ pub async fn get_profile_image(&self, context: &Context) -> Result<Option<PathBu
         Ok(None)
     }
 
+    /// Get the avatar received from the contact in an unencrypted message.
+    ///
+    /// Unencrypted avatars may be spoofed, so they are not returned by
+    /// [`Contact::get_profile_image`] until an encrypted message from the contact
+    /// carries an avatar or the user calls [`Contact::confirm_avatar`].
+    pub async fn get_unconfirmed_avatar(&self, context: &Context) -> Result<Option<PathBuf>> {
+        if let Some(image_rel) = self.param.get(Param::UnconfirmedProfileImage) {
+            if !image_rel.is_empty() {
+                return Ok(Some(get_abs_path(context, image_rel)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Uses the unconfirmed avatar as the contact's profile image.
+    ///
+    /// Does nothing if there is no unconfirmed avatar.
+    pub async fn confirm_avatar(context: &Context, contact_id: ContactId) -> Result<()> {
+        let mut contact = Contact::load_from_db(context, contact_id).await?;
+        if let Some(image_rel) = contact
+            .param
+            .get(Param::UnconfirmedProfileImage)
+            .map(|s| s.to_string())
+        {
+            contact.param.set(Param::ProfileImage, image_rel);
+            contact.param.remove(Param::UnconfirmedProfileImage);
+            contact.update_param(context).await?;
+            context.emit_event(EventType::ContactsChanged(Some(contact_id)));
+        }
+        Ok(())
+    }
+
     /// Get a color for the contact.
     /// The color is calculated from the contact's email address
     /// and can be used for an fallback avatar with white initials
@@ -1085,6 +1350,21 @@ pub fn get_color(&self) -> u32 {
         str_to_color(&self.addr.to_lowercase())
     }
 
+    /// Gets the contact's status as HTML, sanitized for display.
+    ///
+    /// Returns `None` if the status was not taken from an HTML message,
+    /// [`Contact::get_status`] returns the plain text version in any case.
+    pub async fn get_status_html(&self, context: &Context) -> Result<Option<String>> {
+        let status_html: Option<String> = context
+            .sql
+            .query_get_value(
+                "SELECT status_html FROM contacts WHERE id=?",
+                paramsv![self.id],
+            )
+            .await?;
+        Ok(status_html.filter(|html| !html.is_empty()))
+    }
+
     /// Gets the contact's status.
     ///
     /// Status is the last signature received in a message from this contact.
@@ -1092,6 +1372,90 @@ pub fn get_status(&self) -> &str {
         self.status.as_str()
     }
 
+    /// Exports the contact as vCard 4.0, see <https://datatracker.ietf.org/doc/html/rfc6350>.
+    ///
+    /// The vCard contains the display name, the address
+    /// and, if known, the OpenPGP public key of the contact.
+    pub async fn export_to_vcard(&self, context: &Context) -> Result<String> {
+        let (name, key) = if self.id == ContactId::SELF {
+            let name = context
+                .get_config(Config::Displayname)
+                .await?
+                .unwrap_or_default();
+            let key = SignedPublicKey::load_self(context).await?;
+            (name, Some(key))
+        } else {
+            let key = Peerstate::from_addr(context, &self.addr)
+                .await?
+                .and_then(|peerstate| {
+                    peerstate
+                        .peek_key(PeerstateVerifiedStatus::Unverified)
+                        .cloned()
+                });
+            (self.get_display_name().to_string(), key)
+        };
+
+        let mut vcard = format!(
+            "BEGIN:VCARD\r\nVERSION:4.0\r\nFN:{}\r\nEMAIL:{}\r\n",
+            vcard_escape(&name),
+            vcard_escape(&self.addr)
+        );
+        if let Some(key) = key {
+            vcard += &format!(
+                "KEY:data:application/pgp-keys;base64,{}\r\n",
+                key.to_base64()
+            );
+        }
+        vcard += "END:VCARD\r\n";
+        Ok(vcard)
+    }
+
+    /// Returns whether the signature of the contact is stored as its status,
+    /// see [`Contact::set_track_status`].
+    pub fn is_status_tracked(&self) -> bool {
+        !self
+            .param
+            .get_bool(Param::StatusTrackingDisabled)
+            .unwrap_or_default()
+    }
+
+    /// Enables or disables storing the signature of received messages as status of the contact.
+    ///
+    /// This is useful for correspondents appending long disclaimers to their messages.
+    /// Disabling tracking clears the stored status.
+    pub async fn set_track_status(
+        context: &Context,
+        contact_id: ContactId,
+        track: bool,
+    ) -> Result<()> {
+        ensure!(
+            !contact_id.is_special(),
+            "Can not set status tracking of special contact"
+        );
+        let mut contact = Contact::load_from_db(context, contact_id).await?;
+        if contact.is_status_tracked() == track {
+            return Ok(());
+        }
+
+        if track {
+            contact.param.remove(Param::StatusTrackingDisabled);
+        } else {
+            contact.param.set_int(Param::StatusTrackingDisabled, 1);
+            contact.status = String::new();
+            contact.update_status(context).await?;
+            context
+                .sql
+                .execute(
+                    "UPDATE contacts SET status_html='' WHERE id=?",
+                    paramsv![contact_id],
+                )
+                .await?;
+        }
+        contact.update_param(context).await?;
+        context.emit_event(EventType::ContactsChanged(Some(contact_id)));
+        Ok(())
+    }
+
     /// Check if a contact was verified. E.g. by a secure-join QR code scan
     /// and if the key has not changed since this verification.
     ///
@@ -1177,11 +1541,53 @@ pub async fn scaleup_origin_by_id(
 }
 
 /// Returns false if addr is an invalid address, otherwise true.
+///
+/// This check is lenient as it is also used for addresses of received messages,
+/// use [`crate::tools::is_valid_email_addr`] to check addresses entered by the user.
 pub fn may_be_valid_addr(addr: &str) -> bool {
     let res = addr.parse::<EmailAddress>();
     res.is_ok()
 }
 
+/// A recipient address as returned by [`normalize_recipient_list`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NormalizedAddr {
+    /// Address with whitespace trimmed and `mailto:` prefix removed.
+    pub addr: String,
+
+    /// Normalized display name, `None` if there is no display name.
+    pub display_name: Option<String>,
+
+    /// False if the address is invalid and should be dropped.
+    pub is_valid: bool,
+}
+
+/// Normalizes a list of recipient addresses.
+///
+/// Addresses are normalized using [`addr_normalize`] and display names using [`normalize_name`].
+/// Duplicates are removed ignoring case, the first occurrence is kept.
+/// Invalid addresses are not removed but flagged, so callers can decide how to handle them.
+pub fn normalize_recipient_list(addrs: &[SingleInfo]) -> Vec<NormalizedAddr> {
+    let mut seen = HashSet::new();
+    let mut res = Vec::new();
+    for info in addrs {
+        let addr = addr_normalize(&info.addr);
+        if !seen.insert(addr.to_lowercase()) {
+            continue;
+        }
+        res.push(NormalizedAddr {
+            addr: addr.to_string(),
+            display_name: info
+                .display_name
+                .as_deref()
+                .map(normalize_name)
+                .filter(|name| !name.is_empty()),
+            is_valid: may_be_valid_addr(addr),
+        });
+    }
+    res
+}
+
 /// Returns address with whitespace trimmed and `mailto:` prefix removed.
 pub fn addr_normalize(addr: &str) -> &str {
     let norm = addr.trim();
@@ -1218,6 +1624,20 @@ async fn set_block_contact(
     contact_id: ContactId,
     new_blocking: bool,
 ) -> Result<()> {
+    if set_block_contact_without_event(context, contact_id, new_blocking).await? {
+        context.emit_event(EventType::ContactsChanged(Some(contact_id)));
+    }
+    Ok(())
+}
+
+/// Same as `set_block_contact()`, but does not emit `ContactsChanged`.
+///
+/// Returns true if the blocking state of the contact was changed.
+async fn set_block_contact_without_event(
+    context: &Context,
+    contact_id: ContactId,
+    new_blocking: bool,
+) -> Result<bool> {
     ensure!(
         !contact_id.is_special(),
         "Can't block special contact {}",
@@ -1225,52 +1645,93 @@ async fn set_block_contact(
     );
 
     let contact = Contact::load_from_db(context, contact_id).await?;
+    if contact.blocked == new_blocking {
+        return Ok(false);
+    }
 
-    if contact.blocked != new_blocking {
-        context
-            .sql
-            .execute(
-                "UPDATE contacts SET blocked=? WHERE id=?;",
-                paramsv![i32::from(new_blocking), contact_id],
-            )
-            .await?;
+    context
+        .sql
+        .execute(
+            "UPDATE contacts SET blocked=? WHERE id=?;",
+            paramsv![i32::from(new_blocking), contact_id],
+        )
+        .await?;
 
-        // also (un)block all chats with _only_ this contact - we do not delete them to allow a
-        // non-destructive blocking->unblocking.
-        // (Maybe, beside normal chats (type=100) we should also block group chats with only this user.
-        // However, I'm not sure about this point; it may be confusing if the user wants to add other people;
-        // this would result in recreating the same group...)
-        if context
-            .sql
-            .execute(
-                r#"
+    // also (un)block all chats with _only_ this contact - we do not delete them to allow a
+    // non-destructive blocking->unblocking.
+    // (Maybe, beside normal chats (type=100) we should also block group chats with only this user.
+    // However, I'm not sure about this point; it may be confusing if the user wants to add other people;
+    // this would result in recreating the same group...)
+    if context
+        .sql
+        .execute(
+            r#"
 UPDATE chats
 SET blocked=?
 WHERE type=? AND id IN (
   SELECT chat_id FROM chats_contacts WHERE contact_id=?
 );
 "#,
-                paramsv![new_blocking, Chattype::Single, contact_id],
-            )
-            .await
-            .is_ok()
-        {
-            Contact::mark_noticed(context, contact_id).await?;
-            context.emit_event(EventType::ContactsChanged(Some(contact_id)));
+            paramsv![new_blocking, Chattype::Single, contact_id],
+        )
+        .await
+        .is_ok()
+    {
+        Contact::mark_noticed(context, contact_id).await?;
+    }
+
+    // also unblock mailinglist
+    // if the contact is a mailinglist address explicitly created to allow unblocking
+    if !new_blocking && contact.origin == Origin::MailinglistAddress {
+        if let Some((chat_id, _, _)) = chat::get_chat_id_by_grpid(context, &contact.addr).await? {
+            chat_id.unblock(context).await?;
         }
+    }
 
-        // also unblock mailinglist
-        // if the contact is a mailinglist address explicitly created to allow unblocking
-        if !new_blocking && contact.origin == Origin::MailinglistAddress {
-            if let Some((chat_id, _, _)) =
-                chat::get_chat_id_by_grpid(context, &contact.addr).await?
-            {
-                chat_id.unblock(context).await?;
-            }
+    Ok(true)
+}
+
+/// Blocks all members of a chat and the chat itself.
+///
+/// This is useful to get rid of spam arriving as a group invitation.
+/// Note that blocking a group chat deletes it.
+///
+/// Returns the number of newly blocked contacts, contacts that were blocked before are not
+/// counted. `ContactsChanged` is emitted once after all contacts are blocked.
+pub async fn block_all_from_chat(context: &Context, chat_id: ChatId) -> Result<u32> {
+    let mut count = 0;
+    for contact_id in chat::get_chat_contacts(context, chat_id).await? {
+        if contact_id.is_special() {
+            continue;
+        }
+        if set_block_contact_without_event(context, contact_id, true).await? {
+            count += 1;
         }
     }
+    chat_id.block(context).await?;
+    context.emit_event(EventType::ContactsChanged(None));
+    Ok(count)
+}
 
-    Ok(())
+/// Unblocks all members of a chat and the chat itself.
+///
+/// This reverts [`block_all_from_chat`] for chats that still exist, e.g. 1:1 chats and
+/// mailing lists.
+///
+/// Returns the number of newly unblocked contacts.
+pub async fn unblock_all_from_chat(context: &Context, chat_id: ChatId) -> Result<u32> {
+    let mut count = 0;
+    for contact_id in chat::get_chat_contacts(context, chat_id).await? {
+        if contact_id.is_special() {
+            continue;
+        }
+        if set_block_contact_without_event(context, contact_id, false).await? {
+            count += 1;
+        }
+    }
+    chat_id.unblock(context).await?;
+    context.emit_event(EventType::ContactsChanged(None));
+    Ok(count)
 }
 
 /// Set profile image for a contact.
@@ -1297,8 +1758,14 @@ pub(crate) async fn set_profile_image(
                 } else {
                     info!(context, "Do not use unencrypted selfavatar.");
                 }
-            } else {
+            } else if was_encrypted {
                 contact.param.set(Param::ProfileImage, profile_image);
+                contact.param.remove(Param::UnconfirmedProfileImage);
+            } else {
+                info!(context, "Store unencrypted avatar as unconfirmed.");
+                contact
+                    .param
+                    .set(Param::UnconfirmedProfileImage, profile_image);
             }
             true
         }
@@ -1309,8 +1776,11 @@ pub(crate) async fn set_profile_image(
                 } else {
                     info!(context, "Do not use unencrypted selfavatar deletion.");
                 }
-            } else {
+            } else if was_encrypted {
                 contact.param.remove(Param::ProfileImage);
+                contact.param.remove(Param::UnconfirmedProfileImage);
+            } else {
+                contact.param.remove(Param::UnconfirmedProfileImage);
             }
             true
         }
@@ -1322,15 +1792,22 @@ pub(crate) async fn set_profile_image(
     Ok(())
 }
 
+/// Statuses longer than this are truncated when stored.
+const STATUS_MAX_CHARS: usize = 500;
+
 /// Sets contact status.
 ///
 /// For contact SELF, the status is not saved in the contact table, but as Config::Selfstatus.  This
 /// is only done if message is sent from Delta Chat and it is encrypted, to synchronize signature
 /// between Delta Chat devices.
+///
+/// If the status was taken from an HTML part, `status_html` contains the raw HTML,
+/// it is sanitized and stored along with the plain text status of other contacts.
 pub(crate) async fn set_status(
     context: &Context,
     contact_id: ContactId,
     status: String,
+    status_html: Option<&str>,
     encrypted: bool,
     has_chat_version: bool,
 ) -> Result<()> {
@@ -1343,20 +1820,35 @@ pub(crate) async fn set_status(
     } else {
         let mut contact = Contact::load_from_db(context, contact_id).await?;
 
+        let status = truncate(&status, STATUS_MAX_CHARS).to_string();
+        let status_html = status_html.map(sanitize_html).unwrap_or_default();
+        let html_changed = context
+            .sql
+            .execute(
+                "UPDATE contacts SET status_html=? WHERE id=? AND status_html IS NOT ?",
+                paramsv![status_html, contact_id, status_html],
+            )
+            .await?
+            > 0;
         if contact.status != status {
             contact.status = status;
             contact.update_status(context).await?;
             context.emit_event(EventType::ContactsChanged(Some(contact_id)));
+        } else if html_changed {
+            context.emit_event(EventType::ContactsChanged(Some(contact_id)));
         }
     }
     Ok(())
 }
 
 /// Updates last seen timestamp of the contact if it is earlier than the given `timestamp`.
+///
+/// `source` tells which header the `timestamp` was taken from.
 pub(crate) async fn update_last_seen(
     context: &Context,
     contact_id: ContactId,
     timestamp: i64,
+    source: LastSeenSource,
 ) -> Result<()> {
     ensure!(
         !contact_id.is_special(),
@@ -1366,8 +1858,9 @@ pub(crate) async fn update_last_seen(
     context
         .sql
         .execute(
-            "UPDATE contacts SET last_seen = ?1 WHERE last_seen < ?1 AND id = ?2",
-            paramsv![timestamp, contact_id],
+            "UPDATE contacts SET last_seen = ?1, last_seen_source = ?2
+             WHERE last_seen < ?1 AND id = ?3",
+            paramsv![timestamp, source, contact_id],
         )
         .await?;
     Ok(())
@@ -1416,6 +1909,72 @@ fn cat_fingerprint(
     }
 }
 
+/// Returns pairs of contacts whose addresses only differ in case.
+///
+/// The first contact of each pair is the older one,
+/// it is suggested to be kept when calling [`Contact::merge`].
+pub async fn find_duplicate_candidates(context: &Context) -> Result<Vec<(ContactId, ContactId)>> {
+    // `addr` has `COLLATE NOCASE`, so the comparison ignores case.
+    context
+        .sql
+        .query_map(
+            "SELECT c1.id, c2.id
+             FROM contacts c1 INNER JOIN contacts c2 ON c1.addr=c2.addr AND c1.id<c2.id
+             WHERE c1.id>? AND c1.addr!=''
+             ORDER BY c1.id, c2.id",
+            paramsv![ContactId::LAST_SPECIAL],
+            |row| Ok((row.get::<_, ContactId>(0)?, row.get::<_, ContactId>(1)?)),
+            |rows| {
+                rows.collect::<std::result::Result<Vec<_>, _>>()
+                    .map_err(Into::into)
+            },
+        )
+        .await
+}
+
+/// Returns the known contacts without an Autocrypt key, i.e. contacts messages to are not encrypted.
+///
+/// Blocked contacts and contacts only seen in unaccepted messages are not returned.
+pub async fn get_contacts_without_keys(context: &Context) -> Result<Vec<ContactId>> {
+    get_contacts_without_fingerprint(context, "public_key_fingerprint").await
+}
+
+/// Returns the known contacts that are not verified, e.g. by a QR code scan.
+///
+/// Blocked contacts and contacts only seen in unaccepted messages are not returned.
+pub async fn get_unverified_contacts(context: &Context) -> Result<Vec<ContactId>> {
+    get_contacts_without_fingerprint(context, "verified_key_fingerprint").await
+}
+
+/// Returns the known contacts whose peerstate has no fingerprint in the given column.
+async fn get_contacts_without_fingerprint(
+    context: &Context,
+    fingerprint_column: &str,
+) -> Result<Vec<ContactId>> {
+    context
+        .sql
+        .query_map(
+            &format!(
+                "SELECT c.id FROM contacts c
+                 WHERE c.id NOT IN (
+                   SELECT c2.id FROM contacts c2 JOIN acpeerstates ps ON c2.addr=ps.addr
+                   WHERE LENGTH(ps.{}) > 0)
+                 AND c.blocked=0
+                 AND c.origin>=?
+                 AND c.id>?
+                 ORDER BY LOWER(iif(c.name='',c.authname,c.name)||c.addr),c.id",
+                fingerprint_column
+            ),
+            paramsv![Origin::IncomingReplyTo, ContactId::LAST_SPECIAL],
+            |row| row.get::<_, ContactId>(0),
+            |ids| {
+                ids.collect::<std::result::Result<Vec<_>, _>>()
+                    .map_err(Into::into)
+            },
+        )
+        .await
+}
+
 pub fn addr_cmp(addr1: &str, addr2: &str) -> bool {
     let norm1 = addr_normalize(addr1).to_lowercase();
     let norm2 = addr_normalize(addr2).to_lowercase();
@@ -1423,6 +1982,74 @@ pub fn addr_cmp(addr1: &str, addr2: &str) -> bool {
     norm1 == norm2
 }
 
+/// Escapes a vCard property value, see <https://datatracker.ietf.org/doc/html/rfc6350#section-3.4>.
+fn vcard_escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(',', "\\,")
+        .replace(';', "\\;")
+        .replace('\n', "\\n")
+}
+
+/// Reverts [`vcard_escape`].
+fn vcard_unescape(value: &str) -> String {
+    let mut res = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => res.push('\n'),
+                Some(c) => res.push(c),
+                None => {}
+            }
+        } else {
+            res.push(c);
+        }
+    }
+    res
+}
+
+/// Parses names and addresses out of a vCard containing one or more records.
+fn parse_vcard(vcard: &str) -> Vec<(String, String)> {
+    // Unfold lines, see <https://datatracker.ietf.org/doc/html/rfc6350#section-3.2>.
+    let mut lines: Vec<String> = Vec::new();
+    for line in vcard.lines() {
+        match (
+            line.strip_prefix(|c| c == ' ' || c == '\t'),
+            lines.last_mut(),
+        ) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    let mut contacts = Vec::new();
+    let mut name = String::new();
+    let mut addr = String::new();
+    for line in lines {
+        let (property, value) = match line.split_once(':') {
+            Some(res) => res,
+            None => continue,
+        };
+        // Property parameters such as `EMAIL;TYPE=work` and group prefixes are ignored.
+        let property = property.split(';').next().unwrap_or_default();
+        let property = property.rsplit('.').next().unwrap_or_default();
+        if property.eq_ignore_ascii_case("BEGIN") {
+            name.clear();
+            addr.clear();
+        } else if property.eq_ignore_ascii_case("FN") {
+            name = vcard_unescape(value.trim());
+        } else if property.eq_ignore_ascii_case("EMAIL") {
+            if addr.is_empty() {
+                addr = vcard_unescape(value.trim());
+            }
+        } else if property.eq_ignore_ascii_case("END") && !addr.is_empty() {
+            contacts.push((std::mem::take(&mut name), std::mem::take(&mut addr)));
+        }
+    }
+    contacts
+}
+
 fn split_address_book(book: &str) -> Vec<(&str, &str)> {
     book.lines()
         .collect::<Vec<&str>>()
@@ -1440,11 +2067,16 @@ fn split_address_book(book: &str) -> Vec<(&str, &str)> {
 mod tests {
     use super::*;
 
-    use crate::chat::{get_chat_contacts, send_text_msg, Chat};
+    use crate::chat::{
+        add_contact_to_chat, create_group_chat, get_chat_contacts, send_text_msg, Chat,
+        ProtectionStatus,
+    };
     use crate::chatlist::Chatlist;
-    use crate::message::Message;
+    use crate::message::{Message, MsgId, Viewtype};
+    use crate::peerstate::PeerstateKeyType;
     use crate::receive_imf::receive_imf;
-    use crate::test_utils::{self, TestContext};
+    use crate::test_utils::{self, TestContext, TestContextManager};
+    use crate::tools::time;
 
     #[test]
     fn test_contact_id_values() {
@@ -1476,6 +2108,42 @@ fn test_may_be_valid_addr() {
         assert_eq!(may_be_valid_addr("ask dkl@dd.tt"), false);
     }
 
+    #[test]
+    fn test_normalize_recipient_list() {
+        let info = |display_name: Option<&str>, addr: &str| SingleInfo {
+            display_name: display_name.map(|s| s.to_string()),
+            addr: addr.to_string(),
+        };
+        let list = normalize_recipient_list(&[
+            info(Some(" 'Alice' "), " alice@example.org "),
+            info(None, "invalid"),
+            info(Some("Bob"), "mailto:bob@example.net"),
+            info(Some("Alice 2"), "ALICE@example.org"),
+            info(Some(""), "bob@example.net"),
+            info(None, "invalid"),
+        ]);
+        assert_eq!(
+            list,
+            vec![
+                NormalizedAddr {
+                    addr: "alice@example.org".to_string(),
+                    display_name: Some("Alice".to_string()),
+                    is_valid: true,
+                },
+                NormalizedAddr {
+                    addr: "invalid".to_string(),
+                    display_name: None,
+                    is_valid: false,
+                },
+                NormalizedAddr {
+                    addr: "bob@example.net".to_string(),
+                    display_name: Some("Bob".to_string()),
+                    is_valid: true,
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_normalize_name() {
         assert_eq!(&normalize_name(" hello world   "), "hello world");
@@ -2267,6 +2935,330 @@ async fn test_last_seen() -> Result<()> {
         assert!(timestamp > 0);
         let contact = Contact::load_from_db(&alice, contact_id).await?;
         assert_eq!(contact.last_seen(), timestamp);
+        assert_eq!(contact.last_seen_source(), LastSeenSource::Date);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_last_seen_future_date() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+
+        let mime = br#"Subject: Hello
+Message-ID: message@example.net
+To: Alice <alice@example.org>
+From: Bob <bob@example.net>
+Content-Type: text/plain; charset=utf-8; format=flowed; delsp=no
+Chat-Version: 1.0
+Date: Sun, 22 Mar 2150 22:37:55 +0000
+
+Hi."#;
+        receive_imf(&alice, mime, false).await?;
+
+        let contact_id = Contact::lookup_id_by_addr(&alice, "bob@example.net", Origin::Unknown)
+            .await?
+            .unwrap();
+        let contact = Contact::load_from_db(&alice, contact_id).await?;
+        assert!(contact.last_seen() > 0);
+        assert!(contact.last_seen() <= time() + 60);
+        assert_eq!(contact.last_seen_source(), LastSeenSource::Received);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_block_all_from_chat() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        let bob_id = Contact::create(&t, "Bob", "bob@example.net").await?;
+        let claire_id = Contact::create(&t, "Claire", "claire@example.org").await?;
+
+        let group1 = create_group_chat(&t, ProtectionStatus::Unprotected, "group1").await?;
+        let group2 = create_group_chat(&t, ProtectionStatus::Unprotected, "group2").await?;
+        for chat_id in [group1, group2] {
+            add_contact_to_chat(&t, chat_id, bob_id).await?;
+            add_contact_to_chat(&t, chat_id, claire_id).await?;
+        }
+
+        // Bob is blocked already, so only Claire is newly blocked.
+        Contact::block(&t, bob_id).await?;
+        assert_eq!(block_all_from_chat(&t, group1).await?, 1);
+        assert!(Contact::is_blocked_load(&t, bob_id).await?);
+        assert!(Contact::is_blocked_load(&t, claire_id).await?);
+        assert_eq!(Contact::get_all_blocked(&t).await?.len(), 2);
+
+        assert_eq!(unblock_all_from_chat(&t, group2).await?, 2);
+        assert!(!Contact::is_blocked_load(&t, bob_id).await?);
+        assert!(!Contact::is_blocked_load(&t, claire_id).await?);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_get_contacts_without_keys() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let bob = TestContext::new_bob().await;
+        let bob_id = alice.add_or_lookup_contact(&bob).await.id;
+        let fiona_id = Contact::create(&alice, "Fiona", "fiona@example.net").await?;
+        let blocked_id = Contact::create(&alice, "Blocked", "blocked@example.net").await?;
+        Contact::block(&alice, blocked_id).await?;
+
+        assert_eq!(
+            get_contacts_without_keys(&alice).await?,
+            vec![bob_id, fiona_id]
+        );
+        assert_eq!(
+            get_unverified_contacts(&alice).await?,
+            vec![bob_id, fiona_id]
+        );
+
+        // Receiving a message from Bob makes his key known.
+        let chat_id = bob.create_chat(&alice).await.id;
+        alice.recv_msg(&bob.send_text(chat_id, "hi").await).await;
+        assert_eq!(get_contacts_without_keys(&alice).await?, vec![fiona_id]);
+        assert_eq!(
+            get_unverified_contacts(&alice).await?,
+            vec![bob_id, fiona_id]
+        );
+
+        let mut peerstate = Peerstate::from_addr(&alice, "bob@example.net")
+            .await?
+            .unwrap();
+        let fingerprint = peerstate.public_key_fingerprint.clone().unwrap();
+        assert!(peerstate.set_verified(
+            PeerstateKeyType::PublicKey,
+            &fingerprint,
+            PeerstateVerifiedStatus::BidirectVerified
+        ));
+        peerstate.save_to_db(&alice.sql, false).await?;
+        assert_eq!(get_unverified_contacts(&alice).await?, vec![fiona_id]);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_unencrypted_avatar_unconfirmed() -> Result<()> {
+        let t = TestContext::new().await;
+        t.configure_addr("tunis3@example.org").await;
+
+        receive_imf(
+            &t,
+            include_bytes!("../test-data/message/mail_with_user_avatar.eml"),
+            false,
+        )
+        .await?;
+
+        let contact_id = Contact::lookup_id_by_addr(&t, "tunis4@example.org", Origin::Unknown)
+            .await?
+            .unwrap();
+        let contact = Contact::load_from_db(&t, contact_id).await?;
+        assert_eq!(contact.get_profile_image(&t).await?, None);
+        assert!(contact.get_unconfirmed_avatar(&t).await?.is_some());
+
+        Contact::confirm_avatar(&t, contact_id).await?;
+        let contact = Contact::load_from_db(&t, contact_id).await?;
+        assert!(contact.get_profile_image(&t).await?.is_some());
+        assert_eq!(contact.get_unconfirmed_avatar(&t).await?, None);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_encrypted_avatar_applied() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let bob = TestContext::new_bob().await;
+
+        let avatar_src = alice.get_blobdir().join("avatar.png");
+        tokio::fs::write(&avatar_src, test_utils::AVATAR_900x900_BYTES).await?;
+        alice
+            .set_config(Config::Selfavatar, Some(avatar_src.to_str().unwrap()))
+            .await?;
+
+        // Bob sends a message so that Alice can encrypt to him.
+        let chat = bob
+            .create_chat_with_contact("Alice", "alice@example.org")
+            .await;
+        send_text_msg(&bob, chat.id, "Hi".to_string()).await?;
+        alice.recv_msg(&bob.pop_sent_msg().await).await;
+
+        let alice_chat_id = alice.get_last_msg().await.chat_id;
+        alice_chat_id.accept(&alice).await?;
+        send_text_msg(&alice, alice_chat_id, "Hello".to_string()).await?;
+        let sent_msg = alice.pop_sent_msg().await;
+        let msg = bob.recv_msg(&sent_msg).await;
+        assert!(msg.get_showpadlock());
+
+        let contact = Contact::load_from_db(&bob, msg.from_id).await?;
+        assert!(contact.get_profile_image(&bob).await?.is_some());
+        assert_eq!(contact.get_unconfirmed_avatar(&bob).await?, None);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_merge_contacts() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        let bob_id = Contact::create(&t, "Bob", "bob@example.net").await?;
+
+        // Older versions did not always use case-insensitive lookups.
+        let dup_id = ContactId::new(
+            t.sql
+                .insert(
+                    "INSERT INTO contacts (name, addr, origin, param) VALUES ('', ?, ?, ?)",
+                    paramsv!["Bob@Example.net", Origin::IncomingUnknownFrom, "i=foo.jpg"],
+                )
+                .await?
+                .try_into()?,
+        );
+        assert_eq!(find_duplicate_candidates(&t).await?, vec![(bob_id, dup_id)]);
+
+        let both_grp = create_group_chat(&t, ProtectionStatus::Unprotected, "both").await?;
+        add_contact_to_chat(&t, both_grp, bob_id).await?;
+        add_contact_to_chat(&t, both_grp, dup_id).await?;
+        let dup_grp = create_group_chat(&t, ProtectionStatus::Unprotected, "dup").await?;
+        add_contact_to_chat(&t, dup_grp, dup_id).await?;
+        let msg_id = MsgId::new(
+            t.sql
+                .insert(
+                    "INSERT INTO msgs (chat_id, from_id, to_id, type, txt, timestamp, state)
+                     VALUES (?, ?, ?, ?, 'hi', ?, ?)",
+                    paramsv![
+                        dup_grp,
+                        dup_id,
+                        ContactId::SELF,
+                        Viewtype::Text,
+                        time(),
+                        MessageState::InFresh
+                    ],
+                )
+                .await?
+                .try_into()?,
+        );
+        let bob_chat_id = ChatId::create_for_contact(&t, bob_id).await?;
+        let dup_chat_id = ChatId::create_for_contact(&t, dup_id).await?;
+        assert_ne!(bob_chat_id, dup_chat_id);
+        let dup_chat_msg_id = send_text_msg(&t, dup_chat_id, "hello".to_string()).await?;
+
+        assert!(Contact::merge(&t, bob_id, ContactId::SELF).await.is_err());
+        assert!(Contact::merge(&t, bob_id, bob_id).await.is_err());
+        Contact::merge(&t, bob_id, dup_id).await?;
+
+        assert!(Contact::load_from_db(&t, dup_id).await.is_err());
+        assert!(find_duplicate_candidates(&t).await?.is_empty());
+        let bob = Contact::load_from_db(&t, bob_id).await?;
+        assert_eq!(bob.get_name(), "Bob");
+        assert_eq!(bob.get_addr(), "bob@example.net");
+        assert_eq!(bob.param.get(Param::ProfileImage), Some("foo.jpg"));
+
+        let msg = Message::load_from_db(&t, msg_id).await?;
+        assert_eq!(msg.get_from_id(), bob_id);
+        assert_eq!(
+            get_chat_contacts(&t, both_grp).await?,
+            vec![bob_id, ContactId::SELF]
+        );
+        assert_eq!(
+            get_chat_contacts(&t, dup_grp).await?,
+            vec![bob_id, ContactId::SELF]
+        );
+
+        // The duplicate 1:1 chat is merged into the chat with Bob.
+        assert!(Chat::load_from_db(&t, dup_chat_id).await.is_err());
+        assert_eq!(
+            ChatId::lookup_by_contact(&t, bob_id).await?,
+            Some(bob_chat_id)
+        );
+        let msg = Message::load_from_db(&t, dup_chat_msg_id).await?;
+        assert_eq!(msg.chat_id, bob_chat_id);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_vcard() {
+        let vcard = "BEGIN:VCARD\r\n\
+                     VERSION:4.0\r\n\
+                     FN:Doe\\, John\r\n\
+                     item1.EMAIL;TYPE=work:john@exam\r\n ple.org\r\n\
+                     EMAIL:john.doe@example.org\r\n\
+                     END:VCARD\r\n\
+                     BEGIN:VCARD\r\n\
+                     FN:No Address\r\n\
+                     END:VCARD\r\n\
+                     begin:vcard\n\
+                     email:nameless@example.org\n\
+                     end:vcard\n";
+        assert_eq!(
+            parse_vcard(vcard),
+            vec![
+                ("Doe, John".to_string(), "john@example.org".to_string()),
+                ("".to_string(), "nameless@example.org".to_string())
+            ]
+        );
+
+        let escaped = vcard_escape("a\\b,c;d\ne");
+        assert_eq!(escaped, "a\\\\b\\,c\\;d\\ne");
+        assert_eq!(vcard_unescape(&escaped), "a\\b,c;d\ne");
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_vcard_export_import() -> Result<()> {
+        let mut tcm = TestContextManager::new().await;
+        let alice = tcm.alice().await;
+        let bob = tcm.bob().await;
+
+        // Alice knows Bob's key after receiving a message from him.
+        let bob_chat = bob.create_chat(&alice).await;
+        let sent = bob.send_text(bob_chat.id, "Hi Alice").await;
+        alice.recv_msg(&sent).await;
+
+        let bob_id = Contact::lookup_id_by_addr(&alice, "bob@example.net", Origin::Unknown)
+            .await?
+            .unwrap();
+        let bob_contact = Contact::load_from_db(&alice, bob_id).await?;
+        let vcard = bob_contact.export_to_vcard(&alice).await?;
+        assert!(vcard.starts_with("BEGIN:VCARD\r\nVERSION:4.0\r\n"));
+        assert!(vcard.contains("\r\nEMAIL:bob@example.net\r\n"));
+        assert!(vcard.contains("\r\nKEY:data:application/pgp-keys;base64,"));
+        assert!(vcard.ends_with("END:VCARD\r\n"));
+
+        // The 1:1 chat's members vCard contains Bob only.
+        let alice_chat = alice.create_chat(&bob).await;
+        assert_eq!(
+            alice_chat.id.export_members_vcard(&alice).await?,
+            vcard.as_bytes()
+        );
+
+        // Own vCard contains the display name.
+        alice
+            .set_config(Config::Displayname, Some("Alice; A."))
+            .await?;
+        let self_vcard = Contact::load_from_db(&alice, ContactId::SELF)
+            .await?
+            .export_to_vcard(&alice)
+            .await?;
+        assert!(self_vcard.contains("\r\nFN:Alice\\; A.\r\n"));
+
+        // Alice shares a contact with Bob, Bob imports it.
+        let claire_id = Contact::create(&alice, "Claire", "claire@example.org").await?;
+        let claire_vcard = Contact::load_from_db(&alice, claire_id)
+            .await?
+            .export_to_vcard(&alice)
+            .await?;
+        assert!(!claire_vcard.contains("KEY:"));
+        let file = alice.get_blobdir().join("claire.vcf");
+        tokio::fs::write(&file, claire_vcard.as_bytes()).await?;
+        let mut msg = Message::new(Viewtype::File);
+        msg.set_file(file.to_str().unwrap(), None);
+        let sent = alice.send_msg(alice_chat.id, &mut msg).await;
+        assert_eq!(msg.get_viewtype(), Viewtype::Vcard);
+
+        let msg = bob.recv_msg(&sent).await;
+        assert_eq!(msg.get_viewtype(), Viewtype::Vcard);
+        let received = tokio::fs::read_to_string(msg.get_file(&bob).unwrap()).await?;
+        let imported = Contact::import_vcard(&bob, &received).await?;
+        assert_eq!(imported.len(), 1);
+        let claire = Contact::load_from_db(&bob, imported[0]).await?;
+        assert_eq!(claire.get_name(), "Claire");
+        assert_eq!(claire.get_addr(), "claire@example.org");
+        assert_eq!(claire.origin, Origin::ManuallyCreated);
 
         Ok(())
     }
@@ -1,9 +1,10 @@
 //! Context module.
 
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
 use std::ffi::OsString;
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant, SystemTime};
 
@@ -13,18 +14,33 @@
 
 use crate::chat::{get_chat_cnt, ChatId};
 use crate::config::Config;
-use crate::constants::DC_VERSION_STR;
-use crate::contact::Contact;
+use crate::constants::{DC_CHAT_ID_LAST_SPECIAL, DC_VERSION_STR};
+use crate::contact::{Contact, ContactId};
 use crate::events::{Event, EventEmitter, EventType, Events};
 use crate::key::{DcKey, SignedPublicKey};
 use crate::login_param::LoginParam;
 use crate::message::{self, MessageState, MsgId};
+use crate::pgp;
 use crate::quota::QuotaInfo;
 use crate::ratelimit::Ratelimit;
 use crate::scheduler::Scheduler;
 use crate::sql::Sql;
 use crate::tools::{duration_to_str, time};
 
+/// Selects the fresh, unmuted messages in unblocked chats,
+/// see [`Context::get_fresh_msgs`] and [`Context::get_total_unread_count`].
+const FRESH_MSGS_FROM_WHERE: &str = "FROM msgs m
+ LEFT JOIN contacts ct
+        ON m.from_id=ct.id
+ LEFT JOIN chats c
+        ON m.chat_id=c.id
+ WHERE m.state=?
+   AND m.hidden=0
+   AND m.chat_id>9
+   AND ct.blocked=0
+   AND c.blocked=0
+   AND NOT(c.muted_until=-1 OR c.muted_until>?)";
+
 #[derive(Clone, Debug)]
 pub struct Context {
     pub(crate) inner: Arc<InnerContext>,
@@ -80,6 +96,58 @@ pub struct InnerContext {
     /// If the ui wants to display an error after a failure,
     /// `last_error` should be used to avoid races with the event thread.
     pub(crate) last_error: std::sync::RwLock<String>,
+
+    /// State of the event batches, see [`Context::begin_event_batch`].
+    event_batch: std::sync::Mutex<EventBatch>,
+
+    /// Recently emitted events, see [`Context::get_event_log`].
+    event_log: EventLog,
+}
+
+/// `MsgsChanged` events of received messages coalesced while an event batch is active.
+#[derive(Debug, Default)]
+struct EventBatch {
+    /// Number of alive [`EventBatchGuard`]s.
+    depth: usize,
+
+    /// Chats with pending `MsgsChanged` events.
+    chat_ids: BTreeSet<ChatId>,
+}
+
+/// Guard returned by [`Context::begin_event_batch`], ends the batch when dropped.
+#[derive(Debug)]
+#[must_use]
+pub(crate) struct EventBatchGuard<'a> {
+    context: &'a Context,
+}
+
+impl Drop for EventBatchGuard<'_> {
+    fn drop(&mut self) {
+        let chat_ids = {
+            let mut batch = self.context.event_batch.lock().unwrap();
+            batch.depth = batch.depth.saturating_sub(1);
+            if batch.depth > 0 {
+                return;
+            }
+            std::mem::take(&mut batch.chat_ids)
+        };
+        for chat_id in chat_ids {
+            self.context.emit_msgs_changed(chat_id, MsgId::new(0));
+        }
+    }
+}
+
+/// Ring buffer of recently emitted events, for debugging.
+#[derive(Debug, Default)]
+struct EventLog {
+    /// Maximum number of events kept, 0 if the log is disabled.
+    ///
+    /// Cached from [`Config::EventLogSize`] so that `emit_event()` does not need to query
+    /// the database.
+    size: AtomicUsize,
+
+    /// Events with the unix timestamp they were emitted at, oldest first.
+    events: std::sync::Mutex<VecDeque<(u64, EventType)>>,
 }
 
 /// The state of ongoing process.
@@ -199,6 +267,8 @@ pub(crate) async fn with_blobdir(
             creation_time: std::time::SystemTime::now(),
             last_full_folder_scan: Mutex::new(None),
             last_error: std::sync::RwLock::new("".to_string()),
+            event_batch: std::sync::Mutex::new(EventBatch::default()),
+            event_log: EventLog::default(),
         };
 
         let ctx = Context {
@@ -259,12 +329,70 @@ pub fn get_blobdir(&self) -> &Path {
 
     /// Emits a single event.
     pub fn emit_event(&self, event: EventType) {
+        let size = self.event_log.size.load(Ordering::Relaxed);
+        if size > 0 {
+            let mut events = self.event_log.events.lock().unwrap();
+            while events.len() >= size {
+                events.pop_front();
+            }
+            events.push_back((time().max(0) as u64, event.clone()));
+        }
         self.events.emit(Event {
             id: self.id,
             typ: event,
         });
     }
 
+    /// Returns up to `n` most recently emitted events with their unix timestamps, oldest first.
+    ///
+    /// Events are only recorded if [`Config::EventLogSize`] is set to a non-zero value.
+    pub fn get_event_log(&self, n: usize) -> Vec<(u64, EventType)> {
+        let events = self.event_log.events.lock().unwrap();
+        events
+            .iter()
+            .skip(events.len().saturating_sub(n))
+            .cloned()
+            .collect()
+    }
+
+    /// Updates the event log size from [`Config::EventLogSize`],
+    /// dropping the oldest events if the log shrinks.
+    pub(crate) async fn update_event_log_size(&self) -> Result<()> {
+        let size = self.get_config_int(Config::EventLogSize).await?.max(0) as usize;
+        self.event_log.size.store(size, Ordering::Relaxed);
+        let mut events = self.event_log.events.lock().unwrap();
+        while events.len() > size {
+            events.pop_front();
+        }
+        Ok(())
+    }
+
+    /// Starts coalescing `MsgsChanged` events of received messages,
+    /// e.g. while fetching many messages at once.
+    ///
+    /// While the returned guard is alive, events passed to
+    /// [`Context::emit_received_msgs_changed`] are not emitted but remembered per chat.
+    /// All other events, including `MsgsChanged` events caused by the user, are emitted as usual.
+    /// Batches may be nested, when the last guard is dropped a single `MsgsChanged` event
+    /// with a zero message ID is emitted for each chat that changed during the batch.
+    pub(crate) fn begin_event_batch(&self) -> EventBatchGuard<'_> {
+        self.event_batch.lock().unwrap().depth += 1;
+        EventBatchGuard { context: self }
+    }
+
+    /// Emits a `MsgsChanged` event for a received message
+    /// or postpones it if an event batch is active, see [`Context::begin_event_batch`].
+    pub(crate) fn emit_received_msgs_changed(&self, chat_id: ChatId, msg_id: MsgId) {
+        {
+            let mut batch = self.event_batch.lock().unwrap();
+            if batch.depth > 0 {
+                batch.chat_ids.insert(chat_id);
+                return;
+            }
+        }
+        self.emit_msgs_changed(chat_id, msg_id);
+    }
+
     /// Emits a generic MsgsChanged event (without chat or message id)
     pub fn emit_msgs_changed_without_ids(&self) {
         self.emit_event(EventType::MsgsChanged {
@@ -382,9 +510,15 @@ pub async fn get_info(&self) -> Result<BTreeMap<&'static str, String>> {
             .sql
             .count("SELECT COUNT(*) FROM acpeerstates;", paramsv![])
             .await?;
-        let fingerprint_str = match SignedPublicKey::load_self(self).await {
-            Ok(key) => key.fingerprint().hex(),
-            Err(err) => format!("<key failure: {}>", err),
+        let (fingerprint_str, key_strength_str) = match SignedPublicKey::load_self(self).await {
+            Ok(key) => (
+                key.fingerprint().hex(),
+                pgp::key_strength_report(&key).to_string(),
+            ),
+            Err(err) => (
+                format!("<key failure: {}>", err),
+                format!("<key failure: {}>", err),
+            ),
         };
 
         let sentbox_watch = self.get_config_int(Config::SentboxWatch).await?;
@@ -480,6 +614,7 @@ pub async fn get_info(&self) -> Result<BTreeMap<&'static str, String>> {
         res.insert("private_key_count", prv_key_cnt.to_string());
         res.insert("public_key_count", pub_key_cnt.to_string());
         res.insert("fingerprint", fingerprint_str);
+        res.insert("key_strength", key_strength_str);
         res.insert(
             "webrtc_instance",
             self.get_config(Config::WebrtcInstance)
@@ -490,6 +625,16 @@ pub async fn get_info(&self) -> Result<BTreeMap<&'static str, String>> {
             "media_quality",
             self.get_config_int(Config::MediaQuality).await?.to_string(),
         );
+        res.insert(
+            "blob_layout",
+            self.get_config_int(Config::BlobLayout).await?.to_string(),
+        );
+        res.insert(
+            "auto_continue_key_transfer",
+            self.get_config_bool(Config::AutoContinueKeyTransfer)
+                .await?
+                .to_string(),
+        );
         res.insert(
             "delete_device_after",
             self.get_config_int(Config::DeleteDeviceAfter)
@@ -527,6 +672,25 @@ pub async fn get_info(&self) -> Result<BTreeMap<&'static str, String>> {
         Ok(res)
     }
 
+    /// Returns the number of outgoing messages waiting to be sent in all chats,
+    /// e.g. for a badge on an outbox view, see [`crate::message::get_outgoing_pending_msgs`].
+    pub async fn get_pending_send_count(&self) -> Result<u32> {
+        let count = self
+            .sql
+            .count(
+                "SELECT COUNT(*) FROM msgs
+                  WHERE from_id=? AND state IN (?,?) AND hidden=0 AND chat_id>?",
+                paramsv![
+                    ContactId::SELF,
+                    MessageState::OutPending,
+                    MessageState::OutPreparing,
+                    DC_CHAT_ID_LAST_SPECIAL
+                ],
+            )
+            .await?;
+        Ok(u32::try_from(count)?)
+    }
+
     /// Get a list of fresh, unmuted messages in unblocked chats.
     ///
     /// The list starts with the most recent message
@@ -537,20 +701,9 @@ pub async fn get_fresh_msgs(&self) -> Result<Vec<MsgId>> {
         let list = self
             .sql
             .query_map(
-                concat!(
-                    "SELECT m.id",
-                    " FROM msgs m",
-                    " LEFT JOIN contacts ct",
-                    "        ON m.from_id=ct.id",
-                    " LEFT JOIN chats c",
-                    "        ON m.chat_id=c.id",
-                    " WHERE m.state=?",
-                    "   AND m.hidden=0",
-                    "   AND m.chat_id>9",
-                    "   AND ct.blocked=0",
-                    "   AND c.blocked=0",
-                    "   AND NOT(c.muted_until=-1 OR c.muted_until>?)",
-                    " ORDER BY m.timestamp DESC,m.id DESC;"
+                &format!(
+                    "SELECT m.id {} ORDER BY m.timestamp DESC,m.id DESC;",
+                    FRESH_MSGS_FROM_WHERE
                 ),
                 paramsv![MessageState::InFresh, time()],
                 |row| row.get::<_, MsgId>(0),
@@ -566,6 +719,22 @@ pub async fn get_fresh_msgs(&self) -> Result<Vec<MsgId>> {
         Ok(list)
     }
 
+    /// Returns the number of unread messages in all chats
+    /// for the global notification badge.
+    ///
+    /// The same messages as in [`Context::get_fresh_msgs`] are counted,
+    /// i.e. messages in contact requests and muted chats are not included.
+    pub async fn get_total_unread_count(&self) -> Result<u32> {
+        let count = self
+            .sql
+            .count(
+                &format!("SELECT COUNT(*) {}", FRESH_MSGS_FROM_WHERE),
+                paramsv![MessageState::InFresh, time()],
+            )
+            .await?;
+        Ok(u32::try_from(count)?)
+    }
+
     /// Searches for messages containing the query string.
     ///
     /// If `chat_id` is provided this searches only for messages in this chat, if `chat_id`
@@ -577,24 +746,11 @@ pub async fn search_msgs(&self, chat_id: Option<ChatId>, query: &str) -> Result<
         }
         let str_like_in_text = format!("%{}%", real_query);
 
-        let do_query = |query, params| {
-            self.sql.query_map(
-                query,
-                params,
-                |row| row.get::<_, MsgId>("id"),
-                |rows| {
-                    let mut ret = Vec::new();
-                    for id in rows {
-                        ret.push(id?);
-                    }
-                    Ok(ret)
-                },
-            )
-        };
-
-        let list = if let Some(chat_id) = chat_id {
-            do_query(
-                "SELECT m.id AS id
+        let mut list = Vec::new();
+        if let Some(chat_id) = chat_id {
+            self.sql
+                .query_stream(
+                    "SELECT m.id AS id
                  FROM msgs m
                  LEFT JOIN contacts ct
                         ON m.from_id=ct.id
@@ -603,9 +759,13 @@ pub async fn search_msgs(&self, chat_id: Option<ChatId>, query: &str) -> Result<
                    AND ct.blocked=0
                    AND txt LIKE ?
                  ORDER BY m.timestamp,m.id;",
-                paramsv![chat_id, str_like_in_text],
-            )
-            .await?
+                    paramsv![chat_id, str_like_in_text],
+                    |row| {
+                        list.push(row.get::<_, MsgId>("id")?);
+                        Ok(())
+                    },
+                )
+                .await?;
         } else {
             // For performance reasons results are sorted only by `id`, that is in the order of
             // message reception.
@@ -617,8 +777,9 @@ pub async fn search_msgs(&self, chat_id: Option<ChatId>, query: &str) -> Result<
             // of unwanted results that are discarded moments later, we added `LIMIT 1000`.
             // According to some tests, this limit speeds up eg. 2 character searches by factor 10.
             // The limit is documented and UI may add a hint when getting 1000 results.
-            do_query(
-                "SELECT m.id AS id
+            self.sql
+                .query_stream(
+                    "SELECT m.id AS id
                  FROM msgs m
                  LEFT JOIN contacts ct
                         ON m.from_id=ct.id
@@ -630,10 +791,14 @@ pub async fn search_msgs(&self, chat_id: Option<ChatId>, query: &str) -> Result<
                    AND ct.blocked=0
                    AND m.txt LIKE ?
                  ORDER BY m.id DESC LIMIT 1000",
-                paramsv![str_like_in_text],
-            )
-            .await?
-        };
+                    paramsv![str_like_in_text],
+                    |row| {
+                        list.push(row.get::<_, MsgId>("id")?);
+                        Ok(())
+                    },
+                )
+                .await?;
+        }
 
         Ok(list)
     }
@@ -653,6 +818,30 @@ pub async fn is_mvbox(&self, folder_name: &str) -> Result<bool> {
         Ok(mvbox.as_deref() == Some(folder_name))
     }
 
+    /// Returns the statistics of all IMAP folders scanned so far, ordered by folder name.
+    pub async fn get_folder_stats(&self) -> Result<Vec<FolderStats>> {
+        self.sql
+            .query_map(
+                "SELECT folder, uid_next, msg_count, last_seen_at
+                   FROM folder_stats
+                  ORDER BY folder;",
+                paramsv![],
+                |row| {
+                    Ok(FolderStats {
+                        folder: row.get(0)?,
+                        uid_next: row.get(1)?,
+                        msg_count: row.get(2)?,
+                        last_seen_at: row.get(3)?,
+                    })
+                },
+                |rows| {
+                    rows.collect::<std::result::Result<Vec<_>, _>>()
+                        .map_err(Into::into)
+                },
+            )
+            .await
+    }
+
     pub(crate) fn derive_blobdir(dbfile: &Path) -> PathBuf {
         let mut blob_fname = OsString::new();
         blob_fname.push(dbfile.file_name().unwrap_or_default());
@@ -668,6 +857,22 @@ pub(crate) fn derive_walfile(dbfile: &Path) -> PathBuf {
     }
 }
 
+/// Statistics of an IMAP folder as of its last scan, see [`Context::get_folder_stats`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FolderStats {
+    /// Name of the folder.
+    pub folder: String,
+
+    /// UID the next message arriving in the folder will get.
+    pub uid_next: u32,
+
+    /// Number of messages in the folder.
+    pub msg_count: u32,
+
+    /// Timestamp of the last scan.
+    pub last_seen_at: i64,
+}
+
 pub fn get_version_str() -> &'static str {
     &DC_VERSION_STR
 }
@@ -677,7 +882,8 @@ mod tests {
     use super::*;
 
     use crate::chat::{
-        get_chat_contacts, get_chat_msgs, send_msg, set_muted, Chat, ChatId, MuteDuration,
+        get_chat_contacts, get_chat_msgs, marknoticed_chat, send_msg, set_muted, Chat, ChatId,
+        MuteDuration,
     };
     use crate::contact::ContactId;
     use crate::message::{Message, Viewtype};
@@ -689,6 +895,122 @@ mod tests {
     use strum::IntoEnumIterator;
     use tempfile::tempdir;
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_event_log() -> Result<()> {
+        let t = TestContext::new().await;
+        t.emit_event(EventType::Info("not logged".to_string()));
+        assert!(t.get_event_log(10).is_empty());
+
+        t.set_config(Config::EventLogSize, Some("3")).await?;
+        for i in 0..5 {
+            t.emit_event(EventType::Info(format!("event {}", i)));
+        }
+        let infos = |log: Vec<(u64, EventType)>| {
+            log.into_iter()
+                .map(|(timestamp, event)| {
+                    assert!(timestamp > 0);
+                    event
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            infos(t.get_event_log(10)),
+            vec![
+                EventType::Info("event 2".to_string()),
+                EventType::Info("event 3".to_string()),
+                EventType::Info("event 4".to_string()),
+            ]
+        );
+        assert_eq!(
+            infos(t.get_event_log(1)),
+            vec![EventType::Info("event 4".to_string())]
+        );
+
+        t.set_config(Config::EventLogSize, Some("1")).await?;
+        assert_eq!(t.get_event_log(10).len(), 1);
+
+        t.set_config(Config::EventLogSize, None).await?;
+        assert!(t.get_event_log(10).is_empty());
+        t.emit_event(EventType::Info("not logged".to_string()));
+        assert!(t.get_event_log(10).is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_event_batch() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        t.set_config(Config::ShowEmails, Some("2")).await?;
+
+        // Receive incoming and outgoing messages of the chat with Bob alternately,
+        // outgoing messages emit `MsgsChanged`, incoming ones `IncomingMsg`.
+        let batch = t.begin_event_batch();
+        for i in 0..50 {
+            let (from, to) = if i % 2 == 0 {
+                ("bob@example.net", "alice@example.org")
+            } else {
+                ("alice@example.org", "bob@example.net")
+            };
+            receive_imf(
+                &t,
+                format!(
+                    "From: {}\n\
+                     To: {}\n\
+                     Subject: batch\n\
+                     Message-ID: <batch{}@example.net>\n\
+                     Date: Sun, 22 Mar 2020 22:37:57 +0000\n\
+                     \n\
+                     message {}\n",
+                    from, to, i, i
+                )
+                .as_bytes(),
+                false,
+            )
+            .await?;
+        }
+        drop(batch);
+        let chat_id = t.get_last_msg().await.chat_id;
+        t.emit_event(EventType::Info("batch done".to_string()));
+
+        let incoming = std::cell::Cell::new(0);
+        let changed = std::cell::RefCell::new(Vec::new());
+        t.evtracker
+            .get_matching(|evt| match evt {
+                EventType::IncomingMsg { .. } => {
+                    incoming.set(incoming.get() + 1);
+                    false
+                }
+                EventType::MsgsChanged { chat_id, msg_id } => {
+                    changed.borrow_mut().push((*chat_id, *msg_id));
+                    false
+                }
+                EventType::Info(msg) => msg == "batch done",
+                _ => false,
+            })
+            .await;
+        assert_eq!(incoming.get(), 25);
+        let changed = changed.into_inner();
+        assert_eq!(changed.iter().filter(|(id, _)| *id == chat_id).count(), 1);
+        assert!(changed.iter().all(|(_, msg_id)| msg_id.is_unset()));
+
+        // Without a batch, events are emitted right away.
+        t.emit_received_msgs_changed(chat_id, MsgId::new(1));
+        t.evtracker
+            .get_matching(|evt| matches!(evt, EventType::MsgsChanged { .. }))
+            .await;
+
+        // Events not caused by receiving messages are never held back.
+        let _batch = t.begin_event_batch();
+        t.emit_msgs_changed(chat_id, MsgId::new(2));
+        t.evtracker
+            .get_matching(
+                |evt| matches!(evt, EventType::MsgsChanged { msg_id, .. } if msg_id.to_u32() == 2),
+            )
+            .await;
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_wrong_db() -> Result<()> {
         let tmp = tempfile::tempdir()?;
@@ -761,6 +1083,9 @@ async fn test_get_fresh_msgs_and_muted_chats() {
             .unwrap();
         assert_eq!(claire.id.get_fresh_msg_cnt(&t).await.unwrap(), 2);
         assert_eq!(t.get_fresh_msgs().await.unwrap().len(), 4); // muted claires messages are no longer counted
+        assert_eq!(claire.id.get_message_count(&t).await.unwrap(), 2);
+        assert_eq!(claire.id.get_unread_count(&t).await.unwrap(), 2);
+        assert_eq!(t.get_total_unread_count().await.unwrap(), 4);
 
         // receive more messages
         receive_msg(&t, &bob).await;
@@ -776,6 +1101,12 @@ async fn test_get_fresh_msgs_and_muted_chats() {
             .unwrap();
         assert_eq!(claire.id.get_fresh_msg_cnt(&t).await.unwrap(), 3);
         assert_eq!(t.get_fresh_msgs().await.unwrap().len(), 9); // claire is counted again
+        assert_eq!(t.get_total_unread_count().await.unwrap(), 9);
+
+        marknoticed_chat(&t, claire.id).await.unwrap();
+        assert_eq!(claire.id.get_message_count(&t).await.unwrap(), 3);
+        assert_eq!(claire.id.get_unread_count(&t).await.unwrap(), 0);
+        assert_eq!(t.get_total_unread_count().await.unwrap(), 6);
     }
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -936,6 +1267,7 @@ async fn test_get_info_completeness() {
             "socks5_port",
             "socks5_user",
             "socks5_password",
+            "key_transfer_setup_code",
         ];
         let t = TestContext::new().await;
         let info = t.get_info().await.unwrap();
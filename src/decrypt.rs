@@ -10,7 +10,9 @@
 use crate::context::Context;
 use crate::headerdef::HeaderDef;
 use crate::headerdef::HeaderDefMap;
-use crate::key::{DcKey, Fingerprint, SignedPublicKey, SignedSecretKey};
+use crate::key::{
+    load_self_readonly_secret_keys, DcKey, Fingerprint, SignedPublicKey, SignedSecretKey,
+};
 use crate::keyring::Keyring;
 use crate::log::LogExt;
 use crate::peerstate::Peerstate;
@@ -47,12 +49,34 @@ pub async fn try_decrypt(
         .await
         .context("failed to get own keyring")?;
 
-    decrypt_part(
+    let res = decrypt_part(
         encrypted_data_part,
         private_keyring,
+        public_keyring_for_validate.clone(),
+    )
+    .await;
+    if res.is_ok() {
+        return res;
+    }
+
+    // Messages may still be encrypted to a key replaced by `rotate_self_key()`.
+    // Read-only keys are only loaded if the default key does not fit
+    // to avoid parsing them for every message.
+    let readonly_keys = load_self_readonly_secret_keys(context).await?;
+    if readonly_keys.is_empty() {
+        return res;
+    }
+    let mut readonly_keyring = Keyring::new();
+    for key in readonly_keys {
+        readonly_keyring.add(key);
+    }
+    decrypt_part(
+        encrypted_data_part,
+        readonly_keyring,
         public_keyring_for_validate,
     )
     .await
+    .or(res)
 }
 
 pub async fn create_decryption_info(
@@ -266,6 +266,110 @@ fn tag_contains_attr(event: &BytesStart, reader: &Reader<impl BufRead>, name: &s
     })
 }
 
+/// Tags kept by [`sanitize_html`], all other tags are removed.
+const SANITIZED_TAGS: &[&str] = &[
+    "a",
+    "b",
+    "big",
+    "blockquote",
+    "br",
+    "code",
+    "div",
+    "em",
+    "i",
+    "li",
+    "ol",
+    "p",
+    "pre",
+    "s",
+    "small",
+    "span",
+    "strong",
+    "sub",
+    "sup",
+    "u",
+    "ul",
+];
+
+/// Tags whose content is removed by [`sanitize_html`] together with the tag.
+const SANITIZED_CONTENT_TAGS: &[&str] = &["head", "script", "style", "title"];
+
+/// Sanitizes HTML for display.
+///
+/// Only simple formatting tags from an allowlist are kept, all attributes are removed.
+/// This removes scripts, styles and remote URLs, e.g. of images or links,
+/// so displaying the result does not load any remote content.
+/// The result is well-formed, all tags kept are closed.
+pub(crate) fn sanitize_html(html: &str) -> String {
+    let mut res = String::with_capacity(html.len());
+    // Tags kept from the input which are not closed yet.
+    let mut open_tags: Vec<String> = Vec::new();
+    // Number of open tags whose content is removed.
+    let mut skip_depth = 0;
+
+    let mut reader = quick_xml::Reader::from_str(html);
+    reader.check_end_names(false);
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event(&mut buf) {
+            Ok(quick_xml::events::Event::Start(ref e)) => {
+                let tag = String::from_utf8_lossy(e.name()).trim().to_lowercase();
+                if SANITIZED_CONTENT_TAGS.contains(&tag.as_str()) {
+                    skip_depth += 1;
+                } else if skip_depth == 0 && SANITIZED_TAGS.contains(&tag.as_str()) {
+                    res += &format!("<{}>", tag);
+                    if tag != "br" {
+                        open_tags.push(tag);
+                    }
+                }
+            }
+            Ok(quick_xml::events::Event::Empty(ref e)) => {
+                let tag = String::from_utf8_lossy(e.name()).trim().to_lowercase();
+                if skip_depth == 0 && SANITIZED_TAGS.contains(&tag.as_str()) {
+                    if tag == "br" {
+                        res += "<br>";
+                    } else {
+                        res += &format!("<{}></{}>", tag, tag);
+                    }
+                }
+            }
+            Ok(quick_xml::events::Event::End(ref e)) => {
+                let tag = String::from_utf8_lossy(e.name()).trim().to_lowercase();
+                if SANITIZED_CONTENT_TAGS.contains(&tag.as_str()) {
+                    skip_depth = std::cmp::max(skip_depth, 1) - 1;
+                } else if let Some(pos) = open_tags.iter().rposition(|open| *open == tag) {
+                    for open in open_tags.drain(pos..).rev() {
+                        res += &format!("</{}>", open);
+                    }
+                }
+            }
+            Ok(quick_xml::events::Event::Text(ref e)) => {
+                if skip_depth == 0 {
+                    sanitize_text_cb(e, &mut res);
+                }
+            }
+            Ok(quick_xml::events::Event::CData(e)) => {
+                if skip_depth == 0 {
+                    sanitize_text_cb(&e.escape(), &mut res);
+                }
+            }
+            Ok(quick_xml::events::Event::Eof) => break,
+            Err(_) => break,
+            _ => (),
+        }
+        buf.clear();
+    }
+    for open in open_tags.into_iter().rev() {
+        res += &format!("</{}>", open);
+    }
+    res
+}
+
+fn sanitize_text_cb(event: &BytesText, res: &mut String) {
+    let text = escaper::decode_html_buf_sloppy(event.escaped()).unwrap_or_default();
+    *res += &escaper::encode_minimal(&text);
+}
+
 pub fn dehtml_manually(buf: &str) -> String {
     // Just strip out everything between "<" and ">"
     let mut strbuilder = String::new();
@@ -382,6 +486,22 @@ fn test_pre_tag() {
         assert_eq!(txt.trim(), "two\nlines");
     }
 
+    #[test]
+    fn test_sanitize_html() {
+        assert_eq!(
+            sanitize_html(
+                "<b onclick=\"evil()\">Bob</b><br/>\
+                 <img src=\"https://example.org/track.png\">\
+                 <a href=\"https://example.org\">Web</a>\
+                 <style>p { background: url(https://example.org/x.png) }</style>\
+                 <script>evil()</script><p style=\"color: red\">1 &lt; 2"
+            ),
+            "<b>Bob</b><br><a>Web</a><p>1 &lt; 2</p>"
+        );
+        assert_eq!(sanitize_html("<i><u>text</i></u>"), "<i><u>text</u></i>");
+        assert_eq!(sanitize_html("plain"), "plain");
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_quote_div() {
         let input = include_str!("../test-data/message/gmx-quote-body.eml");
@@ -153,6 +153,19 @@ impl Message {
     pub fn download_state(&self) -> DownloadState {
         self.download_state
     }
+
+    /// Returns the full size in bytes of a partially downloaded message.
+    ///
+    /// Returns `None` if the message is completely downloaded
+    /// or was received before the size was stored.
+    pub fn download_size(&self) -> Option<u32> {
+        if self.download_state == DownloadState::Done {
+            return None;
+        }
+        self.param
+            .get(Param::DownloadSize)
+            .and_then(|size| size.parse().ok())
+    }
 }
 
 impl Job {
@@ -389,6 +402,7 @@ async fn test_partial_receive_imf() -> Result<()> {
         .await?;
         let msg = t.get_last_msg().await;
         assert_eq!(msg.download_state(), DownloadState::Available);
+        assert_eq!(msg.download_size(), Some(100000));
         assert_eq!(msg.get_subject(), "foo");
         assert!(msg
             .get_text()
@@ -406,6 +420,8 @@ async fn test_partial_receive_imf() -> Result<()> {
         .await?;
         let msg = t.get_last_msg().await;
         assert_eq!(msg.download_state(), DownloadState::Done);
+        assert_eq!(msg.download_size(), None);
+        assert!(!msg.param.exists(Param::DownloadSize));
         assert_eq!(msg.get_subject(), "foo");
         assert_eq!(msg.get_text(), Some("100k text...".to_string()));
 
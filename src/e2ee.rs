@@ -6,10 +6,11 @@
 use crate::aheader::{Aheader, EncryptPreference};
 use crate::config::Config;
 use crate::context::Context;
-use crate::key::{DcKey, SignedPublicKey, SignedSecretKey};
+use crate::key::{self, DcKey, Fingerprint, KeyPairUse, SignedPublicKey, SignedSecretKey};
 use crate::keyring::Keyring;
 use crate::peerstate::{Peerstate, PeerstateVerifiedStatus};
 use crate::pgp;
+use crate::tools::EmailAddress;
 
 #[derive(Debug)]
 pub struct EncryptHelper {
@@ -142,6 +143,28 @@ pub async fn ensure_secret_key_exists(context: &Context) -> Result<String> {
     Ok(self_addr)
 }
 
+/// Replaces the default key of the user with a newly generated one.
+///
+/// The old key is kept for decryption only, so messages encrypted to it can still be read.
+/// All messages sent afterwards advertise the new key in the Autocrypt header.
+/// Contacts who verified the old key need to verify the new one again.
+///
+/// Returns the fingerprint of the new key.
+pub async fn rotate_self_key(context: &Context) -> Result<Fingerprint> {
+    let addr = EmailAddress::new(&context.get_primary_self_addr().await?)?;
+    let _guard = context.generating_key_mutex.lock().await;
+
+    let keypair = key::create_keypair(context, addr).await?;
+    // Storing the new key as default makes all other keys read-only.
+    key::store_self_keypair(context, &keypair, KeyPairUse::Default).await?;
+    let fingerprint = keypair.public.fingerprint();
+    info!(
+        context,
+        "Rotated self key, new fingerprint {}.", fingerprint
+    );
+    Ok(fingerprint)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::chat;
@@ -303,6 +326,47 @@ fn new_peerstates(prefer_encrypt: EncryptPreference) -> Vec<(Option<Peerstate>,
         vec![(Some(peerstate), addr)]
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_rotate_self_key() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let bob = TestContext::new_bob().await;
+        let chat_alice = alice.create_chat(&bob).await.id;
+        let chat_bob = bob.create_chat(&alice).await.id;
+
+        let sent = alice.send_text(chat_alice, "Hi Bob").await;
+        bob.recv_msg(&sent).await;
+        let old_fingerprint = SignedPublicKey::load_self(&alice).await?.fingerprint();
+
+        // Bob encrypts to the old key of Alice.
+        let sent_old = bob.send_text(chat_bob, "Encrypted to the old key").await;
+
+        let new_fingerprint = rotate_self_key(&alice).await?;
+        assert_ne!(new_fingerprint, old_fingerprint);
+        assert_eq!(
+            SignedPublicKey::load_self(&alice).await?.fingerprint(),
+            new_fingerprint
+        );
+
+        let msg = alice.recv_msg(&sent_old).await;
+        assert_eq!(msg.text.as_deref(), Some("Encrypted to the old key"));
+        assert!(msg.get_showpadlock());
+
+        // The new key is advertised to Bob.
+        let sent = alice.send_text(chat_alice, "New key").await;
+        bob.recv_msg(&sent).await;
+        let peerstate = Peerstate::from_addr(&bob, "alice@example.org")
+            .await?
+            .expect("no peerstate found in the database");
+        assert_eq!(peerstate.public_key_fingerprint, Some(new_fingerprint));
+
+        let sent = bob.send_text(chat_bob, "Encrypted to the new key").await;
+        let msg = alice.recv_msg(&sent).await;
+        assert_eq!(msg.text.as_deref(), Some("Encrypted to the new key"));
+        assert!(msg.get_showpadlock());
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_should_encrypt() {
         let t = TestContext::new_alice().await;
@@ -66,20 +66,29 @@
 use serde::{Deserialize, Serialize};
 use tokio::time::timeout;
 
-use crate::chat::{send_msg, ChatId};
+use crate::chat::{send_msg, Chat, ChatId};
+use crate::config::Config;
 use crate::constants::{DC_CHAT_ID_LAST_SPECIAL, DC_CHAT_ID_TRASH};
 use crate::contact::ContactId;
 use crate::context::Context;
-use crate::download::MIN_DELETE_SERVER_AFTER;
+use crate::download::{DownloadState, MIN_DELETE_SERVER_AFTER};
 use crate::events::EventType;
 use crate::log::LogExt;
 use crate::message::{Message, MessageState, MsgId, Viewtype};
 use crate::mimeparser::SystemMessage;
+use crate::param::Param;
+use crate::scheduler::InterruptInfo;
 use crate::sql::{self, params_iter};
 use crate::stock_str;
 use crate::tools::{duration_to_str, time};
 use std::cmp::max;
 
+/// Time in seconds an expired ephemeral message stays in the
+/// [`MessageState::InExpired`] state before it is moved to the trash.
+///
+/// This gives UIs the chance to animate the disappearance of the message.
+pub(crate) const EPHEMERAL_GRACE_PERIOD: i64 = 30;
+
 #[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
 pub enum Timer {
     Disabled,
@@ -182,6 +191,12 @@ pub(crate) async fn inner_set_ephemeral_timer(
             )
             .await?;
 
+        let mut chat = Chat::load_from_db(context, self).await?;
+        if chat.param.exists(Param::EphemeralTimerUnannounced) {
+            chat.param.remove(Param::EphemeralTimerUnannounced);
+            chat.update_param(context).await?;
+        }
+
         context.emit_event(EventType::ChatEphemeralTimerModified {
             chat_id: self,
             timer,
@@ -189,6 +204,42 @@ pub(crate) async fn inner_set_ephemeral_timer(
         Ok(())
     }
 
+    /// Applies [`Config::DefaultEphemeralTimer`] to a newly created chat.
+    ///
+    /// The timer is announced with the first outgoing message.
+    /// Timer values of received messages sent before `created_timestamp`
+    /// do not override the default.
+    pub(crate) async fn apply_default_ephemeral_timer(
+        self,
+        context: &Context,
+        created_timestamp: i64,
+    ) -> Result<()> {
+        let duration = context
+            .get_config_u64(Config::DefaultEphemeralTimer)
+            .await?;
+        let timer = Timer::from_u32(u32::try_from(duration).unwrap_or(u32::MAX));
+        if timer == Timer::Disabled {
+            return Ok(());
+        }
+
+        let mut chat = Chat::load_from_db(context, self).await?;
+        chat.param
+            .set_i64(Param::EphemeralSettingsTimestamp, created_timestamp);
+        chat.param.set_int(Param::EphemeralTimerUnannounced, 1);
+        context
+            .sql
+            .execute(
+                "UPDATE chats SET ephemeral_timer=?, param=? WHERE id=?;",
+                paramsv![timer, chat.param.to_string(), self],
+            )
+            .await?;
+        info!(
+            context,
+            "Applied default ephemeral timer {:?} to chat {}", timer, self
+        );
+        Ok(())
+    }
+
     /// Set ephemeral message timer value in seconds.
     ///
     /// If timer value is 0, disable ephemeral message timer.
@@ -297,6 +348,31 @@ pub(crate) async fn start_ephemeral_timer(self, context: &Context) -> Result<()>
         }
         Ok(())
     }
+
+    /// Exempts the message from deletion by ephemeral message timers.
+    ///
+    /// The timer of the message is disabled and the message is marked as saved,
+    /// see [`crate::chat::get_saved_msgs`].
+    /// The timer of the chat and of other messages is not changed.
+    /// Messages that have already expired cannot be saved anymore.
+    pub async fn save_from_ephemeral(self, context: &Context) -> Result<()> {
+        let mut msg = Message::load_from_db(context, self).await?;
+        ensure!(
+            !msg.chat_id.is_trash() && msg.state != MessageState::InExpired,
+            "Message {} is already expired or deleted",
+            self
+        );
+        msg.param.set_int(Param::Saved, 1);
+        context
+            .sql
+            .execute(
+                "UPDATE msgs SET ephemeral_timer=0, ephemeral_timestamp=0, param=? WHERE id=?",
+                paramsv![msg.param.to_string(), self],
+            )
+            .await?;
+        context.emit_msgs_changed(msg.chat_id, self);
+        Ok(())
+    }
 }
 
 pub(crate) async fn start_ephemeral_timers_msgids(
@@ -329,12 +405,47 @@ pub(crate) async fn start_ephemeral_timers_msgids(
 /// Deletes messages which are expired according to
 /// `delete_device_after` setting or `ephemeral_timestamp` column.
 ///
+/// Incoming messages with an expired `ephemeral_timestamp` are first set to
+/// [`MessageState::InExpired`] and only deleted after
+/// [`EPHEMERAL_GRACE_PERIOD`].
+/// Outgoing messages keep their state and are deleted right away.
+///
 /// Returns true if any message is deleted, so caller can emit
 /// MsgsChanged event. If nothing has been deleted, returns
 /// false. This function does not emit the MsgsChanged event itself,
 /// because it is also called when chatlist is reloaded, and emitting
 /// MsgsChanged there will cause infinite reload loop.
 pub(crate) async fn delete_expired_messages(context: &Context, now: i64) -> Result<()> {
+    let expired_msgs = context
+        .sql
+        .query_map(
+            "SELECT id, chat_id FROM msgs
+             WHERE ephemeral_timestamp != 0
+               AND ephemeral_timestamp <= ?
+               AND chat_id != ?
+               AND state != ?
+               AND state < ?",
+            paramsv![
+                now,
+                DC_CHAT_ID_TRASH,
+                MessageState::InExpired,
+                MessageState::OutPreparing
+            ],
+            |row| Ok((row.get::<_, MsgId>(0)?, row.get::<_, ChatId>(1)?)),
+            |rows| {
+                rows.collect::<std::result::Result<Vec<_>, _>>()
+                    .map_err(Into::into)
+            },
+        )
+        .await
+        .context("failed to select expired messages")?;
+    for (msg_id, chat_id) in expired_msgs {
+        msg_id
+            .update_state(context, MessageState::InExpired)
+            .await?;
+        context.emit_msgs_changed(chat_id, msg_id);
+    }
+
     let mut updated = context
         .sql
         .execute(
@@ -344,13 +455,21 @@ pub(crate) async fn delete_expired_messages(context: &Context, now: i64) -> Resu
 UPDATE msgs
 SET 
   chat_id=?, txt='', subject='', txt_raw='', 
-  mime_headers='', from_id=0, to_id=0, param=''
+  mime_headers='', from_id=0, to_id=0, param='', timestamp_trashed=?
 WHERE
   ephemeral_timestamp != 0
-  AND ephemeral_timestamp <= ?
+  AND ((state = ? AND ephemeral_timestamp <= ?) OR (state >= ? AND ephemeral_timestamp <= ?))
   AND chat_id != ?
 "#,
-            paramsv![DC_CHAT_ID_TRASH, now, DC_CHAT_ID_TRASH],
+            paramsv![
+                DC_CHAT_ID_TRASH,
+                now,
+                MessageState::InExpired,
+                now.saturating_sub(EPHEMERAL_GRACE_PERIOD),
+                MessageState::OutPreparing,
+                now,
+                DC_CHAT_ID_TRASH
+            ],
         )
         .await
         .context("update failed")?
@@ -375,13 +494,15 @@ pub(crate) async fn delete_expired_messages(context: &Context, now: i64) -> Resu
             .execute(
                 "UPDATE msgs \
              SET chat_id = ?, txt = '', subject='', txt_raw='', \
-                 mime_headers='', from_id=0, to_id=0, param='' \
+                 mime_headers='', from_id=0, to_id=0, param='', timestamp_trashed=? \
              WHERE timestamp < ? \
              AND chat_id > ? \
              AND chat_id != ? \
-             AND chat_id != ?",
+             AND chat_id != ? \
+             AND NOT (param GLOB '4=1*' OR param GLOB '*' || char(10) || '4=1*')",
                 paramsv![
                     DC_CHAT_ID_TRASH,
+                    now,
                     threshold_timestamp,
                     DC_CHAT_ID_LAST_SPECIAL,
                     self_chat_id,
@@ -420,7 +541,8 @@ async fn next_delete_device_after_timestamp(context: &Context) -> Result<Option<
                 FROM msgs
                 WHERE chat_id > ?
                   AND chat_id != ?
-                  AND chat_id != ?;
+                  AND chat_id != ?
+                  AND NOT (param GLOB '4=1*' OR param GLOB '*' || char(10) || '4=1*');
                 "#,
                 paramsv![DC_CHAT_ID_TRASH, self_chat_id, device_chat_id],
             )
@@ -441,12 +563,16 @@ async fn next_expiration_timestamp(context: &Context) -> Option<i64> {
         .sql
         .query_get_value(
             r#"
-            SELECT min(ephemeral_timestamp)
+            SELECT min(CASE WHEN state = ? THEN ephemeral_timestamp + ? ELSE ephemeral_timestamp END)
             FROM msgs
             WHERE ephemeral_timestamp != 0
               AND chat_id != ?;
             "#,
-            paramsv![DC_CHAT_ID_TRASH], // Trash contains already deleted messages, skip them
+            paramsv![
+                MessageState::InExpired,
+                EPHEMERAL_GRACE_PERIOD,
+                DC_CHAT_ID_TRASH // Trash contains already deleted messages, skip them
+            ],
         )
         .await
     {
@@ -507,6 +633,15 @@ pub(crate) async fn ephemeral_loop(context: &Context, interrupt_receiver: Receiv
     }
 }
 
+/// Selects the Message-IDs of the messages to delete from the server.
+///
+/// Takes the timestamp thresholds for fully and partially downloaded messages
+/// and the timestamp at which ephemeral messages are expired as parameters.
+const EXPIRED_RFC724_MIDS: &str = "SELECT rfc724_mid FROM msgs
+     WHERE ((download_state = 0 AND timestamp < ?) OR
+            (download_state != 0 AND timestamp < ?) OR
+            (ephemeral_timestamp != 0 AND ephemeral_timestamp <= ?))";
+
 /// Schedules expired IMAP messages for deletion.
 pub(crate) async fn delete_expired_imap_messages(context: &Context) -> Result<()> {
     let now = time();
@@ -523,14 +658,10 @@ pub(crate) async fn delete_expired_imap_messages(context: &Context) -> Result<()
     context
         .sql
         .execute(
-            "UPDATE imap
-             SET target=''
-             WHERE rfc724_mid IN (
-               SELECT rfc724_mid FROM msgs
-               WHERE ((download_state = 0 AND timestamp < ?) OR
-                      (download_state != 0 AND timestamp < ?) OR
-                      (ephemeral_timestamp != 0 AND ephemeral_timestamp <= ?))
-             )",
+            &format!(
+                "UPDATE imap SET target='' WHERE rfc724_mid IN ({})",
+                EXPIRED_RFC724_MIDS
+            ),
             paramsv![threshold_timestamp, threshold_timestamp_extended, now],
         )
         .await?;
@@ -538,6 +669,97 @@ pub(crate) async fn delete_expired_imap_messages(context: &Context) -> Result<()
     Ok(())
 }
 
+/// Number of IMAP entries updated at once by
+/// [`Context::schedule_retroactive_server_deletion`].
+const RETROACTIVE_DELETION_BATCH_SIZE: usize = 500;
+
+impl Context {
+    /// Schedules deletion of messages that are already on the server
+    /// and older than allowed by the `delete_server_after` setting.
+    ///
+    /// Only messages that are stored locally are deleted,
+    /// so e-mails that are not shown because of the `show_emails` setting stay on the server.
+    /// Messages that are not fully downloaded are never deleted.
+    ///
+    /// Emits [`EventType::ServerDeletionProgress`] events
+    /// and returns the number of IMAP messages scheduled for deletion.
+    pub async fn schedule_retroactive_server_deletion(&self) -> Result<usize> {
+        let res = self.schedule_retroactive_server_deletion_inner().await;
+        match res {
+            Ok(_) => self.emit_event(EventType::ServerDeletionProgress(1000)),
+            Err(_) => self.emit_event(EventType::ServerDeletionProgress(0)),
+        }
+        res
+    }
+
+    async fn schedule_retroactive_server_deletion_inner(&self) -> Result<usize> {
+        let delete_server_after = match self.get_config_delete_server_after().await? {
+            Some(delete_server_after) => delete_server_after,
+            None => return Ok(0),
+        };
+        let threshold_timestamp = time() - delete_server_after;
+
+        let ids: Vec<u32> = self
+            .sql
+            .query_map(
+                &format!(
+                    "SELECT id FROM imap
+                     WHERE target!=''
+                       AND rfc724_mid IN ({})
+                       AND rfc724_mid NOT IN (
+                         SELECT rfc724_mid FROM msgs
+                         WHERE chat_id=? OR download_state!=?
+                       )
+                     ORDER BY id",
+                    EXPIRED_RFC724_MIDS
+                ),
+                // Partially downloaded and trashed messages are excluded anyway,
+                // so only the threshold for fully downloaded messages is needed.
+                paramsv![
+                    threshold_timestamp,
+                    0,
+                    0,
+                    DC_CHAT_ID_TRASH,
+                    DownloadState::Done
+                ],
+                |row| row.get::<_, u32>(0),
+                |ids| {
+                    ids.collect::<std::result::Result<Vec<_>, _>>()
+                        .map_err(Into::into)
+                },
+            )
+            .await?;
+
+        let total = ids.len();
+        let mut done = 0;
+        for batch in ids.chunks(RETROACTIVE_DELETION_BATCH_SIZE) {
+            self.sql
+                .execute(
+                    &format!(
+                        "UPDATE imap SET target='' WHERE id IN ({})",
+                        sql::repeat_vars(batch.len())
+                    ),
+                    rusqlite::params_from_iter(params_iter(batch)),
+                )
+                .await?;
+            done += batch.len();
+            let progress = 1000 * done / total;
+            if progress > 0 && progress < 1000 {
+                self.emit_event(EventType::ServerDeletionProgress(progress));
+            }
+        }
+
+        info!(
+            self,
+            "Scheduled {} old messages for deletion from the server.", total
+        );
+        if total > 0 {
+            self.interrupt_inbox(InterruptInfo::new(false)).await;
+        }
+        Ok(total)
+    }
+}
+
 /// Start ephemeral timers for seen messages if they are not started
 /// yet.
 ///
@@ -578,6 +800,7 @@ mod tests {
     use crate::tools::MAX_SECONDS_TO_LEND_FROM_FUTURE;
     use crate::{
         chat::{self, Chat, ChatItem},
+        message,
         tools::IsNoneOrEmpty,
     };
 
@@ -932,6 +1155,58 @@ async fn test_ephemeral_delete_msgs() -> Result<()> {
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_save_from_ephemeral() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let bob = TestContext::new_bob().await;
+        let alice_chat = alice.create_chat(&bob).await;
+        alice_chat
+            .id
+            .set_ephemeral_timer(&alice, Timer::Enabled { duration: 1 })
+            .await?;
+        bob.recv_msg(&alice.pop_sent_msg().await).await;
+
+        let sent = alice.send_text(alice_chat.id, "Saved").await;
+        let saved = bob.recv_msg(&sent).await;
+        let sent = alice.send_text(alice_chat.id, "Not saved").await;
+        let unsaved = bob.recv_msg(&sent).await;
+        assert_eq!(saved.ephemeral_timer, Timer::Enabled { duration: 1 });
+        message::markseen_msgs(&bob, vec![saved.id, unsaved.id]).await?;
+
+        saved.id.save_from_ephemeral(&bob).await?;
+        let msg = Message::load_from_db(&bob, saved.id).await?;
+        assert_eq!(msg.ephemeral_timer, Timer::Disabled);
+        assert_eq!(msg.ephemeral_timestamp, 0);
+        assert_eq!(
+            chat::get_saved_msgs(&bob, saved.chat_id).await?,
+            vec![saved.id]
+        );
+
+        delete_expired_messages(&bob, time() + 10 + EPHEMERAL_GRACE_PERIOD).await?;
+
+        let msg = Message::load_from_db(&bob, saved.id).await?;
+        assert_eq!(msg.chat_id, saved.chat_id);
+        assert_eq!(msg.text.as_deref(), Some("Saved"));
+        let msg = Message::load_from_db(&bob, unsaved.id).await?;
+        assert_eq!(msg.chat_id, DC_CHAT_ID_TRASH);
+        assert!(unsaved.id.save_from_ephemeral(&bob).await.is_err());
+
+        // Marking the saved message as seen again does not restart the timer.
+        message::markseen_msgs(&bob, vec![saved.id]).await?;
+        let msg = Message::load_from_db(&bob, saved.id).await?;
+        assert_eq!(msg.ephemeral_timestamp, 0);
+
+        // Saved messages are not deleted by `delete_device_after` either.
+        bob.set_config(Config::DeleteDeviceAfter, Some("3600"))
+            .await?;
+        delete_expired_messages(&bob, time() + 7200).await?;
+        let msg = Message::load_from_db(&bob, saved.id).await?;
+        assert_eq!(msg.chat_id, saved.chat_id);
+        assert_eq!(next_delete_device_after_timestamp(&bob).await?, None);
+
+        Ok(())
+    }
+
     async fn check_msg_will_be_deleted(
         t: &TestContext,
         msg_id: MsgId,
@@ -950,6 +1225,7 @@ async fn check_msg_will_be_deleted(
 
         assert!(next_expiration < deleted_at);
         delete_expired_messages(t, deleted_at).await?;
+        delete_expired_messages(t, deleted_at + EPHEMERAL_GRACE_PERIOD).await?;
 
         let loaded = Message::load_from_db(t, msg_id).await?;
         assert_eq!(loaded.text.unwrap(), "");
@@ -985,6 +1261,70 @@ async fn check_msg_is_deleted(t: &TestContext, chat: &Chat, msg_id: MsgId) {
         }
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_schedule_retroactive_server_deletion() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        const HOUR: i64 = 60 * 60;
+        let now = time();
+        let chat_id = t.get_self_chat().await.id;
+        for (id, timestamp, chat_id, download_state) in &[
+            (1000, now - 48 * HOUR, chat_id, DownloadState::Done),
+            (1010, now - HOUR, chat_id, DownloadState::Done),
+            (1020, now - 48 * HOUR, DC_CHAT_ID_TRASH, DownloadState::Done),
+            (1030, now - 48 * HOUR, chat_id, DownloadState::Available),
+        ] {
+            let message_id = id.to_string();
+            t.sql
+                .execute(
+                    "INSERT INTO msgs (id, rfc724_mid, timestamp, chat_id, download_state)
+                     VALUES (?,?,?,?,?);",
+                    paramsv![id, message_id, timestamp, chat_id, download_state],
+                )
+                .await?;
+            t.sql
+                .execute(
+                    "INSERT INTO imap (rfc724_mid, folder, uid, target) VALUES (?,'INBOX',?,'INBOX');",
+                    paramsv![message_id, id],
+                )
+                .await?;
+        }
+
+        // Nothing is deleted if deletion from the server is disabled.
+        assert_eq!(t.schedule_retroactive_server_deletion().await?, 0);
+        t.evtracker
+            .get_matching(|evt| matches!(evt, EventType::ServerDeletionProgress(1000)))
+            .await;
+
+        t.set_config(Config::DeleteServerAfter, Some(&*(24 * HOUR).to_string()))
+            .await?;
+        assert_eq!(t.schedule_retroactive_server_deletion().await?, 1);
+        let deleted: Vec<String> = t
+            .sql
+            .query_map(
+                "SELECT rfc724_mid FROM imap WHERE target=''",
+                paramsv![],
+                |row| row.get(0),
+                |rows| {
+                    rows.collect::<std::result::Result<Vec<_>, _>>()
+                        .map_err(Into::into)
+                },
+            )
+            .await?;
+        assert_eq!(deleted, vec!["1000".to_string()]);
+
+        // "Delete at once" also deletes recent messages.
+        t.set_config(Config::DeleteServerAfter, Some("1")).await?;
+        assert_eq!(t.schedule_retroactive_server_deletion().await?, 1);
+        assert_eq!(
+            t.sql
+                .count("SELECT COUNT(*) FROM imap WHERE target=''", paramsv![])
+                .await?,
+            2
+        );
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_delete_expired_imap_messages() -> Result<()> {
         let t = TestContext::new_alice().await;
@@ -1095,6 +1435,50 @@ async fn remove_uid(context: &Context, id: u32) -> Result<()> {
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_ephemeral_in_expired_state() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let t = TestContext::new_bob().await;
+        let alice_chat = alice.create_chat(&t).await;
+        alice_chat
+            .id
+            .set_ephemeral_timer(&alice, Timer::Enabled { duration: 60 })
+            .await?;
+        t.recv_msg(&alice.pop_sent_msg().await).await;
+        let sent = alice.send_text(alice_chat.id, "Message text").await;
+        let msg_id = t.recv_msg(&sent).await.id;
+        let chat = Chat::load_from_db(&t, t.get_last_msg().await.chat_id).await?;
+        message::markseen_msgs(&t, vec![msg_id]).await?;
+        let expires_at = next_expiration_timestamp(&t).await.unwrap();
+
+        // Outgoing messages are deleted right away.
+        delete_expired_messages(&alice, time() + 120).await?;
+        let loaded = Message::load_from_db(&alice, sent.sender_msg_id).await?;
+        assert_eq!(loaded.chat_id, DC_CHAT_ID_TRASH);
+        assert_ne!(loaded.state, MessageState::InExpired);
+
+        delete_expired_messages(&t, expires_at).await?;
+        let loaded = Message::load_from_db(&t, msg_id).await?;
+        assert_eq!(loaded.state, MessageState::InExpired);
+        assert_eq!(loaded.text.unwrap(), "Message text");
+        assert_eq!(loaded.chat_id, chat.id);
+        assert_eq!(
+            next_expiration_timestamp(&t).await,
+            Some(expires_at + EPHEMERAL_GRACE_PERIOD)
+        );
+
+        delete_expired_messages(&t, expires_at + EPHEMERAL_GRACE_PERIOD - 1).await?;
+        let loaded = Message::load_from_db(&t, msg_id).await?;
+        assert_eq!(loaded.chat_id, chat.id);
+
+        delete_expired_messages(&t, expires_at + EPHEMERAL_GRACE_PERIOD).await?;
+        let loaded = Message::load_from_db(&t, msg_id).await?;
+        assert_eq!(loaded.chat_id, DC_CHAT_ID_TRASH);
+        check_msg_is_deleted(&t, &chat, msg_id).await;
+
+        Ok(())
+    }
+
     // Regression test for a bug in the timer rollback protection.
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_ephemeral_timer_references() -> Result<()> {
@@ -1179,4 +1563,83 @@ async fn test_ephemeral_timer_references() -> Result<()> {
 
         Ok(())
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_default_ephemeral_timer() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let bob = TestContext::new_bob().await;
+        alice
+            .set_config(Config::DefaultEphemeralTimer, Some("2419200"))
+            .await?;
+        let timer = Timer::Enabled { duration: 2419200 };
+        async fn timer_changes(t: &TestContext, chat_id: ChatId) -> Result<usize> {
+            let mut timer_changes = 0;
+            for item in chat::get_chat_msgs(t, chat_id, 0).await? {
+                if let ChatItem::Message { msg_id } = item {
+                    let msg = Message::load_from_db(t, msg_id).await?;
+                    if msg.get_info_type() == SystemMessage::EphemeralTimerChanged {
+                        timer_changes += 1;
+                    }
+                }
+            }
+            Ok(timer_changes)
+        }
+
+        // The message creating the chat is usually sent shortly before the chat is created,
+        // its disabled timer does not override the default.
+        let date = chrono::Utc::now().to_rfc2822();
+        receive_imf(
+            &alice,
+            format!(
+                "From: Bob <bob@example.net>\n\
+                 To: alice@example.org\n\
+                 Subject: hi\n\
+                 Message-ID: <first@example.net>\n\
+                 Chat-Version: 1.0\n\
+                 Date: {}\n\
+                 \n\
+                 hello\n",
+                date
+            )
+            .as_bytes(),
+            false,
+        )
+        .await?;
+        let msg = alice.get_last_msg().await;
+        let chat_id = msg.chat_id;
+        assert_eq!(chat_id.get_ephemeral_timer(&alice).await?, timer);
+
+        // The first outgoing message announces the timer.
+        chat_id.accept(&alice).await?;
+        let sent = alice.send_text(chat_id, "hello back").await;
+        let msg = bob.recv_msg(&sent).await;
+        assert_eq!(msg.chat_id.get_ephemeral_timer(&bob).await?, timer);
+        alice.send_text(chat_id, "second").await;
+        assert_eq!(timer_changes(&alice, chat_id).await?, 1);
+
+        // The timer is not announced to classic email contacts.
+        alice.set_config(Config::ShowEmails, Some("2")).await?;
+        let classic_email = |msg_id: &str| {
+            format!(
+                "From: Claire <claire@example.com>\n\
+                 To: alice@example.org\n\
+                 Subject: hi\n\
+                 Message-ID: <{}@example.com>\n\
+                 Date: {}\n\
+                 \n\
+                 hello\n",
+                msg_id,
+                chrono::Utc::now().to_rfc2822()
+            )
+        };
+        receive_imf(&alice, classic_email("first").as_bytes(), false).await?;
+        let chat_id = alice.get_last_msg().await.chat_id;
+        chat_id.accept(&alice).await?;
+        assert_eq!(chat_id.get_ephemeral_timer(&alice).await?, timer);
+        alice.send_text(chat_id, "hello back").await;
+        assert_eq!(timer_changes(&alice, chat_id).await?, 0);
+        receive_imf(&alice, classic_email("second").as_bytes(), false).await?;
+        assert_eq!(chat_id.get_ephemeral_timer(&alice).await?, timer);
+        Ok(())
+    }
 }
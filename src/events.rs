@@ -7,7 +7,8 @@
 use crate::chat::ChatId;
 use crate::contact::ContactId;
 use crate::ephemeral::Timer as EphemeralTimer;
-use crate::message::MsgId;
+use crate::message::{MessageState, MsgId};
+use crate::securejoin::SecurejoinStage;
 use crate::webxdc::StatusUpdateSerial;
 
 /// Event channel.
@@ -200,11 +201,32 @@ pub enum EventType {
         msg_id: MsgId,
     },
 
-    /// A single message is read by the receiver. State changed from DC_STATE_OUT_DELIVERED to
+    /// A single message is read by a receiver.
+    ///
+    /// The first read receipt changes the state from DC_STATE_OUT_DELIVERED to
     /// DC_STATE_OUT_MDN_RCVD, see dc_msg_get_state().
+    /// In groups, this event is emitted once for every member who sent a read receipt,
+    /// also if the state did not change.
     MsgRead {
         chat_id: ChatId,
         msg_id: MsgId,
+
+        /// ID of the contact who read the message.
+        contact_id: ContactId,
+    },
+
+    /// The state of a single message changed, see dc_msg_get_state().
+    ///
+    /// This is emitted in addition to events such as `MsgDelivered` or `MsgRead`
+    /// and allows the UI to animate the transition between the states.
+    ///
+    /// @param data1 (int) msg_id
+    /// @param data2 (int) old state in the upper 16 bits, new state in the lower 16 bits
+    MessageStateChanged {
+        chat_id: ChatId,
+        msg_id: MsgId,
+        old_state: MessageState,
+        new_state: MessageState,
     },
 
     /// Chat changed.  The name or the image of a chat group was changed or members were added or removed.
@@ -222,6 +244,13 @@ pub enum EventType {
         timer: EphemeralTimer,
     },
 
+    /// A single chatlist entry may have changed,
+    /// e.g. because a message was received or a contact request was accepted.
+    ///
+    /// UIs can reload this entry using `get_chatlist_item()`
+    /// instead of reloading the whole chatlist.
+    ChatlistItemChanged(ChatId),
+
     /// Contact(s) created, renamed, blocked or deleted.
     ///
     /// @param data1 (int) If set, this is the contact_id of an added contact that should be selected.
@@ -260,6 +289,13 @@ pub enum EventType {
     /// @param data2 0
     ImexFileWritten(PathBuf),
 
+    /// Inform about the progress of scheduling old messages for deletion from the server
+    /// started by schedule_retroactive_server_deletion().
+    ///
+    /// @param data1 (usize) 0=error, 1-999=progress in permille, 1000=success and done
+    /// @param data2 0
+    ServerDeletionProgress(usize),
+
     /// Progress information of a secure-join handshake from the view of the inviter
     /// (Alice, the person who shows the QR code).
     ///
@@ -296,10 +332,62 @@ pub enum EventType {
     /// dc_get_connectivity_html() for details.
     ConnectivityChanged,
 
+    /// Inform about the result of an IMAP roundtrip measurement started by ping().
+    ///
+    /// @param data1 (i64) Roundtrip time of an IMAP NOOP command in milliseconds.
+    /// @param data2 0
+    PingResult {
+        latency_ms: i64,
+    },
+
     SelfavatarChanged,
 
     WebxdcStatusUpdate {
         msg_id: MsgId,
         status_update_serial: StatusUpdateSerial,
     },
+
+    /// An ephemeral status update for the webxdc instance `msg_id` was received,
+    /// see `Context::send_webxdc_ephemeral_update()`.
+    ///
+    /// `payload` is the JSON-serialized payload of the update, it is not stored.
+    WebxdcEphemeralUpdate {
+        msg_id: MsgId,
+        payload: String,
+    },
+
+    /// Statistics of an IMAP folder after it was scanned for new messages,
+    /// see also `Context::get_folder_stats()`.
+    ///
+    /// Only emitted if `Config::EmitFolderStats` is enabled.
+    ///
+    /// @param data1 (int) Number of messages in the folder.
+    /// @param data2 (char*) Name of the folder.
+    ImapFolderStats {
+        folder: String,
+        uid_next: u32,
+        msg_count: u32,
+        has_new_messages: bool,
+    },
+
+    /// Progress of a secure-join handshake with named stages,
+    /// emitted alongside `SecurejoinInviterProgress` and `SecurejoinJoinerProgress`.
+    ///
+    /// `chat_id` is the group chat for group joins, if it is known at this stage.
+    ///
+    /// @param data1 (int) ID of the contact the handshake is done with.
+    /// @param data2 (int) Stage as one of the DC_SECUREJOIN_STAGE_* constants.
+    SecurejoinProgress {
+        contact_id: ContactId,
+        chat_id: Option<ChatId>,
+        stage: SecurejoinStage,
+    },
+
+    /// The number of outgoing messages waiting to be sent changed,
+    /// see `Context::get_pending_send_count()` and `message::get_outgoing_pending_msgs()`.
+    ///
+    /// @param data1 (int) Number of pending outgoing messages.
+    OutboxChanged {
+        pending_count: u32,
+    },
 }
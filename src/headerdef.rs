@@ -32,13 +32,31 @@ pub enum HeaderDef {
     /// header, so it can be used to ignore such messages.
     XMozillaDraftInfo,
 
+    /// Address the message was delivered to, added by the receiving MTA.
+    /// There may be several of them if the message was forwarded,
+    /// the topmost one is the final recipient.
+    DeliveredTo,
+
+    /// Postfix header with the envelope recipient before alias expansion.
+    XOriginalTo,
+
+    /// DKIM, SPF and DMARC results, added by the receiving server.
+    AuthenticationResults,
+
     ListId,
     ListPost,
+    ListHelp,
+    ListArchive,
     References,
     InReplyTo,
     Precedence,
+
+    /// Marks automatically sent messages, see RFC 3834.
+    AutoSubmitted,
     ContentType,
+    ContentDisposition,
     ContentId,
+    ContentLanguage,
     ChatVersion,
     ChatGroupId,
     ChatGroupName,
@@ -53,6 +71,10 @@ pub enum HeaderDef {
     ChatDuration,
     ChatDispositionNotificationTo,
     ChatWebrtcRoom,
+
+    /// Marks webxdc status updates that should not be stored, see
+    /// `Context::send_webxdc_ephemeral_update()`.
+    ChatWebxdcEphemeral,
     Autocrypt,
     AutocryptSetupMessage,
     SecureJoin,
@@ -13,11 +13,13 @@
 
 use anyhow::{Context as _, Result};
 use lettre_email::mime::{self, Mime};
+use once_cell::sync::Lazy;
 
+use crate::config::Config;
 use crate::headerdef::{HeaderDef, HeaderDefMap};
 use crate::message::{Message, MsgId};
 use crate::mimeparser::parse_message_id;
-use crate::param::Param::SendHtml;
+use crate::param::Param::{HasRemoteContent, SendHtml};
 use crate::plaintext::PlainText;
 use crate::{context::Context, message};
 use lettre_email::PartBuilder;
@@ -34,6 +36,15 @@ pub fn has_html(&self) -> bool {
         self.mime_modified
     }
 
+    /// Check if remote content is removed from the HTML returned by `MsgId.get_html()`
+    /// because of `Config::BlockRemoteContent`.
+    /// The UI may offer to load the remote content using `MsgId.get_html_with_remote_content()`.
+    /// The corresponding ffi-function is `dc_msg_is_remote_content_blocked()`.
+    pub async fn is_remote_content_blocked(&self, context: &Context) -> Result<bool> {
+        Ok(self.param.get_bool(HasRemoteContent).unwrap_or_default()
+            && context.get_config_bool(Config::BlockRemoteContent).await?)
+    }
+
     /// Set HTML-part part of a message that is about to be sent.
     /// The HTML-part is written to the database before sending and
     /// used as the `text/html` part in the MIME-structure.
@@ -239,13 +250,81 @@ fn mimepart_to_data_url(mail: &mailparse::ParsedMail<'_>) -> Result<String> {
     Ok(format!("data:{};base64,{}", mail.ctype.mimetype, data))
 }
 
+/// Matches `src`, `background` and `poster` attributes referencing remote content,
+/// `cid:` and `data:` sources are not matched.
+static REMOTE_ATTR_RE: Lazy<regex::Regex> = Lazy::new(|| {
+    regex::Regex::new(
+        r#"(?i)(\b(?:src|background|poster)\s*=\s*)(?:"\s*(?:https?:)?//[^"]*"|'\s*(?:https?:)?//[^']*'|(?:https?:)?//[^\s>]*)"#,
+    )
+    .unwrap()
+});
+
+/// Matches `srcset` attributes containing at least one remote source.
+static REMOTE_SRCSET_RE: Lazy<regex::Regex> = Lazy::new(|| {
+    regex::Regex::new(
+        r#"(?i)(\bsrcset\s*=\s*)(?:"(?:[^"]*[\s,])?(?:https?:)?//[^"]*"|'(?:[^']*[\s,])?(?:https?:)?//[^']*'|(?:https?:)?//[^\s>]*)"#,
+    )
+    .unwrap()
+});
+
+/// Matches CSS `url()` values and `@import` rules referencing remote content,
+/// e.g. in `style` attributes or `<style>` elements.
+static REMOTE_CSS_RE: Lazy<regex::Regex> = Lazy::new(|| {
+    regex::Regex::new(
+        r#"(?i)(?:url\(\s*(?:"\s*(?:https?:)?//[^"]*"|'\s*(?:https?:)?//[^']*'|(?:https?:)?//[^)]*?)\s*\)|@import\s+(?:"\s*(?:https?:)?//[^"]*"|'\s*(?:https?:)?//[^']*'))"#,
+    )
+    .unwrap()
+});
+
+/// Returns true if `html` references remote content that would be loaded when displayed.
+fn has_remote_content(html: &str) -> bool {
+    REMOTE_ATTR_RE.is_match(html) || REMOTE_SRCSET_RE.is_match(html) || REMOTE_CSS_RE.is_match(html)
+}
+
+/// Returns true if a `text/html` part of `mail` references remote content.
+///
+/// This is checked on the already parsed message when it is received,
+/// so the HTML does not need to be extracted once more.
+pub(crate) fn mail_has_remote_content(mail: &mailparse::ParsedMail<'_>) -> bool {
+    if mail.ctype.mimetype == "text/html" {
+        return mail
+            .get_body()
+            .map_or(false, |html| has_remote_content(&html));
+    }
+    mail.subparts.iter().any(mail_has_remote_content)
+}
+
+/// Removes references to remote content, see [`has_remote_content`].
+fn block_remote_content(html: &str) -> String {
+    let html = REMOTE_ATTR_RE.replace_all(html, "${1}\"\"");
+    let html = REMOTE_SRCSET_RE.replace_all(&html, "${1}\"\"");
+    REMOTE_CSS_RE.replace_all(&html, "").into_owned()
+}
+
 impl MsgId {
     /// Get HTML from a message-id.
     /// This requires `mime_headers` field to be set for the message;
     /// this is the case at least when `Message.has_html()` returns true
     /// (we do not save raw mime unconditionally in the database to save space).
+    /// If `Config::BlockRemoteContent` is enabled, remote images are removed.
     /// The corresponding ffi-function is `dc_get_msg_html()`.
     pub async fn get_html(self, context: &Context) -> Result<Option<String>> {
+        let block_remote_content = context.get_config_bool(Config::BlockRemoteContent).await?;
+        self.get_html_ex(context, block_remote_content).await
+    }
+
+    /// Get HTML from a message-id including remote images,
+    /// regardless of `Config::BlockRemoteContent`.
+    /// The corresponding ffi-function is `dc_get_msg_html_with_remote_content()`.
+    pub async fn get_html_with_remote_content(self, context: &Context) -> Result<Option<String>> {
+        self.get_html_ex(context, false).await
+    }
+
+    async fn get_html_ex(
+        self,
+        context: &Context,
+        block_remote_content: bool,
+    ) -> Result<Option<String>> {
         let rawmime = message::get_mime_headers(context, self).await?;
 
         if !rawmime.is_empty() {
@@ -254,6 +333,7 @@ pub async fn get_html(self, context: &Context) -> Result<Option<String>> {
                     warn!(context, "get_html: parser error: {}", err);
                     Ok(None)
                 }
+                Ok(parser) if block_remote_content => Ok(Some(block_remote_content(&parser.html))),
                 Ok(parser) => Ok(Some(parser.html)),
             }
         } else {
@@ -566,4 +646,73 @@ async fn test_cp1252_html() -> Result<()> {
         assert!(html.contains("foo bar ä ö ü ß"));
         Ok(())
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_block_remote_content() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        t.set_config(Config::ShowEmails, Some("2")).await?;
+        receive_imf(
+            &t,
+            b"From: sender@example.net\n\
+              To: alice@example.org\n\
+              Subject: Newsletter\n\
+              Message-ID: <tracking@example.net>\n\
+              Date: Sun, 22 Mar 2020 22:37:57 +0000\n\
+              Content-Type: text/html; charset=utf-8\n\
+              \n\
+              <html><head><style>@import \"https://tracker.example.net/style.css\";</style></head>\
+              <body background=\"https://tracker.example.net/bg.gif\"><p>Hello</p>\
+              <img src=\"https://tracker.example.net/pixel.gif?id=123\" width=\"1\" height=\"1\">\
+              <img srcset=\"logo.png 1x, //tracker.example.net/logo.png 2x\">\
+              <div style=\"background-image: url('https://tracker.example.net/div.gif')\">Hi</div>\
+              <img src=\"cid:logo@example.net\"></body></html>\n",
+            false,
+        )
+        .await?;
+        let msg = t.get_last_msg().await;
+        assert!(msg.has_html());
+        assert!(msg.is_remote_content_blocked(&t).await?);
+
+        let html = msg.get_id().get_html(&t).await?.unwrap();
+        assert!(!html.contains("tracker.example.net"));
+        assert!(html.contains("<img src=\"\" width=\"1\" height=\"1\">"));
+        assert!(html.contains("<img srcset=\"\">"));
+        assert!(html.contains("cid:logo@example.net"));
+
+        let html = msg
+            .get_id()
+            .get_html_with_remote_content(&t)
+            .await?
+            .unwrap();
+        assert!(html.contains("https://tracker.example.net/pixel.gif?id=123"));
+
+        // The current config decides, not the one at the time of receiving.
+        t.set_config_bool(Config::BlockRemoteContent, false).await?;
+        assert!(!msg.is_remote_content_blocked(&t).await?);
+        receive_imf(
+            &t,
+            b"From: sender@example.net\n\
+              To: alice@example.org\n\
+              Subject: Newsletter\n\
+              Message-ID: <tracking2@example.net>\n\
+              Date: Sun, 22 Mar 2020 22:38:57 +0000\n\
+              Content-Type: text/html; charset=utf-8\n\
+              \n\
+              <html><body><p>Hello</p>\
+              <img src=\"https://tracker.example.net/pixel.gif?id=456\"></body></html>\n",
+            false,
+        )
+        .await?;
+        let msg = t.get_last_msg().await;
+        assert!(msg.has_html());
+        assert!(!msg.is_remote_content_blocked(&t).await?);
+        let html = msg.get_id().get_html(&t).await?.unwrap();
+        assert!(html.contains("https://tracker.example.net/pixel.gif?id=456"));
+
+        t.set_config_bool(Config::BlockRemoteContent, true).await?;
+        assert!(msg.is_remote_content_blocked(&t).await?);
+        let html = msg.get_id().get_html(&t).await?.unwrap();
+        assert!(!html.contains("tracker.example.net"));
+        Ok(())
+    }
 }
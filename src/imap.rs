@@ -8,6 +8,7 @@
     cmp::max,
     collections::{BTreeMap, BTreeSet},
     iter::Peekable,
+    time::{Duration, Instant},
 };
 
 use anyhow::{bail, format_err, Context as _, Result};
@@ -17,6 +18,7 @@
 };
 use futures::StreamExt;
 use num_traits::FromPrimitive;
+use tokio::time::timeout;
 
 use crate::chat::{self, ChatId, ChatIdBlocked};
 use crate::config::Config;
@@ -28,6 +30,7 @@
 use crate::events::EventType;
 use crate::headerdef::{HeaderDef, HeaderDefMap};
 use crate::job;
+use crate::log::LogExt;
 use crate::login_param::{
     CertificateChecks, LoginParam, ServerAddress, ServerLoginParam, Socks5Config,
 };
@@ -42,7 +45,7 @@
 use crate::scheduler::InterruptInfo;
 use crate::sql;
 use crate::stock_str;
-use crate::tools::create_id;
+use crate::tools::{create_id, time};
 
 mod client;
 mod idle;
@@ -520,6 +523,31 @@ pub async fn trigger_reconnect(&mut self, context: &Context) {
         self.should_reconnect = true;
     }
 
+    /// Sends a NOOP command over the existing session and returns the roundtrip time in
+    /// milliseconds.
+    ///
+    /// If the server does not answer within `ping_timeout` or the connection fails with an I/O
+    /// error, reconnect is triggered and the error is returned, see [`is_network_error`].
+    pub(crate) async fn ping(&mut self, context: &Context, ping_timeout: Duration) -> Result<i64> {
+        let session = self
+            .session
+            .as_mut()
+            .context("Can't ping because connection was not established")?;
+        let start = Instant::now();
+        match timeout(ping_timeout, session.noop()).await {
+            Ok(Ok(_)) => Ok(i64::try_from(start.elapsed().as_millis()).unwrap_or(i64::MAX)),
+            Ok(Err(err @ async_imap::error::Error::Io(_))) => {
+                self.trigger_reconnect(context).await;
+                Err(err).context("NOOP failed")
+            }
+            Ok(Err(err)) => Err(err).context("NOOP command error"),
+            Err(err) => {
+                self.trigger_reconnect(context).await;
+                Err(err).with_context(|| format!("NOOP timed out after {:?}", ping_timeout))
+            }
+        }
+    }
+
     /// FETCH-MOVE-DELETE iteration.
     ///
     /// Prefetches headers and downloads new message from the folder, moves messages away from the
@@ -760,9 +788,18 @@ pub(crate) async fn fetch_new_messages(
         }
 
         let new_emails = self.select_with_uidvalidity(context, folder).await?;
+        let msg_count = self
+            .config
+            .selected_mailbox
+            .as_ref()
+            .map_or(0, |mailbox| mailbox.exists);
 
         if !new_emails && !fetch_existing_msgs {
             info!(context, "No new emails in folder {}", folder);
+            update_folder_stats(context, folder, msg_count, false)
+                .await
+                .log_err(context, "Failed to update folder stats")
+                .ok();
             return Ok(false);
         }
 
@@ -859,6 +896,8 @@ pub(crate) async fn fetch_new_messages(
         }
 
         // Actually download messages.
+        // `MsgsChanged` events are coalesced per chat to not flood the UI during bulk reception.
+        let event_batch = context.begin_event_batch();
         let (largest_uid_fully_fetched, mut received_msgs) = self
             .fetch_many_msgs(
                 context,
@@ -881,6 +920,7 @@ pub(crate) async fn fetch_new_messages(
             )
             .await?;
         received_msgs.extend(received_msgs_2);
+        drop(event_batch);
 
         // determine which uid_next to use to update to
         // receive_imf() returns an `Err` value only on recoverable errors, otherwise it just logs an error.
@@ -904,6 +944,10 @@ pub(crate) async fn fetch_new_messages(
         info!(context, "{} mails read from \"{}\".", read_cnt, folder);
 
         chat::mark_old_messages_as_noticed(context, received_msgs).await?;
+        update_folder_stats(context, folder, msg_count, read_cnt > 0)
+            .await
+            .log_err(context, "Failed to update folder stats")
+            .ok();
 
         Ok(read_cnt > 0)
     }
@@ -1727,6 +1771,16 @@ pub(crate) async fn get_quota_roots(
     }
 }
 
+/// Returns true if the error returned by [`Imap::ping`] indicates that the connection is broken,
+/// i.e. the server did not answer in time or there was an I/O error.
+pub(crate) fn is_network_error(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<tokio::time::error::Elapsed>().is_some()
+        || matches!(
+            err.downcast_ref::<async_imap::error::Error>(),
+            Some(async_imap::error::Error::Io(_))
+        )
+}
+
 async fn should_move_out_of_spam(
     context: &Context,
     headers: &[mailparse::MailHeader<'_>],
@@ -2099,24 +2153,18 @@ async fn mark_seen_by_uid(
             )
         })?
     {
-        let updated = context
+        let state: MessageState = context
             .sql
-            .execute(
-                "UPDATE msgs SET state=?1
-                     WHERE (state=?2 OR state=?3)
-                     AND id=?4",
-                paramsv![
-                    MessageState::InSeen,
-                    MessageState::InFresh,
-                    MessageState::InNoticed,
-                    msg_id
-                ],
-            )
-            .await
-            .with_context(|| format!("failed to update msg {} state", msg_id))?
-            > 0;
+            .query_get_value("SELECT state FROM msgs WHERE id=?", paramsv![msg_id])
+            .await?
+            .unwrap_or_default();
+        let updated = state == MessageState::InFresh || state == MessageState::InNoticed;
 
         if updated {
+            msg_id
+                .update_state(context, MessageState::InSeen)
+                .await
+                .with_context(|| format!("failed to update msg {} state", msg_id))?;
             msg_id
                 .start_ephemeral_timer(context)
                 .await
@@ -2150,6 +2198,31 @@ pub(crate) async fn markseen_on_imap_table(context: &Context, message_id: &str)
     Ok(())
 }
 
+/// Schedules marking several messages as Seen on IMAP, see [`markseen_on_imap_table`].
+///
+/// All messages are added to `imap_markseen` table with a single statement
+/// and IMAP loop is interrupted only once.
+/// `Imap::store_seen_flags_on_imap()` then marks them using one `UID STORE` command per folder.
+pub(crate) async fn markseen_bulk(context: &Context, rfc724_mids: &[String]) -> Result<()> {
+    if rfc724_mids.is_empty() {
+        return Ok(());
+    }
+    context
+        .sql
+        .execute(
+            &format!(
+                "INSERT OR IGNORE INTO imap_markseen (id)
+                 SELECT id FROM imap WHERE rfc724_mid IN ({})",
+                sql::repeat_vars(rfc724_mids.len())
+            ),
+            rusqlite::params_from_iter(rfc724_mids),
+        )
+        .await?;
+    context.interrupt_inbox(InterruptInfo::new(false)).await;
+
+    Ok(())
+}
+
 /// uid_next is the next unique identifier value from the last time we fetched a folder
 /// See <https://tools.ietf.org/html/rfc3501#section-2.3.1.1>
 /// This function is used to update our uid_next after fetching messages.
@@ -2165,6 +2238,36 @@ pub(crate) async fn set_uid_next(context: &Context, folder: &str, uid_next: u32)
     Ok(())
 }
 
+/// Stores the statistics of a scanned folder, see [`Context::get_folder_stats`],
+/// and emits [`EventType::ImapFolderStats`] if [`Config::EmitFolderStats`] is enabled.
+async fn update_folder_stats(
+    context: &Context,
+    folder: &str,
+    msg_count: u32,
+    has_new_messages: bool,
+) -> Result<()> {
+    let uid_next = get_uid_next(context, folder).await?;
+    let now = time();
+    context
+        .sql
+        .execute(
+            "INSERT INTO folder_stats (folder, uid_next, msg_count, last_seen_at) VALUES (?,?,?,?)
+                ON CONFLICT(folder) DO UPDATE SET uid_next=?, msg_count=?, last_seen_at=? WHERE folder=?;",
+            paramsv![folder, uid_next, msg_count, now, uid_next, msg_count, now, folder],
+        )
+        .await?;
+
+    if context.get_config_bool(Config::EmitFolderStats).await? {
+        context.emit_event(EventType::ImapFolderStats {
+            folder: folder.to_string(),
+            uid_next,
+            msg_count,
+            has_new_messages,
+        });
+    }
+    Ok(())
+}
+
 /// uid_next is the next unique identifier value from the last time we fetched a folder
 /// See <https://tools.ietf.org/html/rfc3501#section-2.3.1.1>
 /// This method returns the uid_next from the last time we fetched messages.
@@ -2404,6 +2507,67 @@ fn test_get_folder_meaning_by_name() {
         assert_eq!(get_folder_meaning_by_name("SPAM"), FolderMeaning::Spam);
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_markseen_bulk() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        for (rfc724_mid, folder, uid) in [
+            ("1@example.org", "INBOX", 1),
+            ("2@example.org", "INBOX", 2),
+            ("3@example.org", "INBOX", 5),
+            ("4@example.org", "DeltaChat", 7),
+            ("5@example.org", "INBOX", 8),
+        ] {
+            t.sql
+                .execute(
+                    "INSERT INTO imap (rfc724_mid, folder, target, uid, uidvalidity)
+                     VALUES (?, ?, ?, ?, 1);",
+                    paramsv![rfc724_mid, folder, folder, uid],
+                )
+                .await?;
+        }
+
+        markseen_bulk(&t, &[]).await?;
+        markseen_bulk(
+            &t,
+            &[
+                "1@example.org".to_string(),
+                "2@example.org".to_string(),
+                "3@example.org".to_string(),
+                "4@example.org".to_string(),
+                "unknown@example.org".to_string(),
+            ],
+        )
+        .await?;
+
+        let rows = t
+            .sql
+            .query_map(
+                "SELECT imap.id, uid, folder FROM imap, imap_markseen
+                 WHERE imap.id = imap_markseen.id AND target = folder
+                 ORDER BY folder, uid",
+                [],
+                |row| {
+                    let rowid: i64 = row.get(0)?;
+                    let uid: u32 = row.get(1)?;
+                    let folder: String = row.get(2)?;
+                    Ok((rowid, uid, folder))
+                },
+                |rows| rows.collect::<Result<Vec<_>, _>>().map_err(Into::into),
+            )
+            .await?;
+        let stores: Vec<(String, String)> = UidGrouper::from(rows)
+            .map(|(folder, _, uid_set)| (folder, uid_set))
+            .collect();
+        assert_eq!(
+            stores,
+            vec![
+                ("DeltaChat".to_string(), "7".to_string()),
+                ("INBOX".to_string(), "1:2,5".to_string()),
+            ]
+        );
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_set_uid_next_validity() {
         let t = TestContext::new_alice().await;
@@ -2420,6 +2584,46 @@ async fn test_set_uid_next_validity() {
         assert_eq!(get_uidvalidity(&t.ctx, "Inbox").await.unwrap(), 6);
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_update_folder_stats() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        assert!(t.get_folder_stats().await?.is_empty());
+
+        set_uid_next(&t, "INBOX", 5).await?;
+        update_folder_stats(&t, "INBOX", 3, true).await?;
+        update_folder_stats(&t, "Archive", 0, false).await?;
+        let stats = t.get_folder_stats().await?;
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].folder, "Archive");
+        assert_eq!(stats[1].folder, "INBOX");
+        assert_eq!(stats[1].uid_next, 5);
+        assert_eq!(stats[1].msg_count, 3);
+        assert!(stats[1].last_seen_at > 0);
+
+        // Stats are updated on the next scan, the event is only emitted if enabled.
+        t.set_config_bool(Config::EmitFolderStats, true).await?;
+        set_uid_next(&t, "INBOX", 7).await?;
+        update_folder_stats(&t, "INBOX", 4, true).await?;
+        let event = t
+            .evtracker
+            .get_matching(|evt| matches!(evt, EventType::ImapFolderStats { .. }))
+            .await;
+        assert_eq!(
+            event,
+            EventType::ImapFolderStats {
+                folder: "INBOX".to_string(),
+                uid_next: 7,
+                msg_count: 4,
+                has_new_messages: true,
+            }
+        );
+        let stats = t.get_folder_stats().await?;
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[1].uid_next, 7);
+        assert_eq!(stats[1].msg_count, 4);
+        Ok(())
+    }
+
     #[test]
     fn test_build_sequence_sets() {
         let cases = vec![
@@ -2678,4 +2882,80 @@ async fn test_get_imap_search_command() -> Result<()> {
 
         Ok(())
     }
+
+    /// Starts a fake IMAP server on localhost which accepts a single connection
+    /// and answers every command with OK, delaying answers to NOOP by `noop_delay`.
+    async fn start_mock_imap_server(noop_delay: Duration) -> Result<u16> {
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let port = listener.local_addr()?.port();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await?;
+            let (reader, mut writer) = stream.into_split();
+            writer.write_all(b"* OK IMAP4rev1 ready\r\n").await?;
+            let mut lines = BufReader::new(reader).lines();
+            while let Some(line) = lines.next_line().await? {
+                let (tag, command) = line.split_once(' ').unwrap_or((&line, ""));
+                if command.eq_ignore_ascii_case("NOOP") {
+                    tokio::time::sleep(noop_delay).await;
+                }
+                writer
+                    .write_all(format!("{} OK done\r\n", tag).as_bytes())
+                    .await?;
+            }
+            Ok::<_, anyhow::Error>(())
+        });
+        Ok(port)
+    }
+
+    async fn configure_mock_imap_server(t: &TestContext, port: u16) -> Result<()> {
+        t.set_config(Config::ConfiguredMailServer, Some("127.0.0.1"))
+            .await?;
+        t.set_config(Config::ConfiguredMailPort, Some(&port.to_string()))
+            .await?;
+        t.set_config(
+            Config::ConfiguredMailSecurity,
+            Some(&(Socket::Plain as i32).to_string()),
+        )
+        .await?;
+        t.set_config(Config::ConfiguredMailUser, Some("alice"))
+            .await?;
+        t.set_config(Config::ConfiguredMailPw, Some("secret"))
+            .await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_ping() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        let port = start_mock_imap_server(Duration::from_millis(200)).await?;
+        configure_mock_imap_server(&t, port).await?;
+
+        let (_idle_interrupt_sender, idle_interrupt_receiver) = async_channel::bounded(1);
+        let mut imap = Imap::new_configured(&t, idle_interrupt_receiver).await?;
+        assert!(imap.ping(&t, Duration::from_secs(30)).await.is_err());
+
+        imap.connect(&t).await?;
+        let latency_ms = imap.ping(&t, Duration::from_secs(30)).await?;
+        assert!(latency_ms >= 200);
+
+        // Without started IO there is no connection to ping over.
+        assert!(t.ping().await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_ping_timeout() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        let port = start_mock_imap_server(Duration::from_secs(5)).await?;
+        configure_mock_imap_server(&t, port).await?;
+
+        let (_idle_interrupt_sender, idle_interrupt_receiver) = async_channel::bounded(1);
+        let mut imap = Imap::new_configured(&t, idle_interrupt_receiver).await?;
+        imap.connect(&t).await?;
+        let err = imap.ping(&t, Duration::from_millis(100)).await.unwrap_err();
+        assert!(is_network_error(&err));
+        Ok(())
+    }
 }
@@ -1,6 +1,8 @@
 use std::{collections::BTreeMap, time::Instant};
 
 use anyhow::{Context as _, Result};
+use async_channel as channel;
+use futures::future;
 use futures::stream::StreamExt;
 
 use crate::config::Config;
@@ -10,6 +12,9 @@
 
 use super::{get_folder_meaning, get_folder_meaning_by_name};
 
+/// Maximum value of [`Config::ImapConnectionCount`].
+const MAX_IMAP_CONNECTION_COUNT: i32 = 4;
+
 impl Imap {
     /// Returns true if folders were scanned, false if scanning was postponed.
     pub(crate) async fn scan_folders(&mut self, context: &Context) -> Result<bool> {
@@ -32,6 +37,7 @@ pub(crate) async fn scan_folders(&mut self, context: &Context) -> Result<bool> {
         let watched_folders = get_watched_folders(context).await?;
 
         let mut folder_configs = BTreeMap::new();
+        let mut folders_to_scan = Vec::new();
 
         for folder in folders {
             let folder_meaning = get_folder_meaning(&folder);
@@ -63,19 +69,33 @@ pub(crate) async fn scan_folders(&mut self, context: &Context) -> Result<bool> {
 
             // Don't scan folders that are watched anyway
             if !watched_folders.contains(&folder.name().to_string()) && !is_drafts {
-                // Drain leftover unsolicited EXISTS messages
-                self.server_sent_unsolicited_exists(context)?;
-
-                loop {
-                    self.fetch_move_delete(context, folder.name(), is_spam_folder)
-                        .await
-                        .ok_or_log_msg(context, "Can't fetch new msgs in scanned folder");
-
-                    // If the server sent an unsocicited EXISTS during the fetch, we need to fetch again
-                    if !self.server_sent_unsolicited_exists(context)? {
-                        break;
-                    }
-                }
+                folders_to_scan.push((folder.name().to_string(), is_spam_folder));
+            }
+        }
+
+        let connection_count = context
+            .get_config_int(Config::ImapConnectionCount)
+            .await?
+            .clamp(1, MAX_IMAP_CONNECTION_COUNT) as usize;
+        let mut partitions = partition_round_robin(folders_to_scan, connection_count).into_iter();
+        // The first partition is always scanned on this connection,
+        // which is the one watching the inbox.
+        let own_folders = partitions.next().unwrap_or_default();
+        let (own_res, other_res) = future::join(
+            self.scan_folder_list(context, &own_folders),
+            future::join_all(
+                partitions
+                    .filter(|folders| !folders.is_empty())
+                    .map(|folders| scan_folders_on_new_connection(context, folders)),
+            ),
+        )
+        .await;
+        own_res?;
+
+        // Folders that could not be scanned on an additional connection are scanned here.
+        for res in other_res {
+            if let Err(folders) = res {
+                self.scan_folder_list(context, &folders).await?;
             }
         }
 
@@ -93,6 +113,32 @@ pub(crate) async fn scan_folders(&mut self, context: &Context) -> Result<bool> {
         Ok(true)
     }
 
+    /// Fetches new messages from the given folders.
+    ///
+    /// Each entry is the folder name and whether the folder is a spam folder.
+    async fn scan_folder_list(
+        &mut self,
+        context: &Context,
+        folders: &[(String, bool)],
+    ) -> Result<()> {
+        for (folder, is_spam_folder) in folders {
+            // Drain leftover unsolicited EXISTS messages
+            self.server_sent_unsolicited_exists(context)?;
+
+            loop {
+                self.fetch_move_delete(context, folder, *is_spam_folder)
+                    .await
+                    .ok_or_log_msg(context, "Can't fetch new msgs in scanned folder");
+
+                // If the server sent an unsocicited EXISTS during the fetch, we need to fetch again
+                if !self.server_sent_unsolicited_exists(context)? {
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Returns the names of all folders on the IMAP server.
     pub async fn list_folders(
         self: &mut Imap,
@@ -119,6 +165,47 @@ pub(crate) async fn get_watched_folder_configs(context: &Context) -> Result<Vec<
     Ok(res)
 }
 
+/// Scans `folders` on an additional IMAP connection.
+///
+/// Returns the folders back if they could not be scanned, so the caller can scan them itself.
+async fn scan_folders_on_new_connection(
+    context: &Context,
+    folders: Vec<(String, bool)>,
+) -> std::result::Result<(), Vec<(String, bool)>> {
+    let res = async {
+        // The connection never IDLEs, so nobody needs to interrupt it.
+        let (_idle_interrupt_sender, idle_interrupt_receiver) = channel::bounded(1);
+        let mut imap = Imap::new_configured(context, idle_interrupt_receiver).await?;
+        imap.connect(context).await?;
+        imap.determine_capabilities(context).await?;
+        imap.scan_folder_list(context, &folders).await
+    }
+    .await;
+    match res {
+        Ok(()) => Ok(()),
+        Err(err) => {
+            warn!(
+                context,
+                "Failed to scan folders on additional connection: {:#}", err
+            );
+            Err(folders)
+        }
+    }
+}
+
+/// Distributes `items` round-robin into `n` partitions.
+///
+/// Always returns `n` partitions, some of them may be empty.
+fn partition_round_robin<T>(items: Vec<T>, n: usize) -> Vec<Vec<T>> {
+    let mut partitions: Vec<Vec<T>> = (0..n).map(|_| Vec::new()).collect();
+    for (i, item) in items.into_iter().enumerate() {
+        if let Some(partition) = i.checked_rem(n).and_then(|j| partitions.get_mut(j)) {
+            partition.push(item);
+        }
+    }
+    partitions
+}
+
 pub(crate) async fn get_watched_folders(context: &Context) -> Result<Vec<String>> {
     let mut res = Vec::new();
     for folder_config in get_watched_folder_configs(context).await? {
@@ -128,3 +215,21 @@ pub(crate) async fn get_watched_folders(context: &Context) -> Result<Vec<String>
     }
     Ok(res)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partition_round_robin() {
+        assert_eq!(
+            partition_round_robin(vec![1, 2, 3, 4, 5], 2),
+            vec![vec![1, 3, 5], vec![2, 4]]
+        );
+        assert_eq!(
+            partition_round_robin(vec![1, 2], 4),
+            vec![vec![1], vec![2], vec![], vec![]]
+        );
+        assert_eq!(partition_round_robin(vec![1, 2, 3], 1), vec![vec![1, 2, 3]]);
+    }
+}
@@ -1,41 +1,56 @@
 //! # Import/export module.
 
 use std::any::Any;
+use std::collections::HashSet;
 use std::ffi::OsStr;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 
+use ::pgp::armor::BlockType;
 use ::pgp::types::KeyTrait;
 use anyhow::{bail, ensure, format_err, Context as _, Result};
-use futures::{StreamExt, TryStreamExt};
+use futures::StreamExt;
 use futures_lite::FutureExt;
 use rand::{thread_rng, Rng};
 use tokio::fs::{self, File};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
 use tokio_tar::Archive;
 
-use crate::blob::BlobObject;
+use crate::blob::{self, BlobObject};
 use crate::chat::{self, delete_and_reset_all_device_msgs, ChatId};
 use crate::config::Config;
+use crate::constants::{DC_CHAT_ID_LAST_SPECIAL, DC_MSG_ID_LAST_SPECIAL};
 use crate::contact::ContactId;
 use crate::context::Context;
 use crate::e2ee;
 use crate::events::EventType;
-use crate::key::{self, DcKey, DcSecretKey, SignedPublicKey, SignedSecretKey};
+use crate::key::{self, DcKey, DcSecretKey, Fingerprint, SignedPublicKey, SignedSecretKey};
 use crate::log::LogExt;
 use crate::message::{Message, MsgId, Viewtype};
 use crate::mimeparser::SystemMessage;
 use crate::param::Param;
-use crate::pgp;
+use crate::pgp::{self, split_armored_data, KeyStrength, HEADER_PASSPHRASE_FORMAT};
 use crate::sql;
+use crate::stats;
 use crate::stock_str;
 use crate::tools::{
-    create_folder, delete_file, get_filesuffix_lc, open_file_std, read_file, time, write_file,
-    EmailAddress,
+    create_folder, delete_file, get_filesuffix_lc, humanize_bytes, open_file_std, read_file, time,
+    timestamp_to_str, write_file, EmailAddress,
 };
 
 // Name of the database file in the backup.
 const DBFILE_BACKUP_NAME: &str = "dc_database_backup.sqlite";
 const BLOBS_BACKUP_NAME: &str = "blobs_backup";
 
+/// Suffix of backups that are still being written or were left over by an interrupted export.
+const PARTIAL_BACKUP_SUFFIX: &str = ".tar.part";
+
+/// Partial backups older than this are removed before exporting a new backup.
+const PARTIAL_BACKUP_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Imported own keys expiring within this many seconds trigger a device message.
+const KEY_EXPIRY_WARNING_SECS: i64 = 30 * 24 * 60 * 60;
+
 #[derive(Debug, Display, Copy, Clone, PartialEq, Eq, FromPrimitive, ToPrimitive)]
 #[repr(u32)]
 pub enum ImexMode {
@@ -50,6 +65,10 @@ pub enum ImexMode {
     /// Public keys are not imported.
     ImportSelfKeys = 2,
 
+    /// Like [`ImexMode::ImportSelfKeys`], but also imports keys that are already expired.
+    /// Without this mode, importing expired keys fails with [`KeyExpiredError`].
+    ImportSelfKeysAllowExpired = 3,
+
     /// Export a backup to the directory given as `path` with the given `passphrase`.
     /// The backup contains all contacts, chats, images and other data and device independent settings.
     /// The backup does not contain device dependent settings as ringtones or LED notification settings.
@@ -63,6 +82,20 @@ pub enum ImexMode {
     ImportBackup = 12,
 }
 
+/// Error returned when importing an own key that is already expired,
+/// so that nobody can encrypt to it anymore.
+///
+/// Use [`ImexMode::ImportSelfKeysAllowExpired`] to import such keys anyway.
+#[derive(Debug, thiserror::Error)]
+#[error("Key {} expired on {}", .fingerprint.hex(), timestamp_to_str(*.expires_at))]
+pub struct KeyExpiredError {
+    /// Fingerprint of the expired key.
+    pub fingerprint: Fingerprint,
+
+    /// Timestamp of the expiration.
+    pub expires_at: i64,
+}
+
 /// Import/export things.
 ///
 /// What to do is defined by the `what` parameter.
@@ -107,16 +140,22 @@ pub async fn imex(
 }
 
 /// Returns the filename of the backup found (otherwise an error)
+///
+/// Partial backups left over by an interrupted export are never returned.
 pub async fn has_backup(_context: &Context, dir_name: &Path) -> Result<String> {
+    ensure_not_partial_backup(dir_name)?;
     let mut dir_iter = tokio::fs::read_dir(dir_name).await?;
     let mut newest_backup_name = "".to_string();
     let mut newest_backup_path: Option<PathBuf> = None;
+    let mut partial_backup_path: Option<PathBuf> = None;
 
     while let Ok(Some(dirent)) = dir_iter.next_entry().await {
         let path = dirent.path();
         let name = dirent.file_name();
         let name: String = name.to_string_lossy().into();
-        if name.starts_with("delta-chat")
+        if name.starts_with("delta-chat") && name.ends_with(PARTIAL_BACKUP_SUFFIX) {
+            partial_backup_path = Some(path);
+        } else if name.starts_with("delta-chat")
             && name.ends_with(".tar")
             && (newest_backup_name.is_empty() || name > newest_backup_name)
         {
@@ -127,12 +166,100 @@ pub async fn has_backup(_context: &Context, dir_name: &Path) -> Result<String> {
         }
     }
 
-    match newest_backup_path {
-        Some(path) => Ok(path.to_string_lossy().into_owned()),
-        None => bail!("no backup found in {}", dir_name.display()),
+    match (newest_backup_path, partial_backup_path) {
+        (Some(path), _) => Ok(path.to_string_lossy().into_owned()),
+        (None, Some(partial)) => bail!(
+            "no backup found in {}, only the incomplete backup {} left over from an interrupted export",
+            dir_name.display(),
+            partial.display()
+        ),
+        (None, None) => bail!("no backup found in {}", dir_name.display()),
     }
 }
 
+/// Fails with a descriptive error if `path` is a partial backup
+/// that is still being written or was left over by an interrupted export.
+fn ensure_not_partial_backup(path: &Path) -> Result<()> {
+    ensure!(
+        !path.to_string_lossy().ends_with(".part"),
+        "{} is an incomplete backup, the export was interrupted or is still running",
+        path.display()
+    );
+    Ok(())
+}
+
+/// Backup file found by [`list_backups`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupInfo {
+    /// Path to the backup file.
+    pub path: PathBuf,
+
+    /// Whether the database in the backup is encrypted,
+    /// i.e. a passphrase is needed to import it.
+    pub encrypted: bool,
+}
+
+/// Returns all backups found in `dir_name`, newest first.
+///
+/// To tell encrypted backups from plain ones,
+/// only the beginning of the database in each backup is read.
+pub async fn list_backups(context: &Context, dir_name: &Path) -> Result<Vec<BackupInfo>> {
+    let mut dir_iter = tokio::fs::read_dir(dir_name).await?;
+    let mut paths = Vec::new();
+    while let Ok(Some(dirent)) = dir_iter.next_entry().await {
+        let name = dirent.file_name();
+        let name: String = name.to_string_lossy().into();
+        if name.starts_with("delta-chat") && name.ends_with(".tar") {
+            paths.push(dirent.path());
+        }
+    }
+    // Same as in `has_backup()`, newer backups have greater names.
+    paths.sort_unstable_by(|a, b| b.file_name().cmp(&a.file_name()));
+
+    let mut backups = Vec::with_capacity(paths.len());
+    for path in paths {
+        match is_backup_encrypted(&path).await {
+            Ok(encrypted) => backups.push(BackupInfo { path, encrypted }),
+            Err(err) => warn!(context, "Cannot read backup {}: {:#}", path.display(), err),
+        }
+    }
+    Ok(backups)
+}
+
+/// Checks whether the database in the backup at `path` is encrypted.
+///
+/// Unencrypted databases start with the SQLite magic header,
+/// encrypted ones look like random data.
+async fn is_backup_encrypted(path: &Path) -> Result<bool> {
+    const SQLITE_MAGIC: &[u8; 16] = b"SQLite format 3\0";
+
+    let mut archive = Archive::new(File::open(path).await?);
+    let mut entries = archive.entries()?;
+    while let Some(entry) = entries.next().await {
+        let mut entry = entry?;
+        if entry.path()?.file_name() == Some(OsStr::new(DBFILE_BACKUP_NAME)) {
+            let mut magic = [0u8; 16];
+            entry
+                .read_exact(&mut magic)
+                .await
+                .context("backup database is too short")?;
+            return Ok(&magic != SQLITE_MAGIC);
+        }
+    }
+    bail!("no database found in backup");
+}
+
+/// Exports the account statistics returned by [`stats::get_stats`] as JSON to `path`.
+pub async fn export_stats_json(context: &Context, path: &Path) -> Result<()> {
+    let stats = stats::get_stats(context).await?;
+    let json = serde_json::to_string_pretty(&stats)?;
+    fs::write(path, json)
+        .await
+        .with_context(|| format!("cannot write stats to {}", path.display()))?;
+    context.emit_event(EventType::ImexFileWritten(path.to_path_buf()));
+    Ok(())
+}
+
 /// Initiates key transfer via Autocrypt Setup Message.
 pub async fn initiate_key_transfer(context: &Context) -> Result<String> {
     use futures::future::FutureExt;
@@ -238,12 +365,17 @@ pub async fn render_setup_file(context: &Context, passphrase: &str) -> Result<St
     ))
 }
 
+/// Creates a new setup code for Autocrypt Setup Messages.
+///
+/// The setup code consists of 9 groups of 4 digits.
+/// The last digit is a checksum over the preceding 35 digits
+/// calculated with the Luhn algorithm, see [`is_valid_setup_code`].
 pub fn create_setup_code(_context: &Context) -> String {
     let mut random_val: u16;
     let mut rng = thread_rng();
-    let mut ret = String::new();
+    let mut digits = String::new();
 
-    for i in 0..9 {
+    for _ in 0..9 {
         loop {
             random_val = rng.gen();
             if random_val as usize <= 60000 {
@@ -251,14 +383,75 @@ pub fn create_setup_code(_context: &Context) -> String {
             }
         }
         random_val = (random_val as usize % 10000) as u16;
-        ret += &format!(
-            "{}{:04}",
-            if 0 != i { "-" } else { "" },
-            random_val as usize
-        );
+        digits += &format!("{:04}", random_val as usize);
+    }
+
+    // Replace the last digit by the checksum.
+    digits.pop();
+    if let Some(check_digit) = setup_code_check_digit(&digits) {
+        digits.push(check_digit);
     }
 
-    ret
+    normalize_setup_code(&digits)
+}
+
+/// Validity of a setup code as returned by [`is_valid_setup_code`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetupCodeValidity {
+    /// The setup code has the correct format and checksum.
+    Valid,
+
+    /// The setup code has the correct format, but the checksum does not match.
+    ///
+    /// This is most likely a typo,
+    /// however, setup codes created by older versions or other clients have no checksum.
+    ChecksumMismatch,
+
+    /// The setup code does not consist of 36 digits.
+    BadFormat,
+}
+
+/// Checks the format and checksum of a setup code
+/// so that UIs can give feedback before the slow decryption is tried.
+///
+/// Digits may be separated by whitespace and dashes.
+pub fn is_valid_setup_code(code: &str) -> SetupCodeValidity {
+    if !code
+        .chars()
+        .all(|c| c.is_ascii_digit() || c.is_whitespace() || c == '-')
+    {
+        return SetupCodeValidity::BadFormat;
+    }
+    let mut digits: String = code.chars().filter(|c| c.is_ascii_digit()).collect();
+    if digits.len() != 36 {
+        return SetupCodeValidity::BadFormat;
+    }
+    let last_digit = digits.pop();
+    if last_digit == setup_code_check_digit(&digits) {
+        SetupCodeValidity::Valid
+    } else {
+        SetupCodeValidity::ChecksumMismatch
+    }
+}
+
+/// Calculates the Luhn check digit for the given digits.
+///
+/// Starting with the rightmost digit, every second digit is doubled,
+/// 9 is subtracted from results greater than 9 and all digits are summed up.
+/// The check digit is the amount needed to make the sum a multiple of 10.
+fn setup_code_check_digit(digits: &str) -> Option<char> {
+    let mut sum = 0;
+    for (i, c) in digits.chars().rev().enumerate() {
+        let mut digit = c.to_digit(10)?;
+        if i % 2 == 0 {
+            digit *= 2;
+            if digit > 9 {
+                digit -= 9;
+            }
+        }
+        sum += digit;
+    }
+    std::char::from_digit((10 - sum % 10) % 10, 10)
 }
 
 async fn maybe_add_bcc_self_device_msg(context: &Context) -> Result<()> {
@@ -291,9 +484,16 @@ pub async fn continue_key_transfer(
 
     if let Some(filename) = msg.get_file(context) {
         let file = open_file_std(context, filename)?;
+        if is_valid_setup_code(setup_code) == SetupCodeValidity::ChecksumMismatch {
+            // Setup codes created by older versions have no checksum, so try anyway.
+            warn!(
+                context,
+                "Setup code checksum mismatch, trying to decrypt anyway."
+            );
+        }
         let sc = normalize_setup_code(setup_code);
         let armored_key = decrypt_setup_file(&sc, file).await?;
-        set_self_key(context, &armored_key, true, true).await?;
+        set_self_key(context, &armored_key, true, true, false).await?;
         maybe_add_bcc_self_device_msg(context).await?;
 
         Ok(())
@@ -302,15 +502,92 @@ pub async fn continue_key_transfer(
     }
 }
 
+/// Continues the key transfer for an Autocrypt Setup Message received from another device
+/// with the code stored as [`Config::KeyTransferSetupCode`]
+/// if [`Config::AutoContinueKeyTransfer`] is enabled.
+///
+/// Only setup files encrypted with a `numeric9x4` setup code are accepted.
+/// Returns whether a key was imported.
+pub(crate) async fn auto_continue_key_transfer(context: &Context, msg_id: MsgId) -> Result<bool> {
+    if !context
+        .get_config_bool(Config::AutoContinueKeyTransfer)
+        .await?
+    {
+        return Ok(false);
+    }
+    let setup_code = match context
+        .sql
+        .get_raw_config(Config::KeyTransferSetupCode)
+        .await?
+    {
+        Some(setup_code) => setup_code,
+        None => {
+            info!(
+                context,
+                "Received Autocrypt Setup Message, but no setup code is stored."
+            );
+            return Ok(false);
+        }
+    };
+
+    let msg = Message::load_from_db(context, msg_id).await?;
+    let filename = msg
+        .get_file(context)
+        .context("Autocrypt Setup Message has no setup file")?;
+    let (typ, headers, _) = split_armored_data(&read_file(context, filename).await?)?;
+    ensure!(
+        typ == BlockType::Message
+            && headers.get(HEADER_PASSPHRASE_FORMAT).map(String::as_str) == Some("numeric9x4"),
+        "Setup file is not encrypted with a setup code."
+    );
+
+    continue_key_transfer(context, msg_id, &setup_code).await?;
+    context
+        .set_config(Config::KeyTransferSetupCode, None)
+        .await?;
+    info!(
+        context,
+        "Imported key from Autocrypt Setup Message {} automatically.", msg_id
+    );
+    Ok(true)
+}
+
 async fn set_self_key(
     context: &Context,
     armored: &str,
     set_default: bool,
     prefer_encrypt_required: bool,
+    allow_expired: bool,
 ) -> Result<()> {
     // try hard to only modify key-state
     let (private_key, header) = SignedSecretKey::from_asc(armored)?;
+    let expires_at = key::expiration_timestamp(&private_key);
+    if let Some(expires_at) = expires_at.filter(|expires_at| *expires_at <= time()) {
+        let fingerprint = DcKey::fingerprint(&private_key);
+        if !allow_expired {
+            return Err(KeyExpiredError {
+                fingerprint,
+                expires_at,
+            }
+            .into());
+        }
+        warn!(
+            context,
+            "Importing key {} expired on {}.",
+            fingerprint.hex(),
+            timestamp_to_str(expires_at)
+        );
+    }
     let public_key = private_key.split_public_key()?;
+    let report = pgp::key_strength_report(&public_key);
+    if report.strength == KeyStrength::Weak {
+        warn!(
+            context,
+            "Importing weak key {} ({}).",
+            DcKey::fingerprint(&public_key).hex(),
+            report
+        );
+    }
     let preferencrypt = header.get("Autocrypt-Prefer-Encrypt");
     match preferencrypt.map(|s| s.as_str()) {
         Some(headerval) => {
@@ -334,6 +611,11 @@ async fn set_self_key(
     };
 
     let self_addr = context.get_primary_self_addr().await?;
+    let old_fingerprint = if set_default {
+        load_default_fingerprint(context, &self_addr).await?
+    } else {
+        None
+    };
     let addr = EmailAddress::new(&self_addr)?;
     let keypair = pgp::KeyPair {
         addr,
@@ -352,9 +634,54 @@ async fn set_self_key(
     .await?;
 
     info!(context, "stored self key: {:?}", keypair.secret.key_id());
+
+    if set_default {
+        let new_fingerprint = DcKey::fingerprint(&keypair.public);
+        if old_fingerprint.as_ref() != Some(&new_fingerprint) {
+            let old_hex = old_fingerprint
+                .map(|fp| truncate_fingerprint(&fp))
+                .unwrap_or_else(|| "-".to_string());
+            let new_hex = truncate_fingerprint(&new_fingerprint);
+            let mut msg = Message::new(Viewtype::Text);
+            msg.text = Some(stock_str::self_key_changed(context, &old_hex, &new_hex).await);
+            let label = format!("self-key-changed-{}", new_fingerprint.hex());
+            chat::add_device_msg(context, Some(&label), Some(&mut msg)).await?;
+        }
+
+        if let Some(expires_at) = expires_at {
+            let now = time();
+            if expires_at > now && expires_at - now < KEY_EXPIRY_WARNING_SECS {
+                let mut msg = Message::new(Viewtype::Text);
+                msg.text = Some(
+                    stock_str::self_key_expires_soon(context, timestamp_to_str(expires_at)).await,
+                );
+                let label = format!("self-key-expires-soon-{}", new_fingerprint.hex());
+                chat::add_device_msg(context, Some(&label), Some(&mut msg)).await?;
+            }
+        }
+    }
     Ok(())
 }
 
+/// Returns the fingerprint of the current default key, if there is one.
+async fn load_default_fingerprint(context: &Context, addr: &str) -> Result<Option<Fingerprint>> {
+    let bytes: Option<Vec<u8>> = context
+        .sql
+        .query_get_value(
+            "SELECT public_key FROM keypairs WHERE addr=? AND is_default=1;",
+            paramsv![addr],
+        )
+        .await?;
+    bytes
+        .map(|bytes| SignedPublicKey::from_slice(&bytes).map(|key| DcKey::fingerprint(&key)))
+        .transpose()
+}
+
+/// Returns the first 16 hex digits of a fingerprint as shown in device messages.
+fn truncate_fingerprint(fingerprint: &Fingerprint) -> String {
+    fingerprint.hex().chars().take(16).collect()
+}
+
 async fn decrypt_setup_file<T: std::io::Read + std::io::Seek>(
     passphrase: &str,
     file: T,
@@ -365,7 +692,7 @@ async fn decrypt_setup_file<T: std::io::Read + std::io::Seek>(
     Ok(plain_text)
 }
 
-fn normalize_setup_code(s: &str) -> String {
+pub(crate) fn normalize_setup_code(s: &str) -> String {
     let mut out = String::new();
     for c in s.chars() {
         if ('0'..='9').contains(&c) {
@@ -399,29 +726,79 @@ async fn imex_inner(
 
     match what {
         ImexMode::ExportSelfKeys => export_self_keys(context, path).await,
-        ImexMode::ImportSelfKeys => import_self_keys(context, path).await,
+        ImexMode::ImportSelfKeys => import_self_keys(context, path, false).await,
+        ImexMode::ImportSelfKeysAllowExpired => import_self_keys(context, path, true).await,
 
         ImexMode::ExportBackup => {
             export_backup(context, path, passphrase.unwrap_or_default()).await
         }
         ImexMode::ImportBackup => {
-            import_backup(context, path, passphrase.unwrap_or_default()).await?;
-            context.sql.run_migrations(context).await
+            let imported_files =
+                import_backup(context, path, passphrase.unwrap_or_default()).await?;
+            context.sql.run_migrations(context).await?;
+            cleanup_after_import(context, &imported_files).await;
+            add_backup_imported_device_msg(context).await
         }
     }
 }
 
+/// Runs housekeeping after a backup import and removes files
+/// that were in the blobdir before the import and are not referenced by the imported database.
+async fn cleanup_after_import(context: &Context, imported_files: &HashSet<String>) {
+    sql::housekeeping(context).await.ok_or_log(context);
+    match sql::remove_stray_files(context, imported_files).await {
+        Ok(reclaimed_bytes) => info!(
+            context,
+            "Backup import cleanup reclaimed {}.",
+            humanize_bytes(reclaimed_bytes)
+        ),
+        Err(err) => warn!(context, "Backup import cleanup failed: {:#}", err),
+    }
+}
+
+/// Adds a device message summarizing the imported backup.
+async fn add_backup_imported_device_msg(context: &Context) -> Result<()> {
+    let backup_time = context
+        .sql
+        .get_raw_config_int64("backup_time")
+        .await?
+        .unwrap_or_default();
+    let chat_cnt = context
+        .sql
+        .count(
+            "SELECT COUNT(*) FROM chats WHERE id>? AND blocked=0;",
+            paramsv![DC_CHAT_ID_LAST_SPECIAL],
+        )
+        .await?;
+    let msg_cnt = context
+        .sql
+        .count(
+            "SELECT COUNT(*) FROM msgs WHERE id>? AND chat_id>? AND hidden=0;",
+            paramsv![DC_MSG_ID_LAST_SPECIAL, DC_CHAT_ID_LAST_SPECIAL],
+        )
+        .await?;
+    let mut msg = Message::new(Viewtype::Text);
+    msg.text = Some(
+        stock_str::backup_imported(context, timestamp_to_str(backup_time), chat_cnt, msg_cnt).await,
+    );
+    let label = format!("backup-imported-{}", backup_time);
+    chat::add_device_msg(context, Some(&label), Some(&mut msg)).await?;
+    Ok(())
+}
+
 /// Imports backup into the currently open database.
 ///
 /// The contents of the currently open database will be lost.
 ///
 /// `passphrase` is the passphrase used to open backup database. If backup is unencrypted, pass
 /// empty string here.
+///
+/// Returns the names of the blobdir files written by the import.
 async fn import_backup(
     context: &Context,
     backup_to_import: &Path,
     passphrase: String,
-) -> Result<()> {
+) -> Result<HashSet<String>> {
     ensure!(
         !context.is_configured().await?,
         "Cannot import backups to accounts in use."
@@ -430,23 +807,32 @@ async fn import_backup(
         context.scheduler.read().await.is_none(),
         "cannot import backup, IO is running"
     );
+    ensure_not_partial_backup(backup_to_import)?;
 
     let backup_file = File::open(backup_to_import).await?;
     let file_size = backup_file.metadata().await?.len();
     info!(
         context,
-        "Import \"{}\" ({} bytes) to \"{}\".",
+        "Import \"{}\" ({}) to \"{}\".",
         backup_to_import.display(),
-        file_size,
+        humanize_bytes(file_size),
         context.get_dbfile().display()
     );
 
+    check_tar_terminator(backup_to_import)
+        .await
+        .context("backup file is truncated or corrupt")?;
+    validate_backup(backup_to_import)
+        .await
+        .context("backup file is truncated or corrupt")?;
+
     context.sql.config_cache.write().await.clear();
 
     let mut archive = Archive::new(backup_file);
 
     let mut entries = archive.entries()?;
     let mut last_progress = 0;
+    let mut imported_files = HashSet::new();
     while let Some(file) = entries.next().await {
         let f = &mut file?;
 
@@ -473,10 +859,18 @@ async fn import_backup(
         } else {
             // async_tar will unpack to blobdir/BLOBS_BACKUP_NAME, so we move the file afterwards.
             f.unpack_in(context.get_blobdir()).await?;
-            let from_path = context.get_blobdir().join(f.path()?);
+            let path = f.path()?.into_owned();
+            let from_path = context.get_blobdir().join(&path);
             if from_path.is_file() {
-                if let Some(name) = from_path.file_name() {
-                    fs::rename(&from_path, context.get_blobdir().join(name)).await?;
+                // Blobs may be in a subdirectory if the per-chat blob layout is used.
+                let rel_path = path.strip_prefix(BLOBS_BACKUP_NAME).unwrap_or(&path);
+                if let Some(name) = blob::name_from_rel_path(rel_path) {
+                    let to_path = context.get_blobdir().join(&name);
+                    if let Some(dir) = to_path.parent() {
+                        fs::create_dir_all(dir).await?;
+                    }
+                    fs::rename(&from_path, to_path).await?;
+                    imported_files.insert(name);
                 } else {
                     warn!(context, "No file name");
                 }
@@ -486,6 +880,59 @@ async fn import_backup(
 
     delete_and_reset_all_device_msgs(context).await?;
 
+    Ok(imported_files)
+}
+
+/// Checks that the archive ends with the end-of-archive marker of two zero-filled blocks.
+///
+/// Only the end of the file is read, so truncated backups are detected quickly.
+async fn check_tar_terminator(backup_to_import: &Path) -> Result<()> {
+    const BLOCK_SIZE: u64 = 512;
+    let mut file = File::open(backup_to_import).await?;
+    let len = file.metadata().await?.len();
+    ensure!(
+        len >= 2 * BLOCK_SIZE && len % BLOCK_SIZE == 0,
+        "size of {} bytes is not a multiple of the tar block size",
+        len
+    );
+    file.seek(std::io::SeekFrom::Start(len - 2 * BLOCK_SIZE))
+        .await?;
+    let mut terminator = [0u8; 2 * BLOCK_SIZE as usize];
+    file.read_exact(&mut terminator).await?;
+    ensure!(
+        terminator.iter().all(|byte| *byte == 0),
+        "end-of-archive marker is missing"
+    );
+    Ok(())
+}
+
+/// Reads through the whole backup archive without unpacking anything.
+///
+/// Fails if any entry is shorter than its header claims or if the archive does not contain
+/// the database, so that a broken backup is rejected before the account is modified.
+async fn validate_backup(backup_to_import: &Path) -> Result<()> {
+    let mut archive = Archive::new(File::open(backup_to_import).await?);
+    let mut entries = archive.entries()?;
+    let mut has_database = false;
+    while let Some(file) = entries.next().await {
+        let mut f = file?;
+        let path = f.path()?.into_owned();
+        let expected_size = f.header().size()?;
+        let size = tokio::io::copy(&mut f, &mut tokio::io::sink())
+            .await
+            .with_context(|| format!("cannot read {}", path.display()))?;
+        ensure!(
+            size == expected_size,
+            "{} has {} bytes, expected {}",
+            path.display(),
+            size,
+            expected_size
+        );
+        if path.file_name() == Some(OsStr::new(DBFILE_BACKUP_NAME)) {
+            has_database = true;
+        }
+    }
+    ensure!(has_database, "no database found in backup");
     Ok(())
 }
 
@@ -522,6 +969,10 @@ fn get_next_backup_path(folder: &Path, backup_time: i64) -> Result<(PathBuf, Pat
 }
 
 async fn export_backup(context: &Context, dir: &Path, passphrase: String) -> Result<()> {
+    remove_partial_backups_older_than(context, dir, PARTIAL_BACKUP_MAX_AGE)
+        .await
+        .ok_or_log(context);
+
     // get a fine backup file name (the name includes the date so that multiple backup instances are possible)
     let now = time();
     let (temp_db_path, temp_path, dest_path) = get_next_backup_path(dir, now)?;
@@ -573,6 +1024,42 @@ async fn export_backup(context: &Context, dir: &Path, passphrase: String) -> Res
 
     res
 }
+
+/// Removes partial backups left over by interrupted exports from `dir`
+/// if they were not modified for `min_age`.
+///
+/// Returns the number of removed files.
+async fn remove_partial_backups_older_than(
+    context: &Context,
+    dir: &Path,
+    min_age: Duration,
+) -> Result<usize> {
+    let keep_files_newer_than = SystemTime::now()
+        .checked_sub(min_age)
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+    let mut removed = 0;
+    let mut dir_iter = fs::read_dir(dir).await?;
+    while let Some(dirent) = dir_iter.next_entry().await? {
+        let name = dirent.file_name();
+        let name = name.to_string_lossy();
+        if !name.starts_with("delta-chat") || !name.ends_with(PARTIAL_BACKUP_SUFFIX) {
+            continue;
+        }
+        let modified = dirent.metadata().await?.modified()?;
+        if modified > keep_files_newer_than {
+            continue;
+        }
+        info!(
+            context,
+            "Removing stale partial backup {}.",
+            dirent.path().display()
+        );
+        fs::remove_file(dirent.path()).await?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
 struct DeleteOnDrop(PathBuf);
 impl Drop for DeleteOnDrop {
     fn drop(&mut self) {
@@ -596,21 +1083,16 @@ async fn export_backup_inner(
         .append_path_with_name(temp_db_path, DBFILE_BACKUP_NAME)
         .await?;
 
-    let read_dir: Vec<_> =
-        tokio_stream::wrappers::ReadDirStream::new(fs::read_dir(context.get_blobdir()).await?)
-            .try_collect()
-            .await?;
+    let read_dir = blob::read_blobdir(context).await?;
     let count = read_dir.len();
     let mut written_files = 0;
 
     let mut last_progress = 0;
-    for entry in read_dir.into_iter() {
-        let name = entry.file_name();
+    for (name, entry) in read_dir.into_iter() {
         if !entry.file_type().await?.is_file() {
             warn!(
                 context,
-                "Export: Found dir entry {} that is not a file, ignoring",
-                name.to_string_lossy()
+                "Export: Found dir entry {} that is not a file, ignoring", name
             );
             continue;
         }
@@ -634,17 +1116,18 @@ async fn export_backup_inner(
 /*******************************************************************************
  * Classic key import
  ******************************************************************************/
-async fn import_self_keys(context: &Context, dir: &Path) -> Result<()> {
+async fn import_self_keys(context: &Context, dir: &Path, allow_expired: bool) -> Result<()> {
     /* hint: even if we switch to import Autocrypt Setup Files, we should leave the possibility to import
     plain ASC keys, at least keys without a password, if we do not want to implement a password entry function.
     Importing ASC keys is useful to use keys in Delta Chat used by any other non-Autocrypt-PGP implementation.
 
     Maybe we should make the "default" key handlong also a little bit smarter
     (currently, the last imported key is the standard key unless it contains the string "legacy" in its name) */
-    let mut set_default: bool;
     let mut imported_cnt = 0;
+    let mut expired_err = None;
 
     let dir_name = dir.to_string_lossy();
+    let mut key_files = Vec::new();
     let mut dir_handle = tokio::fs::read_dir(&dir).await?;
     while let Ok(Some(entry)) = dir_handle.next_entry().await {
         let entry_fn = entry.file_name();
@@ -655,17 +1138,30 @@ async fn import_self_keys(context: &Context, dir: &Path) -> Result<()> {
                 if suffix != "asc" {
                     continue;
                 }
-                set_default = if name_f.contains("legacy") {
+                let set_default = if name_f.contains("legacy") {
                     info!(context, "found legacy key '{}'", path_plus_name.display());
                     false
                 } else {
                     true
-                }
+                };
+                key_files.push((path_plus_name, set_default));
             }
             None => {
                 continue;
             }
         }
+    }
+
+    let count = key_files.len();
+    let mut last_progress = 0;
+    for (processed, (path_plus_name, set_default)) in key_files.into_iter().enumerate() {
+        let progress = 1000 * (processed + 1) / count;
+        if progress != last_progress && progress > 10 && progress < 1000 {
+            // We already emitted ImexProgress(10) in imex_inner()
+            context.emit_event(EventType::ImexProgress(progress));
+            last_progress = progress;
+        }
+
         info!(
             context,
             "considering key file: {}",
@@ -675,8 +1171,13 @@ async fn import_self_keys(context: &Context, dir: &Path) -> Result<()> {
         match read_file(context, &path_plus_name).await {
             Ok(buf) => {
                 let armored = std::string::String::from_utf8_lossy(&buf);
-                if let Err(err) = set_self_key(context, &armored, set_default, false).await {
+                if let Err(err) =
+                    set_self_key(context, &armored, set_default, false, allow_expired).await
+                {
                     error!(context, "set_self_key: {}", err);
+                    if err.is::<KeyExpiredError>() {
+                        expired_err = Some(err);
+                    }
                     continue;
                 }
             }
@@ -684,6 +1185,9 @@ async fn import_self_keys(context: &Context, dir: &Path) -> Result<()> {
         }
         imported_cnt += 1;
     }
+    if let Some(err) = expired_err.filter(|_| imported_cnt == 0) {
+        return Err(err);
+    }
     ensure!(
         imported_cnt > 0,
         "No private keys found in \"{}\".",
@@ -730,6 +1234,19 @@ async fn export_self_keys(context: &Context, dir: &Path) -> Result<()> {
             export_errors += 1;
         }
         if let Ok(key) = private_key {
+            match key::expiration_timestamp(&key) {
+                Some(expires_at) => info!(
+                    context,
+                    "Exporting key {} expiring on {}.",
+                    DcKey::fingerprint(&key).hex(),
+                    timestamp_to_str(expires_at)
+                ),
+                None => info!(
+                    context,
+                    "Exporting key {} without expiration.",
+                    DcKey::fingerprint(&key).hex()
+                ),
+            }
             if export_key_to_asc_file(context, dir, id, &key)
                 .await
                 .is_err()
@@ -742,6 +1259,11 @@ async fn export_self_keys(context: &Context, dir: &Path) -> Result<()> {
     }
 
     ensure!(export_errors == 0, "errors while exporting keys");
+
+    let mut msg = Message::new(Viewtype::Text);
+    msg.text = Some(stock_str::self_keys_exported(context, dir.to_string_lossy()).await);
+    let label = format!("self-keys-exported-{}", time());
+    chat::add_device_msg(context, Some(&label), Some(&mut msg)).await?;
     Ok(())
 }
 
@@ -791,12 +1313,10 @@ async fn export_key_to_asc_file<T>(
 mod tests {
     use super::*;
 
-    use crate::pgp::{split_armored_data, HEADER_AUTOCRYPT, HEADER_SETUPCODE};
+    use crate::pgp::{HEADER_AUTOCRYPT, HEADER_SETUPCODE};
     use crate::stock_str::StockMessage;
     use crate::test_utils::{alice_keypair, TestContext};
 
-    use ::pgp::armor::BlockType;
-
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_render_setup_file() {
         let t = TestContext::new_alice().await;
@@ -838,6 +1358,54 @@ async fn test_create_setup_code() {
         assert_eq!(setupcode.chars().nth(29).unwrap(), '-');
         assert_eq!(setupcode.chars().nth(34).unwrap(), '-');
         assert_eq!(setupcode.chars().nth(39).unwrap(), '-');
+        assert_eq!(is_valid_setup_code(&setupcode), SetupCodeValidity::Valid);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_is_valid_setup_code() {
+        let t = TestContext::new().await;
+        for _ in 0..100 {
+            let setupcode = create_setup_code(&t);
+            assert_eq!(is_valid_setup_code(&setupcode), SetupCodeValidity::Valid);
+
+            // Any single-digit typo is detected.
+            let mut typo: Vec<char> = setupcode.chars().collect();
+            typo[7] = if typo[7] == '0' { '1' } else { '0' };
+            let typo: String = typo.into_iter().collect();
+            assert_eq!(
+                is_valid_setup_code(&typo),
+                SetupCodeValidity::ChecksumMismatch
+            );
+        }
+
+        assert_eq!(
+            is_valid_setup_code("1234-2234-3234-4234-5234-6234-7234-8234-9231"),
+            SetupCodeValidity::Valid
+        );
+        assert_eq!(
+            is_valid_setup_code("1234 2234 3234 4234 5234 6234 7234 8234 9231"),
+            SetupCodeValidity::Valid
+        );
+
+        // Legacy codes without checksum.
+        assert_eq!(
+            is_valid_setup_code("1234-2234-3234-4234-5234-6234-7234-8234-9234"),
+            SetupCodeValidity::ChecksumMismatch
+        );
+        assert_eq!(
+            is_valid_setup_code(S_EM_SETUPCODE),
+            SetupCodeValidity::ChecksumMismatch
+        );
+
+        assert_eq!(is_valid_setup_code(""), SetupCodeValidity::BadFormat);
+        assert_eq!(
+            is_valid_setup_code("1234-2234-3234-4234-5234-6234-7234-8234"),
+            SetupCodeValidity::BadFormat
+        );
+        assert_eq!(
+            is_valid_setup_code("1234-2234-3234-4234-5234-6234-7234-8234-923a"),
+            SetupCodeValidity::BadFormat
+        );
     }
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -855,6 +1423,22 @@ async fn test_export_public_key_to_asc_file() {
         assert_eq!(bytes, key.to_asc(None).into_bytes());
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_export_stats_json() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let chat = alice
+            .create_chat_with_contact("Bob", "bob@example.net")
+            .await;
+        alice.send_text(chat.id, "Hi").await;
+
+        let path = alice.get_blobdir().join("stats.json");
+        export_stats_json(&alice, &path).await?;
+        let json: serde_json::Value = serde_json::from_slice(&tokio::fs::read(&path).await?)?;
+        assert_eq!(json["msgs_by_month"][0]["sent"], 1);
+        assert_eq!(json["contacts"], 1);
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_export_private_key_to_asc_file() {
         let context = TestContext::new().await;
@@ -884,12 +1468,51 @@ async fn test_export_and_import_key() {
         }
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_import_expired_key() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let old_fingerprint = load_default_fingerprint(&alice, "alice@example.org").await?;
+        let keys_dir = tempfile::tempdir()?;
+        fs::write(
+            keys_dir.path().join("private-key-default.asc"),
+            include_str!("../test-data/key/expired-secret.asc"),
+        )
+        .await?;
+
+        let err = imex(&alice, ImexMode::ImportSelfKeys, keys_dir.path(), None)
+            .await
+            .unwrap_err();
+        let err = err.downcast_ref::<KeyExpiredError>().unwrap();
+        assert_eq!(err.expires_at, 1609502400);
+        assert_eq!(
+            load_default_fingerprint(&alice, "alice@example.org").await?,
+            old_fingerprint
+        );
+
+        imex(
+            &alice,
+            ImexMode::ImportSelfKeysAllowExpired,
+            keys_dir.path(),
+            None,
+        )
+        .await?;
+        assert_eq!(
+            load_default_fingerprint(&alice, "alice@example.org").await?,
+            Some(err.fingerprint.clone())
+        );
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_export_and_import_backup() -> Result<()> {
         let backup_dir = tempfile::tempdir().unwrap();
 
         let context1 = TestContext::new_alice().await;
         assert!(context1.is_configured().await?);
+        let setup_code = create_setup_code(&context1);
+        context1
+            .set_config(Config::KeyTransferSetupCode, Some(&setup_code))
+            .await?;
 
         let context2 = TestContext::new().await;
         assert!(!context2.is_configured().await?);
@@ -934,6 +1557,309 @@ async fn test_export_and_import_backup() -> Result<()> {
             context2.get_config(Config::Addr).await?,
             Some("alice@example.org".to_string())
         );
+        assert_eq!(
+            context2
+                .sql
+                .get_raw_config(Config::KeyTransferSetupCode)
+                .await?,
+            None
+        );
+        assert_eq!(
+            context1
+                .sql
+                .get_raw_config(Config::KeyTransferSetupCode)
+                .await?,
+            Some(setup_code)
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_import_truncated_backup() -> Result<()> {
+        let backup_dir = tempfile::tempdir()?;
+        let alice = TestContext::new_alice().await;
+        imex(&alice, ImexMode::ExportBackup, backup_dir.path(), None).await?;
+        let backup = has_backup(&alice, backup_dir.path()).await?;
+
+        let data = fs::read(&backup).await?;
+        let truncated = backup_dir.path().join("delta-chat-truncated.tar");
+        fs::write(&truncated, &data[..data.len() / 2]).await?;
+
+        let t = TestContext::new().await;
+        let err = imex(&t, ImexMode::ImportBackup, &truncated, None)
+            .await
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("backup file is truncated or corrupt"));
+        assert!(!t.is_configured().await?);
+        assert!(t.get_config(Config::Addr).await?.is_none());
+
+        // The intact backup can still be imported afterwards.
+        imex(&t, ImexMode::ImportBackup, backup.as_ref(), None).await?;
+        assert!(t.is_configured().await?);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_import_backup_without_terminator() -> Result<()> {
+        let backup_dir = tempfile::tempdir()?;
+        let alice = TestContext::new_alice().await;
+        imex(&alice, ImexMode::ExportBackup, backup_dir.path(), None).await?;
+        let backup = has_backup(&alice, backup_dir.path()).await?;
+        assert!(check_tar_terminator(backup.as_ref()).await.is_ok());
+
+        // Cut off the end-of-archive marker, all entries are still complete.
+        let data = fs::read(&backup).await?;
+        let truncated = backup_dir.path().join("delta-chat-truncated.tar");
+        fs::write(&truncated, &data[..data.len() - 1024]).await?;
+        assert!(check_tar_terminator(&truncated).await.is_err());
+
+        let t = TestContext::new().await;
+        let err = imex(&t, ImexMode::ImportBackup, &truncated, None)
+            .await
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("backup file is truncated or corrupt"));
+        assert!(!t.is_configured().await?);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_import_partial_backup() -> Result<()> {
+        let backup_dir = tempfile::tempdir()?;
+        let alice = TestContext::new_alice().await;
+        imex(&alice, ImexMode::ExportBackup, backup_dir.path(), None).await?;
+        let backup = has_backup(&alice, backup_dir.path()).await?;
+
+        let partial_dir = tempfile::tempdir()?;
+        let partial = partial_dir
+            .path()
+            .join("delta-chat-backup-2022-07-24-00.tar.part");
+        fs::copy(&backup, &partial).await?;
+
+        let t = TestContext::new().await;
+        let err = has_backup(&t, partial_dir.path()).await.unwrap_err();
+        assert!(format!("{:#}", err).contains("incomplete backup"));
+        let err = imex(&t, ImexMode::ImportBackup, &partial, None)
+            .await
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("incomplete backup"));
+        assert!(!t.is_configured().await?);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_remove_stale_partial_backups() -> Result<()> {
+        let backup_dir = tempfile::tempdir()?;
+        let t = TestContext::new_alice().await;
+        let partial = backup_dir
+            .path()
+            .join("delta-chat-backup-2022-07-24-00.tar.part");
+        fs::write(&partial, b"interrupted").await?;
+        let other = backup_dir.path().join("other.tar.part");
+        fs::write(&other, b"not a backup").await?;
+
+        // Recent partial backups may still be written by another export.
+        assert_eq!(
+            remove_partial_backups_older_than(&t, backup_dir.path(), PARTIAL_BACKUP_MAX_AGE)
+                .await?,
+            0
+        );
+        assert!(partial.exists());
+
+        assert_eq!(
+            remove_partial_backups_older_than(&t, backup_dir.path(), Duration::ZERO).await?,
+            1
+        );
+        assert!(!partial.exists());
+        assert!(other.exists());
+
+        // Exporting still works and leaves only the complete backup.
+        imex(&t, ImexMode::ExportBackup, backup_dir.path(), None).await?;
+        assert!(has_backup(&t, backup_dir.path()).await?.ends_with(".tar"));
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_list_backups() -> Result<()> {
+        let backup_dir = tempfile::tempdir()?;
+        let t = TestContext::new_alice().await;
+        assert!(list_backups(&t, backup_dir.path()).await?.is_empty());
+
+        imex(&t, ImexMode::ExportBackup, backup_dir.path(), None).await?;
+        imex(
+            &t,
+            ImexMode::ExportBackup,
+            backup_dir.path(),
+            Some("foobar".to_string()),
+        )
+        .await?;
+
+        let backups = list_backups(&t, backup_dir.path()).await?;
+        assert_eq!(backups.len(), 2);
+        // The encrypted backup was created last and is listed first.
+        assert!(backups[0].encrypted);
+        assert!(!backups[1].encrypted);
+        assert_eq!(
+            has_backup(&t, backup_dir.path()).await?,
+            backups[0].path.to_string_lossy()
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_import_backup_removes_stray_files() -> Result<()> {
+        let backup_dir = tempfile::tempdir()?;
+
+        let context1 = TestContext::new_alice().await;
+        let avatar_src = context1.get_blobdir().join("avatar.png");
+        fs::write(&avatar_src, crate::test_utils::AVATAR_900x900_BYTES).await?;
+        context1
+            .set_config(Config::Selfavatar, Some(avatar_src.to_str().unwrap()))
+            .await?;
+        imex(&context1, ImexMode::ExportBackup, backup_dir.path(), None).await?;
+
+        let context2 = TestContext::new().await;
+        let stray_file = context2.get_blobdir().join("stray.txt");
+        fs::write(&stray_file, b"left over").await?;
+
+        let backup = has_backup(&context2, backup_dir.path()).await?;
+        imex(&context2, ImexMode::ImportBackup, backup.as_ref(), None).await?;
+
+        assert!(!stray_file.exists());
+        let avatar = context2.get_config(Config::Selfavatar).await?.unwrap();
+        assert!(Path::new(&avatar).exists());
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_export_and_import_backup_per_chat_layout() -> Result<()> {
+        let backup_dir = tempfile::tempdir()?;
+
+        let context1 = TestContext::new_alice().await;
+        context1.set_config(Config::BlobLayout, Some("1")).await?;
+        let bob = TestContext::new_bob().await;
+        let chat = bob.create_chat(&context1).await;
+        let file = bob.get_blobdir().join("hello.txt");
+        fs::write(&file, b"hello").await?;
+        let mut msg = Message::new(Viewtype::File);
+        msg.set_file(file.to_str().unwrap(), None);
+        let sent = bob.send_msg(chat.id, &mut msg).await;
+        let msg = context1.recv_msg(&sent).await;
+        let subdir = format!("chat-{}", msg.chat_id.to_u32());
+        imex(&context1, ImexMode::ExportBackup, backup_dir.path(), None).await?;
+
+        let context2 = TestContext::new().await;
+        let backup = has_backup(&context2, backup_dir.path()).await?;
+        imex(&context2, ImexMode::ImportBackup, backup.as_ref(), None).await?;
+
+        let msg = Message::load_from_db(&context2, msg.id).await?;
+        let path = msg.get_file(&context2).unwrap();
+        assert_eq!(path.parent().unwrap(), context2.get_blobdir().join(subdir));
+        assert_eq!(fs::read(&path).await?, b"hello");
+
+        Ok(())
+    }
+
+    /// Returns the number of messages in the device chat starting with `prefix`.
+    async fn count_device_msgs(context: &Context, prefix: &str) -> Result<usize> {
+        let device_chat_id = ChatId::get_for_contact(context, ContactId::DEVICE).await?;
+        context
+            .sql
+            .count(
+                "SELECT COUNT(*) FROM msgs WHERE chat_id=? AND txt LIKE ?;",
+                paramsv![device_chat_id, format!("{}%", prefix)],
+            )
+            .await
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_import_backup_device_msg() -> Result<()> {
+        let backup_dir = tempfile::tempdir()?;
+
+        let context1 = TestContext::new_alice().await;
+        imex(&context1, ImexMode::ExportBackup, backup_dir.path(), None).await?;
+
+        let context2 = TestContext::new().await;
+        let backup = has_backup(&context2, backup_dir.path()).await?;
+        imex(&context2, ImexMode::ImportBackup, backup.as_ref(), None).await?;
+        assert_eq!(count_device_msgs(&context2, "Backup from ").await?, 1);
+
+        // The label prevents the same backup from being reported twice.
+        add_backup_imported_device_msg(&context2).await?;
+        assert_eq!(count_device_msgs(&context2, "Backup from ").await?, 1);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_self_key_change_device_msgs() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let export_dir = tempfile::tempdir()?;
+        imex(&alice, ImexMode::ExportSelfKeys, export_dir.path(), None).await?;
+        assert_eq!(
+            count_device_msgs(&alice, "Your encryption keys were exported").await?,
+            1
+        );
+
+        let import_dir = tempfile::tempdir()?;
+        let bob_key = crate::test_utils::bob_keypair().secret;
+        fs::write(
+            import_dir.path().join("private-key-bob.asc"),
+            bob_key.to_asc(None),
+        )
+        .await?;
+        imex(&alice, ImexMode::ImportSelfKeys, import_dir.path(), None).await?;
+
+        let expected = stock_str::self_key_changed(
+            &alice,
+            truncate_fingerprint(&DcKey::fingerprint(&alice_keypair().public)),
+            truncate_fingerprint(&DcKey::fingerprint(&bob_key.split_public_key()?)),
+        )
+        .await;
+        assert_eq!(count_device_msgs(&alice, &expected).await?, 1);
+
+        // Importing the same key again does not change the key.
+        imex(&alice, ImexMode::ImportSelfKeys, import_dir.path(), None).await?;
+        assert_eq!(
+            count_device_msgs(&alice, "Your encryption key changed").await?,
+            1
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_import_self_keys_progress() -> Result<()> {
+        let keys_dir = tempfile::tempdir()?;
+        let armored = alice_keypair().secret.to_asc(None);
+        for i in 0..4 {
+            fs::write(keys_dir.path().join(format!("key-{}.asc", i)), &armored).await?;
+        }
+        fs::write(keys_dir.path().join("readme.txt"), b"not a key").await?;
+
+        let context = TestContext::new_alice().await;
+        imex(&context, ImexMode::ImportSelfKeys, keys_dir.path(), None).await?;
+
+        let mut progress_events = Vec::new();
+        loop {
+            let event = context
+                .evtracker
+                .get_matching(|evt| matches!(evt, EventType::ImexProgress(_)))
+                .await;
+            match event {
+                EventType::ImexProgress(1000) => break,
+                EventType::ImexProgress(progress) if progress > 10 => {
+                    progress_events.push(progress)
+                }
+                _ => {}
+            }
+        }
+        assert_eq!(progress_events, vec![250, 500, 750]);
 
         Ok(())
     }
@@ -1020,4 +1946,71 @@ async fn test_key_transfer() -> Result<()> {
 
         Ok(())
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_auto_continue_key_transfer() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+
+        let alice_clone = alice.clone();
+        let key_transfer_task = tokio::task::spawn(async move {
+            let ctx = alice_clone;
+            initiate_key_transfer(&ctx).await
+        });
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        let sent = alice.pop_sent_msg().await;
+        let setup_code = key_transfer_task.await??;
+
+        // A wrong setup code does not import the key and is kept.
+        let alice2 = TestContext::new().await;
+        alice2.configure_addr("alice@example.org").await;
+        alice2
+            .set_config_bool(Config::AutoContinueKeyTransfer, true)
+            .await?;
+        let wrong_code = create_setup_code(&alice2);
+        alice2
+            .set_config(Config::KeyTransferSetupCode, Some(&wrong_code))
+            .await?;
+        alice2.recv_msg(&sent).await;
+        assert!(alice2.get_last_msg().await.is_setupmessage());
+        assert_ne!(
+            SignedSecretKey::load_self(&alice2).await?,
+            alice_keypair().secret
+        );
+        assert_eq!(alice2.get_config(Config::KeyTransferSetupCode).await?, None);
+        assert_eq!(
+            alice2
+                .sql
+                .get_raw_config(Config::KeyTransferSetupCode)
+                .await?,
+            Some(wrong_code)
+        );
+
+        // With the right setup code, the key is imported on reception.
+        let alice3 = TestContext::new().await;
+        alice3.configure_addr("alice@example.org").await;
+        alice3
+            .set_config_bool(Config::AutoContinueKeyTransfer, true)
+            .await?;
+        assert!(alice3
+            .set_config(Config::KeyTransferSetupCode, Some("123"))
+            .await
+            .is_err());
+        alice3
+            .set_config(Config::KeyTransferSetupCode, Some(&setup_code))
+            .await?;
+        alice3.recv_msg(&sent).await;
+        assert_eq!(
+            SignedSecretKey::load_self(&alice3).await?,
+            alice_keypair().secret
+        );
+        assert_eq!(
+            alice3
+                .sql
+                .get_raw_config(Config::KeyTransferSetupCode)
+                .await?,
+            None
+        );
+
+        Ok(())
+    }
 }
@@ -3,19 +3,26 @@
 use std::collections::BTreeMap;
 use std::fmt;
 use std::io::Cursor;
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
 
 use anyhow::{ensure, Context as _, Result};
+use chrono::{DateTime, Utc};
 use futures::Future;
 use num_traits::FromPrimitive;
 use pgp::composed::Deserializable;
+use pgp::packet::Signature;
 use pgp::ser::Serialize;
 use pgp::types::{KeyTrait, SecretKeyTrait};
 use tokio::runtime::Handle;
 
 use crate::config::Config;
 use crate::constants::KeyGenType;
+use crate::contact::{Contact, ContactId};
 use crate::context::Context;
+use crate::events::EventType;
+use crate::peerstate::{Peerstate, PeerstateVerifiedStatus};
+use crate::pgp::KeyStrengthReport;
 use crate::tools::{time, EmailAddress};
 
 // Re-export key types
@@ -207,6 +214,79 @@ fn split_public_key(&self) -> Result<SignedPublicKey> {
     }
 }
 
+/// Returns the timestamp after which the key can no longer be used to encrypt to it,
+/// or `None` if it never expires.
+///
+/// This is the earlier of the expiration of the primary key
+/// and the latest expiration of the encryption subkeys.
+pub(crate) fn expiration_timestamp(key: &SignedSecretKey) -> Option<i64> {
+    combined_expiration_timestamp(
+        key.primary_key.created_at(),
+        key.details
+            .direct_signatures
+            .iter()
+            .chain(key.details.users.iter().flat_map(|user| &user.signatures)),
+        key.secret_subkeys
+            .iter()
+            .filter(|subkey| subkey.is_encryption_key())
+            .map(|subkey| expires_at(subkey.key.created_at(), subkey.signatures.iter())),
+    )
+}
+
+/// Same as [`expiration_timestamp`] for public keys.
+pub(crate) fn public_key_expiration_timestamp(key: &SignedPublicKey) -> Option<i64> {
+    combined_expiration_timestamp(
+        key.primary_key.created_at(),
+        key.details
+            .direct_signatures
+            .iter()
+            .chain(key.details.users.iter().flat_map(|user| &user.signatures)),
+        key.public_subkeys
+            .iter()
+            .filter(|subkey| subkey.is_encryption_key())
+            .map(|subkey| expires_at(subkey.key.created_at(), subkey.signatures.iter())),
+    )
+}
+
+/// Returns the expiration timestamp stored in the self-signatures of a key or subkey.
+fn expires_at<'a>(
+    created_at: &DateTime<Utc>,
+    signatures: impl Iterator<Item = &'a Signature>,
+) -> Option<i64> {
+    // Expiration times are stored in self-signatures as seconds after key creation.
+    signatures
+        .filter_map(|sig| sig.key_expiration_time())
+        .map(|duration| duration.timestamp())
+        .filter(|secs| *secs > 0)
+        .max()
+        .map(|secs| created_at.timestamp().saturating_add(secs))
+}
+
+/// Combines the expiration of the primary key
+/// with the expirations of the encryption subkeys, see [`expiration_timestamp`].
+fn combined_expiration_timestamp<'a>(
+    primary_created_at: &DateTime<Utc>,
+    primary_signatures: impl Iterator<Item = &'a Signature>,
+    subkeys_expire_at: impl Iterator<Item = Option<i64>>,
+) -> Option<i64> {
+    let primary_expires_at = expires_at(primary_created_at, primary_signatures);
+
+    let mut subkeys = subkeys_expire_at.peekable();
+    let subkeys_expire_at = if subkeys.peek().is_none() {
+        None
+    } else {
+        // Any subkey without expiration keeps the key usable.
+        subkeys.try_fold(i64::MIN, |latest, expires_at| {
+            expires_at.map(|expires_at| latest.max(expires_at))
+        })
+    };
+
+    match (primary_expires_at, subkeys_expire_at) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
 async fn generate_keypair(context: &Context) -> Result<KeyPair> {
     let addr = context.get_primary_self_addr().await?;
     let addr = EmailAddress::new(&addr)?;
@@ -216,25 +296,32 @@ async fn generate_keypair(context: &Context) -> Result<KeyPair> {
     match load_keypair(context, &addr).await? {
         Some(key_pair) => Ok(key_pair),
         None => {
-            let start = std::time::SystemTime::now();
-            let keytype = KeyGenType::from_i32(context.get_config_int(Config::KeyGenType).await?)
-                .unwrap_or_default();
-            info!(context, "Generating keypair with type {}", keytype);
-            let keypair = Handle::current()
-                .spawn_blocking(move || crate::pgp::create_keypair(addr, keytype))
-                .await??;
-
+            let keypair = create_keypair(context, addr).await?;
             store_self_keypair(context, &keypair, KeyPairUse::Default).await?;
-            info!(
-                context,
-                "Keypair generated in {:.3}s.",
-                start.elapsed().unwrap_or_default().as_secs()
-            );
             Ok(keypair)
         }
     }
 }
 
+/// Generates a new keypair of the configured [Config::KeyGenType] for `addr`.
+///
+/// The keypair is not stored, the caller should hold `context.generating_key_mutex`.
+pub(crate) async fn create_keypair(context: &Context, addr: EmailAddress) -> Result<KeyPair> {
+    let start = std::time::SystemTime::now();
+    let keytype =
+        KeyGenType::from_i32(context.get_config_int(Config::KeyGenType).await?).unwrap_or_default();
+    info!(context, "Generating keypair with type {}", keytype);
+    let keypair = Handle::current()
+        .spawn_blocking(move || crate::pgp::create_keypair(addr, keytype))
+        .await??;
+    info!(
+        context,
+        "Keypair generated in {:.3}s.",
+        start.elapsed().unwrap_or_default().as_secs()
+    );
+    Ok(keypair)
+}
+
 pub(crate) async fn load_keypair(
     context: &Context,
     addr: &EmailAddress,
@@ -268,6 +355,48 @@ pub(crate) async fn load_keypair(
     })
 }
 
+/// Loads the secret keys of the user which are only kept to decrypt existing messages,
+/// newest first.
+///
+/// Keys which can not be parsed are skipped with a warning.
+pub(crate) async fn load_self_readonly_secret_keys(
+    context: &Context,
+) -> Result<Vec<SignedSecretKey>> {
+    let rows = context
+        .sql
+        .query_map(
+            "SELECT private_key FROM keypairs WHERE is_default=0 ORDER BY id DESC",
+            paramsv![],
+            |row| row.get::<_, Vec<u8>>(0),
+            |rows| {
+                rows.collect::<std::result::Result<Vec<_>, _>>()
+                    .map_err(Into::into)
+            },
+        )
+        .await?;
+    let keys = rows
+        .iter()
+        .filter_map(|bytes| match SignedSecretKey::from_slice(bytes) {
+            Ok(key) => Some(key),
+            Err(err) => {
+                warn!(context, "Skipping unparsable read-only key: {:#}", err);
+                None
+            }
+        })
+        .collect();
+    Ok(keys)
+}
+
+impl Context {
+    /// Returns the algorithm, size and validity of the default self key.
+    ///
+    /// Generates the key if there is none yet, like [`SignedPublicKey::load_self`].
+    pub async fn get_self_key_strength(&self) -> Result<KeyStrengthReport> {
+        let key = SignedPublicKey::load_self(self).await?;
+        Ok(crate::pgp::key_strength_report(&key))
+    }
+}
+
 /// Use of a [KeyPair] for encryption or decryption.
 ///
 /// This is used by [store_self_keypair] to know what kind of key is
@@ -334,6 +463,41 @@ pub async fn store_self_keypair(
     Ok(())
 }
 
+/// Writes the public key of a contact, as known from Autocrypt, to an `.asc` file in `dir`.
+///
+/// Returns the path of the written file.
+/// Fails if no key is known for the contact.
+pub async fn export_contact_public_key(
+    context: &Context,
+    contact_id: ContactId,
+    dir: &Path,
+) -> Result<PathBuf> {
+    let contact = Contact::load_from_db(context, contact_id).await?;
+    let addr = contact.get_addr();
+    let peerstate = Peerstate::from_addr(context, addr).await?;
+    let key = peerstate
+        .as_ref()
+        .and_then(|peerstate| peerstate.peek_key(PeerstateVerifiedStatus::Unverified))
+        .with_context(|| format!("No public key known for {}", addr))?;
+
+    let file_name = dir.join(sanitize_filename::sanitize(format!(
+        "public-key-{}.asc",
+        addr
+    )));
+    info!(
+        context,
+        "Exporting key {} of {} to {}",
+        DcKey::fingerprint(key),
+        addr,
+        file_name.display()
+    );
+    tokio::fs::write(&file_name, key.to_asc(None))
+        .await
+        .with_context(|| format!("Cannot write key to {}", file_name.display()))?;
+    context.emit_event(EventType::ImexFileWritten(file_name.clone()));
+    Ok(file_name)
+}
+
 /// A key fingerprint
 #[derive(Clone, Eq, PartialEq, Hash, serde::Serialize, serde::Deserialize)]
 pub struct Fingerprint(Vec<u8>);
@@ -404,6 +568,16 @@ mod tests {
 
     static KEYPAIR: Lazy<KeyPair> = Lazy::new(alice_keypair);
 
+    #[test]
+    fn test_expiration_timestamp() {
+        let (key, _) =
+            SignedSecretKey::from_asc(include_str!("../test-data/key/expired-secret.asc")).unwrap();
+        // Primary key and encryption subkey expire on 2021-01-01 12:00:00 UTC.
+        assert_eq!(expiration_timestamp(&key), Some(1609502400));
+
+        assert_eq!(expiration_timestamp(&KEYPAIR.secret), None);
+    }
+
     #[test]
     fn test_from_armored_string() {
         let (private_key, _) = SignedSecretKey::from_asc(
@@ -532,6 +706,16 @@ async fn test_load_self_existing() {
         assert_eq!(alice.secret, seckey);
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_get_self_key_strength() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        let report = t.get_self_key_strength().await?;
+        assert_eq!(report.strength, crate::pgp::KeyStrength::Strong);
+        assert_eq!(report.algorithm, "EdDSA");
+        assert!(!report.is_expired);
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_load_self_generate_public() {
         let t = TestContext::new().await;
@@ -611,6 +795,30 @@ async fn test_save_self_key_twice() {
         assert_eq!(nrows().await, 1);
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_export_contact_public_key() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let bob = TestContext::new_bob().await;
+        let dir = tempfile::tempdir()?;
+
+        let bob_id = alice.add_or_lookup_contact(&bob).await.id;
+        let err = export_contact_public_key(&alice, bob_id, dir.path())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("No public key known"));
+
+        let chat = bob.create_chat(&alice).await;
+        alice.recv_msg(&bob.send_text(chat.id, "hi").await).await;
+
+        let path = export_contact_public_key(&alice, bob_id, dir.path()).await?;
+        let (key, _) = SignedPublicKey::from_asc(&tokio::fs::read_to_string(&path).await?)?;
+        assert_eq!(
+            DcKey::fingerprint(&key),
+            DcKey::fingerprint(&SignedPublicKey::load_self(&bob).await?)
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_fingerprint_from_str() {
         let res = Fingerprint::new(vec![
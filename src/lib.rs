@@ -60,7 +60,8 @@ impl<T: rusqlite::ToSql + Send + Sync> ToSql for T {}
 pub mod context;
 mod decrypt;
 pub mod download;
-mod e2ee;
+pub(crate) mod e2ee;
+pub use e2ee::rotate_self_key;
 pub mod ephemeral;
 mod imap;
 pub mod imex;
@@ -86,6 +87,7 @@ impl<T: rusqlite::ToSql + Send + Sync> ToSql for T {}
 pub mod securejoin;
 mod simplify;
 mod smtp;
+pub mod stats;
 pub mod stock_str;
 mod sync;
 mod token;
@@ -1,6 +1,6 @@
 //! # Messages and their identifiers.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashMap};
 use std::path::{Path, PathBuf};
 
 use anyhow::{ensure, format_err, Context as _, Result};
@@ -11,15 +11,17 @@
 use crate::chat::{self, Chat, ChatId};
 use crate::config::Config;
 use crate::constants::{
-    Blocked, Chattype, VideochatType, DC_CHAT_ID_TRASH, DC_DESIRED_TEXT_LEN, DC_MSG_ID_LAST_SPECIAL,
+    Blocked, Chattype, VideochatType, DC_CHAT_ID_LAST_SPECIAL, DC_CHAT_ID_TRASH,
+    DC_DESIRED_TEXT_LEN, DC_MSG_ID_LAST_SPECIAL,
 };
 use crate::contact::{Contact, ContactId, Origin};
 use crate::context::Context;
 use crate::download::DownloadState;
 use crate::ephemeral::{start_ephemeral_timers_msgids, Timer as EphemeralTimer};
 use crate::events::EventType;
-use crate::imap::markseen_on_imap_table;
-use crate::mimeparser::{parse_message_id, DeliveryReport, SystemMessage};
+use crate::imap::markseen_bulk;
+use crate::log::LogExt;
+use crate::mimeparser::{parse_message_id, DeliveryReport, MimeMessage, SystemMessage};
 use crate::param::{Param, Params};
 use crate::pgp::split_armored_data;
 use crate::scheduler::InterruptInfo;
@@ -27,10 +29,23 @@
 use crate::stock_str;
 use crate::summary::Summary;
 use crate::tools::{
-    create_smeared_timestamp, get_filebytes, get_filemeta, gm2local_offset, read_file, time,
-    timestamp_to_str, truncate,
+    create_smeared_timestamp, get_filebytes, get_filemeta, gm2local_offset, humanize_bytes,
+    read_file, time, timestamp_to_str, truncate,
 };
 
+// If you change which information is removed here, also change delete_expired_messages() and
+// which information receive_imf::add_parts() still adds to the db if the chat_id is TRASH
+const TRASH_MSG_SQL: &str = r#"
+UPDATE msgs 
+SET 
+  chat_id=?, txt='', 
+  subject='', txt_raw='', 
+  mime_headers='', 
+  from_id=0, to_id=0, 
+  param='', timestamp_trashed=? 
+WHERE id=?;
+"#;
+
 /// Message ID, including reserved IDs.
 ///
 /// Some message IDs are reserved to identify special message types.
@@ -82,6 +97,37 @@ pub async fn get_state(self, context: &Context) -> Result<MessageState> {
         Ok(result)
     }
 
+    /// Creates a 1:1 chat with the sender of the message and returns its ID.
+    ///
+    /// This allows to reply privately to messages in mailing list chats,
+    /// where the senders are not shown as regular contacts.
+    /// If the chat already exists, it is unblocked.
+    ///
+    /// If `quote` is set, a draft quoting the message is added to the chat.
+    pub async fn create_private_chat_with_sender(
+        self,
+        context: &Context,
+        quote: bool,
+    ) -> Result<ChatId> {
+        let msg = Message::load_from_db(context, self).await?;
+        ensure!(
+            !msg.from_id.is_special(),
+            "Cannot create private chat with sender {}",
+            msg.from_id
+        );
+
+        let chat_id = ChatId::create_for_contact(context, msg.from_id).await?;
+        Contact::scaleup_origin_by_id(context, msg.from_id, Origin::CreateChat).await?;
+
+        if quote {
+            let mut draft = Message::new(Viewtype::Text);
+            draft.set_quote(context, Some(&msg)).await?;
+            chat_id.set_draft(context, Some(&mut draft)).await?;
+        }
+
+        Ok(chat_id)
+    }
+
     /// Put message into trash chat and delete message text.
     ///
     /// It means the message is deleted locally, but not on the server.
@@ -89,29 +135,23 @@ pub async fn get_state(self, context: &Context) -> Result<MessageState> {
     /// 1. not download the same message again
     /// 2. be able to delete the message on the server if we want to
     pub async fn trash(self, context: &Context) -> Result<()> {
-        let chat_id = DC_CHAT_ID_TRASH;
         context
             .sql
-            .execute(
-                // If you change which information is removed here, also change delete_expired_messages() and
-                // which information receive_imf::add_parts() still adds to the db if the chat_id is TRASH
-                r#"
-UPDATE msgs 
-SET 
-  chat_id=?, txt='', 
-  subject='', txt_raw='', 
-  mime_headers='', 
-  from_id=0, to_id=0, 
-  param='' 
-WHERE id=?;
-"#,
-                paramsv![chat_id, self],
-            )
+            .execute(TRASH_MSG_SQL, paramsv![DC_CHAT_ID_TRASH, time(), self])
             .await?;
 
         Ok(())
     }
 
+    /// Same as [`MsgId::trash`], but as part of a running transaction.
+    pub(crate) fn trash_in_transaction(
+        self,
+        transaction: &rusqlite::Transaction<'_>,
+    ) -> Result<()> {
+        transaction.execute(TRASH_MSG_SQL, paramsv![DC_CHAT_ID_TRASH, time(), self])?;
+        Ok(())
+    }
+
     /// Deletes a message, corresponding MDNs and unsent SMTP messages from the database.
     pub async fn delete_from_db(self, context: &Context) -> Result<()> {
         // We don't use transactions yet, so remove MDNs first to make
@@ -138,8 +178,60 @@ pub async fn delete_from_db(self, context: &Context) -> Result<()> {
         Ok(())
     }
 
+    /// Sets the state of the message.
+    ///
+    /// Emits [`EventType::MessageStateChanged`] if the state actually changed.
+    /// The state is only updated if it was not changed concurrently since it was read,
+    /// so the old state in the event is always the state that was replaced.
+    pub(crate) async fn update_state(
+        self,
+        context: &Context,
+        new_state: MessageState,
+    ) -> Result<()> {
+        loop {
+            let (chat_id, old_state) = context
+                .sql
+                .query_row(
+                    "SELECT chat_id, state FROM msgs WHERE id=?",
+                    paramsv![self],
+                    |row| {
+                        let chat_id: ChatId = row.get(0)?;
+                        let state: MessageState = row.get(1)?;
+                        Ok((chat_id, state))
+                    },
+                )
+                .await
+                .with_context(|| format!("failed to get state of message {}", self))?;
+            if old_state == new_state {
+                return Ok(());
+            }
+            let updated = context
+                .sql
+                .execute(
+                    "UPDATE msgs SET state=? WHERE id=? AND state=?;",
+                    paramsv![new_state, self, old_state],
+                )
+                .await?;
+            if updated == 0 {
+                // The state was changed in the meantime, try again.
+                continue;
+            }
+            context.emit_event(EventType::MessageStateChanged {
+                chat_id,
+                msg_id: self,
+                old_state,
+                new_state,
+            });
+            if old_state.is_pending() || new_state.is_pending() {
+                emit_outbox_changed(context).await?;
+            }
+            return Ok(());
+        }
+    }
+
     pub(crate) async fn set_delivered(self, context: &Context) -> Result<()> {
-        update_msg_state(context, self, MessageState::OutDelivered).await?;
+        self.update_state(context, MessageState::OutDelivered)
+            .await?;
         let chat_id: ChatId = context
             .sql
             .query_get_value("SELECT chat_id FROM msgs WHERE id=?", paramsv![self])
@@ -229,6 +321,68 @@ fn default() -> Self {
     }
 }
 
+/// Verdicts from the `Authentication-Results` header (RFC 8601)
+/// added by the receiving server, e.g. `pass`, `fail` or `none`.
+///
+/// A missing verdict means the server did not report the method.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuthResults {
+    /// DKIM verdict.
+    pub dkim: Option<String>,
+
+    /// SPF verdict.
+    pub spf: Option<String>,
+
+    /// DMARC verdict.
+    pub dmarc: Option<String>,
+}
+
+impl AuthResults {
+    /// Adds verdicts from `;`-separated `method=result` pairs.
+    ///
+    /// Methods that already have a verdict are not overwritten,
+    /// so results from the topmost header take precedence.
+    pub(crate) fn add_results(&mut self, s: &str) {
+        for resinfo in s.split(';') {
+            let (method, result) = match resinfo
+                .split_whitespace()
+                .next()
+                .and_then(|token| token.split_once('='))
+            {
+                Some(pair) => pair,
+                None => continue,
+            };
+            let verdict = match method.to_lowercase().as_str() {
+                "dkim" => &mut self.dkim,
+                "spf" => &mut self.spf,
+                "dmarc" => &mut self.dmarc,
+                _ => continue,
+            };
+            if verdict.is_none() && !result.is_empty() {
+                *verdict = Some(result.to_lowercase());
+            }
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.dkim.is_none() && self.spf.is_none() && self.dmarc.is_none()
+    }
+}
+
+impl std::fmt::Display for AuthResults {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let results = [
+            ("dkim", &self.dkim),
+            ("spf", &self.spf),
+            ("dmarc", &self.dmarc),
+        ]
+        .iter()
+        .filter_map(|(method, result)| result.as_ref().map(|r| format!("{}={}", method, r)))
+        .collect::<Vec<_>>();
+        write!(f, "{}", results.join("; "))
+    }
+}
+
 /// An object representing a single message in memory.
 /// The message object is not updated.
 /// If you want an update, you have to recreate the object.
@@ -480,6 +634,10 @@ pub fn get_text(&self) -> Option<String> {
         self.text.as_ref().map(|s| s.to_string())
     }
 
+    /// Returns the subject of the message as received, without the message text.
+    ///
+    /// For classical emails the text returned by [`Message::get_text`]
+    /// may start with the subject, this function returns the subject alone.
     pub fn get_subject(&self) -> &str {
         &self.subject
     }
@@ -553,6 +711,14 @@ pub async fn get_summary(&self, context: &Context, chat: Option<&Chat>) -> Resul
         Ok(Summary::new(context, self, chat, contact.as_ref()).await)
     }
 
+    /// Returns the language of the message as announced by the sender
+    /// in the `Content-Language` header, e.g. `de` or `en-US`.
+    ///
+    /// If the header lists several languages, the first one is returned.
+    pub fn language(&self) -> Option<String> {
+        self.param.get(Param::Language).map(|lang| lang.to_string())
+    }
+
     // It's a little unfortunate that the UI has to first call `dc_msg_get_override_sender_name` and then if it was `NULL`, call
     // `dc_contact_get_display_name` but this was the best solution:
     // - We could load a Contact struct from the db here to call `dc_get_display_name` instead of returning `None`, but then we had a db
@@ -567,6 +733,89 @@ pub fn get_override_sender_name(&self) -> Option<String> {
             .map(|name| name.to_string())
     }
 
+    /// Returns the address an incoming message was delivered to
+    /// as found in the topmost `Delivered-To:` or in the `X-Original-To:` header.
+    ///
+    /// If the user receives mail for several aliases in one mailbox,
+    /// this tells which alias was used.
+    /// For messages in the catch-all chat, see [`crate::config::Config::CatchAllChat`],
+    /// this is the unconfigured address the message was sent to.
+    pub fn get_delivered_to(&self) -> Option<String> {
+        self.param
+            .get(Param::DeliveredTo)
+            .map(|addr| addr.to_string())
+    }
+
+    /// Returns DKIM, SPF and DMARC verdicts of an incoming message
+    /// as reported by the receiving server in the `Authentication-Results:` header.
+    ///
+    /// UIs may use this to mark classic emails coming from a verified sender domain.
+    pub fn authentication_results(&self) -> AuthResults {
+        let mut auth_results = AuthResults::default();
+        if let Some(s) = self.param.get(Param::AuthResults) {
+            auth_results.add_results(s);
+        }
+        auth_results
+    }
+
+    /// Returns reactions to the message by contact.
+    pub fn reactions(&self) -> HashMap<ContactId, String> {
+        self.param
+            .get(Param::Reactions)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| {
+                let (contact_id, reaction) = line.split_once(' ')?;
+                let contact_id = ContactId::new(contact_id.parse().ok()?);
+                Some((contact_id, reaction.to_string()))
+            })
+            .collect()
+    }
+
+    /// Sets the reaction of a contact to the message, an empty reaction removes it.
+    ///
+    /// The message has to be saved with [`Message::update_param`] afterwards.
+    pub(crate) fn set_reaction(&mut self, contact_id: ContactId, reaction: &str) {
+        let mut reactions = self.reactions();
+        if reaction.is_empty() {
+            reactions.remove(&contact_id);
+        } else {
+            reactions.insert(contact_id, reaction.to_string());
+        }
+        if reactions.is_empty() {
+            self.param.remove(Param::Reactions);
+        } else {
+            let mut reactions = reactions.into_iter().collect::<Vec<_>>();
+            reactions.sort_by_key(|(contact_id, _)| contact_id.to_u32());
+            let reactions = reactions
+                .iter()
+                .map(|(contact_id, reaction)| format!("{} {}", contact_id.to_u32(), reaction))
+                .collect::<Vec<_>>()
+                .join("\n");
+            self.param.set(Param::Reactions, reactions);
+        }
+    }
+
+    /// Returns true if the sender's signature was taken from an HTML part of this message,
+    /// so that [`Contact::get_status_html`] may be shown instead of the plain text status.
+    pub fn has_html_status(&self) -> bool {
+        self.param.get_bool(Param::HtmlStatus).unwrap_or_default()
+    }
+
+    /// Returns the contacts a group message was addressed to in To: and Cc:.
+    ///
+    /// Unlike the group members, this does not change when the group membership changes
+    /// later. Returns an empty list for messages that are not group messages.
+    pub fn recipients(&self) -> Vec<ContactId> {
+        self.param
+            .get(Param::MessageRecipients)
+            .unwrap_or_default()
+            .split_ascii_whitespace()
+            .filter_map(|id| id.parse().ok())
+            .map(ContactId::new)
+            .collect()
+    }
+
     // Exposing this function over the ffi instead of get_override_sender_name() would mean that at least Android Java code has
     // to handle raw C-data (as it is done for msg_get_summary())
     pub fn get_sender_name(&self, contact: &Contact) -> String {
@@ -826,6 +1075,19 @@ pub async fn quoted_message(&self, context: &Context) -> Result<Option<Message>>
         Ok(None)
     }
 
+    /// Returns the current text of the quoted message.
+    ///
+    /// Returns `None` if the message has no quote or if the quoted message
+    /// is not available, e.g. because it was deleted or is not downloaded yet.
+    /// [`Message::quoted_text`] still returns the text as quoted by the sender then,
+    /// which can be shown as a placeholder.
+    pub async fn get_quote_text(&self, context: &Context) -> Result<Option<String>> {
+        Ok(self
+            .quoted_message(context)
+            .await?
+            .and_then(|quoted| quoted.text))
+    }
+
     pub async fn parent(&self, context: &Context) -> Result<Option<Message>> {
         if let Some(in_reply_to) = &self.in_reply_to {
             if let Some(msg_id) = rfc724_mid_exists(context, in_reply_to).await? {
@@ -915,6 +1177,12 @@ pub enum MessageState {
     /// IMAP and MDN may be sent.
     InSeen = 16,
 
+    /// Incoming ephemeral message whose timer has expired. UIs may show a
+    /// "This message has expired" placeholder. The message is moved
+    /// to the trash shortly afterwards.
+    /// Outgoing ephemeral messages are moved to the trash right away.
+    InExpired = 17,
+
     /// For files which need time to be prepared before they can be
     /// sent, the message enters this state before
     /// OutPending.
@@ -958,6 +1226,7 @@ fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
                 Self::InFresh => "Fresh",
                 Self::InNoticed => "Noticed",
                 Self::InSeen => "Seen",
+                Self::InExpired => "Expired",
                 Self::OutPreparing => "Preparing",
                 Self::OutDraft => "Draft",
                 Self::OutPending => "Pending",
@@ -984,6 +1253,12 @@ pub fn is_outgoing(self) -> bool {
             OutPreparing | OutDraft | OutPending | OutFailed | OutDelivered | OutMdnRcvd
         )
     }
+
+    /// Returns true if the message is waiting to be sent.
+    pub fn is_pending(self) -> bool {
+        use MessageState::*;
+        matches!(self, OutPreparing | OutPending)
+    }
 }
 
 pub async fn get_msg_info(context: &Context, msg_id: MsgId) -> Result<String> {
@@ -1088,7 +1363,7 @@ pub async fn get_msg_info(context: &Context, msg_id: MsgId) -> Result<String> {
 
     if let Some(path) = msg.get_file(context) {
         let bytes = get_filebytes(context, &path).await;
-        ret += &format!("\nFile: {}, {}, bytes\n", path.display(), bytes);
+        ret += &format!("\nFile: {}, {}\n", path.display(), humanize_bytes(bytes));
     }
 
     if msg.viewtype != Viewtype::Text {
@@ -1123,7 +1398,15 @@ pub async fn get_msg_info(context: &Context, msg_id: MsgId) -> Result<String> {
     Ok(ret)
 }
 
-pub fn guess_msgtype_from_suffix(path: &Path) -> Option<(Viewtype, &str)> {
+/// Returns the size of the file attached via [`Param::File`], 0 if there is none or it is missing.
+pub(crate) async fn get_blob_bytes(context: &Context, param: &Params) -> u64 {
+    match param.get_path(Param::File, context) {
+        Ok(Some(path)) => get_filebytes(context, path).await,
+        _ => 0,
+    }
+}
+
+pub fn guess_msgtype_from_suffix(path: &Path) -> Option<(Viewtype, &'static str)> {
     let extension: &str = &path.extension()?.to_str()?.to_lowercase();
     let info = match extension {
         // before using viewtype other than Viewtype::File,
@@ -1177,8 +1460,8 @@ pub fn guess_msgtype_from_suffix(path: &Path) -> Option<(Viewtype, &str)> {
         "tiff" => (Viewtype::File, "image/tiff"),
         "tif" => (Viewtype::File, "image/tiff"),
         "ttf" => (Viewtype::File, "font/ttf"),
-        "vcard" => (Viewtype::File, "text/vcard"),
-        "vcf" => (Viewtype::File, "text/vcard"),
+        "vcard" => (Viewtype::Vcard, "text/vcard"),
+        "vcf" => (Viewtype::Vcard, "text/vcard"),
         "wav" => (Viewtype::File, "audio/wav"),
         "weba" => (Viewtype::File, "audio/webm"),
         "webm" => (Viewtype::Video, "video/webm"),
@@ -1226,9 +1509,263 @@ pub async fn get_mime_headers(context: &Context, msg_id: MsgId) -> Result<Vec<u8
     Ok(headers)
 }
 
+/// Returns the outgoing messages waiting to be sent in all chats
+/// as `(chat_id, msg_id)` pairs, most recent first.
+///
+/// The number of these messages is returned by [`Context::get_pending_send_count`],
+/// changes are reported by [`EventType::OutboxChanged`].
+pub async fn get_outgoing_pending_msgs(context: &Context) -> Result<Vec<(ChatId, MsgId)>> {
+    let list = context
+        .sql
+        .query_map(
+            "SELECT chat_id, id FROM msgs
+              WHERE from_id=? AND state IN (?,?) AND hidden=0 AND chat_id>?
+              ORDER BY timestamp DESC, id DESC",
+            paramsv![
+                ContactId::SELF,
+                MessageState::OutPending,
+                MessageState::OutPreparing,
+                DC_CHAT_ID_LAST_SPECIAL
+            ],
+            |row| {
+                let chat_id: ChatId = row.get(0)?;
+                let msg_id: MsgId = row.get(1)?;
+                Ok((chat_id, msg_id))
+            },
+            |rows| rows.collect::<Result<Vec<_>, _>>().map_err(Into::into),
+        )
+        .await?;
+    Ok(list)
+}
+
+/// Emits [`EventType::OutboxChanged`] with the current number of pending outgoing messages.
+pub(crate) async fn emit_outbox_changed(context: &Context) -> Result<()> {
+    let pending_count = context.get_pending_send_count().await?;
+    context.emit_event(EventType::OutboxChanged { pending_count });
+    Ok(())
+}
+
+/// Result of [`reparse_from_saved_mime`] for a single message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReparseResult {
+    /// Text, parameters or file of the message were replaced by the result of the new parse.
+    Updated,
+
+    /// The new parse resulted in the same message, nothing was changed.
+    Unchanged,
+
+    /// The raw MIME message is not stored in the database,
+    /// e.g. because [`Config::SaveMimeHeaders`] was not set on reception.
+    NoMimeHeaders,
+
+    /// The message cannot be rebuilt from the stored raw MIME message,
+    /// e.g. because it is an info message, the raw MIME message cannot be parsed
+    /// or cannot be decrypted anymore, or the new parse results in a different number of parts.
+    NotReparsable,
+}
+
+/// Parameters set by the MIME parser for a part,
+/// they are replaced by the result of the new parse in [`reparse_from_saved_mime`].
+/// All other parameters, e.g. labels or reactions, are kept.
+const REPARSED_PARAMS: [Param; 12] = [
+    Param::File,
+    Param::MimeType,
+    Param::Width,
+    Param::Height,
+    Param::Duration,
+    Param::Quote,
+    Param::Forwarded,
+    Param::WantsMdn,
+    Param::Bot,
+    Param::WebrtcRoom,
+    Param::AttachmentSkipped,
+    Param::GuaranteeE2ee,
+];
+
+/// Rebuilds messages from the raw MIME messages saved in the database.
+///
+/// This is useful to repair messages that were received while the MIME parser had a bug.
+/// Chat assignment, timestamps and message IDs are kept,
+/// text, raw text, viewtype, parameters and files are replaced
+/// where the new parse differs from the stored message.
+/// The raw MIME message is available only for messages received
+/// with [`Config::SaveMimeHeaders`] set or with a full HTML version.
+///
+/// Returns the result for each of the given messages.
+/// A `MsgsChanged` event is emitted for each chat with updated messages.
+pub async fn reparse_from_saved_mime(
+    context: &Context,
+    msg_ids: &[MsgId],
+) -> Result<Vec<(MsgId, ReparseResult)>> {
+    let mut results = Vec::with_capacity(msg_ids.len());
+    let mut changed_chats = BTreeSet::new();
+    for &msg_id in msg_ids {
+        let msg = Message::load_from_db(context, msg_id).await?;
+        let result = reparse_msg_from_saved_mime(context, &msg).await?;
+        if result == ReparseResult::Updated {
+            changed_chats.insert(msg.chat_id);
+        }
+        results.push((msg_id, result));
+    }
+    for chat_id in changed_chats {
+        context.emit_msgs_changed(chat_id, MsgId::new(0));
+    }
+    Ok(results)
+}
+
+async fn reparse_msg_from_saved_mime(context: &Context, msg: &Message) -> Result<ReparseResult> {
+    let msg_id = msg.id;
+    let raw = get_mime_headers(context, msg_id).await?;
+    if raw.is_empty() {
+        return Ok(ReparseResult::NoMimeHeaders);
+    }
+    if msg.is_info() || msg.chat_id.is_trash() {
+        return Ok(ReparseResult::NotReparsable);
+    }
+    let mime_parser = match MimeMessage::from_bytes(context, &raw).await {
+        Ok(mime_parser) => mime_parser,
+        Err(err) => {
+            warn!(context, "Cannot reparse message {}: {:#}", msg_id, err);
+            return Ok(ReparseResult::NotReparsable);
+        }
+    };
+
+    let result = update_msg_from_mime_parser(context, msg, &mime_parser).await;
+
+    // `MimeMessage::from_bytes()` writes the files of all parts to the blobdir again,
+    // remove the ones not used by the updated message.
+    let files: Vec<String> = mime_parser
+        .parts
+        .iter()
+        .filter_map(|part| part.param.get(Param::File))
+        .filter_map(|file| file.strip_prefix("$BLOBDIR/"))
+        .map(|file| file.to_string())
+        .collect();
+    sql::remove_files_if_unused(context, &files)
+        .await
+        .ok_or_log(context);
+    result
+}
+
+async fn update_msg_from_mime_parser(
+    context: &Context,
+    msg: &Message,
+    mime_parser: &MimeMessage,
+) -> Result<ReparseResult> {
+    let msg_id = msg.id;
+    if msg.get_showpadlock() && !mime_parser.was_encrypted() {
+        // Do not lose the encryption state, e.g. if the key was changed since reception.
+        warn!(context, "Cannot decrypt message {} for reparsing.", msg_id);
+        return Ok(ReparseResult::NotReparsable);
+    }
+
+    // Each part of a MIME message is stored as a separate database row,
+    // the rows are created in the order of the parts.
+    let row_ids = context
+        .sql
+        .query_map(
+            "SELECT id FROM msgs WHERE rfc724_mid=? AND chat_id=? ORDER BY id",
+            paramsv![msg.rfc724_mid, msg.chat_id],
+            |row| row.get::<_, MsgId>(0),
+            |rows| rows.collect::<Result<Vec<_>, _>>().map_err(Into::into),
+        )
+        .await?;
+    if row_ids.len() != mime_parser.parts.len() {
+        // The parts cannot be assigned to the rows reliably.
+        warn!(
+            context,
+            "Cannot reparse message {}: {} parts stored, {} parts parsed.",
+            msg_id,
+            row_ids.len(),
+            mime_parser.parts.len()
+        );
+        return Ok(ReparseResult::NotReparsable);
+    }
+    let part = match row_ids
+        .iter()
+        .position(|id| *id == msg_id)
+        .and_then(|index| mime_parser.parts.get(index))
+    {
+        Some(part) => part,
+        None => return Ok(ReparseResult::NotReparsable),
+    };
+
+    let txt_raw = if part.typ == Viewtype::Text {
+        format!(
+            "{}\n\n{}",
+            mime_parser.get_subject().unwrap_or_default(),
+            part.msg_raw.as_deref().unwrap_or_default()
+        )
+    } else {
+        String::new()
+    };
+
+    let mut param = part.param.clone();
+    if let (Some(new_file), Some(old_file)) = (
+        param.get_path(Param::File, context)?,
+        msg.param.get_path(Param::File, context)?,
+    ) {
+        // Do not replace the file by a new blob with the same content.
+        let new_data = read_file(context, &new_file).await.ok();
+        if new_file != old_file
+            && new_data.is_some()
+            && new_data == read_file(context, &old_file).await.ok()
+        {
+            if let Some(old_file) = msg.param.get(Param::File) {
+                param.set(Param::File, old_file);
+            }
+        }
+    }
+    let mut old_param = msg.param.clone();
+    for key in REPARSED_PARAMS {
+        old_param.remove(key);
+    }
+    param.merge_missing(&old_param);
+
+    let old_txt_raw: String = context
+        .sql
+        .query_get_value("SELECT txt_raw FROM msgs WHERE id=?", paramsv![msg_id])
+        .await?
+        .unwrap_or_default();
+    if part.typ == msg.viewtype
+        && Some(&part.msg) == msg.text.as_ref()
+        && txt_raw == old_txt_raw
+        && param.to_string() == msg.param.to_string()
+    {
+        return Ok(ReparseResult::Unchanged);
+    }
+
+    context
+        .sql
+        .execute(
+            "UPDATE msgs SET type=?, txt=?, txt_raw=?, param=?, bytes=? WHERE id=?",
+            paramsv![
+                part.typ,
+                part.msg,
+                txt_raw,
+                param.to_string(),
+                part.bytes as isize,
+                msg_id
+            ],
+        )
+        .await?;
+    info!(context, "Message {} rebuilt from saved raw MIME.", msg_id);
+    Ok(ReparseResult::Updated)
+}
+
 pub async fn delete_msgs(context: &Context, msg_ids: &[MsgId]) -> Result<()> {
+    let mut pending_deleted = false;
+    let mut files = Vec::new();
     for msg_id in msg_ids.iter() {
         let msg = Message::load_from_db(context, *msg_id).await?;
+        pending_deleted |= msg.state.is_pending();
+        if let Some(file) = msg
+            .param
+            .get(Param::File)
+            .and_then(|file| file.strip_prefix("$BLOBDIR/"))
+        {
+            files.push(file.to_string());
+        }
         if msg.location_id > 0 {
             delete_poi_location(context, msg.location_id).await?;
         }
@@ -1247,6 +1784,15 @@ pub async fn delete_msgs(context: &Context, msg_ids: &[MsgId]) -> Result<()> {
 
     if !msg_ids.is_empty() {
         context.emit_msgs_changed_without_ids();
+        if pending_deleted {
+            emit_outbox_changed(context).await?;
+        }
+
+        // Delete the files of the deleted messages right away unless they are shared
+        // with other messages, housekeeping removes the remaining unused blobs.
+        sql::remove_files_if_unused(context, &files)
+            .await
+            .ok_or_log(context);
 
         // Run housekeeping to delete unused blobs.
         context.set_config(Config::LastHousekeeping, None).await?;
@@ -1257,6 +1803,118 @@ pub async fn delete_msgs(context: &Context, msg_ids: &[MsgId]) -> Result<()> {
     Ok(())
 }
 
+/// Removes trashed messages from the database and returns the number of removed messages.
+///
+/// Only messages deleted longer than [`Config::TrashRetentionSecs`] ago are removed,
+/// younger ones are still needed to detect duplicates of recently deleted messages.
+/// Messages still present on the server are kept as well,
+/// they are needed to delete the server copies.
+/// Files of the removed messages are deleted if no other message uses them,
+/// afterwards the freed space is returned to the file system.
+pub async fn purge_trash(context: &Context) -> Result<usize> {
+    // Messages trashed on receipt have no `timestamp_trashed`, they were deleted when received.
+    const PURGEABLE: &str = "chat_id=?
+        AND MAX(timestamp_rcvd, timestamp_trashed)<=?
+        AND NOT EXISTS (
+        SELECT * FROM imap WHERE msgs.rfc724_mid=rfc724_mid AND target!=''
+        )";
+
+    let retention = context.get_config_i64(Config::TrashRetentionSecs).await?;
+    let threshold = time().saturating_sub(retention.max(0));
+
+    let files = context
+        .sql
+        .query_map(
+            &format!("SELECT param FROM msgs WHERE {}", PURGEABLE),
+            paramsv![DC_CHAT_ID_TRASH, threshold],
+            |row| row.get::<_, String>(0),
+            |rows| {
+                let mut files = Vec::new();
+                for row in rows {
+                    let param: Params = row?.parse().unwrap_or_default();
+                    if let Some(file) = param
+                        .get(Param::File)
+                        .and_then(|file| file.strip_prefix("$BLOBDIR/"))
+                    {
+                        files.push(file.to_string());
+                    }
+                }
+                Ok(files)
+            },
+        )
+        .await?;
+
+    let deleted = context
+        .sql
+        .transaction(move |transaction| {
+            for table in ["smtp", "msgs_mdns", "msgs_status_updates"] {
+                transaction.execute(
+                    &format!(
+                        "DELETE FROM {} WHERE msg_id IN (SELECT id FROM msgs WHERE {})",
+                        table, PURGEABLE
+                    ),
+                    paramsv![DC_CHAT_ID_TRASH, threshold],
+                )?;
+            }
+            let deleted = transaction.execute(
+                &format!("DELETE FROM msgs WHERE {}", PURGEABLE),
+                paramsv![DC_CHAT_ID_TRASH, threshold],
+            )?;
+            Ok(deleted)
+        })
+        .await?;
+
+    sql::remove_files_if_unused(context, &files).await?;
+    if let Err(err) = context
+        .sql
+        .execute("PRAGMA incremental_vacuum", paramsv![])
+        .await
+    {
+        warn!(context, "Failed to run incremental vacuum: {}", err);
+    }
+
+    info!(context, "Purged {} trashed messages.", deleted);
+    Ok(deleted)
+}
+
+/// Returns the message shown before `msg_id` in its chat,
+/// using the same order as [`chat::get_chat_msgs`].
+///
+/// Returns `None` for the first message of a chat and for trashed messages.
+pub async fn get_previous_in_chat(context: &Context, msg_id: MsgId) -> Result<Option<MsgId>> {
+    get_neighbour_in_chat(context, msg_id, false).await
+}
+
+/// Returns the message shown after `msg_id` in its chat,
+/// using the same order as [`chat::get_chat_msgs`].
+///
+/// Returns `None` for the last message of a chat and for trashed messages.
+pub async fn get_next_in_chat(context: &Context, msg_id: MsgId) -> Result<Option<MsgId>> {
+    get_neighbour_in_chat(context, msg_id, true).await
+}
+
+async fn get_neighbour_in_chat(
+    context: &Context,
+    msg_id: MsgId,
+    next: bool,
+) -> Result<Option<MsgId>> {
+    let (cmp, order) = if next { (">", "ASC") } else { ("<", "DESC") };
+    context
+        .sql
+        .query_get_value(
+            &format!(
+                "SELECT m.id FROM msgs m INNER JOIN msgs cur ON m.chat_id=cur.chat_id
+                 WHERE cur.id=? AND m.chat_id!=? AND m.hidden=0
+                 AND (m.timestamp, m.id) {} (cur.timestamp, cur.id)
+                 ORDER BY m.timestamp {}, m.id {}
+                 LIMIT 1",
+                cmp, order, order
+            ),
+            paramsv![msg_id, DC_CHAT_ID_TRASH],
+        )
+        .await
+}
+
 async fn delete_poi_location(context: &Context, location_id: u32) -> Result<()> {
     context
         .sql
@@ -1326,6 +1984,7 @@ pub async fn markseen_msgs(context: &Context, msg_ids: Vec<MsgId>) -> Result<()>
     }
 
     let mut updated_chat_ids = BTreeSet::new();
+    let mut seen_rfc724_mids = Vec::new();
     for (
         id,
         curr_chat_id,
@@ -1340,11 +1999,9 @@ pub async fn markseen_msgs(context: &Context, msg_ids: Vec<MsgId>) -> Result<()>
         if curr_blocked == Blocked::Not
             && (curr_state == MessageState::InFresh || curr_state == MessageState::InNoticed)
         {
-            update_msg_state(context, id, MessageState::InSeen).await?;
+            id.update_state(context, MessageState::InSeen).await?;
             info!(context, "Seen message {}.", id);
 
-            markseen_on_imap_table(context, &curr_rfc724_mid).await?;
-
             // Read receipts for system messages are never sent. These messages have no place to
             // display received read receipt anyway.  And since their text is locally generated,
             // quoting them is dangerous as it may contain contact names. E.g., for original message
@@ -1367,10 +2024,13 @@ pub async fn markseen_msgs(context: &Context, msg_ids: Vec<MsgId>) -> Result<()>
                     context.interrupt_smtp(InterruptInfo::new(false)).await;
                 }
             }
+            seen_rfc724_mids.push(curr_rfc724_mid);
             updated_chat_ids.insert(curr_chat_id);
         }
     }
 
+    markseen_bulk(context, &seen_rfc724_mids).await?;
+
     for updated_chat_id in updated_chat_ids {
         context.emit_event(EventType::MsgsNoticed(updated_chat_id));
     }
@@ -1378,21 +2038,6 @@ pub async fn markseen_msgs(context: &Context, msg_ids: Vec<MsgId>) -> Result<()>
     Ok(())
 }
 
-pub(crate) async fn update_msg_state(
-    context: &Context,
-    msg_id: MsgId,
-    state: MessageState,
-) -> Result<()> {
-    context
-        .sql
-        .execute(
-            "UPDATE msgs SET state=? WHERE id=?;",
-            paramsv![state, msg_id],
-        )
-        .await?;
-    Ok(())
-}
-
 // as we do not cut inside words, this results in about 32-42 characters.
 // Do not use too long subjects - we add a tag after the subject which gets truncated by the clients otherwise.
 // It should also be very clear, the subject is _not_ the whole message.
@@ -1417,6 +2062,129 @@ pub async fn exists(context: &Context, msg_id: MsgId) -> Result<bool> {
     }
 }
 
+/// Maximum length of a message label in characters.
+const MAX_LABEL_LEN: usize = 50;
+
+/// Removes commas from a label, trims it and limits it to [`MAX_LABEL_LEN`] characters.
+fn sanitize_label(label: &str) -> Result<String> {
+    let label: String = label
+        .replace(',', "")
+        .trim()
+        .chars()
+        .take(MAX_LABEL_LEN)
+        .collect();
+    let label = label.trim_end().to_string();
+    ensure!(!label.is_empty(), "Empty label");
+    Ok(label)
+}
+
+fn labels_from_param(param: &Params) -> Vec<String> {
+    param
+        .get(Param::Labels)
+        .unwrap_or_default()
+        .split(',')
+        .filter(|label| !label.is_empty())
+        .map(|label| label.to_string())
+        .collect()
+}
+
+/// Stores the labels of a message in `Param::Labels` and in the `msg_labels` table.
+async fn set_labels(context: &Context, mut msg: Message, labels: Vec<String>) -> Result<()> {
+    if labels.is_empty() {
+        msg.param.remove(Param::Labels);
+    } else {
+        msg.param.set(Param::Labels, labels.join(","));
+    }
+    let msg_id = msg.id;
+    let param = msg.param.to_string();
+    context
+        .sql
+        .transaction(move |transaction| {
+            transaction.execute(
+                "UPDATE msgs SET param=? WHERE id=?;",
+                paramsv![param, msg_id],
+            )?;
+            transaction.execute("DELETE FROM msg_labels WHERE msg_id=?;", paramsv![msg_id])?;
+            for label in &labels {
+                transaction.execute(
+                    "INSERT INTO msg_labels (msg_id, label) VALUES (?, ?);",
+                    paramsv![msg_id, label],
+                )?;
+            }
+            Ok(())
+        })
+        .await?;
+    context.emit_msgs_changed(msg.chat_id, msg_id);
+    Ok(())
+}
+
+/// Adds a label to a message.
+///
+/// Commas are removed from the label and it is truncated to 50 characters.
+/// Adding a label that is already set does nothing.
+pub async fn add_label(context: &Context, msg_id: MsgId, label: &str) -> Result<()> {
+    let label = sanitize_label(label)?;
+    let msg = Message::load_from_db(context, msg_id).await?;
+    let mut labels = labels_from_param(&msg.param);
+    if !labels.contains(&label) {
+        labels.push(label);
+        set_labels(context, msg, labels).await?;
+    }
+    Ok(())
+}
+
+/// Removes a label from a message.
+pub async fn remove_label(context: &Context, msg_id: MsgId, label: &str) -> Result<()> {
+    let label = sanitize_label(label)?;
+    let msg = Message::load_from_db(context, msg_id).await?;
+    let mut labels = labels_from_param(&msg.param);
+    if labels.contains(&label) {
+        labels.retain(|l| l != &label);
+        set_labels(context, msg, labels).await?;
+    }
+    Ok(())
+}
+
+/// Returns the labels of a message.
+pub async fn get_labels(context: &Context, msg_id: MsgId) -> Result<Vec<String>> {
+    let msg = Message::load_from_db(context, msg_id).await?;
+    Ok(labels_from_param(&msg.param))
+}
+
+/// Returns the messages with the given label, oldest first.
+///
+/// If `chat_id` is set, only messages of this chat are returned.
+pub async fn get_messages_by_label(
+    context: &Context,
+    chat_id: Option<ChatId>,
+    label: &str,
+) -> Result<Vec<MsgId>> {
+    let label = sanitize_label(label)?;
+    context
+        .sql
+        .query_map(
+            "SELECT m.id
+               FROM msg_labels l
+               INNER JOIN msgs m ON m.id=l.msg_id
+              WHERE l.label=?
+                AND m.chat_id!=?
+                AND (?=0 OR m.chat_id=?)
+              ORDER BY m.timestamp, m.id;",
+            paramsv![
+                label,
+                DC_CHAT_ID_TRASH,
+                chat_id.unwrap_or_default(),
+                chat_id.unwrap_or_default()
+            ],
+            |row| row.get::<_, MsgId>(0),
+            |ids| {
+                ids.collect::<std::result::Result<Vec<_>, _>>()
+                    .map_err(Into::into)
+            },
+        )
+        .await
+}
+
 pub async fn set_msg_failed(context: &Context, msg_id: MsgId, error: &str) {
     if let Ok(mut msg) = Message::load_from_db(context, msg_id).await {
         if msg.state.can_fail() {
@@ -1429,14 +2197,19 @@ pub async fn set_msg_failed(context: &Context, msg_id: MsgId, error: &str) {
             )
         }
 
-        match context
-            .sql
-            .execute(
-                "UPDATE msgs SET state=?, error=? WHERE id=?;",
-                paramsv![msg.state, error, msg_id],
-            )
-            .await
-        {
+        let res = match msg_id.update_state(context, msg.state).await {
+            Ok(()) => {
+                context
+                    .sql
+                    .execute(
+                        "UPDATE msgs SET error=? WHERE id=?;",
+                        paramsv![error, msg_id],
+                    )
+                    .await
+            }
+            Err(err) => Err(err),
+        };
+        match res {
             Ok(_) => context.emit_event(EventType::MsgFailed {
                 chat_id: msg.chat_id,
                 msg_id,
@@ -1448,7 +2221,10 @@ pub async fn set_msg_failed(context: &Context, msg_id: MsgId, error: &str) {
     }
 }
 
-/// returns Some if an event should be send
+/// Records an MDN received from `from_id`.
+///
+/// Returns `Some` if this is the first MDN of this contact for the message,
+/// so that the caller can emit a `MsgRead` event for every reader.
 pub async fn handle_mdn(
     context: &Context,
     from_id: ContactId,
@@ -1500,14 +2276,23 @@ pub async fn handle_mdn(
         return Ok(None);
     };
 
-    if !context
+    if msg_state == MessageState::OutPreparing
+        || msg_state == MessageState::OutPending
+        || msg_state == MessageState::OutDelivered
+    {
+        msg_id
+            .update_state(context, MessageState::OutMdnRcvd)
+            .await?;
+    }
+
+    let is_new_reader = !context
         .sql
         .exists(
             "SELECT COUNT(*) FROM msgs_mdns WHERE msg_id=? AND contact_id=?;",
             paramsv![msg_id, from_id],
         )
-        .await?
-    {
+        .await?;
+    if is_new_reader {
         context
             .sql
             .execute(
@@ -1515,13 +2300,6 @@ pub async fn handle_mdn(
                 paramsv![msg_id, from_id, timestamp_sent],
             )
             .await?;
-    }
-
-    if msg_state == MessageState::OutPreparing
-        || msg_state == MessageState::OutPending
-        || msg_state == MessageState::OutDelivered
-    {
-        update_msg_state(context, msg_id, MessageState::OutMdnRcvd).await?;
         Ok(Some((chat_id, msg_id)))
     } else {
         Ok(None)
@@ -1742,6 +2520,7 @@ pub(crate) async fn rfc724_mid_exists(
     Copy,
     PartialEq,
     Eq,
+    Hash,
     FromPrimitive,
     ToPrimitive,
     FromSql,
@@ -1802,6 +2581,11 @@ pub enum Viewtype {
 
     /// Message is an webxdc instance.
     Webxdc = 80,
+
+    /// Message containing one or more contacts in vCard format.
+    /// The file is set via dc_msg_set_file()
+    /// and retrieved via dc_msg_get_file().
+    Vcard = 90,
 }
 
 impl Default for Viewtype {
@@ -1825,6 +2609,7 @@ pub fn has_file(&self) -> bool {
             Viewtype::File => true,
             Viewtype::VideochatInvitation => false,
             Viewtype::Webxdc => true,
+            Viewtype::Vcard => true,
         }
     }
 }
@@ -1855,6 +2640,10 @@ fn test_guess_msgtype_from_suffix() {
             guess_msgtype_from_suffix(Path::new("foo/file.xdc")),
             Some((Viewtype::Webxdc, "application/webxdc+zip"))
         );
+        assert_eq!(
+            guess_msgtype_from_suffix(Path::new("foo/contacts.vcf")),
+            Some((Viewtype::Vcard, "text/vcard"))
+        );
     }
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -2031,6 +2820,12 @@ async fn test_quote() {
             .expect("error while retrieving quoted message")
             .expect("quoted message not found");
         assert!(quoted_msg.get_text() == msg2.quoted_text());
+        assert_eq!(msg2.get_quote_text(ctx).await.unwrap(), msg.get_text());
+
+        // The quoted text is still available after the quoted message is deleted.
+        delete_msgs(ctx, &[msg_id]).await.unwrap();
+        assert_eq!(msg2.get_quote_text(ctx).await.unwrap(), None);
+        assert_eq!(msg2.quoted_text(), msg.get_text());
     }
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -2214,7 +3009,10 @@ async fn assert_state(t: &Context, msg_id: MsgId, state: MessageState) {
         let payload = alice.pop_sent_msg().await;
         assert_state(&alice, alice_msg.id, MessageState::OutDelivered).await;
 
-        update_msg_state(&alice, alice_msg.id, MessageState::OutMdnRcvd).await?;
+        alice_msg
+            .id
+            .update_state(&alice, MessageState::OutMdnRcvd)
+            .await?;
         assert_state(&alice, alice_msg.id, MessageState::OutMdnRcvd).await;
 
         set_msg_failed(&alice, alice_msg.id, "badly failed").await;
@@ -2276,6 +3074,219 @@ async fn test_is_bot() -> Result<()> {
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_labels() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let bob = TestContext::new_bob().await;
+        let alice_chat = alice.create_chat(&bob).await;
+        let self_chat = alice.get_self_chat().await;
+        let msg1 = alice.send_text(alice_chat.id, "one").await.sender_msg_id;
+        let msg2 = alice.send_text(alice_chat.id, "two").await.sender_msg_id;
+        let msg3 = alice.send_text(self_chat.id, "three").await.sender_msg_id;
+
+        add_label(&alice, msg1, "todo").await?;
+        add_label(&alice, msg1, " todo ").await?;
+        add_label(&alice, msg1, "a,b").await?;
+        add_label(&alice, msg2, "todo").await?;
+        add_label(&alice, msg3, "todo").await?;
+        add_label(&alice, msg3, &"x".repeat(100)).await?;
+        assert!(add_label(&alice, msg3, " , ").await.is_err());
+
+        assert_eq!(get_labels(&alice, msg1).await?, vec!["todo", "ab"]);
+        assert_eq!(get_labels(&alice, msg3).await?[1], "x".repeat(50));
+        assert_eq!(
+            get_messages_by_label(&alice, None, "todo").await?,
+            vec![msg1, msg2, msg3]
+        );
+        assert_eq!(
+            get_messages_by_label(&alice, Some(alice_chat.id), "todo").await?,
+            vec![msg1, msg2]
+        );
+        assert_eq!(get_messages_by_label(&alice, None, "ab").await?, vec![msg1]);
+
+        remove_label(&alice, msg1, "todo").await?;
+        assert_eq!(get_labels(&alice, msg1).await?, vec!["ab"]);
+        remove_label(&alice, msg1, "ab").await?;
+        assert!(get_labels(&alice, msg1).await?.is_empty());
+        assert!(get_messages_by_label(&alice, None, "ab").await?.is_empty());
+
+        // Deleted messages are not returned anymore.
+        delete_msgs(&alice, &[msg2]).await?;
+        assert_eq!(
+            get_messages_by_label(&alice, None, "todo").await?,
+            vec![msg3]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_get_previous_and_next_in_chat() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let bob = TestContext::new_bob().await;
+        let chat = alice.create_chat(&bob).await;
+        let mut msg_ids = Vec::new();
+        for i in 0..10 {
+            let sent = alice.send_text(chat.id, &format!("message {}", i)).await;
+            msg_ids.push(sent.sender_msg_id);
+        }
+
+        let mut forward = vec![msg_ids[0]];
+        while let Some(next) = get_next_in_chat(&alice, *forward.last().unwrap()).await? {
+            forward.push(next);
+        }
+        assert_eq!(forward, msg_ids);
+
+        let mut backward = vec![msg_ids[9]];
+        while let Some(prev) = get_previous_in_chat(&alice, *backward.last().unwrap()).await? {
+            backward.push(prev);
+        }
+        backward.reverse();
+        assert_eq!(backward, msg_ids);
+
+        // Trashed messages are skipped and have no neighbours.
+        delete_msgs(&alice, &[msg_ids[5]]).await?;
+        assert_eq!(
+            get_next_in_chat(&alice, msg_ids[4]).await?,
+            Some(msg_ids[6])
+        );
+        assert_eq!(
+            get_previous_in_chat(&alice, msg_ids[6]).await?,
+            Some(msg_ids[4])
+        );
+        assert_eq!(get_next_in_chat(&alice, msg_ids[5]).await?, None);
+        assert_eq!(get_previous_in_chat(&alice, msg_ids[5]).await?, None);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_update_state() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let bob = TestContext::new_bob().await;
+        let alice_chat = alice.create_chat(&bob).await;
+        let sent = alice.send_text(alice_chat.id, "hi").await;
+        let msg_id = sent.sender_msg_id;
+        alice
+            .evtracker
+            .get_matching(|evt| {
+                matches!(
+                    evt,
+                    EventType::MessageStateChanged {
+                        old_state: MessageState::OutPending,
+                        new_state: MessageState::OutDelivered,
+                        ..
+                    }
+                )
+            })
+            .await;
+
+        msg_id
+            .update_state(&alice, MessageState::OutMdnRcvd)
+            .await?;
+        let evt = alice
+            .evtracker
+            .get_matching(|evt| matches!(evt, EventType::MessageStateChanged { .. }))
+            .await;
+        assert_eq!(
+            evt,
+            EventType::MessageStateChanged {
+                chat_id: alice_chat.id,
+                msg_id,
+                old_state: MessageState::OutDelivered,
+                new_state: MessageState::OutMdnRcvd,
+            }
+        );
+        assert_eq!(
+            Message::load_from_db(&alice, msg_id).await?.state,
+            MessageState::OutMdnRcvd
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_delete_msgs_removes_files() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let bob = TestContext::new_bob().await;
+        let chat = alice.create_chat(&bob).await;
+
+        let mut paths = Vec::new();
+        let mut msg_ids = Vec::new();
+        for name in ["deleted.txt", "kept.txt"] {
+            let file = alice.get_blobdir().join(name);
+            tokio::fs::write(&file, name.as_bytes()).await?;
+            let mut msg = Message::new(Viewtype::File);
+            msg.set_file(file.to_str().unwrap(), None);
+            let sent = alice.send_msg(chat.id, &mut msg).await;
+            let msg = Message::load_from_db(&alice, sent.sender_msg_id).await?;
+            paths.push(msg.get_file(&alice).unwrap());
+            msg_ids.push(msg.id);
+        }
+
+        delete_msgs(&alice, &msg_ids[..1]).await?;
+        assert!(!paths[0].exists());
+        assert!(paths[1].exists());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_purge_trash() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let bob = TestContext::new_bob().await;
+        let bob_chat = bob.create_chat(&alice).await;
+        let mut msg_ids = Vec::new();
+        for text in ["one", "two", "three"] {
+            let sent = bob.send_text(bob_chat.id, text).await;
+            msg_ids.push(alice.recv_msg(&sent).await.id);
+        }
+        // Retention is measured from the deletion, not from the reception.
+        alice
+            .sql
+            .execute(
+                "UPDATE msgs SET timestamp_rcvd=?",
+                paramsv![time() - 30 * 24 * 60 * 60],
+            )
+            .await?;
+        delete_msgs(&alice, &msg_ids).await?;
+        let trash_cnt = || async {
+            alice
+                .sql
+                .count(
+                    "SELECT COUNT(*) FROM msgs WHERE chat_id=?",
+                    paramsv![DC_CHAT_ID_TRASH],
+                )
+                .await
+        };
+        assert_eq!(trash_cnt().await?, 3);
+
+        // Recently trashed messages are kept for deduplication.
+        assert_eq!(purge_trash(&alice).await?, 0);
+        assert_eq!(trash_cnt().await?, 3);
+
+        // Messages still to be deleted from the server are kept.
+        let rfc724_mid = Message::load_from_db(&alice, msg_ids[0]).await?.rfc724_mid;
+        alice
+            .sql
+            .execute(
+                "INSERT INTO imap (rfc724_mid, folder, target, uid, uidvalidity)
+                 VALUES (?, 'INBOX', 'INBOX', 1, 1)",
+                paramsv![rfc724_mid],
+            )
+            .await?;
+
+        alice
+            .set_config(Config::TrashRetentionSecs, Some("0"))
+            .await?;
+        assert_eq!(purge_trash(&alice).await?, 2);
+        assert_eq!(trash_cnt().await?, 1);
+        assert!(Message::load_from_db(&alice, msg_ids[0]).await.is_ok());
+        for msg_id in &msg_ids[1..] {
+            assert!(Message::load_from_db(&alice, *msg_id).await.is_err());
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn test_viewtype_derive_display_works_as_expected() {
         assert_eq!(format!("{}", Viewtype::Audio), "Audio");
@@ -2299,5 +3310,184 @@ fn test_viewtype_values() {
             Viewtype::from_i32(70).unwrap()
         );
         assert_eq!(Viewtype::Webxdc, Viewtype::from_i32(80).unwrap());
+        assert_eq!(Viewtype::Vcard, Viewtype::from_i32(90).unwrap());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_reparse_from_saved_mime() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        t.set_config(Config::ShowEmails, Some("2")).await?;
+        t.set_config_bool(Config::SaveMimeHeaders, true).await?;
+        receive_imf(
+            &t,
+            b"From: sender@example.net\n\
+              To: alice@example.org\n\
+              Subject: Html only\n\
+              Message-ID: <html-only@example.net>\n\
+              Date: Sun, 22 Mar 2020 22:37:57 +0000\n\
+              Content-Type: text/html; charset=utf-8\n\
+              \n\
+              <html><body><p>Hello <b>world</b></p></body></html>\n",
+            false,
+        )
+        .await?;
+        let msg = t.get_last_msg().await;
+        let chat_id = msg.chat_id;
+        assert_eq!(msg.get_text(), Some("Hello world".to_string()));
+        add_label(&t, msg.id, "important").await?;
+
+        // Simulate a parser bug that lost the text.
+        t.sql
+            .execute(
+                "UPDATE msgs SET txt='', txt_raw='' WHERE id=?",
+                paramsv![msg.id],
+            )
+            .await?;
+        assert_eq!(
+            Message::load_from_db(&t, msg.id).await?.get_text(),
+            Some("".to_string())
+        );
+
+        t.set_config_bool(Config::SaveMimeHeaders, false).await?;
+        let plain = receive_imf(
+            &t,
+            b"From: sender@example.net\n\
+              To: alice@example.org\n\
+              Subject: Plain\n\
+              Message-ID: <plain@example.net>\n\
+              Date: Sun, 22 Mar 2020 22:38:57 +0000\n\
+              \n\
+              plain text\n",
+            false,
+        )
+        .await?
+        .unwrap()
+        .msg_ids[0];
+
+        let results = reparse_from_saved_mime(&t, &[msg.id, plain]).await?;
+        assert_eq!(
+            results,
+            vec![
+                (msg.id, ReparseResult::Updated),
+                (plain, ReparseResult::NoMimeHeaders)
+            ]
+        );
+        t.evtracker
+            .get_matching(|evt| match evt {
+                EventType::MsgsChanged {
+                    chat_id: id,
+                    msg_id,
+                } => *id == chat_id && msg_id.is_unset(),
+                _ => false,
+            })
+            .await;
+
+        let reparsed = Message::load_from_db(&t, msg.id).await?;
+        assert_eq!(reparsed.id, msg.id);
+        assert_eq!(reparsed.chat_id, msg.chat_id);
+        assert_eq!(reparsed.timestamp_sort, msg.timestamp_sort);
+        assert_eq!(reparsed.get_text(), Some("Hello world".to_string()));
+        assert_eq!(get_labels(&t, msg.id).await?, vec!["important".to_string()]);
+
+        // Reparsing again does not change anything.
+        let results = reparse_from_saved_mime(&t, &[msg.id]).await?;
+        assert_eq!(results, vec![(msg.id, ReparseResult::Unchanged)]);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_reparse_from_saved_mime_keeps_blobdir() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        t.set_config(Config::ShowEmails, Some("2")).await?;
+        t.set_config_bool(Config::SaveMimeHeaders, true).await?;
+        receive_imf(
+            &t,
+            b"From: sender@example.net\n\
+              To: alice@example.org\n\
+              Subject: Attachment\n\
+              Message-ID: <attachment@example.net>\n\
+              Date: Sun, 22 Mar 2020 22:37:57 +0000\n\
+              Content-Type: multipart/mixed; boundary=\"==break==\"\n\
+              \n\
+              --==break==\n\
+              Content-Type: text/plain; charset=utf-8\n\
+              \n\
+              See attachment.\n\
+              --==break==\n\
+              Content-Type: text/plain; name=\"notes.txt\"\n\
+              Content-Disposition: attachment; filename=\"notes.txt\"\n\
+              \n\
+              some notes\n\
+              --==break==--\n",
+            false,
+        )
+        .await?;
+        let msg = t.get_last_msg().await;
+        assert_eq!(msg.get_viewtype(), Viewtype::File);
+        let blobdir_count = || std::fs::read_dir(t.get_blobdir()).unwrap().count();
+        let count = blobdir_count();
+
+        let results = reparse_from_saved_mime(&t, &[msg.id]).await?;
+        assert_eq!(results, vec![(msg.id, ReparseResult::Unchanged)]);
+        assert_eq!(blobdir_count(), count);
+
+        // A different number of parts cannot be assigned to the stored rows.
+        t.sql
+            .execute(
+                "INSERT INTO msgs (rfc724_mid, chat_id) SELECT rfc724_mid, chat_id FROM msgs WHERE id=?",
+                paramsv![msg.id],
+            )
+            .await?;
+        let results = reparse_from_saved_mime(&t, &[msg.id]).await?;
+        assert_eq!(results, vec![(msg.id, ReparseResult::NotReparsable)]);
+        assert_eq!(blobdir_count(), count);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_get_outgoing_pending_msgs() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let bob = TestContext::new_bob().await;
+        let chat_id = alice.create_chat(&bob).await.id;
+        assert!(get_outgoing_pending_msgs(&alice).await?.is_empty());
+        assert_eq!(alice.get_pending_send_count().await?, 0);
+
+        let first = chat::send_text_msg(&alice, chat_id, "first".to_string()).await?;
+        alice
+            .evtracker
+            .get_matching(|evt| matches!(evt, EventType::OutboxChanged { pending_count: 1 }))
+            .await;
+        let second = chat::send_text_msg(&alice, chat_id, "second".to_string()).await?;
+        alice
+            .evtracker
+            .get_matching(|evt| matches!(evt, EventType::OutboxChanged { pending_count: 2 }))
+            .await;
+        assert_eq!(
+            get_outgoing_pending_msgs(&alice).await?,
+            vec![(chat_id, second), (chat_id, first)]
+        );
+        assert_eq!(alice.get_pending_send_count().await?, 2);
+
+        // Sending the message removes it from the outbox.
+        let sent = alice.pop_sent_msg().await;
+        assert_eq!(sent.sender_msg_id, second);
+        alice
+            .evtracker
+            .get_matching(|evt| matches!(evt, EventType::OutboxChanged { pending_count: 1 }))
+            .await;
+        assert_eq!(
+            get_outgoing_pending_msgs(&alice).await?,
+            vec![(chat_id, first)]
+        );
+
+        // So does deleting it.
+        delete_msgs(&alice, &[first]).await?;
+        alice
+            .evtracker
+            .get_matching(|evt| matches!(evt, EventType::OutboxChanged { pending_count: 0 }))
+            .await;
+        assert!(get_outgoing_pending_msgs(&alice).await?.is_empty());
+        assert_eq!(alice.get_pending_send_count().await?, 0);
+        Ok(())
     }
 }
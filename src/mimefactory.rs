@@ -948,6 +948,17 @@ async fn render_message(
                     "Auto-Submitted".to_string(),
                     "auto-generated".to_string(),
                 ));
+
+                // Status updates sent by `send_webxdc_ephemeral_update()`
+                // are not stored by the recipients.
+                if command == SystemMessage::WebxdcStatusUpdate
+                    && self.msg.param.get_bool(Param::Arg2).unwrap_or_default()
+                {
+                    headers.protected.push(Header::new(
+                        "Chat-Webxdc-Ephemeral".to_string(),
+                        "1".to_string(),
+                    ));
+                }
             }
             SystemMessage::AutocryptSetupMessage => {
                 headers
@@ -1127,7 +1138,7 @@ async fn render_message(
         if self.msg.has_html() {
             let html = if let Some(orig_msg_id) = self.msg.param.get_int(Param::Forwarded) {
                 MsgId::new(orig_msg_id.try_into()?)
-                    .get_html(context)
+                    .get_html_with_remote_content(context)
                     .await?
             } else {
                 self.msg.param.get(Param::SendHtml).map(|s| s.to_string())
@@ -4,7 +4,7 @@
 use std::future::Future;
 use std::pin::Pin;
 
-use anyhow::{bail, Context as _, Result};
+use anyhow::{bail, ensure, Context as _, Result};
 use deltachat_derive::{FromSql, ToSql};
 use lettre_email::mime::{self, Mime};
 use mailparse::{addrparse_header, DispositionType, MailHeader, MailHeaderMap, SingleInfo};
@@ -12,23 +12,25 @@
 
 use crate::aheader::Aheader;
 use crate::blob::BlobObject;
+use crate::config::Config;
 use crate::constants::{DC_DESIRED_TEXT_LEN, DC_ELLIPSIS};
-use crate::contact::{addr_cmp, addr_normalize, ContactId};
+use crate::contact::{addr_cmp, addr_normalize, may_be_valid_addr, ContactId};
 use crate::context::Context;
 use crate::decrypt::{create_decryption_info, try_decrypt};
 use crate::dehtml::dehtml;
 use crate::events::EventType;
 use crate::format_flowed::unformat_flowed;
 use crate::headerdef::{HeaderDef, HeaderDefMap};
-use crate::key::Fingerprint;
+use crate::html;
+use crate::key::{DcKey, Fingerprint, SignedPublicKey};
 use crate::location;
-use crate::message::{self, Viewtype};
+use crate::message::{self, AuthResults, Viewtype};
 use crate::param::{Param, Params};
 use crate::peerstate::Peerstate;
 use crate::simplify::{simplify, SimplifiedText};
 use crate::stock_str;
 use crate::sync::SyncItems;
-use crate::tools::{get_filemeta, parse_receive_headers, truncate};
+use crate::tools::{get_filemeta, parse_receive_headers, strip_bom, truncate};
 
 /// A parsed MIME message.
 ///
@@ -78,6 +80,9 @@ pub struct MimeMessage {
     /// Standard USENET signature, if any.
     pub(crate) footer: Option<String>,
 
+    /// Raw HTML of the signature if [`MimeMessage::footer`] was taken from a `text/html` part.
+    pub(crate) footer_html: Option<String>,
+
     // if this flag is set, the parts/text/etc. are just close to the original mime-message;
     // clients should offer a way to view the original message in this case
     pub is_mime_modified: bool,
@@ -89,6 +94,29 @@ pub struct MimeMessage {
     pub decoded_data: Vec<u8>,
 
     pub(crate) hop_info: String,
+
+    /// Address from the topmost `Delivered-To` or the `X-Original-To` header,
+    /// normalized and lowercased.
+    pub(crate) delivered_to: Option<String>,
+
+    /// Verdicts from the `Authentication-Results` headers.
+    pub(crate) authentication_results: AuthResults,
+
+    /// Reaction to the message referenced in `In-Reply-To`,
+    /// if this message is a reaction (`Chat-Content: reaction` or
+    /// `Content-Disposition: reaction` as of RFC 9078).
+    ///
+    /// An empty reaction removes the previous reaction of the sender.
+    pub(crate) reaction: Option<String>,
+
+    /// Nesting level of the MIME part currently parsed.
+    mime_depth: usize,
+
+    /// Set if parts were dropped because of [`MAX_MIME_DEPTH`] or [`MAX_MIME_PARTS`].
+    parts_truncated: bool,
+
+    /// Set if a `text/html` part references remote content, e.g. tracking pixels.
+    pub(crate) has_remote_content: bool,
 }
 
 #[derive(Debug, PartialEq)]
@@ -157,6 +185,19 @@ fn default() -> Self {
 
 const MIME_AC_SETUP_FILE: &str = "application/autocrypt-setup";
 
+/// Maximum nesting level of MIME parts.
+///
+/// `mailparse` has no limits of its own, so messages with more deeply nested multiparts
+/// are rejected by [`check_mime_structure`] before they are parsed.
+/// Deeper parts not found by the check, e.g. nested `message/rfc822` parts, are dropped.
+pub(crate) const MAX_MIME_DEPTH: usize = 50;
+
+/// Maximum number of parts of a message, see [`MAX_MIME_DEPTH`].
+pub(crate) const MAX_MIME_PARTS: usize = 100;
+
+/// Maximum number of decoded bytes kept from a single text part, the rest is cut off.
+pub(crate) const MAX_TEXT_PART_BYTES: usize = 1_000_000;
+
 impl MimeMessage {
     pub async fn from_bytes(context: &Context, body: &[u8]) -> Result<Self> {
         MimeMessage::from_bytes_with_partial(context, body, None).await
@@ -171,6 +212,7 @@ pub async fn from_bytes_with_partial(
         body: &[u8],
         partial: Option<u32>,
     ) -> Result<Self> {
+        check_mime_structure(body)?;
         let mail = mailparse::parse_mail(body)?;
 
         let message_time = mail
@@ -179,6 +221,10 @@ pub async fn from_bytes_with_partial(
             .and_then(|v| mailparse::dateparse(&v).ok())
             .unwrap_or_default();
         let hop_info = parse_receive_headers(&mail.get_headers());
+        let delivered_to = get_delivered_to(&mail.headers);
+        let authentication_results_headers = mail
+            .headers
+            .get_all_values(HeaderDef::AuthenticationResults.get_headername());
 
         let mut headers = Default::default();
         let mut recipients = Default::default();
@@ -308,6 +354,13 @@ pub async fn from_bytes_with_partial(
                     (Err(err), HashSet::new(), true)
                 }
             };
+        let authentication_results = get_authentication_results(
+            context,
+            &authentication_results_headers,
+            from.first(),
+            &signatures,
+        )
+        .await?;
 
         let mut parser = MimeMessage {
             parts: Vec::new(),
@@ -333,9 +386,16 @@ pub async fn from_bytes_with_partial(
             group_avatar: None,
             delivery_report: None,
             footer: None,
+            footer_html: None,
             is_mime_modified: false,
             decoded_data: Vec::new(),
             hop_info,
+            delivered_to,
+            authentication_results,
+            reaction: None,
+            mime_depth: 0,
+            parts_truncated: false,
+            has_remote_content: false,
         };
 
         match partial {
@@ -347,6 +407,7 @@ pub async fn from_bytes_with_partial(
             None => match mail {
                 Ok(mail) => {
                     parser.parse_mime_recursive(context, &mail, false).await?;
+                    parser.has_remote_content = html::mail_has_remote_content(&mail);
                 }
                 Err(err) => {
                     let msg_body = stock_str::cant_decrypt_msg_body(context).await;
@@ -364,6 +425,17 @@ pub async fn from_bytes_with_partial(
             },
         };
 
+        if parser.parts_truncated {
+            let txt = stock_str::msg_parts_truncated(context).await;
+            parser.parts.push(Part {
+                typ: Viewtype::Text,
+                msg_raw: Some(txt.clone()),
+                msg: txt,
+                ..Default::default()
+            });
+            parser.is_mime_modified = true;
+        }
+
         parser.maybe_remove_bad_parts();
         parser.maybe_remove_inline_mailinglist_footer();
         parser.heuristically_parse_ndn(context).await;
@@ -452,6 +524,22 @@ fn parse_videochat_headers(&mut self) {
         }
     }
 
+    fn parse_reaction_headers(&mut self) {
+        let is_reaction = self.get_header(HeaderDef::ChatContent).map(|s| s.as_str())
+            == Some("reaction")
+            || self
+                .get_header(HeaderDef::ContentDisposition)
+                .map_or(false, |s| s.trim().to_lowercase().starts_with("reaction"));
+        if is_reaction && self.get_header(HeaderDef::InReplyTo).is_some() {
+            let reaction = self
+                .parts
+                .first()
+                .map(|part| part.msg.split_whitespace().collect::<Vec<_>>().join(" "))
+                .unwrap_or_default();
+            self.reaction = Some(reaction);
+        }
+    }
+
     /// Squashes mutlipart chat messages with attachment into single-part messages.
     ///
     /// Delta Chat sends attachments, such as images, in two-part messages, with the first message
@@ -469,7 +557,8 @@ fn squash_attachment_parts(&mut self) {
                     | Viewtype::Voice
                     | Viewtype::Video
                     | Viewtype::File
-                    | Viewtype::Webxdc => true,
+                    | Viewtype::Webxdc
+                    | Viewtype::Vcard => true,
                     Viewtype::Unknown | Viewtype::Text | Viewtype::VideochatInvitation => false,
                 };
 
@@ -532,6 +621,7 @@ async fn parse_headers(&mut self, context: &Context) -> Result<()> {
         self.parse_system_message_headers(context);
         self.parse_avatar_headers(context).await;
         self.parse_videochat_headers();
+        self.parse_reaction_headers();
         if self.delivery_report.is_none() {
             self.squash_attachment_parts();
         }
@@ -709,6 +799,50 @@ pub fn get_header(&self, headerdef: HeaderDef) -> Option<&String> {
         self.header.get(headerdef.get_headername())
     }
 
+    /// Returns true if the message has a header with the given name, case-insensitively.
+    pub(crate) fn has_header_named(&self, name: &str) -> bool {
+        self.header.contains_key(&name.trim().to_lowercase())
+    }
+
+    /// Returns true if the `Auto-Submitted` header marks the message
+    /// as `auto-generated` or `auto-replied`.
+    pub(crate) fn is_auto_submitted(&self) -> bool {
+        self.get_header(HeaderDef::AutoSubmitted)
+            .map(|value| {
+                let value = value.trim().to_lowercase();
+                value.starts_with("auto-generated") || value.starts_with("auto-replied")
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns the human-readable description from the `List-Id` header,
+    /// e.g. `Delta Chat Devs` for `"Delta Chat Devs" <delta.codespeak.net>`.
+    pub(crate) fn get_list_description(&self) -> Option<String> {
+        let list_id = self.get_header(HeaderDef::ListId)?;
+        let (description, _) = list_id.rsplit_once('<')?;
+        let description = description.trim().trim_matches('"').trim();
+        if description.is_empty() {
+            None
+        } else {
+            Some(description.to_string())
+        }
+    }
+
+    /// Returns the URL from the `List-Help` header, if any.
+    pub(crate) fn get_list_help_url(&self) -> Option<String> {
+        parse_list_url_header(self.get_header(HeaderDef::ListHelp)?)
+    }
+
+    /// Returns the URL from the `List-Archive` header, if any.
+    pub(crate) fn get_list_archive_url(&self) -> Option<String> {
+        parse_list_url_header(self.get_header(HeaderDef::ListArchive)?)
+    }
+
+    /// Returns the first language tag from the `Content-Language` header, if any.
+    pub(crate) fn get_content_language(&self) -> Option<String> {
+        parse_content_language(self.get_header(HeaderDef::ContentLanguage)?)
+    }
+
     fn parse_mime_recursive<'a>(
         &'a mut self,
         context: &'a Context,
@@ -731,6 +865,19 @@ fn parse_mime_recursive<'a>(
                 warn!(context, "Ignoring nested protected headers");
             }
 
+            if self.mime_depth >= MAX_MIME_DEPTH || self.parts.len() >= MAX_MIME_PARTS {
+                if !self.parts_truncated {
+                    warn!(
+                        context,
+                        "MIME structure too complex, ignoring remaining parts (depth {}, {} parts).",
+                        self.mime_depth,
+                        self.parts.len()
+                    );
+                    self.parts_truncated = true;
+                }
+                return Ok(false);
+            }
+
             enum MimeS {
                 Multiple,
                 Single,
@@ -756,19 +903,23 @@ enum MimeS {
             };
 
             let is_related = is_related || mimetype == "multipart/related";
-            match m {
+            self.mime_depth += 1;
+            let res = match m {
                 MimeS::Multiple => self.handle_multiple(context, mail, is_related).await,
                 MimeS::Message => {
                     let raw = mail.get_body_raw()?;
                     if raw.is_empty() {
-                        return Ok(false);
-                    }
-                    let mail = mailparse::parse_mail(&raw).context("failed to parse mail")?;
+                        Ok(false)
+                    } else {
+                        let mail = mailparse::parse_mail(&raw).context("failed to parse mail")?;
 
-                    self.parse_mime_recursive(context, &mail, is_related).await
+                        self.parse_mime_recursive(context, &mail, is_related).await
+                    }
                 }
                 MimeS::Single => self.add_single_part_if_known(context, mail, is_related).await,
-            }
+            };
+            self.mime_depth -= 1;
+            res
         }
         .boxed()
     }
@@ -952,7 +1103,7 @@ async fn add_single_part_if_known(
                         return Ok(false);
                     }
                     mime::TEXT | mime::HTML => {
-                        let decoded_data = match mail.get_body() {
+                        let mut decoded_data = match mail.get_body() {
                             Ok(decoded_data) => decoded_data,
                             Err(err) => {
                                 warn!(context, "Invalid body parsed {:?}", err);
@@ -960,8 +1111,26 @@ async fn add_single_part_if_known(
                                 return Ok(false);
                             }
                         };
+                        // The decoded text is UTF-8, so only a UTF-8 BOM may be left.
+                        let bom_len = decoded_data.len() - strip_bom(decoded_data.as_bytes()).len();
+                        decoded_data.drain(..bom_len);
+                        if decoded_data.len() > MAX_TEXT_PART_BYTES {
+                            warn!(
+                                context,
+                                "Text part has {} bytes, cutting off at {}.",
+                                decoded_data.len(),
+                                MAX_TEXT_PART_BYTES
+                            );
+                            let mut end = MAX_TEXT_PART_BYTES;
+                            while !decoded_data.is_char_boundary(end) {
+                                end -= 1;
+                            }
+                            decoded_data.truncate(end);
+                            self.is_mime_modified = true;
+                        }
 
                         let mut dehtml_failed = false;
+                        let is_html = mime_type == mime::TEXT_HTML;
 
                         let SimplifiedText {
                             text: simplified_txt,
@@ -972,7 +1141,6 @@ async fn add_single_part_if_known(
                         } = if decoded_data.is_empty() {
                             Default::default()
                         } else {
-                            let is_html = mime_type == mime::TEXT_HTML;
                             let out = if is_html {
                                 self.is_mime_modified = true;
                                 dehtml(&decoded_data).unwrap_or_else(|| {
@@ -1040,6 +1208,10 @@ async fn add_single_part_if_known(
                             self.is_forwarded = true;
                         }
 
+                        self.footer_html = match footer {
+                            Some(_) if is_html && !dehtml_failed => get_html_footer(&decoded_data),
+                            _ => None,
+                        };
                         self.footer = footer;
                     }
                     _ => {}
@@ -1112,6 +1284,30 @@ async fn do_add_single_file_part(
         /* we have a regular file attachment,
         write decoded data to new blob object */
 
+        let max_blob_size = context
+            .get_config_u64(Config::MaxBlobSizeBytes)
+            .await
+            .unwrap_or_default();
+        if max_blob_size > 0 && decoded_data.len() as u64 > max_blob_size {
+            warn!(
+                context,
+                "Attachment {} has {} bytes, skipping.",
+                filename,
+                decoded_data.len()
+            );
+            let mut part = Part {
+                typ: Viewtype::Text,
+                msg: format!(
+                    "[{}]",
+                    stock_str::attachment_too_large(context, decoded_data.len() as u64).await
+                ),
+                ..Default::default()
+            };
+            part.param.set_int(Param::AttachmentSkipped, 1);
+            self.do_add_single_part(part);
+            return;
+        }
+
         let blob = match BlobObject::create(context, filename, decoded_data).await {
             Ok(blob) => blob,
             Err(err) => {
@@ -1138,6 +1334,7 @@ async fn do_add_single_file_part(
         part.mimetype = Some(mime_type);
         part.bytes = decoded_data.len();
         part.param.set(Param::File, blob.as_name());
+        part.param.set_i64(Param::Bytes, decoded_data.len() as i64);
         part.param.set(Param::MimeType, raw_mime);
         part.is_related = is_related;
 
@@ -1280,6 +1477,7 @@ fn process_delivery_status(
     ) -> Result<Option<DeliveryReport>> {
         // Assume failure.
         let mut failure = true;
+        let mut failed_recipients = Vec::new();
 
         if let Some(status_part) = report.subparts.get(1) {
             // RFC 3464 defines `message/delivery-status`
@@ -1307,6 +1505,11 @@ fn process_delivery_status(
                 } else {
                     warn!(context, "DSN without action");
                 }
+                failed_recipients = get_failed_recipients(status_body)?;
+                if !failed_recipients.is_empty() {
+                    // The first recipient may have been delivered while others failed.
+                    failure = true;
+                }
             } else {
                 warn!(context, "DSN without per-recipient fields");
             }
@@ -1328,18 +1531,27 @@ fn process_delivery_status(
                 .get_header_value(HeaderDef::MessageId)
                 .and_then(|v| parse_message_id(&v).ok())
             {
-                let mut to_list = get_all_addresses_from_header(&report.headers, |header_key| {
-                    header_key == "x-failed-recipients"
-                });
+                // Some MTAs only put the failed address into `X-Failed-Recipients`,
+                // so use it if the delivery status does not contain a failed `Final-Recipient`.
+                let mut to_list = if failed_recipients.is_empty() {
+                    get_all_addresses_from_header(&report.headers, |header_key| {
+                        header_key == "x-failed-recipients"
+                    })
+                    .into_iter()
+                    .map(|s| s.addr)
+                    .collect()
+                } else {
+                    failed_recipients
+                };
                 let to = if to_list.len() == 1 {
-                    Some(to_list.pop().unwrap())
+                    to_list.pop()
                 } else {
                     None // We do not know which recipient failed
                 };
 
                 return Ok(Some(DeliveryReport {
                     rfc724_mid: original_message_id,
-                    failed_recipient: to.map(|s| s.addr),
+                    failed_recipient: to,
                     failure,
                 }));
             }
@@ -1466,7 +1678,11 @@ pub async fn handle_reports(
                     .await
                 {
                     Ok(Some((chat_id, msg_id))) => {
-                        context.emit_event(EventType::MsgRead { chat_id, msg_id });
+                        context.emit_event(EventType::MsgRead {
+                            chat_id,
+                            msg_id,
+                            contact_id: from_id,
+                        });
                     }
                     Ok(None) => {}
                     Err(err) => {
@@ -1656,7 +1872,9 @@ fn get_mime_type(mail: &mailparse::ParsedMail<'_>) -> Result<(Mime, Viewtype)> {
 
     let viewtype = match mimetype.type_() {
         mime::TEXT => {
-            if !is_attachment_disposition(mail) {
+            if mimetype.subtype() == "vcard" || mimetype.subtype() == "x-vcard" {
+                Viewtype::Vcard
+            } else if !is_attachment_disposition(mail) {
                 match mimetype.subtype() {
                     mime::PLAIN | mime::HTML => Viewtype::Text,
                     _ => Viewtype::File,
@@ -1763,6 +1981,313 @@ pub(crate) fn get_from(headers: &[MailHeader]) -> Vec<SingleInfo> {
     get_all_addresses_from_header(headers, |header_key| header_key == "from")
 }
 
+/// Returns the addresses of the `Final-Recipient` fields
+/// of all per-recipient field groups with `Action: failed` in a `message/delivery-status` body.
+///
+/// Returned addresses are normalized and lowercased.
+fn get_failed_recipients(mut per_recipient_fields: &[u8]) -> Result<Vec<String>> {
+    let mut recipients = Vec::new();
+    while !per_recipient_fields.is_empty() {
+        let (fields, sz) = mailparse::parse_headers(per_recipient_fields)?;
+        if sz == 0 {
+            break;
+        }
+        let failed = fields
+            .get_first_value("action")
+            .map_or(false, |action| action.trim().eq_ignore_ascii_case("failed"));
+        // The field has the form `address-type; address`, e.g. `rfc822; bob@example.org`.
+        if let Some((addr_type, addr)) = fields
+            .get_first_value("final-recipient")
+            .filter(|_| failed)
+            .as_deref()
+            .and_then(|value| value.split_once(';'))
+        {
+            let addr = addr.trim().trim_start_matches('<').trim_end_matches('>');
+            if addr_type.trim().eq_ignore_ascii_case("rfc822") && !addr.is_empty() {
+                recipients.push(addr_normalize(addr).to_lowercase());
+            }
+        }
+        per_recipient_fields = per_recipient_fields.get(sz..).unwrap_or_default();
+    }
+    Ok(recipients)
+}
+
+/// Returns the address the message was delivered to.
+///
+/// `Delivered-To` headers are prepended by each MTA the message passes,
+/// so the topmost one is used. `X-Original-To` is used as a fallback.
+///
+/// Returned address is normalized and lowercased.
+fn get_delivered_to(headers: &[MailHeader]) -> Option<String> {
+    let value = headers
+        .get_header_value(HeaderDef::DeliveredTo)
+        .or_else(|| headers.get_header_value(HeaderDef::XOriginalTo))?;
+    let addr = value.trim().trim_start_matches('<').trim_end_matches('>');
+    let addr = addr_normalize(addr).to_lowercase();
+    if may_be_valid_addr(&addr) {
+        Some(addr)
+    } else {
+        None
+    }
+}
+
+/// Collects DKIM, SPF and DMARC verdicts from the values of the `Authentication-Results` headers
+/// added by the own provider, see [`Config::AuthservId`].
+///
+/// If the `authserv-id` is not known yet, it is learned from the topmost header
+/// of a message sent and signed by the user, such a header can only be added by the own provider.
+async fn get_authentication_results(
+    context: &Context,
+    values: &[String],
+    from: Option<&SingleInfo>,
+    signatures: &HashSet<Fingerprint>,
+) -> Result<AuthResults> {
+    let authserv_ids = match context.get_config(Config::AuthservId).await? {
+        Some(authserv_ids) => authserv_ids,
+        None => {
+            let (value, from) = match (values.first(), from) {
+                (Some(value), Some(from)) if !signatures.is_empty() => (value, from),
+                _ => return Ok(AuthResults::default()),
+            };
+            if !context.is_self_addr(&from.addr).await? {
+                return Ok(AuthResults::default());
+            }
+            let self_fingerprint = SignedPublicKey::load_self(context).await?.fingerprint();
+            if !signatures.contains(&self_fingerprint) {
+                return Ok(AuthResults::default());
+            }
+            let (authserv_id, _) = value.split_once(';').unwrap_or((value.as_str(), ""));
+            let authserv_id = parse_authserv_id(authserv_id);
+            info!(context, "Learned authserv-id {:?}.", authserv_id);
+            context
+                .set_config(Config::AuthservId, Some(&authserv_id))
+                .await?;
+            authserv_id
+        }
+    };
+    let from_domain = from
+        .and_then(|from| from.addr.rsplit_once('@'))
+        .map(|(_, domain)| domain);
+    Ok(parse_authentication_results(
+        values,
+        &authserv_ids,
+        from_domain,
+    ))
+}
+
+/// Parses the `authserv-id` that starts an `Authentication-Results` header.
+fn parse_authserv_id(authserv_id: &str) -> String {
+    // The `authserv-id` may be followed by a version number.
+    authserv_id
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+/// Collects DKIM, SPF and DMARC verdicts from the values of the `Authentication-Results` headers.
+///
+/// Each header starts with the `authserv-id` followed by `;`-separated results.
+/// Headers are prepended by each receiving server, so the topmost headers are added by our own
+/// server, headers further down may be added by other servers or forged by the sender.
+/// Only the topmost headers with one of the comma-separated `authserv_ids` are used,
+/// if the topmost header has another `authserv-id`, no verdicts are returned.
+/// DKIM and DMARC passes are only used if they are for `from_domain`.
+/// Of several results of a method, the topmost one is used.
+fn parse_authentication_results(
+    values: &[String],
+    authserv_ids: &str,
+    from_domain: Option<&str>,
+) -> AuthResults {
+    let mut auth_results = AuthResults::default();
+    for value in values {
+        let (authserv_id, results) = value.split_once(';').unwrap_or((value.as_str(), ""));
+        let authserv_id = parse_authserv_id(authserv_id);
+        if !authserv_ids
+            .split(',')
+            .any(|id| id.trim().eq_ignore_ascii_case(&authserv_id))
+        {
+            break;
+        }
+        for resinfo in results.split(';') {
+            if is_aligned_result(resinfo, from_domain) {
+                auth_results.add_results(resinfo);
+            }
+        }
+    }
+    auth_results
+}
+
+/// Returns false for a DKIM or DMARC pass of another domain than `from_domain`,
+/// e.g. a DKIM signature of the sender's mailing service says nothing about the From address.
+fn is_aligned_result(resinfo: &str, from_domain: Option<&str>) -> bool {
+    let mut tokens = resinfo.split_whitespace();
+    let (method, result) = match tokens.next().and_then(|token| token.split_once('=')) {
+        Some(pair) => pair,
+        None => return true,
+    };
+    let is_dkim = method.eq_ignore_ascii_case("dkim");
+    if !(is_dkim || method.eq_ignore_ascii_case("dmarc")) || !result.eq_ignore_ascii_case("pass") {
+        return true;
+    }
+    let from_domain = match from_domain {
+        Some(from_domain) => from_domain.to_lowercase(),
+        None => return false,
+    };
+    let domain = tokens
+        .filter_map(|token| token.split_once('='))
+        .find_map(|(key, value)| {
+            if key.eq_ignore_ascii_case(if is_dkim { "header.d" } else { "header.from" }) {
+                Some(value)
+            } else if is_dkim && key.eq_ignore_ascii_case("header.i") {
+                value.rsplit_once('@').map(|(_, domain)| domain)
+            } else {
+                None
+            }
+        });
+    domain.map_or(false, |domain| {
+        let domain = domain.trim_matches('"').to_lowercase();
+        from_domain == domain || from_domain.ends_with(&format!(".{}", domain))
+    })
+}
+
+/// Checks the multipart structure of a raw message against [`MAX_MIME_DEPTH`]
+/// and [`MAX_MIME_PARTS`] without parsing it.
+///
+/// Boundaries are collected from `boundary` parameters and a multipart is counted
+/// as opened when the first delimiter line of its boundary appears.
+fn check_mime_structure(body: &[u8]) -> Result<()> {
+    let mut declared = HashSet::new();
+    let mut open: Vec<&[u8]> = Vec::new();
+    let mut parts = 0;
+    for line in body.split(|&b| b == b'\n') {
+        if let Some(boundary) = get_boundary_param(line) {
+            declared.insert(boundary);
+            continue;
+        }
+        let delimiter = match line.strip_prefix(b"--") {
+            Some(delimiter) => {
+                let end = delimiter
+                    .iter()
+                    .rposition(|b| !b.is_ascii_whitespace())
+                    .map_or(0, |pos| pos + 1);
+                delimiter.get(..end).unwrap_or_default()
+            }
+            None => continue,
+        };
+        if let Some(pos) = open.iter().position(|boundary| *boundary == delimiter) {
+            open.truncate(pos + 1);
+            parts += 1;
+        } else if let Some(pos) = delimiter
+            .strip_suffix(b"--")
+            .and_then(|boundary| open.iter().position(|open| *open == boundary))
+        {
+            open.truncate(pos);
+        } else if declared.contains(delimiter) {
+            open.push(delimiter);
+            parts += 1;
+        }
+        ensure!(
+            open.len() <= MAX_MIME_DEPTH && parts <= MAX_MIME_PARTS,
+            "MIME structure too complex (depth {}, {} parts)",
+            open.len(),
+            parts
+        );
+    }
+    Ok(())
+}
+
+/// Returns the value of a `boundary` parameter in a raw header line.
+fn get_boundary_param(line: &[u8]) -> Option<&[u8]> {
+    const PARAM: &[u8] = b"boundary=";
+    let pos = line
+        .windows(PARAM.len())
+        .position(|window| window.eq_ignore_ascii_case(PARAM))?;
+    let value = line.get(pos + PARAM.len()..)?;
+    let value = if let Some(quoted) = value.strip_prefix(b"\"") {
+        quoted.get(..quoted.iter().position(|&b| b == b'"')?)?
+    } else {
+        let end = value
+            .iter()
+            .position(|&b| b == b';' || b.is_ascii_whitespace())
+            .unwrap_or(value.len());
+        value.get(..end)?
+    };
+    Some(value).filter(|value| !value.is_empty())
+}
+
+/// Returns the HTML following the signature separator (`-- ` on a line of its own).
+///
+/// The separator may be followed by a line break or be the only content of a paragraph.
+fn get_html_footer(html: &str) -> Option<String> {
+    static SEPARATOR: Lazy<regex::Regex> = Lazy::new(|| {
+        regex::Regex::new(
+            r"(?i)(?:^|\n|<br\s*/?>|<p[^>]*>|<div[^>]*>)[ \t]*--(?:[ \t]|&nbsp;|&#160;)*(?:\r?\n|<br\s*/?>|</p>|</div>)",
+        )
+        .unwrap()
+    });
+    let footer = html.get(SEPARATOR.find(html)?.end()..)?.trim();
+    if footer.is_empty() {
+        None
+    } else {
+        Some(footer.to_string())
+    }
+}
+
+/// Extracts a URL from a `List-*` header as defined in RFC 2369.
+///
+/// The header contains a comma-separated list of URLs in angle brackets,
+/// possibly interspersed with comments in parentheses.
+/// Whitespace inside angle brackets is ignored.
+/// HTTP(S) URLs are preferred, otherwise the first URL is returned.
+fn parse_list_url_header(value: &str) -> Option<String> {
+    let mut urls = Vec::new();
+    let mut url = String::new();
+    let mut comment_depth = 0;
+    let mut in_url = false;
+    for c in value.chars() {
+        match c {
+            '(' if !in_url => comment_depth += 1,
+            ')' if !in_url && comment_depth > 0 => comment_depth -= 1,
+            '<' if !in_url && comment_depth == 0 => {
+                in_url = true;
+                url.clear();
+            }
+            '>' if in_url => {
+                in_url = false;
+                if !url.is_empty() {
+                    urls.push(url.clone());
+                }
+            }
+            c if in_url && !c.is_whitespace() => url.push(c),
+            _ => {}
+        }
+    }
+
+    urls.iter()
+        .find(|url| {
+            let url = url.to_lowercase();
+            url.starts_with("https://") || url.starts_with("http://")
+        })
+        .or_else(|| urls.first())
+        .cloned()
+}
+
+/// Returns the first language tag of a `Content-Language` header value, see RFC 3282.
+///
+/// Tags that are not well-formed are ignored.
+fn parse_content_language(value: &str) -> Option<String> {
+    let tag = value
+        .split(',')
+        .map(str::trim)
+        .find(|tag| !tag.is_empty())?;
+    if tag.len() <= 35 && tag.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        Some(tag.to_string())
+    } else {
+        None
+    }
+}
+
 /// Returned addresses are normalized and lowercased.
 pub(crate) fn get_list_post(headers: &[MailHeader]) -> Option<String> {
     get_all_addresses_from_header(headers, |header_key| header_key == "list-post")
@@ -2209,6 +2734,23 @@ async fn test_mimeparser_with_context() {
             .is_none());
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_mimeparser_strips_bom() {
+        let t = TestContext::new_alice().await;
+        let raw = "From: bob@example.net\n\
+                   To: alice@example.org\n\
+                   Subject: bom\n\
+                   Message-ID: <bom@example.net>\n\
+                   Date: Sun, 22 Mar 2020 22:37:57 +0000\n\
+                   Chat-Version: 1.0\n\
+                   Content-Type: text/plain; charset=utf-8\n\
+                   \n\
+                   \u{feff}Hello";
+        let mimeparser = MimeMessage::from_bytes(&t, raw.as_bytes()).await.unwrap();
+        assert_eq!(mimeparser.parts.len(), 1);
+        assert_eq!(mimeparser.parts[0].msg, "Hello");
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_mimeparser_with_avatars() {
         let t = TestContext::new().await;
@@ -3299,4 +3841,300 @@ async fn test_ms_exchange_mdn() -> Result<()> {
 
         Ok(())
     }
+
+    #[test]
+    fn test_parse_list_url_header() {
+        assert_eq!(parse_list_url_header(""), None);
+        assert_eq!(parse_list_url_header("no url here"), None);
+        assert_eq!(
+            parse_list_url_header("<mailto:list@example.org?subject=help>"),
+            Some("mailto:list@example.org?subject=help".to_string())
+        );
+        assert_eq!(
+            parse_list_url_header(
+                "<mailto:list-request@example.org?subject=help> (List Instructions),\n <https://example.org/list/help>"
+            ),
+            Some("https://example.org/list/help".to_string())
+        );
+        assert_eq!(
+            parse_list_url_header(
+                "(Web Archive <https://ignored.example>) <https://example.org/ archive/>"
+            ),
+            Some("https://example.org/archive/".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_content_language() {
+        assert_eq!(parse_content_language("de"), Some("de".to_string()));
+        assert_eq!(
+            parse_content_language(" en-US, fr"),
+            Some("en-US".to_string())
+        );
+        assert_eq!(parse_content_language(", mi"), Some("mi".to_string()));
+        assert_eq!(parse_content_language(""), None);
+        assert_eq!(parse_content_language("de; q=1"), None);
+    }
+
+    #[test]
+    fn test_get_delivered_to() -> Result<()> {
+        let (headers, _) = mailparse::parse_headers(
+            b"Delivered-To: <Alias@Example.org>\n\
+              X-Original-To: other@example.org\n\
+              Delivered-To: alice@example.org\n\
+              \n",
+        )?;
+        assert_eq!(
+            get_delivered_to(&headers),
+            Some("alias@example.org".to_string())
+        );
+
+        let (headers, _) = mailparse::parse_headers(b"X-Original-To: alias@example.org\n\n")?;
+        assert_eq!(
+            get_delivered_to(&headers),
+            Some("alias@example.org".to_string())
+        );
+
+        let (headers, _) = mailparse::parse_headers(b"Delivered-To: not an address\n\n")?;
+        assert_eq!(get_delivered_to(&headers), None);
+
+        let (headers, _) = mailparse::parse_headers(b"Subject: foo\n\n")?;
+        assert_eq!(get_delivered_to(&headers), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_authentication_results() -> Result<()> {
+        let parse = |raw: &[u8], from_domain| -> Result<AuthResults> {
+            let (headers, _) = mailparse::parse_headers(raw)?;
+            let values = headers.get_all_values(HeaderDef::AuthenticationResults.get_headername());
+            Ok(parse_authentication_results(
+                &values,
+                "other.example.org, mx.example.org",
+                from_domain,
+            ))
+        };
+
+        let raw = b"Authentication-Results: mx.example.org;\n\
+              \tdkim=pass (2048-bit key) header.d=example.com header.i=@example.com;\n\
+              \tspf=FAIL smtp.mailfrom=bob@example.com\n\
+              Authentication-Results: MX.example.org 1; dkim=fail; dmarc=pass header.from=example.com\n\
+              \n";
+        assert_eq!(
+            parse(raw, Some("example.com"))?,
+            AuthResults {
+                dkim: Some("pass".to_string()),
+                spf: Some("fail".to_string()),
+                dmarc: Some("pass".to_string()),
+            }
+        );
+
+        // Passes for another domain than the From domain are ignored.
+        assert_eq!(
+            parse(raw, Some("example.net"))?,
+            AuthResults {
+                dkim: Some("fail".to_string()),
+                spf: Some("fail".to_string()),
+                dmarc: None,
+            }
+        );
+        assert_eq!(
+            parse(raw, Some("sub.example.com"))?.dkim,
+            Some("pass".to_string())
+        );
+
+        // Headers of other servers below the ones of our own server are ignored.
+        let raw = b"Authentication-Results: mx.example.org; dkim=fail\n\
+              Authentication-Results: evil.example.net; dkim=pass header.d=example.com\n\
+              Authentication-Results: mx.example.org; spf=pass\n\
+              \n";
+        assert_eq!(
+            parse(raw, Some("example.com"))?,
+            AuthResults {
+                dkim: Some("fail".to_string()),
+                spf: None,
+                dmarc: None,
+            }
+        );
+
+        // Headers forged by the sender are ignored if the own server did not add one.
+        let raw = b"Authentication-Results: evil.example.net; dkim=pass header.d=example.com\n\
+              Authentication-Results: mx.example.org; dkim=pass header.d=example.com\n\
+              \n";
+        assert!(parse(raw, Some("example.com"))?.is_empty());
+
+        let raw = b"Authentication-Results: mx.example.org; none\n\n";
+        assert!(parse(raw, Some("example.com"))?.is_empty());
+
+        assert!(parse(b"Subject: foo\n\n", Some("example.com"))?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_html_footer() {
+        assert_eq!(
+            get_html_footer("<p>Hi</p><p>-- <br><b>Bob</b></p>"),
+            Some("<b>Bob</b></p>".to_string())
+        );
+        assert_eq!(
+            get_html_footer("Hi<br>--&nbsp;<br/>\n<i>Bob</i>"),
+            Some("<i>Bob</i>".to_string())
+        );
+        assert_eq!(
+            get_html_footer("<div>Hi</div><div>--</div><div>Bob</div>"),
+            Some("<div>Bob</div>".to_string())
+        );
+        assert_eq!(get_html_footer("<p>a -- b</p><!-- comment -->"), None);
+        assert_eq!(get_html_footer("<p>Hi</p><p>-- </p>"), None);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_mime_depth_limit() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        let depth = MAX_MIME_DEPTH * 4;
+        let mut raw = "From: bob@example.net\n\
+                       To: alice@example.org\n\
+                       Subject: Nested\n\
+                       Message-ID: <nested@example.net>\n\
+                       Date: Sun, 22 Mar 2020 22:37:57 +0000\n"
+            .to_string();
+        for i in 0..depth {
+            raw += &format!(
+                "Content-Type: multipart/mixed; boundary=\"boundary{:04}\"\n\n--boundary{:04}\n",
+                i, i
+            );
+        }
+        raw += "Content-Type: text/plain\n\ndeep text\n";
+        for i in (0..depth).rev() {
+            raw += &format!("--boundary{:04}--\n", i);
+        }
+
+        // The message is rejected before it is parsed.
+        assert!(check_mime_structure(raw.as_bytes()).is_err());
+        assert!(MimeMessage::from_bytes(&t, raw.as_bytes()).await.is_err());
+        assert!(receive_imf(&t, raw.as_bytes(), false).await?.is_none());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_mime_parts_limit() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        t.set_config(Config::ShowEmails, Some("2")).await?;
+        let mut raw = "From: bob@example.net\n\
+                       To: alice@example.org\n\
+                       Subject: Many parts\n\
+                       Message-ID: <manyparts@example.net>\n\
+                       Date: Sun, 22 Mar 2020 22:37:57 +0000\n\
+                       Content-Type: multipart/mixed; boundary=\"boundary\"\n\
+                       \n"
+        .to_string();
+        let mut too_many = raw.clone();
+        for i in 0..MAX_MIME_PARTS {
+            raw += &format!("--boundary\nContent-Type: text/plain\n\npart {}\n", i);
+        }
+        raw += "--boundary--\n";
+        for i in 0..MAX_MIME_PARTS * 3 {
+            too_many += &format!("--boundary\nContent-Type: text/plain\n\npart {}\n", i);
+        }
+        too_many += "--boundary--\n";
+
+        let mimeparser = MimeMessage::from_bytes(&t, raw.as_bytes()).await?;
+        assert_eq!(mimeparser.parts.len(), MAX_MIME_PARTS);
+
+        assert!(MimeMessage::from_bytes(&t, too_many.as_bytes())
+            .await
+            .is_err());
+        assert!(receive_imf(&t, too_many.as_bytes(), false).await?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_mime_structure() {
+        assert!(check_mime_structure(b"Subject: foo\n\nno multipart\n").is_ok());
+
+        // Delimiters of undeclared boundaries and boundaries that are never used are ignored.
+        let mut raw = "Content-Type: text/plain; boundary=\"unused\"\n\n".to_string();
+        for _ in 0..MAX_MIME_PARTS * 2 {
+            raw += "--unknown\n";
+        }
+        assert!(check_mime_structure(raw.as_bytes()).is_ok());
+
+        // Closed multiparts do not count towards the depth.
+        let mut raw = String::new();
+        for i in 0..MAX_MIME_DEPTH + 10 {
+            raw += &format!(
+                "Content-Type: multipart/mixed;\r\n boundary=b{}\r\n\r\n--b{}\r\n\r\n--b{}-- \r\n",
+                i, i, i
+            );
+        }
+        assert!(check_mime_structure(raw.as_bytes()).is_ok());
+        assert_eq!(
+            get_boundary_param(b" BOUNDARY=\"a b\"; x=y"),
+            Some(&b"a b"[..])
+        );
+        assert_eq!(get_boundary_param(b"boundary=ab; x=y"), Some(&b"ab"[..]));
+        assert_eq!(get_boundary_param(b"boundary="), None);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_attachment_size_limit() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        let raw = "From: bob@example.net\n\
+                   To: alice@example.org\n\
+                   Subject: Large attachment\n\
+                   Message-ID: <large-attachment@example.net>\n\
+                   Date: Sun, 22 Mar 2020 22:37:57 +0000\n\
+                   Chat-Version: 1.0\n\
+                   Content-Type: multipart/mixed; boundary=\"XXX\"\n\
+                   \n\
+                   --XXX\n\
+                   Content-Type: text/plain; charset=utf-8\n\
+                   \n\
+                   See attachment\n\
+                   --XXX\n\
+                   Content-Type: application/octet-stream\n\
+                   Content-Disposition: attachment; filename=\"data.bin\"\n\
+                   \n\
+                   0123456789abcdefghij\n\
+                   --XXX--\n";
+
+        let mimeparser = MimeMessage::from_bytes(&t, raw.as_bytes()).await?;
+        assert_eq!(mimeparser.parts.len(), 1);
+        assert_eq!(mimeparser.parts[0].typ, Viewtype::File);
+        assert!(!mimeparser.parts[0].param.exists(Param::AttachmentSkipped));
+
+        t.set_config(Config::MaxBlobSizeBytes, Some("10")).await?;
+        let mimeparser = MimeMessage::from_bytes(&t, raw.as_bytes()).await?;
+        assert_eq!(mimeparser.parts.len(), 2);
+        assert_eq!(mimeparser.parts[0].msg, "See attachment");
+        let part = &mimeparser.parts[1];
+        assert_eq!(part.typ, Viewtype::Text);
+        assert!(part.msg.starts_with("[Attachment too large: "));
+        assert_eq!(part.param.get_bool(Param::AttachmentSkipped), Some(true));
+        assert!(part.param.get(Param::File).is_none());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_text_part_size_limit() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        let raw = format!(
+            "From: bob@example.net\n\
+             To: alice@example.org\n\
+             Subject: Large\n\
+             Message-ID: <large@example.net>\n\
+             Date: Sun, 22 Mar 2020 22:37:57 +0000\n\
+             Content-Type: text/plain; charset=utf-8\n\
+             \n\
+             {}\n",
+            "ä".repeat(MAX_TEXT_PART_BYTES)
+        );
+
+        let mimeparser = MimeMessage::from_bytes(&t, raw.as_bytes()).await?;
+        assert_eq!(mimeparser.parts.len(), 1);
+        let msg_raw = mimeparser.parts[0].msg_raw.as_ref().unwrap();
+        assert_eq!(msg_raw.len(), MAX_TEXT_PART_BYTES);
+        assert!(mimeparser.is_mime_modified);
+        Ok(())
+    }
 }
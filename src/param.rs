@@ -69,6 +69,10 @@ pub enum Param {
     /// For Messages: quoted text.
     Quote = b'q',
 
+    /// For Group Messages: space-separated IDs of the contacts
+    /// the message was addressed to in To: and Cc:.
+    MessageRecipients = b'Y',
+
     /// For Messages
     Cmd = b'S',
 
@@ -154,6 +158,29 @@ pub enum Param {
     /// For Contacts and Chats: timestamp of avatar update.
     AvatarTimestamp = b'J',
 
+    /// For Contacts: profile image received in an unencrypted message.
+    ///
+    /// Such an avatar may be spoofed, so it is not used as [`Param::ProfileImage`]
+    /// until an encrypted message carries an avatar or the user confirms it.
+    UnconfirmedProfileImage = b'X',
+
+    /// For Groups: set if the group is a placeholder
+    /// created for a message that could not be decrypted.
+    GroupPlaceholder = b'Z',
+
+    /// For Mailinglists: description taken from the `List-Id` header.
+    ListDescription = b'x',
+
+    /// For Mailinglists: URL from the `List-Help` header.
+    ListHelp = b'y',
+
+    /// For Mailinglists: URL from the `List-Archive` header.
+    ListArchive = b'z',
+
+    /// For Mailinglists: timestamp of the last update of
+    /// [`Param::ListDescription`], [`Param::ListHelp`] and [`Param::ListArchive`].
+    ListMetadataTimestamp = b'v',
+
     /// For Chats: timestamp of status/signature/footer update.
     EphemeralSettingsTimestamp = b'B',
 
@@ -180,6 +207,79 @@ pub enum Param {
 
     /// For Webxdc Message Instances: timestamp of summary update.
     WebxdcSummaryTimestamp = b'Q',
+
+    /// For Messages: comma-separated list of labels set by the user,
+    /// see [`crate::message::add_label`].
+    Labels = b'0',
+
+    /// For Messages: the original Message-ID if it was reused by the sender
+    /// and the message was stored with a synthetic Message-ID instead.
+    OriginalRfc724Mid = b'1',
+
+    /// For Messages: set to 1 if the message had no recipients in To/Cc,
+    /// i.e. we received it as a blind (Bcc) copy.
+    BccDelivery = b'2',
+
+    /// For Groups: text sent as 1:1 message to members added to the group,
+    /// see [`crate::chat::set_welcome_message`].
+    WelcomeMessage = b'3',
+
+    /// For Messages: set to 1 if the message was saved from ephemeral deletion,
+    /// see [`crate::message::MsgId::save_from_ephemeral`].
+    Saved = b'4',
+
+    /// For Messages: address from the `Delivered-To` or `X-Original-To` header,
+    /// see [`crate::message::Message::get_delivered_to`].
+    DeliveredTo = b'5',
+
+    /// For Messages: DKIM, SPF and DMARC verdicts from the `Authentication-Results` header,
+    /// see [`crate::message::Message::authentication_results`].
+    AuthResults = b'6',
+
+    /// For Messages: set to 1 if the signature of the sender was taken from an HTML part,
+    /// see [`crate::message::Message::has_html_status`].
+    HtmlStatus = b'7',
+
+    /// For Messages: reactions of contacts to the message,
+    /// one `<contact id> <reaction>` pair per line,
+    /// see [`crate::message::Message::reactions`].
+    Reactions = b'8',
+
+    /// For Contacts: set to 1 if the signature of the contact should not be stored as status,
+    /// see [`crate::contact::Contact::set_track_status`].
+    StatusTrackingDisabled = b'9',
+
+    /// For Messages: full size in bytes of a partially downloaded message,
+    /// see [`crate::message::Message::download_size`].
+    DownloadSize = b'#',
+
+    /// For Messages: set to 1 if an attachment was not stored
+    /// because it exceeded [`crate::config::Config::MaxBlobSizeBytes`].
+    AttachmentSkipped = b'$',
+
+    /// For Chats: set to 1 if [`crate::config::Config::DefaultEphemeralTimer`]
+    /// was applied on creation and the timer was not yet announced to the chat members.
+    EphemeralTimerUnannounced = b'%',
+
+    /// For Messages: set to 1 if the HTML part references remote content,
+    /// which is blocked if [`crate::config::Config::BlockRemoteContent`] is set.
+    HasRemoteContent = b'&',
+
+    /// For Messages: language tag from the `Content-Language` header,
+    /// see [`crate::message::Message::language`].
+    Language = b'(',
+
+    /// For Messages: set to 1 if the `Date` header was in the future when the message was received,
+    /// indicating a wrong clock of the sender or of this device.
+    FutureDated = b')',
+
+    /// For Chats: set to 1 once an info message telling that the clock of a sender
+    /// seems to be wrong was added to the chat.
+    SenderClockWarned = b'+',
+
+    /// For Messages: size of the attached file in bytes when it was stored,
+    /// used to compute statistics without reading the file metadata.
+    Bytes = b'*',
 }
 
 /// An object for handling key=value parameter lists.
@@ -274,6 +374,14 @@ pub fn remove(&mut self, key: Param) -> &mut Self {
         self
     }
 
+    /// Sets all keys from `other` that are not set yet, existing values are kept.
+    pub fn merge_missing(&mut self, other: &Params) -> &mut Self {
+        for (key, value) in &other.inner {
+            self.inner.entry(*key).or_insert_with(|| value.clone());
+        }
+        self
+    }
+
     /// Check if there are any values in this.
     pub fn is_empty(&self) -> bool {
         self.inner.is_empty()
@@ -478,6 +586,14 @@ fn test_dc_param() {
         assert_eq!(p1.len(), 0)
     }
 
+    #[test]
+    fn test_merge_missing() {
+        let mut p1: Params = "a=1\nc=3".parse().unwrap();
+        let p2: Params = "a=2\nd=4".parse().unwrap();
+        p1.merge_missing(&p2);
+        assert_eq!(p1.to_string(), "a=1\nc=3\nd=4");
+    }
+
     #[test]
     fn test_roundtrip() {
         let mut params = Params::new();
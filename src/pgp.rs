@@ -1,6 +1,7 @@
 //! OpenPGP helper module using [rPGP facilities](https://github.com/rpgp/rpgp).
 
 use std::collections::{BTreeMap, HashSet};
+use std::fmt;
 use std::io;
 use std::io::Cursor;
 
@@ -10,20 +11,21 @@
     Deserializable, KeyType as PgpKeyType, Message, SecretKeyParamsBuilder, SignedPublicKey,
     SignedPublicSubKey, SignedSecretKey, StandaloneSignature, SubkeyParamsBuilder,
 };
-use pgp::crypto::{HashAlgorithm, SymmetricKeyAlgorithm};
+use pgp::crypto::{ECCCurve, HashAlgorithm, SymmetricKeyAlgorithm};
 use pgp::types::{
-    CompressionAlgorithm, KeyTrait, Mpi, PublicKeyTrait, SecretKeyTrait, StringToKey,
+    CompressionAlgorithm, KeyTrait, Mpi, PublicKeyTrait, PublicParams, SecretKeyTrait, StringToKey,
 };
 use rand::{thread_rng, CryptoRng, Rng};
 use tokio::runtime::Handle;
 
 use crate::constants::KeyGenType;
-use crate::key::{DcKey, Fingerprint};
+use crate::key::{public_key_expiration_timestamp, DcKey, Fingerprint};
 use crate::keyring::Keyring;
-use crate::tools::EmailAddress;
+use crate::tools::{time, EmailAddress};
 
 pub const HEADER_AUTOCRYPT: &str = "autocrypt-prefer-encrypt";
 pub const HEADER_SETUPCODE: &str = "passphrase-begin";
+pub const HEADER_PASSPHRASE_FORMAT: &str = "passphrase-format";
 
 /// A wrapper for rPGP public key types
 #[derive(Debug)]
@@ -192,6 +194,94 @@ pub(crate) fn create_keypair(addr: EmailAddress, keygen_type: KeyGenType) -> Res
     })
 }
 
+/// Rough classification of the cryptographic strength of a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyStrength {
+    /// RSA keys shorter than 2048 bits.
+    Weak,
+    /// RSA keys of at least 2048 bits.
+    Adequate,
+    /// RSA keys of at least 4096 bits, Ed25519 and Curve25519 keys.
+    Strong,
+    /// Algorithm is not classified.
+    Unknown,
+}
+
+/// Summary of the algorithm, size and validity of a public key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyStrengthReport {
+    /// Public key algorithm of the primary key, e.g. `RSA` or `EdDSA`.
+    pub algorithm: String,
+    /// Size of the key in bits, `None` if unknown.
+    pub key_size_bits: Option<u32>,
+    /// Creation timestamp of the primary key.
+    pub creation_date: i64,
+    /// Timestamp after which the key can no longer be used, `None` if it never expires.
+    pub expiration_date: Option<i64>,
+    /// Whether the expiration date is in the past.
+    pub is_expired: bool,
+    /// Classification of the key.
+    pub strength: KeyStrength,
+}
+
+impl fmt::Display for KeyStrengthReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}, {}", self.strength, self.algorithm)?;
+        if let Some(bits) = self.key_size_bits {
+            write!(f, " {} bits", bits)?;
+        }
+        if self.is_expired {
+            write!(f, ", expired")?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the size of a big-endian integer in bits.
+fn mpi_bits(mpi: &Mpi) -> u32 {
+    let mut bytes = mpi.as_bytes().iter().skip_while(|b| **b == 0);
+    match bytes.next() {
+        Some(first) => bytes.count() as u32 * 8 + (8 - first.leading_zeros()),
+        None => 0,
+    }
+}
+
+/// Classifies the primary key of `key` by algorithm and size.
+pub fn key_strength_report(key: &SignedPublicKey) -> KeyStrengthReport {
+    let (key_size_bits, strength) = match key.primary_key.public_params() {
+        PublicParams::RSA { n, .. } => {
+            let bits = mpi_bits(n);
+            let strength = if bits >= 4096 {
+                KeyStrength::Strong
+            } else if bits >= 2048 {
+                KeyStrength::Adequate
+            } else {
+                KeyStrength::Weak
+            };
+            (Some(bits), strength)
+        }
+        PublicParams::EdDSA {
+            curve: ECCCurve::Ed25519,
+            ..
+        }
+        | PublicParams::ECDH {
+            curve: ECCCurve::Curve25519,
+            ..
+        } => (Some(256), KeyStrength::Strong),
+        _ => (None, KeyStrength::Unknown),
+    };
+    let expiration_date = public_key_expiration_timestamp(key);
+
+    KeyStrengthReport {
+        algorithm: format!("{:?}", key.algorithm()),
+        key_size_bits,
+        creation_date: key.primary_key.created_at().timestamp(),
+        expiration_date,
+        is_expired: expiration_date.map_or(false, |expires_at| expires_at <= time()),
+        strength,
+    }
+}
+
 /// Select public key or subkey to use for encryption.
 ///
 /// First, tries to use subkeys. If none of the subkeys are suitable
@@ -430,6 +520,22 @@ fn test_create_keypair() {
         assert_ne!(keypair0.public, keypair1.public);
     }
 
+    #[test]
+    fn test_key_strength_report() {
+        let report = key_strength_report(&alice_keypair().public);
+        assert_eq!(report.strength, KeyStrength::Strong);
+        assert_eq!(report.key_size_bits, Some(256));
+        assert_eq!(report.expiration_date, None);
+        assert!(!report.is_expired);
+
+        // Bob's key from `test-data/key/` is an RSA-2048 key.
+        let report = key_strength_report(&bob_keypair().public);
+        assert_eq!(report.strength, KeyStrength::Adequate);
+        assert_eq!(report.key_size_bits, Some(2048));
+        assert!(report.creation_date > 0);
+        assert!(!report.is_expired);
+    }
+
     /// [Key] objects to use in tests.
     struct TestKeys {
         alice_secret: SignedSecretKey,
@@ -3,19 +3,23 @@
 use std::cmp::min;
 use std::collections::HashSet;
 use std::convert::TryFrom;
+use std::fmt;
 
 use anyhow::{bail, ensure, Context as _, Result};
 use mailparse::{parse_mail, SingleInfo};
 use num_traits::FromPrimitive;
 use once_cell::sync::Lazy;
 use regex::Regex;
+use sha1::{Digest, Sha1};
 
-use crate::chat::{self, Chat, ChatId, ChatIdBlocked, ProtectionStatus};
+use crate::blob::{self, BlobObject};
+use crate::chat::{self, Chat, ChatId, ChatIdBlocked, MuteDuration, ProtectionStatus, ReplyKind};
 use crate::config::Config;
 use crate::constants::{Blocked, Chattype, ShowEmails, DC_CHAT_ID_TRASH};
 use crate::contact;
 use crate::contact::{
-    may_be_valid_addr, normalize_name, Contact, ContactId, Origin, VerifiedStatus,
+    addr_cmp, addr_normalize, normalize_name, normalize_recipient_list, Contact, ContactId,
+    LastSeenSource, Origin, VerifiedStatus,
 };
 use crate::context::Context;
 use crate::download::DownloadState;
@@ -23,10 +27,12 @@
 use crate::events::EventType;
 use crate::headerdef::{HeaderDef, HeaderDefMap};
 use crate::imap::markseen_on_imap_table;
+use crate::imex;
 use crate::location;
 use crate::log::LogExt;
 use crate::message::{
-    self, rfc724_mid_exists, Message, MessageState, MessengerMessage, MsgId, Viewtype,
+    self, get_blob_bytes, rfc724_mid_exists, Message, MessageState, MessengerMessage, MsgId,
+    Viewtype,
 };
 use crate::mimeparser::{
     parse_message_id, parse_message_ids, AvatarAction, MailinglistType, MimeMessage, SystemMessage,
@@ -36,7 +42,17 @@
 use crate::securejoin::{self, handle_securejoin_handshake, observe_securejoin_on_other_device};
 use crate::sql;
 use crate::stock_str;
-use crate::tools::{create_id, extract_grpid_from_rfc724_mid, smeared_time};
+use crate::tools::{
+    create_id, create_smeared_timestamp, extract_grpid_from_rfc724_mid, get_abs_path, smeared_time,
+    timestamp_to_str,
+};
+
+/// Messages dated further in the future than this number of seconds
+/// are flagged with [`Param::FutureDated`].
+const FUTURE_DATE_TOLERANCE: i64 = 60;
+
+/// Number of future-dated messages within a day after which a wrong clock is reported.
+const FUTURE_DATED_WARN_COUNT: usize = 3;
 
 /// This is the struct that is returned after receiving one email (aka MIME message).
 ///
@@ -73,6 +89,239 @@ pub async fn receive_imf(
     receive_imf_inner(context, &rfc724_mid, imf_raw, seen, None, false).await
 }
 
+/// One decision taken while assigning a received message to a chat.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReceptionStep {
+    /// What was checked, e.g. `reply parent`.
+    pub check: String,
+
+    /// Result of the check, e.g. `none` or `found Chat#12`.
+    pub outcome: String,
+}
+
+impl fmt::Display for ReceptionStep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} → {}", self.check, self.outcome)
+    }
+}
+
+/// Decisions taken by [`receive_imf_trace`] in the order they were taken.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ReceptionTrace {
+    pub steps: Vec<ReceptionStep>,
+
+    /// Chat the message would be assigned to,
+    /// `None` if a new chat would be created or no chat was found.
+    pub chat_id: Option<ChatId>,
+}
+
+impl ReceptionTrace {
+    fn push(&mut self, check: &str, outcome: impl Into<String>) {
+        self.steps.push(ReceptionStep {
+            check: check.to_string(),
+            outcome: outcome.into(),
+        });
+    }
+
+    /// Returns the outcome of the first step with the given check.
+    pub fn outcome(&self, check: &str) -> Option<&str> {
+        self.steps
+            .iter()
+            .find(|step| step.check == check)
+            .map(|step| step.outcome.as_str())
+    }
+}
+
+impl fmt::Display for ReceptionTrace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for step in &self.steps {
+            writeln!(f, "{}", step)?;
+        }
+        Ok(())
+    }
+}
+
+/// Traces how [`receive_imf`] would assign a message to a chat, without adding it.
+///
+/// This follows the chat assignment of `add_parts()` as a dry run: the chat lookups
+/// are done with creation disallowed and the message is not added to the database,
+/// even if a message with the same Message-ID was received before.
+/// Nothing is created and no group changes are applied.
+/// If the message would create a new chat, [`ReceptionTrace::chat_id`] is `None`.
+/// Note that parsing an encrypted message may still update the sender's peerstate.
+pub async fn receive_imf_trace(context: &Context, imf_raw: &[u8]) -> Result<ReceptionTrace> {
+    let mime_parser = MimeMessage::from_bytes(context, imf_raw)
+        .await
+        .context("can't parse mail")?;
+    let mut trace = ReceptionTrace::default();
+
+    let from_id = match mime_parser.from.first() {
+        Some(from) => Contact::lookup_id_by_addr(context, &from.addr, Origin::Unknown).await?,
+        None => None,
+    };
+    trace.push(
+        "sender",
+        from_id.map_or_else(|| "unknown contact".to_string(), |id| id.to_string()),
+    );
+    let incoming = from_id != Some(ContactId::SELF);
+    let from_id = from_id.unwrap_or(ContactId::UNDEFINED);
+    trace.push("direction", if incoming { "incoming" } else { "outgoing" });
+
+    let mut to_ids = Vec::new();
+    for recipient in &mime_parser.recipients {
+        if let Some(id) =
+            Contact::lookup_id_by_addr(context, &recipient.addr, Origin::Unknown).await?
+        {
+            to_ids.push(id);
+        }
+    }
+    if incoming && mime_parser.recipients.is_empty() {
+        trace.push("recipients", "none, Bcc delivery");
+        to_ids.push(ContactId::SELF);
+    }
+
+    let parent = get_parent_message(context, &mime_parser).await?;
+    match &parent {
+        Some(parent) => trace.push(
+            "reply parent",
+            format!("found {} in {}", parent.id, parent.chat_id),
+        ),
+        None => trace.push("reply parent", "none"),
+    }
+
+    let is_dc_message = if mime_parser.has_chat_version() {
+        MessengerMessage::Yes
+    } else if let Some(parent) = &parent {
+        match parent.is_dc_message {
+            MessengerMessage::No => MessengerMessage::No,
+            MessengerMessage::Yes | MessengerMessage::Reply => MessengerMessage::Reply,
+        }
+    } else {
+        MessengerMessage::No
+    };
+    trace.push("messenger message", format!("{:?}", is_dc_message));
+
+    let mut chat_id = None;
+    if mime_parser.is_system_message != SystemMessage::AutocryptSetupMessage
+        && is_dc_message == MessengerMessage::No
+    {
+        let show_emails = ShowEmails::from_i32(context.get_config_int(Config::ShowEmails).await?)
+            .unwrap_or_default();
+        trace.push("show_emails", format!("{:?}", show_emails));
+        if show_emails == ShowEmails::Off {
+            chat_id = Some(DC_CHAT_ID_TRASH);
+        }
+    }
+
+    if chat_id.is_none() && mime_parser.get_header(HeaderDef::SecureJoin).is_some() {
+        trace.push(
+            "secure-join",
+            "handshake message, chat depends on the handshake",
+        );
+    }
+    if chat_id.is_none() && incoming && mime_parser.delivery_report.is_some() {
+        trace.push("delivery report", "yes");
+        chat_id = Some(DC_CHAT_ID_TRASH);
+    }
+    if chat_id.is_none()
+        && !incoming
+        && mime_parser
+            .get_header(HeaderDef::XMozillaDraftInfo)
+            .is_some()
+    {
+        trace.push("draft", "yes");
+        chat_id = Some(DC_CHAT_ID_TRASH);
+    }
+
+    if chat_id.is_none() {
+        match lookup_chat_by_reply(context, &mime_parser, &parent, &to_ids, from_id).await? {
+            Some((id, _)) => {
+                trace.push("chat of reply parent", format!("found {}", id));
+                chat_id = Some(id);
+            }
+            None => trace.push("chat of reply parent", "none"),
+        }
+    }
+
+    if chat_id.is_none() {
+        match try_getting_grpid(&mime_parser) {
+            Some(grpid) => match chat::get_chat_id_by_grpid(context, &grpid).await? {
+                Some((id, _, _)) => {
+                    trace.push("grpid", format!("{}, found {}", grpid, id));
+                    chat_id = Some(id);
+                }
+                None => trace.push("grpid", format!("{}, no chat", grpid)),
+            },
+            None => trace.push("grpid", "none"),
+        }
+    }
+
+    if chat_id.is_none() && incoming {
+        let mailinglist_type = mime_parser.get_mailinglist_type();
+        trace.push("mailinglist type", format!("{:?}", mailinglist_type));
+        let list_id_header = match mailinglist_type {
+            MailinglistType::ListIdBased => mime_parser.get_header(HeaderDef::ListId),
+            MailinglistType::SenderBased => mime_parser.get_header(HeaderDef::Sender),
+            MailinglistType::None => None,
+        };
+        if let Some(list_id_header) = list_id_header {
+            let (_, listid) = parse_list_id_header(list_id_header);
+            match create_or_lookup_mailinglist(context, false, list_id_header, &mime_parser).await?
+            {
+                Some((id, _)) => {
+                    trace.push("mailing list chat", format!("{}, found {}", listid, id));
+                    chat_id = Some(id);
+                }
+                None => trace.push("mailing list chat", format!("{}, no chat", listid)),
+            }
+        }
+    }
+
+    if chat_id.is_none() {
+        let contact_id = if incoming {
+            Some(from_id)
+        } else {
+            to_ids.first().copied()
+        };
+        if let Some(contact_id) = contact_id.filter(|id| *id != ContactId::UNDEFINED) {
+            match ChatIdBlocked::lookup_by_contact(context, contact_id).await? {
+                Some(chat) => {
+                    trace.push(
+                        "1:1 chat",
+                        format!("with {}, found {}", contact_id, chat.id),
+                    );
+                    chat_id = Some(chat.id);
+                }
+                None => trace.push("1:1 chat", format!("with {}, no chat", contact_id)),
+            }
+        }
+    }
+
+    if mime_parser.webxdc_status_update.is_some() && mime_parser.parts.len() == 1 {
+        if let Some(part) = mime_parser.parts.first() {
+            if part.typ == Viewtype::Text && part.msg.is_empty() {
+                trace.push("webxdc status update only", "yes");
+                chat_id = Some(DC_CHAT_ID_TRASH);
+            }
+        }
+    }
+    if mime_parser.reaction.is_some() {
+        trace.push("reaction", "yes");
+        chat_id = Some(DC_CHAT_ID_TRASH);
+    }
+    if !mime_parser.mdn_reports.is_empty() {
+        trace.push("read receipt", "yes");
+        chat_id = Some(DC_CHAT_ID_TRASH);
+    }
+
+    trace.push(
+        "final chat",
+        chat_id.map_or_else(|| "new chat".to_string(), |id| id.to_string()),
+    );
+    trace.chat_id = chat_id;
+    Ok(trace)
+}
+
 /// Receive a message and add it to the database.
 ///
 /// Returns an error on recoverable errors, e.g. database errors. In this case,
@@ -121,6 +370,7 @@ pub(crate) async fn receive_imf_inner(
 
     // check, if the mail is already in our database.
     // make sure, this check is done eg. before securejoin-processing.
+    let mut synthetic_rfc724_mid = None;
     let replace_partial_download =
         if let Some(old_msg_id) = message::rfc724_mid_exists(context, rfc724_mid).await? {
             let msg = Message::load_from_db(context, old_msg_id).await?;
@@ -131,6 +381,28 @@ pub(crate) async fn receive_imf_inner(
                     "Message already partly in DB, replacing by full message."
                 );
                 Some(old_msg_id)
+            } else if is_partial_download.is_none() && is_reused_rfc724_mid(&msg, &mime_parser) {
+                // Some broken senders use the same Message-ID for different messages.
+                // Store them with a synthetic Message-ID, so they are not dropped as duplicates.
+                let new_rfc724_mid = create_synthetic_rfc724_mid(rfc724_mid, imf_raw);
+                if message::rfc724_mid_exists(context, &new_rfc724_mid)
+                    .await?
+                    .is_some()
+                {
+                    info!(context, "Message already in DB, doing nothing.");
+                    return Ok(None);
+                }
+                info!(
+                    context,
+                    "Message-ID {} is reused by a different message, using {}.",
+                    rfc724_mid,
+                    new_rfc724_mid
+                );
+                for part in mime_parser.parts.iter_mut() {
+                    part.param.set(Param::OriginalRfc724Mid, rfc724_mid);
+                }
+                synthetic_rfc724_mid = Some(new_rfc724_mid);
+                None
             } else {
                 // the message was probably moved around.
                 info!(context, "Message already in DB, doing nothing.");
@@ -141,8 +413,7 @@ pub(crate) async fn receive_imf_inner(
         };
 
     // the function returns the number of created messages in the database
-    let prevent_rename =
-        mime_parser.is_mailinglist_message() || mime_parser.get_header(HeaderDef::Sender).is_some();
+    let prevent_rename = should_prevent_rename(context, &mime_parser).await?;
 
     // get From: (it can be an address list!) and check if it is known (for known From:'s we add
     // the other To:/Cc: in the 3rd pass)
@@ -172,10 +443,19 @@ pub(crate) async fn receive_imf_inner(
     .await?;
 
     let rcvd_timestamp = smeared_time(context).await;
-    let sent_timestamp = mime_parser
+    let date_timestamp = mime_parser
         .get_header(HeaderDef::Date)
-        .and_then(|value| mailparse::dateparse(value).ok())
-        .map_or(rcvd_timestamp, |value| min(value, rcvd_timestamp));
+        .and_then(|value| mailparse::dateparse(value).ok());
+    let sent_timestamp = date_timestamp.map_or(rcvd_timestamp, |value| min(value, rcvd_timestamp));
+    let future_dated = incoming
+        && date_timestamp.map_or(false, |value| {
+            value > rcvd_timestamp.saturating_add(FUTURE_DATE_TOLERANCE)
+        });
+    if future_dated {
+        for part in mime_parser.parts.iter_mut() {
+            part.param.set_int(Param::FutureDated, 1);
+        }
+    }
 
     // Add parts
     let received_msg = add_parts(
@@ -184,7 +464,7 @@ pub(crate) async fn receive_imf_inner(
         imf_raw,
         incoming,
         &to_ids,
-        rfc724_mid,
+        synthetic_rfc724_mid.as_deref().unwrap_or(rfc724_mid),
         sent_timestamp,
         rcvd_timestamp,
         from_id,
@@ -198,7 +478,13 @@ pub(crate) async fn receive_imf_inner(
     .context("add_parts error")?;
 
     if !from_id.is_special() {
-        contact::update_last_seen(context, from_id, sent_timestamp).await?;
+        // `sent_timestamp` is never in the future, a later `Date:` is replaced by the time
+        // the message was received.
+        let last_seen_source = match date_timestamp {
+            Some(date_timestamp) if date_timestamp <= rcvd_timestamp => LastSeenSource::Date,
+            _ => LastSeenSource::Received,
+        };
+        contact::update_last_seen(context, from_id, sent_timestamp, last_seen_source).await?;
     }
 
     // Update gossiped timestamp for the chat if someone else or our other device sent
@@ -222,6 +508,12 @@ pub(crate) async fn receive_imf_inner(
         }
     }
 
+    if future_dated && !from_id.is_special() && !chat_id.is_special() {
+        warn_on_future_dated(context, chat_id, from_id, rcvd_timestamp)
+            .await
+            .ok_or_log(context);
+    }
+
     let insert_msg_id = if let Some(msg_id) = received_msg.msg_ids.last() {
         *msg_id
     } else {
@@ -245,10 +537,19 @@ pub(crate) async fn receive_imf_inner(
     }
 
     if let Some(ref status_update) = mime_parser.webxdc_status_update {
-        if let Err(err) = context
-            .receive_status_update(from_id, insert_msg_id, status_update)
-            .await
+        let res = if mime_parser
+            .get_header(HeaderDef::ChatWebxdcEphemeral)
+            .is_some()
         {
+            context
+                .receive_ephemeral_status_update(insert_msg_id, status_update)
+                .await
+        } else {
+            context
+                .receive_status_update(from_id, insert_msg_id, status_update)
+                .await
+        };
+        if let Err(err) = res {
             warn!(context, "receive_imf cannot update status: {}", err);
         }
     }
@@ -285,6 +586,9 @@ pub(crate) async fn receive_imf_inner(
         && !mime_parser.is_mailinglist_message()
         && is_partial_download.is_none()
         && from_id != ContactId::UNDEFINED
+        && Contact::load_from_db(context, from_id)
+            .await?
+            .is_status_tracked()
         && context
             .update_contacts_timestamp(from_id, Param::StatusTimestamp, sent_timestamp)
             .await?
@@ -293,6 +597,7 @@ pub(crate) async fn receive_imf_inner(
             context,
             from_id,
             mime_parser.footer.clone().unwrap_or_default(),
+            mime_parser.footer_html.as_deref(),
             mime_parser.was_encrypted(),
             mime_parser.has_chat_version(),
         )
@@ -302,6 +607,29 @@ pub(crate) async fn receive_imf_inner(
         }
     }
 
+    // Only Autocrypt Setup Messages sent by our other devices to ourselves are imported,
+    // the setup code stored for this protects against messages with a forged `From:`.
+    if mime_parser.is_system_message == SystemMessage::AutocryptSetupMessage
+        && !incoming
+        && to_ids == [ContactId::SELF]
+        && is_partial_download.is_none()
+        && !chat_id.is_trash()
+    {
+        if let Some(msg_id) = received_msg.msg_ids.last() {
+            if let Err(err) = imex::auto_continue_key_transfer(context, *msg_id).await {
+                warn!(
+                    context,
+                    "Cannot import key from Autocrypt Setup Message: {:#}", err
+                );
+            }
+        }
+    }
+
+    // Reports must update the original messages before they may be deleted from the server.
+    mime_parser
+        .handle_reports(context, from_id, sent_timestamp, &mime_parser.parts)
+        .await;
+
     // Get user-configured server deletion
     let delete_server_after = context.get_config_delete_server_after().await?;
 
@@ -323,25 +651,85 @@ pub(crate) async fn receive_imf_inner(
     }
 
     if replace_partial_download.is_some() {
-        context.emit_msgs_changed(chat_id, MsgId::new(0));
+        context.emit_received_msgs_changed(chat_id, MsgId::new(0));
     } else if !chat_id.is_trash() {
         let fresh = received_msg.state == MessageState::InFresh;
         for msg_id in &received_msg.msg_ids {
             if incoming && fresh {
                 context.emit_incoming_msg(chat_id, *msg_id);
             } else {
-                context.emit_msgs_changed(chat_id, *msg_id);
+                context.emit_received_msgs_changed(chat_id, *msg_id);
             };
         }
     }
 
-    mime_parser
-        .handle_reports(context, from_id, sent_timestamp, &mime_parser.parts)
-        .await;
-
     Ok(Some(received_msg))
 }
 
+/// Maximum difference in seconds between the `Date` headers of two messages
+/// with the same Message-ID that are still considered to be the same message.
+const REUSED_RFC724_MID_DATE_TOLERANCE: i64 = 60;
+
+/// Returns true if the From display name should not rename the contact,
+/// but only be used as override name for the message.
+///
+/// This is the case for mailing lists, messages with a `Sender` header
+/// and automatically sent classic emails, where the display name often belongs to a service
+/// rather than to the person owning the address.
+async fn should_prevent_rename(context: &Context, mime_parser: &MimeMessage) -> Result<bool> {
+    if mime_parser.is_mailinglist_message() || mime_parser.get_header(HeaderDef::Sender).is_some() {
+        return Ok(true);
+    }
+    if mime_parser.has_chat_version() {
+        // Delta Chat bots set `Auto-Submitted` as well, their names are used as usual.
+        return Ok(false);
+    }
+    if mime_parser.is_auto_submitted() {
+        return Ok(true);
+    }
+    let markers = context
+        .get_config(Config::PreventRenameHeaders)
+        .await?
+        .unwrap_or_default();
+    Ok(markers
+        .split(',')
+        .filter(|name| !name.trim().is_empty())
+        .any(|name| mime_parser.has_header_named(name)))
+}
+
+/// Returns whether the message reuses the Message-ID of `old_msg` for a different message.
+///
+/// Real duplicates, e.g. copies moved between folders, have the same `Date` header and text,
+/// so the message is only considered different if the date differs significantly
+/// and the text differs as well.
+/// Own messages are never considered different, their copies fetched from the server
+/// are always duplicates.
+fn is_reused_rfc724_mid(old_msg: &Message, mime_parser: &MimeMessage) -> bool {
+    if old_msg.from_id == ContactId::SELF || old_msg.timestamp_sent == 0 {
+        return false;
+    }
+    let date = match mime_parser
+        .get_header(HeaderDef::Date)
+        .and_then(|value| mailparse::dateparse(value).ok())
+    {
+        // The sent timestamp of the old message was limited to its reception time.
+        Some(date) => min(date, old_msg.timestamp_rcvd),
+        None => return false,
+    };
+    if (date - old_msg.timestamp_sent).abs() <= REUSED_RFC724_MID_DATE_TOLERANCE {
+        return false;
+    }
+    mime_parser.parts.first().map(|part| &part.msg) != old_msg.text.as_ref()
+}
+
+/// Creates a unique Message-ID for a message whose Message-ID was reused by the sender.
+///
+/// The same message always gets the same Message-ID, so duplicates are still detected.
+fn create_synthetic_rfc724_mid(rfc724_mid: &str, imf_raw: &[u8]) -> String {
+    let hash = hex::encode(Sha1::digest(imf_raw));
+    format!("{}.{}", rfc724_mid, hash.get(..16).unwrap_or_default())
+}
+
 /// Converts "From" field to contact id.
 ///
 /// Also returns whether it is blocked or not and its origin.
@@ -388,6 +776,72 @@ pub async fn from_field_to_contact_id(
     }
 }
 
+/// Reports a wrong clock if messages with a `Date` in the future are received repeatedly.
+///
+/// If such messages come from several contacts, the clock of this device is likely wrong
+/// and a device message is added.  Otherwise the clock of the sender seems to be wrong,
+/// this is told once per chat by an info message.
+async fn warn_on_future_dated(
+    context: &Context,
+    chat_id: ChatId,
+    from_id: ContactId,
+    rcvd_timestamp: i64,
+) -> Result<()> {
+    let since = rcvd_timestamp.saturating_sub(24 * 60 * 60);
+    let senders = context
+        .sql
+        .count(
+            "SELECT COUNT(DISTINCT from_id) FROM msgs
+             WHERE timestamp_rcvd>?
+             AND (param GLOB ')=1*' OR param GLOB '*' || char(10) || ')=1*')",
+            paramsv![since],
+        )
+        .await?;
+    if senders >= FUTURE_DATED_WARN_COUNT {
+        let mut msg = Message::new(Viewtype::Text);
+        msg.text =
+            Some(stock_str::bad_time_msg_body(context, timestamp_to_str(rcvd_timestamp)).await);
+        // Warn at most once a day.
+        let label = format!("bad-time-warning-{}", rcvd_timestamp / (24 * 60 * 60));
+        chat::add_device_msg(context, Some(&label), Some(&mut msg)).await?;
+        return Ok(());
+    }
+
+    let count = context
+        .sql
+        .count(
+            "SELECT COUNT(*) FROM msgs
+             WHERE from_id=? AND timestamp_rcvd>?
+             AND (param GLOB ')=1*' OR param GLOB '*' || char(10) || ')=1*')",
+            paramsv![from_id, since],
+        )
+        .await?;
+    if count < FUTURE_DATED_WARN_COUNT {
+        return Ok(());
+    }
+    let mut chat = Chat::load_from_db(context, chat_id).await?;
+    if chat
+        .param
+        .get_bool(Param::SenderClockWarned)
+        .unwrap_or_default()
+    {
+        return Ok(());
+    }
+    chat.param.set_int(Param::SenderClockWarned, 1);
+    chat.update_param(context).await?;
+
+    let contact = Contact::get_by_id(context, from_id).await?;
+    let text = stock_str::sender_clock_wrong(context, contact.get_display_name()).await;
+    chat::add_info_msg(
+        context,
+        chat_id,
+        &text,
+        create_smeared_timestamp(context).await,
+    )
+    .await?;
+    Ok(())
+}
+
 #[allow(clippy::too_many_arguments, clippy::cognitive_complexity)]
 async fn add_parts(
     context: &Context,
@@ -461,6 +915,41 @@ async fn add_parts(
     if incoming {
         to_id = ContactId::SELF;
 
+        // If we are only addressed via Bcc, To/Cc do not list any recipient,
+        // but the message was obviously delivered to us.
+        let bcc_delivery = to_ids.is_empty();
+        let to_ids: &[ContactId] = if bcc_delivery {
+            info!(
+                context,
+                "Message has no To/Cc recipients, assuming Bcc delivery."
+            );
+            for part in mime_parser.parts.iter_mut() {
+                part.param.set_int(Param::BccDelivery, 1);
+            }
+            &[ContactId::SELF]
+        } else {
+            to_ids
+        };
+
+        if let Some(delivered_to) = &mime_parser.delivered_to {
+            for part in mime_parser.parts.iter_mut() {
+                part.param.set(Param::DeliveredTo, delivered_to);
+            }
+        }
+
+        if !mime_parser.authentication_results.is_empty() {
+            let auth_results = mime_parser.authentication_results.to_string();
+            for part in mime_parser.parts.iter_mut() {
+                part.param.set(Param::AuthResults, &auth_results);
+            }
+        }
+
+        if mime_parser.footer_html.is_some() {
+            for part in mime_parser.parts.iter_mut() {
+                part.param.set_int(Param::HtmlStatus, 1);
+            }
+        }
+
         // Whether the message is a part of securejoin handshake that should be marked as seen
         // automatically.
         let securejoin_seen;
@@ -540,7 +1029,12 @@ async fn add_parts(
             {
                 chat_id = Some(new_chat_id);
                 chat_id_blocked = new_chat_id_blocked;
-                if chat_id_blocked != Blocked::Not && create_blocked == Blocked::Not {
+                // Undecryptable messages do not accept chats,
+                // they may go to a placeholder group which stays a contact request.
+                if chat_id_blocked != Blocked::Not
+                    && create_blocked == Blocked::Not
+                    && !mime_parser.decrypting_failed
+                {
                     new_chat_id.unblock(context).await?;
                     chat_id_blocked = Blocked::Not;
                 }
@@ -616,7 +1110,26 @@ async fn add_parts(
         }
 
         if let Some(chat_id) = chat_id {
-            apply_mailinglist_changes(context, mime_parser, chat_id).await?;
+            apply_mailinglist_changes(context, mime_parser, chat_id, sent_timestamp).await?;
+        }
+
+        if chat_id.is_none()
+            && is_dc_message == MessengerMessage::No
+            && context.get_config_bool(Config::CatchAllChat).await?
+        {
+            if let Some(catch_all_addr) = get_catch_all_addr(context, mime_parser).await? {
+                if let Some((new_chat_id, new_chat_id_blocked)) =
+                    create_or_lookup_catch_all_chat(context, allow_creation).await?
+                {
+                    chat_id = Some(new_chat_id);
+                    chat_id_blocked = new_chat_id_blocked;
+                    for part in mime_parser.parts.iter_mut() {
+                        part.param.set(Param::DeliveredTo, &catch_all_addr);
+                    }
+                    // Make `Config::ShowDeliveredToAlias` show the original recipient.
+                    mime_parser.delivered_to = Some(catch_all_addr);
+                }
+            }
         }
 
         // if contact renaming is prevented (for mailinglists and bots),
@@ -631,6 +1144,29 @@ async fn add_parts(
             }
         }
 
+        // show the alias a classic email was delivered to next to the sender name
+        if is_dc_message == MessengerMessage::No
+            && context
+                .get_config_bool(Config::ShowDeliveredToAlias)
+                .await?
+        {
+            if let (Some(delivered_to), Some(from)) =
+                (&mime_parser.delivered_to, mime_parser.from.first())
+            {
+                if !addr_cmp(delivered_to, &context.get_primary_self_addr().await?) {
+                    for part in mime_parser.parts.iter_mut() {
+                        let name = part
+                            .param
+                            .get(Param::OverrideSenderDisplayname)
+                            .or(from.display_name.as_deref())
+                            .unwrap_or(&from.addr);
+                        let name = format!("{} (via {})", name, delivered_to);
+                        part.param.set(Param::OverrideSenderDisplayname, name);
+                    }
+                }
+            }
+        }
+
         if chat_id.is_none() {
             // try to create a normal chat
             let create_blocked = if from_id == ContactId::SELF {
@@ -662,6 +1198,12 @@ async fn add_parts(
                     if chat_id_blocked != create_blocked {
                         chat_id.set_blocked(context, create_blocked).await?;
                     }
+                    // Check the origin before it is scaled up below,
+                    // a first-time sender replying to a known message is not known yet.
+                    let from_is_known = Contact::load_from_db(context, from_id)
+                        .await?
+                        .origin
+                        .is_known();
                     if create_blocked == Blocked::Request && parent.is_some() {
                         // we do not want any chat to be created implicitly.  Because of the origin-scale-up,
                         // the contact requests will pop up and this should be just fine.
@@ -672,6 +1214,19 @@ async fn add_parts(
                             "Message is a reply to a known message, mark sender as known.",
                         );
                     }
+                    if create_blocked == Blocked::Request
+                        && from_is_known
+                        && context
+                            .get_config_bool(Config::AutoAcceptKnownContactRequests)
+                            .await?
+                    {
+                        info!(
+                            context,
+                            "Auto-accepting contact request from known contact."
+                        );
+                        chat_id.unblock(context).await?;
+                        chat_id_blocked = Blocked::Not;
+                    }
                 }
             }
         }
@@ -826,6 +1381,29 @@ async fn add_parts(
         }
     }
 
+    if let Some(reaction) = &mime_parser.reaction {
+        let in_reply_to = mime_parser
+            .get_header(HeaderDef::InReplyTo)
+            .map(|s| s.as_str())
+            .unwrap_or_default();
+        if let Some(mut target) = get_rfc724_mid_in_list(context, in_reply_to).await? {
+            if from_id == ContactId::SELF
+                || chat::is_contact_in_chat(context, target.chat_id, from_id).await?
+            {
+                target.set_reaction(from_id, reaction);
+                target.update_param(context).await?;
+                context.emit_received_msgs_changed(target.chat_id, target.id);
+            } else {
+                warn!(
+                    context,
+                    "Ignoring reaction of {} not in chat {}.", from_id, target.chat_id
+                );
+            }
+        }
+        chat_id = Some(DC_CHAT_ID_TRASH);
+        info!(context, "Message is a reaction (TRASH)");
+    }
+
     if is_mdn {
         chat_id = Some(DC_CHAT_ID_TRASH);
     }
@@ -890,11 +1468,25 @@ async fn add_parts(
                 ephemeral_timer,
                 chat_id
             );
-        } else if chat_id
-            .update_timestamp(context, Param::EphemeralSettingsTimestamp, sent_timestamp)
-            .await?
+        } else if mime_parser.is_system_message != SystemMessage::EphemeralTimerChanged
+            && Chat::load_from_db(context, chat_id)
+                .await?
+                .param
+                .exists(Param::EphemeralTimerUnannounced)
         {
-            if let Err(err) = chat_id
+            // The sender does not know the default timer of the chat yet,
+            // only an explicit timer change overrides it.
+            info!(
+                context,
+                "ignoring ephemeral timer {:?} for chat {} with unannounced default timer",
+                ephemeral_timer,
+                chat_id
+            );
+        } else if chat_id
+            .update_timestamp(context, Param::EphemeralSettingsTimestamp, sent_timestamp)
+            .await?
+        {
+            if let Err(err) = chat_id
                 .inner_set_ephemeral_timer(context, ephemeral_timer)
                 .await
             {
@@ -988,9 +1580,13 @@ async fn add_parts(
     //
     // This does not help if parent message arrives later than the
     // reply.
+    //
+    // The parent timestamp is clamped to the receive time the same way as
+    // the sent timestamp, otherwise a parent with a timestamp in the future
+    // would pin all replies to the bottom of the chat.
     let parent_timestamp = mime_parser.get_parent_timestamp(context).await?;
     let sort_timestamp = parent_timestamp.map_or(sort_timestamp, |parent_timestamp| {
-        std::cmp::max(sort_timestamp, parent_timestamp)
+        std::cmp::max(sort_timestamp, min(parent_timestamp, rcvd_timestamp))
     });
 
     // if the mime-headers should be saved, find out its size
@@ -1016,6 +1612,23 @@ async fn add_parts(
 
     let is_system_message = mime_parser.is_system_message;
 
+    // Group membership may change later, so remember whom the message was addressed to.
+    let recipients = if !chat_id.is_special()
+        && Chat::load_from_db(context, chat_id).await?.typ == Chattype::Group
+    {
+        let mut recipients: Vec<u32> = to_ids.iter().map(|id| id.to_u32()).collect();
+        recipients.sort_unstable();
+        Some(
+            recipients
+                .iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>()
+                .join(" "),
+        )
+    } else {
+        None
+    };
+
     // if indicated by the parser,
     // we save the full mime-message and add a flag
     // that the ui should show button to display the full message.
@@ -1033,11 +1646,40 @@ async fn add_parts(
         Vec::new()
     };
 
+    // Attachments are written while parsing, before the chat is known,
+    // so they are moved to the chat's subdirectory here if the blob layout uses one.
+    if !chat_id.is_trash() {
+        if let Some(subdir) = blob::blob_subdir(context, chat_id).await? {
+            for part in mime_parser.parts.iter_mut() {
+                if let Some(name) = part.param.get(Param::File).map(|name| name.to_string()) {
+                    match BlobObject::from_name(context, name.clone()) {
+                        Ok(blob) => match blob.move_to_subdir(context, &subdir).await {
+                            Ok(moved) => {
+                                part.param.set(Param::File, moved.as_name());
+                            }
+                            Err(err) => {
+                                warn!(context, "Cannot move {} to {}: {:#}", name, subdir, err)
+                            }
+                        },
+                        Err(err) => warn!(context, "Cannot move {}: {:#}", name, err),
+                    }
+                }
+            }
+        }
+    }
+
+    let language = mime_parser.get_content_language();
+
     let mut created_db_entries = Vec::with_capacity(mime_parser.parts.len());
 
+    let mut parts_blob_bytes = Vec::with_capacity(mime_parser.parts.len());
+    for part in &mime_parser.parts {
+        parts_blob_bytes.push(get_blob_bytes(context, &part.param).await);
+    }
+
     let conn = context.sql.get_conn().await?;
 
-    for part in &mime_parser.parts {
+    for (part, blob_bytes) in mime_parser.parts.iter().zip(parts_blob_bytes) {
         let mut txt_raw = "".to_string();
         let mut stmt = conn.prepare_cached(
             r#"
@@ -1049,7 +1691,7 @@ async fn add_parts(
     txt, subject, txt_raw, param, 
     bytes, mime_headers, mime_in_reply_to,
     mime_references, mime_modified, error, ephemeral_timer,
-    ephemeral_timestamp, download_state, hop_info
+    ephemeral_timestamp, download_state, hop_info, blob_bytes
   )
   VALUES (
     ?, ?, ?, ?,
@@ -1057,7 +1699,8 @@ async fn add_parts(
     ?, ?, ?, ?,
     ?, ?, ?, ?,
     ?, ?, ?, ?,
-    ?, ?, ?, ?
+    ?, ?, ?, ?,
+    ?
   );
 "#,
         )?;
@@ -1084,6 +1727,28 @@ async fn add_parts(
         if is_system_message != SystemMessage::Unknown {
             param.set_int(Param::Cmd, is_system_message as i32);
         }
+        // Remember the added or removed member as done for outgoing messages,
+        // this is used by `chat::members_at()`.
+        let member_header = match is_system_message {
+            SystemMessage::MemberAddedToGroup => Some(HeaderDef::ChatGroupMemberAdded),
+            SystemMessage::MemberRemovedFromGroup => Some(HeaderDef::ChatGroupMemberRemoved),
+            _ => None,
+        };
+        if let Some(addr) = member_header.and_then(|header| mime_parser.get_header(header)) {
+            param.set(Param::Arg, addr);
+        }
+        if let Some(recipients) = &recipients {
+            param.set(Param::MessageRecipients, recipients);
+        }
+        if let Some(download_size) = is_partial_download {
+            param.set(Param::DownloadSize, download_size.to_string());
+        }
+        if mime_modified && mime_parser.has_remote_content {
+            param.set_int(Param::HasRemoteContent, 1);
+        }
+        if let Some(language) = &language {
+            param.set(Param::Language, language);
+        }
 
         let ephemeral_timestamp = if in_fresh {
             0
@@ -1137,7 +1802,8 @@ async fn add_parts(
             } else {
                 DownloadState::Done
             },
-            mime_parser.hop_info
+            mime_parser.hop_info,
+            if trash { 0 } else { blob_bytes as i64 }
         ])?;
         let row_id = conn.last_insert_rowid();
 
@@ -1188,6 +1854,10 @@ async fn add_parts(
         }
     }
 
+    if !chat_id.is_special() {
+        context.emit_event(EventType::ChatlistItemChanged(chat_id));
+    }
+
     if !incoming && is_mdn && is_dc_message == MessengerMessage::Yes {
         // Normally outgoing MDNs sent by us never appear in mailboxes, but Gmail saves all
         // outgoing messages, including MDNs, to the Sent folder. If we detect such saved MDN,
@@ -1195,6 +1865,14 @@ async fn add_parts(
         needs_delete_job = true;
     }
 
+    if (is_mdn || mime_parser.delivery_report.is_some())
+        && context
+            .get_config_bool(Config::DeleteMdnsFromServer)
+            .await?
+    {
+        needs_delete_job = true;
+    }
+
     Ok(ReceivedMsg {
         chat_id,
         state,
@@ -1336,27 +2014,15 @@ async fn is_probably_private_reply(
     mime_parser: &MimeMessage,
     parent_chat_id: ChatId,
 ) -> Result<bool> {
-    // Usually we don't want to show private replies in the parent chat, but in the
-    // 1:1 chat with the sender.
-    //
-    // There is one exception: Classical MUA replies to two-member groups
-    // should be assigned to the group chat. We restrict this exception to classical emails, as chat-group-messages
-    // contain a Chat-Group-Id header and can be sorted into the correct chat this way.
-
-    let private_message =
-        (to_ids == [ContactId::SELF]) || (from_id == ContactId::SELF && to_ids.len() == 1);
-    if !private_message {
-        return Ok(false);
-    }
-
-    if !mime_parser.has_chat_version() {
-        let chat_contacts = chat::get_chat_contacts(context, parent_chat_id).await?;
-        if chat_contacts.len() == 2 && chat_contacts.contains(&ContactId::SELF) {
-            return Ok(false);
-        }
-    }
-
-    Ok(true)
+    let kind = chat::classify_reply(
+        context,
+        to_ids,
+        from_id,
+        mime_parser.has_chat_version(),
+        parent_chat_id,
+    )
+    .await?;
+    Ok(kind == ReplyKind::PrivateReply)
 }
 
 /// This function tries to extract the group-id from the message and returns the corresponding
@@ -1383,7 +2049,19 @@ async fn create_or_lookup_group(
             member_ids.push(ContactId::SELF);
         }
 
-        let res = create_adhoc_group(context, mime_parser, create_blocked, &member_ids)
+        let min_origin = context.get_config_int(Config::AdhocGroupMinOrigin).await?;
+        if from_id != ContactId::SELF && min_origin > 0 {
+            let from = Contact::load_from_db(context, from_id).await?;
+            if i64::from(from.origin as u32) < i64::from(min_origin) {
+                info!(
+                    context,
+                    "not creating ad-hoc group: sender origin {:?} too low", from.origin
+                );
+                return Ok(None);
+            }
+        }
+
+        let res = create_adhoc_group(context, mime_parser, create_blocked, from_id, &member_ids)
             .await
             .context("could not create ad hoc group")?
             .map(|chat_id| (chat_id, create_blocked));
@@ -1411,6 +2089,10 @@ async fn create_or_lookup_group(
         {
             return Ok(None);
         }
+
+        if !mime_parser.decrypting_failed {
+            merge_group_placeholder(context, mime_parser, chat_id, from_id, to_ids).await?;
+        }
     }
 
     let create_protected = if mime_parser.get_header(HeaderDef::ChatVerified).is_some() {
@@ -1466,6 +2148,8 @@ async fn self_explicitly_added(
         .await
         .with_context(|| format!("Failed to create group '{}' for grpid={}", grpname, grpid))?;
 
+        new_chat_id.set_admin_id(context, from_id).await?;
+        new_chat_id.set_creator_id(context, from_id).await?;
         chat_id = Some(new_chat_id);
         chat_id_blocked = create_blocked;
 
@@ -1505,9 +2189,19 @@ async fn self_explicitly_added(
         // yet unknown group, which was rejected because
         // Chat-Group-Name, which is in the encrypted part, was
         // not found. We can't create a properly named group in
-        // this case, so assign error message to 1:1 chat with the
-        // sender instead.
-        Ok(None)
+        // this case, so create a placeholder group that is merged
+        // into the real group once a decryptable message arrives.
+        // If this is not possible, assign error message to 1:1 chat
+        // with the sender instead.
+        if !allow_creation
+            || grpid.is_empty()
+            || mime_parser.is_mailinglist_message()
+            || chat::is_group_explicitly_left(context, &grpid).await?
+        {
+            return Ok(None);
+        }
+        let new_chat_id = create_group_placeholder(context, &grpid, from_id).await?;
+        Ok(Some((new_chat_id, Blocked::Request)))
     } else {
         // The message was decrypted successfully, but contains a late "quit" or otherwise
         // unwanted message.
@@ -1516,6 +2210,95 @@ async fn self_explicitly_added(
     }
 }
 
+/// Creates a placeholder group for a message to an unknown group that could not be decrypted.
+///
+/// The group contains only SELF and the sender and is a contact request.
+async fn create_group_placeholder(
+    context: &Context,
+    grpid: &str,
+    from_id: ContactId,
+) -> Result<ChatId> {
+    let name = stock_str::unknown_group(context).await;
+    let mut param = Params::new();
+    param.set_int(Param::GroupPlaceholder, 1);
+    let chat_id = ChatId::create_multiuser_record(
+        context,
+        Chattype::Group,
+        grpid,
+        &name,
+        Blocked::Request,
+        ProtectionStatus::Unprotected,
+        Some(param.to_string()),
+    )
+    .await
+    .with_context(|| format!("Failed to create placeholder group for grpid={}", grpid))?;
+    info!(
+        context,
+        "Created placeholder group {} for undecryptable message.", chat_id
+    );
+
+    chat::add_to_chat_contacts_table(context, chat_id, ContactId::SELF).await?;
+    if !from_id.is_special() {
+        chat::add_to_chat_contacts_table(context, chat_id, from_id).await?;
+    }
+    context.emit_event(EventType::ChatModified(chat_id));
+    Ok(chat_id)
+}
+
+/// Turns a placeholder group created by [`create_group_placeholder`] into the real group
+/// once a decryptable message for it arrives.
+///
+/// The chat is renamed and gets the members of the message,
+/// messages already in the placeholder are kept.
+///
+/// Only encrypted messages from a member of the placeholder are merged,
+/// otherwise anyone knowing the group ID could take over the group.
+async fn merge_group_placeholder(
+    context: &Context,
+    mime_parser: &MimeMessage,
+    chat_id: ChatId,
+    from_id: ContactId,
+    to_ids: &[ContactId],
+) -> Result<()> {
+    let mut chat = Chat::load_from_db(context, chat_id).await?;
+    if !chat.param.exists(Param::GroupPlaceholder) {
+        return Ok(());
+    }
+    if !mime_parser.was_encrypted() || !chat::is_contact_in_chat(context, chat_id, from_id).await? {
+        info!(
+            context,
+            "Not merging placeholder group {} with unencrypted message or message from non-member.",
+            chat_id
+        );
+        return Ok(());
+    }
+    let grpname = match mime_parser.get_header(HeaderDef::ChatGroupName) {
+        Some(grpname) => grpname,
+        None => return Ok(()),
+    };
+
+    info!(
+        context,
+        "Merging placeholder group {} into group {:?}.", chat_id, grpname
+    );
+    context
+        .sql
+        .execute(
+            "UPDATE chats SET name=? WHERE id=?;",
+            paramsv![grpname.to_string(), chat_id],
+        )
+        .await?;
+    for &to_id in to_ids.iter() {
+        if to_id != ContactId::SELF && !chat::is_contact_in_chat(context, chat_id, to_id).await? {
+            chat::add_to_chat_contacts_table(context, chat_id, to_id).await?;
+        }
+    }
+    chat.param.remove(Param::GroupPlaceholder);
+    chat.update_param(context).await?;
+    context.emit_event(EventType::ChatModified(chat_id));
+    Ok(())
+}
+
 /// Apply group member list, name, avatar and protection status changes from the MIME message.
 ///
 /// Optionally returns better message to replace the original system message.
@@ -1532,6 +2315,19 @@ async fn apply_group_changes(
         return Ok(None);
     }
 
+    // The avatar part may have been stripped, e.g. by attachment filters on the way.
+    if let Some(AvatarAction::Change(profile_image)) = &mime_parser.group_avatar {
+        if !is_valid_avatar_blob(context, profile_image).await {
+            warn!(
+                context,
+                "Group avatar {} for chat {} is missing or not an image, ignoring.",
+                profile_image,
+                chat_id
+            );
+            mime_parser.group_avatar = None;
+        }
+    }
+
     let mut recreate_member_list = false;
     let mut send_event_chat_modified = false;
 
@@ -1566,38 +2362,44 @@ async fn apply_group_changes(
                 .get_header(HeaderDef::ChatGroupName)
                 .filter(|grpname| grpname.len() < 200)
             {
-                if chat_id
-                    .update_timestamp(context, Param::GroupNameTimestamp, sent_timestamp)
-                    .await?
-                {
-                    info!(context, "updating grpname for chat {}", chat_id);
-                    context
-                        .sql
-                        .execute(
-                            "UPDATE chats SET name=? WHERE id=?;",
-                            paramsv![grpname.to_string(), chat_id],
-                        )
-                        .await?;
-                    send_event_chat_modified = true;
-                }
+                if chat.is_name_editable_by(context, from_id).await? {
+                    if chat_id
+                        .update_timestamp(context, Param::GroupNameTimestamp, sent_timestamp)
+                        .await?
+                    {
+                        info!(context, "updating grpname for chat {}", chat_id);
+                        context
+                            .sql
+                            .execute(
+                                "UPDATE chats SET name=? WHERE id=?;",
+                                paramsv![grpname.to_string(), chat_id],
+                            )
+                            .await?;
+                        send_event_chat_modified = true;
+                    }
 
-                better_msg =
-                    Some(stock_str::msg_grp_name(context, old_name, grpname, from_id).await);
+                    better_msg =
+                        Some(stock_str::msg_grp_name(context, old_name, grpname, from_id).await);
+                } else {
+                    warn!(
+                        context,
+                        "{} is not the admin of chat {}, ignoring name change.", from_id, chat_id
+                    );
+                }
             }
         } else if let Some(value) = mime_parser.get_header(HeaderDef::ChatContent) {
             if value == "group-avatar-changed" {
-                if let Some(avatar_action) = &mime_parser.group_avatar {
-                    // this is just an explicit message containing the group-avatar,
-                    // apart from that, the group-avatar is send along with various other messages
-                    better_msg = match avatar_action {
-                        AvatarAction::Delete => {
-                            Some(stock_str::msg_grp_img_deleted(context, from_id).await)
-                        }
-                        AvatarAction::Change(_) => {
-                            Some(stock_str::msg_grp_img_changed(context, from_id).await)
-                        }
-                    };
-                }
+                // this is just an explicit message containing the group-avatar,
+                // apart from that, the group-avatar is send along with various other messages
+                better_msg = match &mime_parser.group_avatar {
+                    Some(AvatarAction::Delete) => {
+                        Some(stock_str::msg_grp_img_deleted(context, from_id).await)
+                    }
+                    Some(AvatarAction::Change(_)) => {
+                        Some(stock_str::msg_grp_img_changed(context, from_id).await)
+                    }
+                    None => Some(stock_str::msg_grp_img_change_failed(context).await),
+                };
             }
         }
     }
@@ -1632,23 +2434,17 @@ async fn apply_group_changes(
             .update_timestamp(context, Param::MemberListTimestamp, sent_timestamp)
             .await?
         {
-            if removed_id.is_some()
-                || !chat::is_contact_in_chat(context, chat_id, ContactId::SELF).await?
+            // Only members named in `Chat-Group-Member-Removed` are removed.
+            // The recipient list is not authoritative as it may be incomplete,
+            // e.g. if the server expanded an alias address
+            // and the copy of the message only contains the alias in `To:`.
+            if let Some(removed_id) = removed_id {
+                chat::remove_from_chat_contacts_table(context, chat_id, removed_id).await?;
+            }
+            if removed_id != Some(ContactId::SELF)
+                && !chat::is_contact_in_chat(context, chat_id, ContactId::SELF).await?
             {
-                // Members could have been removed while we were
-                // absent. We can't use existing member list and need to
-                // start from scratch.
-                context
-                    .sql
-                    .execute(
-                        "DELETE FROM chats_contacts WHERE chat_id=?;",
-                        paramsv![chat_id],
-                    )
-                    .await?;
-
-                if removed_id != Some(ContactId::SELF) {
-                    chat::add_to_chat_contacts_table(context, chat_id, ContactId::SELF).await?;
-                }
+                chat::add_to_chat_contacts_table(context, chat_id, ContactId::SELF).await?;
             }
             if !from_id.is_special()
                 && from_id != ContactId::SELF
@@ -1709,6 +2505,46 @@ async fn apply_group_changes(
     Ok(better_msg)
 }
 
+/// Returns true if the blob referenced by a group avatar change exists and can be decoded as image.
+async fn is_valid_avatar_blob(context: &Context, profile_image: &str) -> bool {
+    match tokio::fs::read(get_abs_path(context, profile_image)).await {
+        Ok(buf) => image::load_from_memory(&buf).is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Checks if `domain` is one of the comma-separated `trusted_domains` or a subdomain of one.
+fn is_trusted_mailinglist_domain(trusted_domains: &str, domain: &str) -> bool {
+    let domain = domain.to_lowercase();
+    trusted_domains
+        .split(',')
+        .map(|trusted| trusted.trim().to_lowercase())
+        .filter(|trusted| !trusted.is_empty())
+        .any(|trusted| {
+            domain == trusted
+                || domain
+                    .strip_suffix(&trusted)
+                    .map_or(false, |prefix| prefix.ends_with('.'))
+        })
+}
+
+/// Splits a `List-Id` or `Sender` header into the list name and the list ID.
+#[allow(clippy::indexing_slicing)]
+fn parse_list_id_header(list_id_header: &str) -> (String, String) {
+    static LIST_ID: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(.+)<(.+)>$").unwrap());
+    match LIST_ID.captures(list_id_header) {
+        Some(cap) => (cap[1].trim().to_string(), cap[2].trim().to_string()),
+        None => (
+            "".to_string(),
+            list_id_header
+                .trim()
+                .trim_start_matches('<')
+                .trim_end_matches('>')
+                .to_string(),
+        ),
+    }
+}
+
 /// Create or lookup a mailing list chat.
 ///
 /// `list_id_header` contains the Id that must be used for the mailing list
@@ -1725,18 +2561,7 @@ async fn create_or_lookup_mailinglist(
     list_id_header: &str,
     mime_parser: &MimeMessage,
 ) -> Result<Option<(ChatId, Blocked)>> {
-    static LIST_ID: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(.+)<(.+)>$").unwrap());
-    let (mut name, listid) = match LIST_ID.captures(list_id_header) {
-        Some(cap) => (cap[1].trim().to_string(), cap[2].trim().to_string()),
-        None => (
-            "".to_string(),
-            list_id_header
-                .trim()
-                .trim_start_matches('<')
-                .trim_end_matches('>')
-                .to_string(),
-        ),
-    };
+    let (mut name, listid) = parse_list_id_header(list_id_header);
 
     if let Some((chat_id, _, blocked)) = chat::get_chat_id_by_grpid(context, &listid).await? {
         return Ok(Some((chat_id, blocked)));
@@ -1805,12 +2630,28 @@ async fn create_or_lookup_mailinglist(
             p.to_string()
         });
 
+        let trusted_domains = context
+            .get_config(Config::TrustedMailinglistDomains)
+            .await?
+            .unwrap_or_default();
+        let is_trusted = is_trusted_mailinglist_domain(&trusted_domains, &listid)
+            || mime_parser.from.first().map_or(false, |from| {
+                from.addr.rsplit_once('@').map_or(false, |(_, domain)| {
+                    is_trusted_mailinglist_domain(&trusted_domains, domain)
+                })
+            });
+        let blocked = if is_trusted {
+            Blocked::Not
+        } else {
+            Blocked::Request
+        };
+
         let chat_id = ChatId::create_multiuser_record(
             context,
             Chattype::Mailinglist,
             &listid,
             &name,
-            Blocked::Request,
+            blocked,
             ProtectionStatus::Unprotected,
             param,
         )
@@ -1823,26 +2664,114 @@ async fn create_or_lookup_mailinglist(
         })?;
 
         chat::add_to_chat_contacts_table(context, chat_id, ContactId::SELF).await?;
-        Ok(Some((chat_id, Blocked::Request)))
+        if context.get_config_bool(Config::MuteNewMailinglists).await? {
+            chat::set_muted(context, chat_id, MuteDuration::Forever).await?;
+        }
+        Ok(Some((chat_id, blocked)))
     } else {
         info!(context, "creating list forbidden by caller");
         Ok(None)
     }
 }
 
+/// Returns the envelope recipient of a message
+/// if it is an address of the own domain that is not a configured self address,
+/// e.g. because the message was received via a catch-all domain.
+///
+/// Only the envelope headers added by the receiving server are used,
+/// `To` and `Cc` are set by the sender and may not contain the envelope recipient at all.
+/// `X-Original-To` is preferred as the server may rewrite `Delivered-To` to the mailbox address.
+async fn get_catch_all_addr(
+    context: &Context,
+    mime_parser: &MimeMessage,
+) -> Result<Option<String>> {
+    let self_addr = context.get_primary_self_addr().await?;
+    let self_domain = match self_addr.rsplit_once('@') {
+        Some((_, domain)) => domain,
+        None => return Ok(None),
+    };
+    let original_to = mime_parser.get_header(HeaderDef::XOriginalTo).map(|value| {
+        addr_normalize(value.trim().trim_start_matches('<').trim_end_matches('>')).to_lowercase()
+    });
+    let addr = match original_to.or_else(|| mime_parser.delivered_to.clone()) {
+        Some(addr) => addr,
+        None => return Ok(None),
+    };
+    let is_own_domain = addr.rsplit_once('@').map_or(false, |(_, domain)| {
+        domain.eq_ignore_ascii_case(self_domain)
+    });
+    if !is_own_domain || context.is_self_addr(&addr).await? {
+        return Ok(None);
+    }
+    Ok(Some(addr))
+}
+
+/// Returns the chat for [`Config::CatchAllChat`], creating it if needed and allowed.
+///
+/// The chat is a read-only mailing list chat,
+/// answers can be sent via the 1:1 chat with the sender.
+async fn create_or_lookup_catch_all_chat(
+    context: &Context,
+    allow_creation: bool,
+) -> Result<Option<(ChatId, Blocked)>> {
+    let chat_id = u32::try_from(context.get_config_u64(Config::CatchAllChatId).await?)?;
+    if chat_id != 0 {
+        let chat_id = ChatId::new(chat_id);
+        // The chat does not exist anymore if the user deleted it.
+        if let Some(blocked) = context
+            .sql
+            .query_get_value(
+                "SELECT IFNULL(blocked, 0) FROM chats WHERE id=?",
+                paramsv![chat_id],
+            )
+            .await?
+        {
+            return Ok(Some((chat_id, blocked)));
+        }
+    }
+    if !allow_creation {
+        info!(context, "creating catch-all chat forbidden by caller");
+        return Ok(None);
+    }
+
+    // The user enabled the catch-all chat, so it is not a contact request.
+    // The chat is looked up by its ID, the random group ID cannot collide with a `List-Id`.
+    let chat_id = ChatId::create_multiuser_record(
+        context,
+        Chattype::Mailinglist,
+        &create_id(),
+        &stock_str::catch_all_chat(context).await,
+        Blocked::Not,
+        ProtectionStatus::Unprotected,
+        None,
+    )
+    .await
+    .context("Failed to create catch-all chat")?;
+    context
+        .set_config(Config::CatchAllChatId, Some(&chat_id.to_u32().to_string()))
+        .await?;
+    chat::add_to_chat_contacts_table(context, chat_id, ContactId::SELF).await?;
+    Ok(Some((chat_id, Blocked::Not)))
+}
+
 /// Set ListId param on the contact and ListPost param the chat.
 /// Only called for incoming messages since outgoing messages never have a
 /// List-Post header, anyway.
+///
+/// Also updates the mailing list description, help and archive URLs
+/// if the message is newer than the one these were taken from.
 async fn apply_mailinglist_changes(
     context: &Context,
     mime_parser: &MimeMessage,
     chat_id: ChatId,
+    sent_timestamp: i64,
 ) -> Result<()> {
+    let mut chat = Chat::load_from_db(context, chat_id).await?;
+    if chat.typ != Chattype::Mailinglist {
+        return Ok(());
+    }
+
     if let Some(list_post) = &mime_parser.list_post {
-        let mut chat = Chat::load_from_db(context, chat_id).await?;
-        if chat.typ != Chattype::Mailinglist {
-            return Ok(());
-        }
         let listid = &chat.grpid;
 
         let (contact_id, _) =
@@ -1866,6 +2795,24 @@ async fn apply_mailinglist_changes(
         }
     }
 
+    let metadata = [
+        (Param::ListDescription, mime_parser.get_list_description()),
+        (Param::ListHelp, mime_parser.get_list_help_url()),
+        (Param::ListArchive, mime_parser.get_list_archive_url()),
+    ];
+    if metadata.iter().any(|(_, value)| value.is_some())
+        && chat
+            .param
+            .update_timestamp(Param::ListMetadataTimestamp, sent_timestamp)?
+    {
+        for (key, value) in metadata {
+            if let Some(value) = value {
+                chat.param.set(key, value);
+            }
+        }
+        chat.update_param(context).await?;
+    }
+
     Ok(())
 }
 
@@ -1905,8 +2852,14 @@ async fn create_adhoc_group(
     context: &Context,
     mime_parser: &MimeMessage,
     create_blocked: Blocked,
+    from_id: ContactId,
     member_ids: &[ContactId],
 ) -> Result<Option<ChatId>> {
+    if context.get_config_bool(Config::DisableAdhocGroups).await? {
+        info!(context, "not creating ad-hoc group: disabled by config");
+        return Ok(None);
+    }
+
     if mime_parser.is_mailinglist_message() {
         info!(
             context,
@@ -1952,6 +2905,7 @@ async fn create_adhoc_group(
         None,
     )
     .await?;
+    new_chat_id.set_creator_id(context, from_id).await?;
     for &member_id in member_ids.iter() {
         chat::add_to_chat_contacts_table(context, new_chat_id, member_id).await?;
     }
@@ -2022,15 +2976,16 @@ async fn check_verified_properties(
         .sql
         .query_map(
             &format!(
-                "SELECT c.addr, LENGTH(ps.verified_key_fingerprint)  FROM contacts c  \
+                "SELECT c.id, c.addr, LENGTH(ps.verified_key_fingerprint)  FROM contacts c  \
              LEFT JOIN acpeerstates ps ON c.addr=ps.addr  WHERE c.id IN({}) ",
                 sql::repeat_vars(to_ids.len())
             ),
             rusqlite::params_from_iter(to_ids),
             |row| {
-                let to_addr: String = row.get(0)?;
-                let is_verified: i32 = row.get(1).unwrap_or(0);
-                Ok((to_addr, is_verified != 0))
+                let to_id: ContactId = row.get(0)?;
+                let to_addr: String = row.get(1)?;
+                let is_verified: i32 = row.get(2).unwrap_or(0);
+                Ok((to_id, to_addr, is_verified != 0))
             },
             |rows| {
                 rows.collect::<std::result::Result<Vec<_>, _>>()
@@ -2039,7 +2994,7 @@ async fn check_verified_properties(
         )
         .await?;
 
-    for (to_addr, mut is_verified) in rows.into_iter() {
+    for (to_id, to_addr, mut is_verified) in rows.into_iter() {
         info!(
             context,
             "check_verified_properties: {:?} self={:?}",
@@ -2069,6 +3024,7 @@ async fn check_verified_properties(
                             PeerstateVerifiedStatus::BidirectVerified,
                         );
                         peerstate.save_to_db(&context.sql, false).await?;
+                        chat::accept_verified_contact_request(context, to_id).await?;
                         is_verified = true;
                     }
                 }
@@ -2181,18 +3137,18 @@ async fn add_or_lookup_contacts_by_address_list(
     prevent_rename: bool,
 ) -> Result<Vec<ContactId>> {
     let mut contact_ids = HashSet::new();
-    for info in address_list.iter() {
-        let addr = &info.addr;
-        if !may_be_valid_addr(addr) {
+    for recipient in normalize_recipient_list(address_list) {
+        if !recipient.is_valid {
             continue;
         }
         let display_name = if prevent_rename {
             Some("")
         } else {
-            info.display_name.as_deref()
+            recipient.display_name.as_deref()
         };
-        contact_ids
-            .insert(add_or_lookup_contact_by_addr(context, display_name, addr, origin).await?);
+        contact_ids.insert(
+            add_or_lookup_contact_by_addr(context, display_name, &recipient.addr, origin).await?,
+        );
     }
 
     Ok(contact_ids.into_iter().collect::<Vec<ContactId>>())
@@ -2224,10 +3180,11 @@ mod tests {
     use crate::chat::get_chat_contacts;
     use crate::chat::{get_chat_msgs, ChatItem, ChatVisibility};
     use crate::chatlist::Chatlist;
-    use crate::constants::DC_GCL_NO_SPECIALS;
+    use crate::constants::{DC_GCL_NO_SPECIALS, DC_GCM_INFO_ONLY};
     use crate::imap::prefetch_should_download;
-    use crate::message::Message;
+    use crate::message::{AuthResults, Message};
     use crate::test_utils::{get_chat_msg, TestContext, TestContextManager};
+    use crate::tools::time;
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_grpid_simple() {
@@ -2397,24 +3354,137 @@ async fn test_adhoc_group_show_all() {
     }
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
-    async fn test_read_receipt_and_unarchive() -> Result<()> {
-        // create alice's account
+    async fn test_adhoc_group_min_origin() -> Result<()> {
         let t = TestContext::new_alice().await;
+        t.set_config(Config::ShowEmails, Some("2")).await?;
+        t.set_config(
+            Config::AdhocGroupMinOrigin,
+            Some(&(Origin::IncomingReplyTo as u32).to_string()),
+        )
+        .await?;
 
-        let bob_id = Contact::create(&t, "bob", "bob@example.com").await?;
-        let one2one_id = ChatId::create_for_contact(&t, bob_id).await?;
-        one2one_id
-            .set_visibility(&t, ChatVisibility::Archived)
-            .await
-            .unwrap();
-        let one2one = Chat::load_from_db(&t, one2one_id).await?;
-        assert!(one2one.get_visibility() == ChatVisibility::Archived);
+        // Unknown sender, the message goes to the 1:1 chat as contact request.
+        receive_imf(&t, GRP_MAIL, false).await?;
+        let msg = t.get_last_msg().await;
+        let chat = Chat::load_from_db(&t, msg.chat_id).await?;
+        assert_eq!(chat.typ, Chattype::Single);
+        assert!(chat.is_contact_request());
 
-        // create a group with bob, archive group
-        let group_id = chat::create_group_chat(&t, ProtectionStatus::Unprotected, "foo").await?;
-        chat::add_contact_to_chat(&t, group_id, bob_id).await?;
-        assert_eq!(chat::get_chat_msgs(&t, group_id, 0).await.unwrap().len(), 0);
-        group_id
+        // Known sender, an ad-hoc group is created.
+        Contact::create(&t, "Dave", "dave@example.com").await?;
+        receive_imf(
+            &t,
+            b"From: dave@example.com\n\
+              To: alice@example.org, claire@example.com\n\
+              Subject: group with Alice, Claire and Dave\n\
+              Message-ID: <4444@example.com>\n\
+              Date: Sun, 22 Mar 2020 22:37:57 +0000\n\
+              \n\
+              hello\n",
+            false,
+        )
+        .await?;
+        let msg = t.get_last_msg().await;
+        let chat = Chat::load_from_db(&t, msg.chat_id).await?;
+        assert_eq!(chat.typ, Chattype::Group);
+        assert_eq!(chat::get_chat_contacts(&t, chat.id).await?.len(), 3);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_adhoc_group_rename() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        t.set_config(Config::ShowEmails, Some("2")).await?;
+        let dave_id = Contact::create(&t, "Dave", "dave@example.com").await?;
+        receive_imf(
+            &t,
+            b"From: dave@example.com\n\
+              To: alice@example.org, claire@example.com\n\
+              Subject: group with Alice, Claire and Dave\n\
+              Message-ID: <5555@example.com>\n\
+              Date: Sun, 22 Mar 2020 22:37:57 +0000\n\
+              \n\
+              hello\n",
+            false,
+        )
+        .await?;
+        let chat_id = t.get_last_msg().await.chat_id;
+        chat_id.accept(&t).await?;
+        let chat = Chat::load_from_db(&t, chat_id).await?;
+        assert_eq!(chat.typ, Chattype::Group);
+        assert_eq!(chat.get_creator_id(), Some(dave_id));
+
+        // Ad-hoc groups have no admin, every member may rename them.
+        assert_eq!(chat.get_admin_id(&t).await?, None);
+        assert!(chat.can_edit_name(&t).await?);
+        chat::set_chat_name(&t, chat_id, "Renamed").await?;
+        assert_eq!(Chat::load_from_db(&t, chat_id).await?.name, "Renamed");
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_disable_adhoc_groups() -> Result<()> {
+        let mut tcm = TestContextManager::new().await;
+        let alice = tcm.alice().await;
+        let bob = tcm.bob().await;
+        alice.set_config(Config::ShowEmails, Some("2")).await?;
+        alice
+            .set_config_bool(Config::DisableAdhocGroups, true)
+            .await?;
+
+        receive_imf(&alice, GRP_MAIL, false).await?;
+        let msg = alice.get_last_msg().await;
+        let chat = Chat::load_from_db(&alice, msg.chat_id).await?;
+        assert_eq!(chat.typ, Chattype::Single);
+        assert_eq!(
+            chat::get_chat_contacts(&alice, chat.id).await?,
+            vec![msg.from_id]
+        );
+        assert_eq!(
+            Contact::load_from_db(&alice, msg.from_id).await?.get_addr(),
+            "bob@example.com"
+        );
+
+        // Groups with a group ID are still created.
+        let bob_chat_id =
+            chat::create_group_chat(&bob, ProtectionStatus::Unprotected, "foo").await?;
+        chat::add_contact_to_chat(
+            &bob,
+            bob_chat_id,
+            bob.add_or_lookup_contact(&alice).await.id,
+        )
+        .await?;
+        let msg = alice
+            .recv_msg(&bob.send_text(bob_chat_id, "hi").await)
+            .await;
+        let chat = Chat::load_from_db(&alice, msg.chat_id).await?;
+        assert_eq!(chat.typ, Chattype::Group);
+        assert_eq!(chat.name, "foo");
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_read_receipt_and_unarchive() -> Result<()> {
+        // create alice's account
+        let t = TestContext::new_alice().await;
+
+        let bob_id = Contact::create(&t, "bob", "bob@example.com").await?;
+        let one2one_id = ChatId::create_for_contact(&t, bob_id).await?;
+        one2one_id
+            .set_visibility(&t, ChatVisibility::Archived)
+            .await
+            .unwrap();
+        let one2one = Chat::load_from_db(&t, one2one_id).await?;
+        assert!(one2one.get_visibility() == ChatVisibility::Archived);
+
+        // create a group with bob, archive group
+        let group_id = chat::create_group_chat(&t, ProtectionStatus::Unprotected, "foo").await?;
+        chat::add_contact_to_chat(&t, group_id, bob_id).await?;
+        assert_eq!(chat::get_chat_msgs(&t, group_id, 0).await.unwrap().len(), 0);
+        group_id
             .set_visibility(&t, ChatVisibility::Archived)
             .await?;
         let group = Chat::load_from_db(&t, group_id).await?;
@@ -2510,6 +3580,79 @@ async fn test_read_receipt_and_unarchive() -> Result<()> {
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_msg_read_event_per_reader() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        let bob_id = Contact::create(&t, "bob", "bob@example.net").await?;
+        let fiona_id = Contact::create(&t, "fiona", "fiona@example.net").await?;
+        let group_id = chat::create_group_chat(&t, ProtectionStatus::Unprotected, "foo").await?;
+        chat::add_contact_to_chat(&t, group_id, bob_id).await?;
+        chat::add_contact_to_chat(&t, group_id, fiona_id).await?;
+        let sent = t.send_text(group_id, "hello").await;
+        let msg = Message::load_from_db(&t, sent.sender_msg_id).await?;
+
+        for (i, addr) in ["bob@example.net", "fiona@example.net"].iter().enumerate() {
+            receive_imf(
+                &t,
+                format!(
+                    "From: {addr}\n\
+                     To: alice@example.org\n\
+                     Subject: message opened\n\
+                     Date: Sun, 22 Mar 2020 23:37:57 +0000\n\
+                     Chat-Version: 1.0\n\
+                     Message-ID: <Mr.{i}.12345678902@example.net>\n\
+                     Content-Type: multipart/report; report-type=disposition-notification; boundary=\"SNIPP\"\n\
+                     \n\
+                     \n\
+                     --SNIPP\n\
+                     Content-Type: text/plain; charset=utf-8\n\
+                     \n\
+                     Read receipts do not guarantee sth. was read.\n\
+                     \n\
+                     \n\
+                     --SNIPP\n\
+                     Content-Type: message/disposition-notification\n\
+                     \n\
+                     Reporting-UA: Delta Chat 1.28.0\n\
+                     Original-Recipient: rfc822;{addr}\n\
+                     Final-Recipient: rfc822;{addr}\n\
+                     Original-Message-ID: <{mid}>\n\
+                     Disposition: manual-action/MDN-sent-automatically; displayed\n\
+                     \n\
+                     \n\
+                     --SNIPP--",
+                    addr = addr,
+                    i = i,
+                    mid = msg.rfc724_mid
+                )
+                .as_bytes(),
+                false,
+            )
+            .await?;
+        }
+
+        for expected_contact_id in [bob_id, fiona_id] {
+            let event = t
+                .evtracker
+                .get_matching(|evt| matches!(evt, EventType::MsgRead { .. }))
+                .await;
+            if let EventType::MsgRead {
+                chat_id,
+                msg_id,
+                contact_id,
+            } = event
+            {
+                assert_eq!(chat_id, group_id);
+                assert_eq!(msg_id, msg.id);
+                assert_eq!(contact_id, expected_contact_id);
+            }
+        }
+
+        let msg = Message::load_from_db(&t, msg.id).await?;
+        assert_eq!(msg.state, MessageState::OutMdnRcvd);
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_no_from() {
         // if there is no from given, from_id stays 0 which is just fine. These messages
@@ -2872,6 +4015,148 @@ async fn test_parse_ndn_group_msg() -> Result<()> {
         Ok(())
     }
 
+    /// Tests that `X-Failed-Recipients` is used
+    /// if the delivery status does not contain `Final-Recipient`.
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_parse_ndn_x_failed_recipients() -> Result<()> {
+        let t = TestContext::new().await;
+        t.configure_addr("alice@example.org").await;
+
+        receive_imf(
+            &t,
+            b"From: alice@example.org\n\
+                 To: bob@example.net, claire@example.net\n\
+                 Subject: foo\n\
+                 Message-ID: <Gr.abcde.12345@example.org>\n\
+                 Chat-Version: 1.0\n\
+                 Chat-Group-ID: abcde\n\
+                 Chat-Group-Name: foo\n\
+                 Date: Sun, 22 Mar 2020 22:37:57 +0000\n\
+                 \n\
+                 hello\n",
+            false,
+        )
+        .await?;
+        let msg_id = t.get_last_msg().await.id;
+
+        receive_imf(
+            &t,
+            b"From: Mail Delivery System <MAILER-DAEMON@example.org>\n\
+                 To: alice@example.org\n\
+                 Subject: Undelivered Mail Returned to Sender\n\
+                 Message-ID: <ndn-1@example.org>\n\
+                 X-Failed-Recipients: claire@example.net\n\
+                 Date: Sun, 22 Mar 2020 22:40:00 +0000\n\
+                 Content-Type: multipart/report; report-type=delivery-status; boundary=\"XXX\"\n\
+                 \n\
+                 --XXX\n\
+                 Content-Type: text/plain\n\
+                 \n\
+                 Delivery failed.\n\
+                 \n\
+                 --XXX\n\
+                 Content-Type: message/delivery-status\n\
+                 \n\
+                 Reporting-MTA: dns; mail.example.org\n\
+                 \n\
+                 Action: failed\n\
+                 Status: 5.1.1\n\
+                 \n\
+                 --XXX\n\
+                 Content-Type: message/rfc822\n\
+                 \n\
+                 Message-ID: <Gr.abcde.12345@example.org>\n\
+                 \n\
+                 --XXX--\n",
+            false,
+        )
+        .await?;
+
+        let msg = Message::load_from_db(&t, msg_id).await?;
+        assert_eq!(msg.state, MessageState::OutFailed);
+        let last_msg = t.get_last_msg_in(msg.chat_id).await;
+        assert_eq!(last_msg.from_id, ContactId::INFO);
+        assert_eq!(
+            last_msg.text,
+            Some(stock_str::failed_sending_to(&t, "claire@example.net").await)
+        );
+
+        Ok(())
+    }
+
+    /// Tests that only recipients with `Action: failed` are reported by a DSN
+    /// which also lists delivered recipients.
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_parse_ndn_partially_delivered() -> Result<()> {
+        let t = TestContext::new().await;
+        t.configure_addr("alice@example.org").await;
+
+        receive_imf(
+            &t,
+            b"From: alice@example.org\n\
+                 To: bob@example.net, claire@example.net\n\
+                 Subject: foo\n\
+                 Message-ID: <Gr.abcde.12345@example.org>\n\
+                 Chat-Version: 1.0\n\
+                 Chat-Group-ID: abcde\n\
+                 Chat-Group-Name: foo\n\
+                 Date: Sun, 22 Mar 2020 22:37:57 +0000\n\
+                 \n\
+                 hello\n",
+            false,
+        )
+        .await?;
+        let msg_id = t.get_last_msg().await.id;
+
+        receive_imf(
+            &t,
+            b"From: Mail Delivery System <MAILER-DAEMON@example.org>\n\
+                 To: alice@example.org\n\
+                 Subject: Undelivered Mail Returned to Sender\n\
+                 Message-ID: <ndn-2@example.org>\n\
+                 Date: Sun, 22 Mar 2020 22:40:00 +0000\n\
+                 Content-Type: multipart/report; report-type=delivery-status; boundary=\"XXX\"\n\
+                 \n\
+                 --XXX\n\
+                 Content-Type: text/plain\n\
+                 \n\
+                 Delivery failed for some recipients.\n\
+                 \n\
+                 --XXX\n\
+                 Content-Type: message/delivery-status\n\
+                 \n\
+                 Reporting-MTA: dns; mail.example.org\n\
+                 \n\
+                 Final-Recipient: rfc822; bob@example.net\n\
+                 Action: delivered\n\
+                 Status: 2.0.0\n\
+                 \n\
+                 Final-Recipient: rfc822; claire@example.net\n\
+                 Action: failed\n\
+                 Status: 5.1.1\n\
+                 \n\
+                 --XXX\n\
+                 Content-Type: message/rfc822\n\
+                 \n\
+                 Message-ID: <Gr.abcde.12345@example.org>\n\
+                 \n\
+                 --XXX--\n",
+            false,
+        )
+        .await?;
+
+        let msg = Message::load_from_db(&t, msg_id).await?;
+        assert_eq!(msg.state, MessageState::OutFailed);
+        let last_msg = t.get_last_msg_in(msg.chat_id).await;
+        assert_eq!(last_msg.from_id, ContactId::INFO);
+        assert_eq!(
+            last_msg.text,
+            Some(stock_str::failed_sending_to(&t, "claire@example.net").await)
+        );
+
+        Ok(())
+    }
+
     async fn load_imf_email(context: &Context, imf_raw: &[u8]) -> Message {
         context
             .set_config(Config::ShowEmails, Some("2"))
@@ -2988,6 +4273,118 @@ async fn test_github_mailing_list() -> Result<()> {
     \n\
     body 4\n";
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_mailing_list_metadata() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        t.set_config(Config::ShowEmails, Some("2")).await?;
+
+        receive_imf(&t, DC_MAILINGLIST, false).await?;
+        let msg = t.get_last_msg().await;
+        let chat_id = msg.chat_id;
+
+        // Messages already in the database are traced as well and not added again.
+        let trace = receive_imf_trace(&t, DC_MAILINGLIST).await?;
+        assert_eq!(trace.chat_id, Some(chat_id));
+        assert_eq!(t.get_last_msg().await.id, msg.id);
+        let chat = Chat::load_from_db(&t, chat_id).await?;
+        assert_eq!(
+            chat.get_mailinglist_description(),
+            Some("discussions about and around https://delta.chat developments")
+        );
+        assert_eq!(chat.get_mailinglist_help_url(), None);
+        assert_eq!(chat.get_mailinglist_archive_url(), None);
+
+        receive_imf(
+            &t,
+            b"From: Bob <bob@posteo.org>\n\
+            To: delta@codespeak.net\n\
+            Subject: [delta-dev] Newer message\n\
+            Message-ID: <38944@posteo.org>\n\
+            List-ID: Delta Chat Devs <delta.codespeak.net>\n\
+            List-Post: <mailto:delta@codespeak.net>\n\
+            List-Help: <mailto:delta-request@codespeak.net?subject=help> (List Instructions), <https://codespeak.net/mailman/listinfo/delta>\n\
+            List-Archive: <https://codespeak.net/pipermail/delta/> (Web Archive)\n\
+            Precedence: list\n\
+            Date: Mon, 23 Mar 2020 10:00:00 +0000\n\
+            \n\
+            body\n",
+            false,
+        )
+        .await?;
+        let chat = Chat::load_from_db(&t, chat_id).await?;
+        assert_eq!(chat.get_mailinglist_description(), Some("Delta Chat Devs"));
+        assert_eq!(
+            chat.get_mailinglist_help_url(),
+            Some("https://codespeak.net/mailman/listinfo/delta")
+        );
+        assert_eq!(
+            chat.get_mailinglist_archive_url(),
+            Some("https://codespeak.net/pipermail/delta/")
+        );
+
+        // An older message does not overwrite the metadata.
+        receive_imf(
+            &t,
+            b"From: Bob <bob@posteo.org>\n\
+            To: delta@codespeak.net\n\
+            Subject: [delta-dev] Older message\n\
+            Message-ID: <38945@posteo.org>\n\
+            List-ID: \"Old description\" <delta.codespeak.net>\n\
+            List-Post: <mailto:delta@codespeak.net>\n\
+            List-Archive: <https://old.example.org/archive/>\n\
+            Precedence: list\n\
+            Date: Sat, 21 Mar 2020 10:00:00 +0000\n\
+            \n\
+            body\n",
+            false,
+        )
+        .await?;
+        let chat = Chat::load_from_db(&t, chat_id).await?;
+        assert_eq!(chat.get_mailinglist_description(), Some("Delta Chat Devs"));
+        assert_eq!(
+            chat.get_mailinglist_archive_url(),
+            Some("https://codespeak.net/pipermail/delta/")
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_receive_imf_trace_mailinglist() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        t.set_config(Config::ShowEmails, Some("2")).await?;
+
+        let trace = receive_imf_trace(&t, DC_MAILINGLIST).await?;
+        assert_eq!(trace.outcome("direction"), Some("incoming"));
+        assert_eq!(trace.outcome("mailinglist type"), Some("ListIdBased"));
+        assert_eq!(
+            trace.outcome("mailing list chat"),
+            Some("delta.codespeak.net, no chat")
+        );
+        assert_eq!(trace.outcome("final chat"), Some("new chat"));
+        assert_eq!(trace.chat_id, None);
+        // Tracing does not create the chat.
+        assert!(chat::get_chat_id_by_grpid(&t, "delta.codespeak.net")
+            .await?
+            .is_none());
+
+        receive_imf(&t, DC_MAILINGLIST, false).await?;
+        let chat_id = t.get_last_msg().await.chat_id;
+
+        let trace = receive_imf_trace(&t, DC_MAILINGLIST2).await?;
+        assert_eq!(trace.outcome("mailinglist type"), Some("ListIdBased"));
+        assert_eq!(
+            trace.outcome("mailing list chat"),
+            Some(format!("delta.codespeak.net, found {}", chat_id).as_str())
+        );
+        assert_eq!(trace.chat_id, Some(chat_id));
+        assert_eq!(
+            trace.steps.last().unwrap().to_string(),
+            format!("final chat → {}", chat_id)
+        );
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_classic_mailing_list() -> Result<()> {
         let t = TestContext::new_alice().await;
@@ -3036,27 +4433,227 @@ async fn test_classic_mailing_list() -> Result<()> {
     }
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
-    async fn test_other_device_writes_to_mailinglist() -> Result<()> {
+    async fn test_mailing_list_quote_threading() -> Result<()> {
         let t = TestContext::new_alice().await;
         t.set_config(Config::ShowEmails, Some("2")).await?;
-        receive_imf(&t, DC_MAILINGLIST, false).await.unwrap();
-        let first_msg = t.get_last_msg().await;
-        let first_chat = Chat::load_from_db(&t, first_msg.chat_id).await?;
-        assert_eq!(
-            first_chat.param.get(Param::ListPost).unwrap(),
-            "delta@codespeak.net"
-        );
+        receive_imf(&t, DC_MAILINGLIST, false).await?;
+        let quoted = t.get_last_msg().await;
+        let chat_id = quoted.chat_id;
+        chat_id.accept(&t).await?;
+        receive_imf(&t, DC_MAILINGLIST2, false).await?;
+
+        // Quoting an older list message threads the reply below it.
+        let mut msg = Message::new(Viewtype::Text);
+        msg.set_text(Some("Reply to Bob".to_string()));
+        msg.set_quote(&t, Some(&quoted)).await?;
+        let sent = t.send_msg(chat_id, &mut msg).await;
+        let mime = sent.payload();
+        assert!(mime.contains("In-Reply-To: <38942@posteo.org>\r\n"));
+        assert!(mime.contains("References: <38942@posteo.org>\r\n"));
+        assert!(!mime.contains("38943@posteo.org"));
 
-        let list_post_contact_id =
-            Contact::lookup_id_by_addr(&t, "delta@codespeak.net", Origin::Unknown)
-                .await?
-                .unwrap();
-        let list_post_contact = Contact::load_from_db(&t, list_post_contact_id).await?;
-        assert_eq!(
-            list_post_contact.param.get(Param::ListId).unwrap(),
-            "delta.codespeak.net"
-        );
-        assert_eq!(
+        // The quoted message's references are kept.
+        receive_imf(
+            &t,
+            b"From: Charlie <charlie@posteo.org>\n\
+            To: delta@codespeak.net\n\
+            Subject: Re: [delta-dev] What's up?\n\
+            Message-ID: <38944@posteo.org>\n\
+            In-Reply-To: <38942@posteo.org>\n\
+            References: <38940@posteo.org> <38942@posteo.org>\n\
+            List-ID: \"discussions about and around https://delta.chat developments\" <delta.codespeak.net>\n\
+            List-Post: <mailto:delta@codespeak.net>\n\
+            Precedence: list\n\
+            Date: Sun, 22 Mar 2020 22:38:57 +0000\n\
+            \n\
+            body 3\n",
+            false,
+        )
+        .await?;
+        let quoted = t.get_last_msg_in(chat_id).await;
+        let newer = String::from_utf8_lossy(DC_MAILINGLIST2).replace("38943", "38945");
+        receive_imf(&t, newer.as_bytes(), false).await?;
+
+        let mut msg = Message::new(Viewtype::Text);
+        msg.set_text(Some("Reply to Charlie".to_string()));
+        msg.set_quote(&t, Some(&quoted)).await?;
+        let sent = t.send_msg(chat_id, &mut msg).await;
+        let mime = sent.payload();
+        assert!(mime.contains("In-Reply-To: <38944@posteo.org>\r\n"));
+        assert!(mime.contains("References: <38940@posteo.org> <38944@posteo.org>\r\n"));
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_group_message_recipients() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        receive_imf(
+            &t,
+            b"From: Bob <bob@example.net>\n\
+                 To: alice@example.org, claire@example.com\n\
+                 Cc: dave@example.org\n\
+                 Chat-Version: 1.0\n\
+                 Chat-Group-ID: abcde12345\n\
+                 Chat-Group-Name: Group\n\
+                 Subject: Hello\n\
+                 Message-ID: <recipients@example.net>\n\
+                 Date: Sun, 22 Mar 2020 22:37:57 +0000\n\
+                 \n\
+                 hello\n",
+            false,
+        )
+        .await?;
+        let msg = t.get_last_msg().await;
+        let chat = Chat::load_from_db(&t, msg.chat_id).await?;
+        assert_eq!(chat.typ, Chattype::Group);
+
+        let claire_id = Contact::lookup_id_by_addr(&t, "claire@example.com", Origin::Unknown)
+            .await?
+            .unwrap();
+        let dave_id = Contact::lookup_id_by_addr(&t, "dave@example.org", Origin::Unknown)
+            .await?
+            .unwrap();
+        let mut expected = vec![ContactId::SELF, claire_id, dave_id];
+        expected.sort_unstable_by_key(|id| id.to_u32());
+        assert_eq!(msg.recipients(), expected);
+
+        // 1:1 messages do not store recipients.
+        receive_imf(
+            &t,
+            b"From: Bob <bob@example.net>\n\
+                 To: alice@example.org\n\
+                 Chat-Version: 1.0\n\
+                 Subject: Hello\n\
+                 Message-ID: <recipients2@example.net>\n\
+                 Date: Sun, 22 Mar 2020 22:38:57 +0000\n\
+                 \n\
+                 hello\n",
+            false,
+        )
+        .await?;
+        let msg = t.get_last_msg().await;
+        assert!(msg.recipients().is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_trusted_mailinglist_domain() {
+        assert!(is_trusted_mailinglist_domain(
+            "example.org, codespeak.net",
+            "delta.codespeak.net"
+        ));
+        assert!(is_trusted_mailinglist_domain(
+            "Codespeak.net",
+            "codespeak.net"
+        ));
+        assert!(!is_trusted_mailinglist_domain(
+            "codespeak.net",
+            "notcodespeak.net"
+        ));
+        assert!(!is_trusted_mailinglist_domain("", "codespeak.net"));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_trusted_mailinglist_domain() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        t.set_config(Config::ShowEmails, Some("2")).await?;
+        t.set_config(
+            Config::TrustedMailinglistDomains,
+            Some("example.org,codespeak.net"),
+        )
+        .await?;
+
+        receive_imf(&t, DC_MAILINGLIST, false).await?;
+        let msg = t.get_last_msg().await;
+        let chat = Chat::load_from_db(&t, msg.chat_id).await?;
+        assert_eq!(chat.typ, Chattype::Mailinglist);
+        assert!(!chat.is_contact_request());
+        assert_eq!(chat.blocked, Blocked::Not);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_mute_new_mailinglists() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        t.set_config(Config::ShowEmails, Some("2")).await?;
+        t.set_config_bool(Config::MuteNewMailinglists, true).await?;
+
+        receive_imf(&t, DC_MAILINGLIST, false).await?;
+        let msg = t.get_last_msg().await;
+        let chat = Chat::load_from_db(&t, msg.chat_id).await?;
+        assert_eq!(chat.typ, Chattype::Mailinglist);
+        assert!(chat.is_muted());
+
+        chat.id.accept(&t).await?;
+        chat.id.set_visibility(&t, ChatVisibility::Archived).await?;
+        receive_imf(&t, DC_MAILINGLIST2, false).await?;
+        let msg = t.get_last_msg().await;
+        assert_eq!(msg.chat_id, chat.id);
+        let chat = Chat::load_from_db(&t, chat.id).await?;
+        assert!(chat.is_muted());
+        assert_eq!(chat.get_visibility(), ChatVisibility::Archived);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_create_private_chat_with_mailinglist_sender() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        t.set_config(Config::ShowEmails, Some("2")).await?;
+        receive_imf(&t, DC_MAILINGLIST, false).await?;
+        let msg = t.get_last_msg().await;
+        let mailinglist = Chat::load_from_db(&t, msg.chat_id).await?;
+        assert_eq!(mailinglist.typ, Chattype::Mailinglist);
+
+        let chat_id = msg.id.create_private_chat_with_sender(&t, true).await?;
+        assert_ne!(chat_id, msg.chat_id);
+        let chat = Chat::load_from_db(&t, chat_id).await?;
+        assert_eq!(chat.typ, Chattype::Single);
+        assert!(!chat.is_contact_request());
+        assert!(chat.can_send(&t).await?);
+
+        let contacts = get_chat_contacts(&t, chat_id).await?;
+        assert_eq!(contacts.len(), 1);
+        let contact = Contact::load_from_db(&t, contacts[0]).await?;
+        assert_eq!(contact.get_addr(), "bob@posteo.org");
+
+        let draft = chat_id.get_draft(&t).await?.unwrap();
+        assert_eq!(draft.quoted_text(), msg.get_text());
+
+        // Calling it again returns the same chat.
+        assert_eq!(
+            msg.id.create_private_chat_with_sender(&t, false).await?,
+            chat_id
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_other_device_writes_to_mailinglist() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        t.set_config(Config::ShowEmails, Some("2")).await?;
+        receive_imf(&t, DC_MAILINGLIST, false).await.unwrap();
+        let first_msg = t.get_last_msg().await;
+        let first_chat = Chat::load_from_db(&t, first_msg.chat_id).await?;
+        assert_eq!(
+            first_chat.param.get(Param::ListPost).unwrap(),
+            "delta@codespeak.net"
+        );
+
+        let list_post_contact_id =
+            Contact::lookup_id_by_addr(&t, "delta@codespeak.net", Origin::Unknown)
+                .await?
+                .unwrap();
+        let list_post_contact = Contact::load_from_db(&t, list_post_contact_id).await?;
+        assert_eq!(
+            list_post_contact.param.get(Param::ListId).unwrap(),
+            "delta.codespeak.net"
+        );
+        assert_eq!(
             chat::get_chat_id_by_grpid(&t, "delta.codespeak.net")
                 .await?
                 .unwrap(),
@@ -3230,6 +4827,87 @@ async fn test_mailing_list_multiple_names_in_subject() -> Result<()> {
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_auto_submitted_prevents_rename() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        t.set_config(Config::ShowEmails, Some("2")).await?;
+        receive_imf(
+            &t,
+            b"From: Bob Real <bob@example.net>\n\
+    To: alice@example.org\n\
+    Subject: hi\n\
+    Message-ID: <1@example.net>\n\
+    Date: Sun, 22 Mar 2020 22:37:57 +0000\n\
+    \n\
+    hello\n",
+            false,
+        )
+        .await?;
+        let bob_id = t.get_last_msg().await.from_id;
+
+        receive_imf(
+            &t,
+            b"From: GitLab <bob@example.net>\n\
+    To: alice@example.org\n\
+    Subject: pipeline failed\n\
+    Message-ID: <2@example.net>\n\
+    Auto-Submitted: auto-generated\n\
+    Date: Sun, 22 Mar 2020 22:38:57 +0000\n\
+    \n\
+    failed\n",
+            false,
+        )
+        .await?;
+        let msg = t.get_last_msg().await;
+        assert_eq!(msg.from_id, bob_id);
+        assert_eq!(
+            msg.param.get(Param::OverrideSenderDisplayname),
+            Some("GitLab")
+        );
+        let bob = Contact::load_from_db(&t, bob_id).await?;
+        assert_eq!(bob.get_authname(), "Bob Real");
+
+        // Configured header markers prevent renaming as well.
+        receive_imf(
+            &t,
+            b"From: Out of Office <bob@example.net>\n\
+    To: alice@example.org\n\
+    Subject: away\n\
+    Message-ID: <3@example.net>\n\
+    X-Auto-Response-Suppress: All\n\
+    Date: Sun, 22 Mar 2020 22:39:57 +0000\n\
+    \n\
+    away\n",
+            false,
+        )
+        .await?;
+        let msg = t.get_last_msg().await;
+        assert_eq!(
+            msg.param.get(Param::OverrideSenderDisplayname),
+            Some("Out of Office")
+        );
+        let bob = Contact::load_from_db(&t, bob_id).await?;
+        assert_eq!(bob.get_authname(), "Bob Real");
+
+        // Without markers, the name is updated as usual.
+        receive_imf(
+            &t,
+            b"From: Bob Renamed <bob@example.net>\n\
+    To: alice@example.org\n\
+    Subject: new name\n\
+    Message-ID: <4@example.net>\n\
+    Date: Sun, 22 Mar 2020 22:40:57 +0000\n\
+    \n\
+    hi\n",
+            false,
+        )
+        .await?;
+        let bob = Contact::load_from_db(&t, bob_id).await?;
+        assert_eq!(bob.get_authname(), "Bob Renamed");
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_majordomo_mailing_list() -> Result<()> {
         let t = TestContext::new_alice().await;
@@ -3346,6 +5024,105 @@ async fn test_dhl_mailing_list() -> Result<()> {
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_reply_to_future_parent_sort_timestamp() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        t.set_config(Config::ShowEmails, Some("2")).await?;
+        let tomorrow = time() + 24 * 60 * 60;
+
+        receive_imf(
+            &t,
+            b"From: bob@example.net\n\
+              To: alice@example.org\n\
+              Subject: Future\n\
+              Message-ID: <parent@example.net>\n\
+              Date: Sun, 22 Mar 2099 22:37:57 +0000\n\
+              \n\
+              Sent from the future\n",
+            false,
+        )
+        .await?;
+        let parent = t.get_last_msg().await;
+        assert!(parent.get_sort_timestamp() < tomorrow);
+
+        // Databases written by older versions may contain parents with timestamps in the future.
+        t.sql
+            .execute(
+                "UPDATE msgs SET timestamp=? WHERE id=?",
+                paramsv![time() + 365 * 24 * 60 * 60, parent.id],
+            )
+            .await?;
+
+        let received = receive_imf(
+            &t,
+            b"From: bob@example.net\n\
+              To: alice@example.org\n\
+              Subject: Re: Future\n\
+              Message-ID: <reply@example.net>\n\
+              In-Reply-To: <parent@example.net>\n\
+              Date: Sun, 22 Mar 2020 22:37:57 +0000\n\
+              \n\
+              Reply from the present\n",
+            false,
+        )
+        .await?
+        .unwrap();
+        let reply = Message::load_from_db(&t, received.msg_ids[0]).await?;
+        assert_eq!(reply.chat_id, parent.chat_id);
+        assert!(reply.get_sort_timestamp() < tomorrow);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_mailing_list_why_cant_send() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        t.set_config(Config::ShowEmails, Some("2")).await?;
+
+        // delta-dev uses the same List-Post header in all messages and is writable.
+        receive_imf(&t, DC_MAILINGLIST, false).await?;
+        let chat_id = t.get_last_msg().await.chat_id;
+        let chat = Chat::load_from_db(&t, chat_id).await?;
+        assert_eq!(
+            chat.why_cant_send(&t).await?,
+            Some(chat::CantSendReason::ContactRequest)
+        );
+        chat_id.accept(&t).await?;
+        receive_imf(&t, DC_MAILINGLIST2, false).await?;
+        let chat = Chat::load_from_db(&t, chat_id).await?;
+        assert_eq!(chat.why_cant_send(&t).await?, None);
+
+        // GitHub uses a different List-Post header for each message.
+        receive_imf(&t, GH_MAILINGLIST, false).await?;
+        let chat_id = t.get_last_msg().await.chat_id;
+        chat_id.accept(&t).await?;
+        let chat = Chat::load_from_db(&t, chat_id).await?;
+        assert_eq!(chat.why_cant_send(&t).await?, None);
+        receive_imf(&t, GH_MAILINGLIST2.as_bytes(), false).await?;
+        let chat = Chat::load_from_db(&t, chat_id).await?;
+        assert_eq!(
+            chat.why_cant_send(&t).await?,
+            Some(chat::CantSendReason::MailinglistVaryingListPost)
+        );
+
+        // DHL sends notifications only, without List-Post header.
+        receive_imf(
+            &t,
+            include_bytes!("../test-data/message/mailinglist_dhl.eml"),
+            false,
+        )
+        .await?;
+        let chat_id = t.get_last_msg().await.chat_id;
+        chat_id.accept(&t).await?;
+        let chat = Chat::load_from_db(&t, chat_id).await?;
+        assert_eq!(
+            chat.why_cant_send(&t).await?,
+            Some(chat::CantSendReason::MailinglistWithoutListPost)
+        );
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_dpd_mailing_list() -> Result<()> {
         let t = TestContext::new_alice().await;
@@ -3721,6 +5498,70 @@ async fn test_in_reply_to() {
         assert!(!msg.chat_id.is_special());
     }
 
+    /// Test that a member removal only removes the member named in `Chat-Group-Member-Removed`,
+    /// even if `To:` contains only a subset of the members, e.g. because of an alias.
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_member_removal_with_incomplete_recipients() -> Result<()> {
+        let t = TestContext::new_bob().await;
+
+        receive_imf(
+            &t,
+            b"From: alice@example.org\n\
+              To: bob@example.net, fiona@example.net, claire@example.net\n\
+              Subject: foo\n\
+              Message-ID: <create@example.org>\n\
+              Chat-Version: 1.0\n\
+              Chat-Group-ID: abcdefghijk\n\
+              Chat-Group-Name: foo\n\
+              Date: Sun, 22 Mar 2020 22:37:57 +0000\n\
+              \n\
+              hello\n",
+            false,
+        )
+        .await?;
+        let chat_id = t.get_last_msg().await.chat_id;
+        assert_eq!(get_chat_contacts(&t, chat_id).await?.len(), 4);
+
+        receive_imf(
+            &t,
+            b"From: alice@example.org\n\
+              To: team@example.org\n\
+              Subject: foo\n\
+              Message-ID: <remove@example.org>\n\
+              Chat-Version: 1.0\n\
+              Chat-Group-ID: abcdefghijk\n\
+              Chat-Group-Name: foo\n\
+              Chat-Group-Member-Removed: claire@example.net\n\
+              Date: Sun, 22 Mar 2020 22:38:57 +0000\n\
+              \n\
+              Member claire@example.net removed.\n",
+            false,
+        )
+        .await?;
+        assert_eq!(t.get_last_msg().await.chat_id, chat_id);
+
+        let mut addrs = Vec::new();
+        for contact_id in get_chat_contacts(&t, chat_id).await? {
+            addrs.push(
+                Contact::load_from_db(&t, contact_id)
+                    .await?
+                    .get_addr()
+                    .to_string(),
+            );
+        }
+        addrs.sort();
+        assert_eq!(
+            addrs,
+            vec![
+                "alice@example.org",
+                "bob@example.net",
+                "fiona@example.net",
+                "team@example.org"
+            ]
+        );
+        Ok(())
+    }
+
     /// Test that classical MUA messages are assigned to group chats
     /// based on the `In-Reply-To` header for two-member groups.
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -4220,24 +6061,252 @@ async fn test_duplicate_message() -> Result<()> {
     }
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
-    async fn test_ignore_footer_status_from_mailinglist() -> Result<()> {
-        let t = TestContext::new_alice().await;
-        t.set_config(Config::ShowEmails, Some("2")).await?;
-        let bob_id = Contact::add_or_lookup(&t, "", "bob@example.net", Origin::IncomingUnknownCc)
-            .await?
-            .0;
-        let bob = Contact::load_from_db(&t, bob_id).await?;
-        assert_eq!(bob.get_status(), "");
-        assert_eq!(Chatlist::try_load(&t, 0, None, None).await?.len(), 0);
+    async fn test_reaction() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let bob = TestContext::new_bob().await;
+        let alice_chat = alice.create_chat(&bob).await;
+        alice.send_text(alice_chat.id, "Hi Bob").await;
+        let msg = alice.get_last_msg_in(alice_chat.id).await;
+        let msgs_cnt = get_chat_msgs(&alice, alice_chat.id, 0).await?.len();
 
         receive_imf(
-            &t,
-            b"From: Bob <bob@example.net>
-To: Alice <alice@example.org>
-Message-ID: <1@example.org>
-Subject: first message
-
-body 1
+            &alice,
+            format!(
+                "From: Bob <bob@example.net>\n\
+                 To: alice@example.org\n\
+                 Message-ID: <reaction1@example.net>\n\
+                 In-Reply-To: <{}>\n\
+                 Chat-Version: 1.0\n\
+                 Chat-Content: reaction\n\
+                 Date: Sun, 22 Mar 2020 22:37:57 +0000\n\
+                 Content-Type: text/plain; charset=utf-8\n\
+                 \n\
+                 👍\n",
+                msg.rfc724_mid
+            )
+            .as_bytes(),
+            false,
+        )
+        .await?;
+        let bob_id = alice.add_or_lookup_contact(&bob).await.id;
+        let msg = Message::load_from_db(&alice, msg.id).await?;
+        assert_eq!(
+            msg.reactions(),
+            [(bob_id, "👍".to_string())].into_iter().collect()
+        );
+        assert_eq!(
+            get_chat_msgs(&alice, alice_chat.id, 0).await?.len(),
+            msgs_cnt
+        );
+
+        // RFC 9078 reaction with an empty body removes the reaction.
+        receive_imf(
+            &alice,
+            format!(
+                "From: Bob <bob@example.net>\n\
+                 To: alice@example.org\n\
+                 Subject: Re: Hi\n\
+                 Message-ID: <reaction2@example.net>\n\
+                 In-Reply-To: <{}>\n\
+                 Date: Sun, 22 Mar 2020 22:38:57 +0000\n\
+                 Content-Type: text/plain; charset=utf-8\n\
+                 Content-Disposition: reaction\n\
+                 \n\
+                 \n",
+                msg.rfc724_mid
+            )
+            .as_bytes(),
+            false,
+        )
+        .await?;
+        let msg = Message::load_from_db(&alice, msg.id).await?;
+        assert!(msg.reactions().is_empty());
+        assert_eq!(
+            get_chat_msgs(&alice, alice_chat.id, 0).await?.len(),
+            msgs_cnt
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_classic_email_subject() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        alice.set_config(Config::ShowEmails, Some("2")).await?;
+
+        receive_imf(
+            &alice,
+            b"Subject: Meeting tomorrow
+Message-ID: <subject@example.org>
+To: Alice <alice@example.org>
+From: Bob <bob@example.org>
+Date: Sun, 22 Mar 2020 22:37:57 +0000
+Content-Type: text/plain; charset=utf-8
+
+Let's meet at ten.",
+            false,
+        )
+        .await?;
+        let msg = alice.get_last_msg().await;
+        assert_eq!(
+            msg.get_text().unwrap(),
+            "Meeting tomorrow – Let's meet at ten."
+        );
+        assert_eq!(msg.get_subject(), "Meeting tomorrow");
+
+        // The subject is loaded from the database as well.
+        let msg = Message::load_from_db(&alice, msg.id).await?;
+        assert_eq!(msg.get_subject(), "Meeting tomorrow");
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_html_status() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        alice.set_config(Config::ShowEmails, Some("2")).await?;
+
+        receive_imf(
+            &alice,
+            b"Subject: Offer
+Message-ID: <html-status@example.org>
+To: Alice <alice@example.org>
+From: Bob <bob@example.org>
+Date: Sun, 22 Mar 2020 22:37:57 +0000
+Content-Type: text/html; charset=utf-8
+
+<html><body><p>Message content</p>
+<p>-- <br><b>Bob</b><br><a href=\"https://example.org/\">example.org</a><script>alert(1)</script></p>
+</body></html>
+",
+            false,
+        )
+        .await?;
+        let msg = alice.get_last_msg().await;
+        assert!(msg.has_html_status());
+        let contact = Contact::load_from_db(&alice, msg.from_id).await?;
+        assert!(contact.get_status().contains("Bob"));
+        let status_html = contact.get_status_html(&alice).await?.unwrap();
+        assert!(status_html.starts_with("<b>Bob</b><br><a>example.org</a>"));
+        assert!(!status_html.contains("https://"));
+        assert!(!status_html.contains("script"));
+
+        // A plain text signature replaces the HTML one.
+        receive_imf(
+            &alice,
+            b"Subject: Offer
+Message-ID: <plain-status@example.org>
+To: Alice <alice@example.org>
+From: Bob <bob@example.org>
+Date: Sun, 22 Mar 2020 22:38:57 +0000
+Content-Type: text/plain; charset=utf-8
+
+Message content
+
+-- 
+Plain signature",
+            false,
+        )
+        .await?;
+        let msg = alice.get_last_msg().await;
+        assert!(!msg.has_html_status());
+        let contact = Contact::load_from_db(&alice, msg.from_id).await?;
+        assert_eq!(contact.get_status(), "Plain signature");
+        assert_eq!(contact.get_status_html(&alice).await?, None);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_status_tracking() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        alice.set_config(Config::ShowEmails, Some("2")).await?;
+        let bob_id =
+            Contact::add_or_lookup(&alice, "", "bob@example.net", Origin::IncomingUnknownCc)
+                .await?
+                .0;
+        let claire_id =
+            Contact::add_or_lookup(&alice, "", "claire@example.net", Origin::IncomingUnknownCc)
+                .await?
+                .0;
+
+        // Bob's status is not tracked.
+        Contact::set_track_status(&alice, bob_id, false).await?;
+        for i in 1..=2 {
+            receive_imf(
+                &alice,
+                format!(
+                    "From: Bob <bob@example.net>\n\
+                     To: Alice <alice@example.org>\n\
+                     Message-ID: <{}@example.net>\n\
+                     Subject: message {}\n\
+                     Date: Sun, 22 Mar 2020 22:3{}:57 +0000\n\
+                     \n\
+                     body\n\
+                     \n\
+                     -- \n\
+                     Disclaimer {}",
+                    i, i, i, i
+                )
+                .as_bytes(),
+                false,
+            )
+            .await?;
+            let bob = Contact::load_from_db(&alice, bob_id).await?;
+            assert!(!bob.is_status_tracked());
+            assert_eq!(bob.get_status(), "");
+        }
+
+        // Claire's long disclaimer is truncated.
+        let disclaimer = "This message is confidential. ".repeat(170);
+        receive_imf(
+            &alice,
+            format!(
+                "From: Claire <claire@example.net>\n\
+                 To: Alice <alice@example.org>\n\
+                 Message-ID: <claire@example.net>\n\
+                 Subject: legal\n\
+                 Date: Sun, 22 Mar 2020 22:37:57 +0000\n\
+                 \n\
+                 body\n\
+                 \n\
+                 -- \n\
+                 {}",
+                disclaimer
+            )
+            .as_bytes(),
+            false,
+        )
+        .await?;
+        let claire = Contact::load_from_db(&alice, claire_id).await?;
+        assert!(claire.is_status_tracked());
+        let status = claire.get_status();
+        assert!(status.len() < 600);
+        assert!(status.starts_with("This message is confidential."));
+        assert!(status.ends_with("[...]"));
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_ignore_footer_status_from_mailinglist() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        t.set_config(Config::ShowEmails, Some("2")).await?;
+        let bob_id = Contact::add_or_lookup(&t, "", "bob@example.net", Origin::IncomingUnknownCc)
+            .await?
+            .0;
+        let bob = Contact::load_from_db(&t, bob_id).await?;
+        assert_eq!(bob.get_status(), "");
+        assert_eq!(Chatlist::try_load(&t, 0, None, None).await?.len(), 0);
+
+        receive_imf(
+            &t,
+            b"From: Bob <bob@example.net>
+To: Alice <alice@example.org>
+Message-ID: <1@example.org>
+Subject: first message
+
+body 1
 
 --
 Original signature",
@@ -4355,25 +6424,407 @@ async fn test_chat_assignment_private_classical_reply() {
 MIME-Version: 1.0
 In-Reply-To: <{0}>
 
-Private reply"#,
-                    if *outgoing_is_classical {
-                        "abcd@gmx.de"
-                    } else {
-                        "Gr.eJ_llQIXf0K.buxmrnMmG0Y@gmx.de"
-                    }
+Private reply"#,
+                    if *outgoing_is_classical {
+                        "abcd@gmx.de"
+                    } else {
+                        "Gr.eJ_llQIXf0K.buxmrnMmG0Y@gmx.de"
+                    }
+                )
+                .as_bytes(),
+                false,
+            )
+            .await
+            .unwrap();
+
+            let private_msg = t.get_last_msg().await;
+            assert_eq!(private_msg.text.unwrap(), "Private reply");
+            let private_chat = Chat::load_from_db(&t, private_msg.chat_id).await.unwrap();
+            assert_eq!(private_chat.typ, Chattype::Single);
+            assert_ne!(private_msg.chat_id, group_msg.chat_id);
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_bcc_only_delivery() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        t.set_config(Config::ShowEmails, Some("2")).await?;
+
+        receive_imf(
+            &t,
+            b"Subject: Group
+Message-ID: <group@example.org>
+Date: Fri, 28 May 2021 10:15:05 +0000
+To: Bob <bob@example.com>, <claire@example.com>
+From: Alice <alice@example.org>
+Content-Type: text/plain; charset=utf-8
+
+Hello all",
+            false,
+        )
+        .await?;
+        let group_msg = t.get_last_msg().await;
+        let group_chat = Chat::load_from_db(&t, group_msg.chat_id).await?;
+        assert_eq!(group_chat.typ, Chattype::Group);
+
+        // Reply without To/Cc, we are only a Bcc recipient.
+        let received = receive_imf(
+            &t,
+            b"Subject: Re: Group
+Message-ID: <bcc-reply@example.com>
+In-Reply-To: <group@example.org>
+References: <group@example.org>
+Date: Fri, 28 May 2021 10:20:05 +0000
+From: Bob <bob@example.com>
+Content-Type: text/plain; charset=utf-8
+
+Blind reply",
+            false,
+        )
+        .await?
+        .unwrap();
+        let msg = Message::load_from_db(&t, received.msg_ids[0]).await?;
+        assert_ne!(msg.chat_id, group_msg.chat_id);
+        assert_eq!(msg.param.get_int(Param::BccDelivery), Some(1));
+
+        let chat = Chat::load_from_db(&t, msg.chat_id).await?;
+        assert_eq!(chat.typ, Chattype::Single);
+        let bob_id = Contact::lookup_id_by_addr(&t, "bob@example.com", Origin::Unknown)
+            .await?
+            .unwrap();
+        assert_eq!(get_chat_contacts(&t, msg.chat_id).await?, vec![bob_id]);
+
+        // Messages with recipients are not tagged.
+        assert!(!group_msg.param.exists(Param::BccDelivery));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_known_aliases() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        t.set_config(Config::ShowEmails, Some("2")).await?;
+        t.set_config(Config::KnownAliases, Some("alice@forward.example"))
+            .await?;
+
+        // Incoming message to an alias goes to the 1:1 chat with the sender.
+        let received = receive_imf(
+            &t,
+            b"From: Bob <bob@example.net>
+To: alice@forward.example
+Subject: Hi
+Message-ID: <alias-in@example.net>
+Date: Fri, 28 May 2021 10:15:05 +0000
+Content-Type: text/plain; charset=utf-8
+
+Hello Alice",
+            false,
+        )
+        .await?
+        .unwrap();
+        let msg = Message::load_from_db(&t, received.msg_ids[0]).await?;
+        assert_eq!(msg.to_id, ContactId::SELF);
+        let chat = Chat::load_from_db(&t, msg.chat_id).await?;
+        assert_eq!(chat.typ, Chattype::Single);
+        let bob_id = Contact::lookup_id_by_addr(&t, "bob@example.net", Origin::Unknown)
+            .await?
+            .unwrap();
+        assert_eq!(get_chat_contacts(&t, chat.id).await?, vec![bob_id]);
+        assert_eq!(
+            Contact::lookup_id_by_addr(&t, "alice@forward.example", Origin::Unknown).await?,
+            Some(ContactId::SELF)
+        );
+
+        // Message sent from an alias is an outgoing message in the same chat.
+        let received = receive_imf(
+            &t,
+            b"From: Alice <alice@forward.example>
+To: bob@example.net
+Subject: Re: Hi
+Message-ID: <alias-out@forward.example>
+In-Reply-To: <alias-in@example.net>
+Date: Fri, 28 May 2021 10:20:05 +0000
+Content-Type: text/plain; charset=utf-8
+
+Hello Bob",
+            false,
+        )
+        .await?
+        .unwrap();
+        let msg = Message::load_from_db(&t, received.msg_ids[0]).await?;
+        assert_eq!(msg.from_id, ContactId::SELF);
+        assert_eq!(msg.chat_id, chat.id);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_delivered_to() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        t.set_config(Config::ShowEmails, Some("2")).await?;
+
+        receive_imf(
+            &t,
+            b"Subject: Hello
+Message-ID: <alias1@example.com>
+Delivered-To: <Shop@Example.org>
+Delivered-To: alice@example.org
+Date: Fri, 28 May 2021 10:15:05 +0000
+To: shop@example.org
+From: Bob <bob@example.com>
+Content-Type: text/plain; charset=utf-8
+
+Order confirmed",
+            false,
+        )
+        .await?;
+        let msg = t.get_last_msg().await;
+        assert_eq!(msg.get_delivered_to(), Some("shop@example.org".to_string()));
+        assert_eq!(msg.get_override_sender_name(), None);
+
+        receive_imf(
+            &t,
+            b"Subject: Hello
+Message-ID: <alias2@example.com>
+X-Original-To: news@example.org
+Date: Fri, 28 May 2021 10:16:05 +0000
+To: news@example.org
+From: Bob <bob@example.com>
+Content-Type: text/plain; charset=utf-8
+
+Newsletter",
+            false,
+        )
+        .await?;
+        let msg = t.get_last_msg().await;
+        assert_eq!(msg.get_delivered_to(), Some("news@example.org".to_string()));
+
+        // With the option enabled, the alias is shown next to the sender name.
+        t.set_config_bool(Config::ShowDeliveredToAlias, true)
+            .await?;
+        receive_imf(
+            &t,
+            b"Subject: Hello
+Message-ID: <alias3@example.com>
+Delivered-To: shop@example.org
+Date: Fri, 28 May 2021 10:17:05 +0000
+To: shop@example.org
+From: Bob <bob@example.com>
+Content-Type: text/plain; charset=utf-8
+
+Shipped",
+            false,
+        )
+        .await?;
+        let msg = t.get_last_msg().await;
+        assert_eq!(
+            msg.get_override_sender_name(),
+            Some("Bob (via shop@example.org)".to_string())
+        );
+
+        // Mails delivered to the primary address are not tagged.
+        receive_imf(
+            &t,
+            b"Subject: Hello
+Message-ID: <alias4@example.com>
+Delivered-To: alice@example.org
+Date: Fri, 28 May 2021 10:18:05 +0000
+To: alice@example.org
+From: Bob <bob@example.com>
+Content-Type: text/plain; charset=utf-8
+
+Personal",
+            false,
+        )
+        .await?;
+        let msg = t.get_last_msg().await;
+        assert_eq!(
+            msg.get_delivered_to(),
+            Some("alice@example.org".to_string())
+        );
+        assert_eq!(msg.get_override_sender_name(), None);
+
+        // Chat messages are not tagged.
+        let alice = TestContext::new_alice().await;
+        let bob = TestContext::new_bob().await;
+        alice
+            .set_config_bool(Config::ShowDeliveredToAlias, true)
+            .await?;
+        let chat = bob.create_chat(&alice).await;
+        let sent = bob.send_text(chat.id, "hi").await;
+        let payload = format!("Delivered-To: shop@example.org\n{}", sent.payload());
+        receive_imf(&alice, payload.as_bytes(), false).await?;
+        let msg = alice.get_last_msg().await;
+        assert_eq!(msg.get_delivered_to(), Some("shop@example.org".to_string()));
+        assert_eq!(msg.get_override_sender_name(), None);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_authentication_results() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        t.set_config(Config::ShowEmails, Some("2")).await?;
+
+        // Without a known authserv-id of the own provider, no header is trusted.
+        receive_imf(
+            &t,
+            b"Subject: Hello
+Message-ID: <auth0@example.com>
+Authentication-Results: mx.example.org; dkim=pass header.d=example.com
+Date: Fri, 28 May 2021 10:14:05 +0000
+To: alice@example.org
+From: Bob <bob@example.com>
+Content-Type: text/plain; charset=utf-8
+
+Spoofed",
+            false,
+        )
+        .await?;
+        let msg = t.get_last_msg().await;
+        assert_eq!(msg.authentication_results(), AuthResults::default());
+
+        t.set_config(Config::AuthservId, Some("mx.example.org"))
+            .await?;
+        receive_imf(
+            &t,
+            b"Subject: Hello
+Message-ID: <auth1@example.com>
+Authentication-Results: mx.example.org; dkim=pass header.d=example.com;
+ spf=pass smtp.mailfrom=example.com; dmarc=pass header.from=example.com
+Date: Fri, 28 May 2021 10:15:05 +0000
+To: alice@example.org
+From: Bob <bob@example.com>
+Content-Type: text/plain; charset=utf-8
+
+Signed",
+            false,
+        )
+        .await?;
+        let msg = t.get_last_msg().await;
+        assert_eq!(
+            msg.authentication_results(),
+            AuthResults {
+                dkim: Some("pass".to_string()),
+                spf: Some("pass".to_string()),
+                dmarc: Some("pass".to_string()),
+            }
+        );
+
+        receive_imf(
+            &t,
+            b"Subject: Hello
+Message-ID: <auth2@example.com>
+Authentication-Results: mx.example.org; dkim=fail reason=\"bad signature\"; spf=softfail
+Date: Fri, 28 May 2021 10:16:05 +0000
+To: alice@example.org
+From: Bob <bob@example.com>
+Content-Type: text/plain; charset=utf-8
+
+Forged",
+            false,
+        )
+        .await?;
+        let msg = t.get_last_msg().await;
+        assert_eq!(
+            msg.authentication_results(),
+            AuthResults {
+                dkim: Some("fail".to_string()),
+                spf: Some("softfail".to_string()),
+                dmarc: None,
+            }
+        );
+
+        receive_imf(
+            &t,
+            b"Subject: Hello
+Message-ID: <auth3@example.com>
+Date: Fri, 28 May 2021 10:17:05 +0000
+To: alice@example.org
+From: Bob <bob@example.com>
+Content-Type: text/plain; charset=utf-8
+
+Unknown",
+            false,
+        )
+        .await?;
+        let msg = t.get_last_msg().await;
+        assert_eq!(msg.authentication_results(), AuthResults::default());
+
+        // A DKIM pass of another domain does not authenticate the sender.
+        receive_imf(
+            &t,
+            b"Subject: Hello
+Message-ID: <auth4@example.com>
+Authentication-Results: mx.example.org; dkim=pass header.d=example.net
+Date: Fri, 28 May 2021 10:18:05 +0000
+To: alice@example.org
+From: Bob <bob@example.com>
+Content-Type: text/plain; charset=utf-8
+
+Misaligned",
+            false,
+        )
+        .await?;
+        let msg = t.get_last_msg().await;
+        assert_eq!(msg.authentication_results(), AuthResults::default());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_group_avatar_missing_part() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let bob = TestContext::new_bob().await;
+
+        let alice_chat_id =
+            chat::create_group_chat(&alice, ProtectionStatus::Unprotected, "grp").await?;
+        let bob_contact = alice.add_or_lookup_contact(&bob).await;
+        chat::add_contact_to_chat(&alice, alice_chat_id, bob_contact.id).await?;
+        let sent = alice.send_text(alice_chat_id, "hi").await;
+        let bob_chat_id = bob.recv_msg(&sent).await.chat_id;
+
+        let file = alice.get_blobdir().join("avatar.png");
+        fs::write(&file, include_bytes!("../test-data/image/avatar64x64.png")).await?;
+        chat::set_chat_profile_image(&alice, alice_chat_id, file.to_str().unwrap()).await?;
+        bob.recv_msg(&alice.pop_sent_msg().await).await;
+        let bob_chat = Chat::load_from_db(&bob, bob_chat_id).await?;
+        let avatar = bob_chat.param.get(Param::ProfileImage).unwrap().to_string();
+
+        for (i, header) in ["avatar.png", "base64:AAAA"].iter().enumerate() {
+            receive_imf(
+                &bob,
+                format!(
+                    "From: alice@example.org
+To: bob@example.net
+Subject: Group image changed
+Message-ID: <avatar{}@example.org>
+Chat-Version: 1.0
+Chat-Group-ID: {}
+Chat-Group-Name: grp
+Chat-Content: group-avatar-changed
+Chat-Group-Avatar: {}
+Content-Type: text/plain
+
+Group image changed
+",
+                    i, bob_chat.grpid, header
                 )
                 .as_bytes(),
                 false,
             )
-            .await
-            .unwrap();
+            .await?;
 
-            let private_msg = t.get_last_msg().await;
-            assert_eq!(private_msg.text.unwrap(), "Private reply");
-            let private_chat = Chat::load_from_db(&t, private_msg.chat_id).await.unwrap();
-            assert_eq!(private_chat.typ, Chattype::Single);
-            assert_ne!(private_msg.chat_id, group_msg.chat_id);
+            let msg = bob.get_last_msg_in(bob_chat_id).await;
+            assert_eq!(
+                msg.get_text().unwrap(),
+                "Group image change could not be applied."
+            );
+            let bob_chat = Chat::load_from_db(&bob, bob_chat_id).await?;
+            assert_eq!(
+                bob_chat.param.get(Param::ProfileImage),
+                Some(avatar.as_str())
+            );
         }
+
+        Ok(())
     }
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -5144,4 +7595,520 @@ async fn test_no_private_reply_to_blocked_account() -> Result<()> {
 
         Ok(())
     }
+
+    /// Tests that an undecryptable message to an unknown group creates a placeholder group
+    /// which is merged into the real group once a decryptable message arrives.
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_undecryptable_group_placeholder() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let bob = TestContext::new_bob().await;
+
+        // Alice sends a message to Bob so Bob gets Alice's key.
+        let alice_chat = alice.create_chat(&bob).await;
+        bob.recv_msg(&alice.send_text(alice_chat.id, "hi").await)
+            .await;
+
+        let bob_chat_id =
+            chat::create_group_chat(&bob, ProtectionStatus::Unprotected, "Group").await?;
+        let bob_alice_contact = bob.add_or_lookup_contact(&alice).await;
+        chat::add_contact_to_chat(&bob, bob_chat_id, bob_alice_contact.id).await?;
+
+        // The first message cannot be decrypted by Alice.
+        let sent = bob.send_text(bob_chat_id, "first").await;
+        let payload = sent.payload();
+        let begin = payload.find("-----BEGIN PGP MESSAGE-----").unwrap();
+        let end = payload.find("-----END PGP MESSAGE-----").unwrap();
+        let corrupted = format!(
+            "{}-----BEGIN PGP MESSAGE-----\r\n\r\nZm9vYmFy\r\n{}",
+            &payload[..begin],
+            &payload[end..]
+        );
+        receive_imf(&alice, corrupted.as_bytes(), false).await?;
+        let msg = alice.get_last_msg().await;
+        assert!(msg.error().is_some());
+        let chat = Chat::load_from_db(&alice, msg.chat_id).await?;
+        assert_eq!(chat.typ, Chattype::Group);
+        assert_eq!(chat.name, stock_str::unknown_group(&alice).await);
+        assert!(chat.is_contact_request());
+        assert_eq!(get_chat_contacts(&alice, chat.id).await?.len(), 2);
+
+        // An unencrypted message from a stranger knowing the group ID does not take over the group.
+        let grpid = Chat::load_from_db(&bob, bob_chat_id).await?.grpid;
+        receive_imf(
+            &alice,
+            format!(
+                "From: claire@example.net\n\
+                 To: alice@example.org\n\
+                 Subject: hi\n\
+                 Chat-Version: 1.0\n\
+                 Chat-Group-ID: {}\n\
+                 Chat-Group-Name: Hijacked\n\
+                 Message-ID: <hijack@example.net>\n\
+                 Date: Sun, 22 Mar 2020 22:37:57 +0000\n\
+                 \n\
+                 hi\n",
+                grpid
+            )
+            .as_bytes(),
+            false,
+        )
+        .await?;
+        let chat = Chat::load_from_db(&alice, chat.id).await?;
+        assert_eq!(chat.name, stock_str::unknown_group(&alice).await);
+        assert!(chat.param.exists(Param::GroupPlaceholder));
+        assert_eq!(get_chat_contacts(&alice, chat.id).await?.len(), 2);
+        let msg_cnt = chat.id.get_msg_cnt(&alice).await?;
+
+        // The second message can be decrypted and turns the placeholder into the real group.
+        let received = alice
+            .recv_msg(&bob.send_text(bob_chat_id, "second").await)
+            .await;
+        assert_eq!(received.chat_id, chat.id);
+        let chat = Chat::load_from_db(&alice, chat.id).await?;
+        assert_eq!(chat.name, "Group");
+        assert!(!chat.param.exists(Param::GroupPlaceholder));
+        assert_eq!(chat.id.get_msg_cnt(&alice).await?, msg_cnt + 1);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_auto_accept_known_contact_requests() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let bob = TestContext::new_bob().await;
+        alice
+            .set_config_bool(Config::AutoAcceptKnownContactRequests, true)
+            .await?;
+        let bob_chat = bob.create_chat(&alice).await;
+
+        // First-time senders still end up in a contact request.
+        let received = alice
+            .recv_msg(&bob.send_text(bob_chat.id, "hi").await)
+            .await;
+        let chat = Chat::load_from_db(&alice, received.chat_id).await?;
+        assert!(chat.is_contact_request());
+
+        // Once Bob is known, the contact request is accepted automatically.
+        let alice_bob_contact = alice.add_or_lookup_contact(&bob).await;
+        Contact::scaleup_origin_by_id(&alice, alice_bob_contact.id, Origin::IncomingReplyTo)
+            .await?;
+        let received = alice
+            .recv_msg(&bob.send_text(bob_chat.id, "hi again").await)
+            .await;
+        assert_eq!(received.chat_id, chat.id);
+        let chat = Chat::load_from_db(&alice, chat.id).await?;
+        assert!(!chat.is_contact_request());
+
+        // Without the option, known contacts are still shown as contact requests.
+        let alice2 = TestContext::new_alice().await;
+        let alice2_bob_contact = alice2.add_or_lookup_contact(&bob).await;
+        Contact::scaleup_origin_by_id(&alice2, alice2_bob_contact.id, Origin::IncomingReplyTo)
+            .await?;
+        let received = alice2
+            .recv_msg(&bob.send_text(bob_chat.id, "hi alice2").await)
+            .await;
+        let chat = Chat::load_from_db(&alice2, received.chat_id).await?;
+        assert!(chat.is_contact_request());
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_delete_mdns_from_server() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let bob = TestContext::new_bob().await;
+        alice
+            .set_config_bool(Config::DeleteMdnsFromServer, true)
+            .await?;
+        let alice_chat = alice.create_chat(&bob).await;
+        let sent = alice.send_text(alice_chat.id, "hi").await;
+        let alice_msg = Message::load_from_db(&alice, sent.sender_msg_id).await?;
+        assert_eq!(alice_msg.state, MessageState::OutDelivered);
+
+        let mdn = format!(
+            "From: bob@example.net\n\
+             To: alice@example.org\n\
+             Subject: message opened\n\
+             Date: Sun, 22 Mar 2020 23:37:57 +0000\n\
+             Chat-Version: 1.0\n\
+             Message-ID: <Mr.12345678902@example.net>\n\
+             Content-Type: multipart/report; report-type=disposition-notification; boundary=\"SNIPP\"\n\
+             \n\
+             \n\
+             --SNIPP\n\
+             Content-Type: text/plain; charset=utf-8\n\
+             \n\
+             Read receipts do not guarantee sth. was read.\n\
+             \n\
+             \n\
+             --SNIPP\n\
+             Content-Type: message/disposition-notification\n\
+             \n\
+             Reporting-UA: Delta Chat 1.28.0\n\
+             Original-Recipient: rfc822;bob@example.net\n\
+             Final-Recipient: rfc822;bob@example.net\n\
+             Original-Message-ID: <{}>\n\
+             Disposition: manual-action/MDN-sent-automatically; displayed\n\
+             \n\
+             \n\
+             --SNIPP--",
+            alice_msg.rfc724_mid
+        );
+        alice
+            .sql
+            .execute(
+                "INSERT INTO imap (rfc724_mid, folder, target, uid, uidvalidity)
+                 VALUES ('Mr.12345678902@example.net', 'INBOX', 'INBOX', 1, 1);",
+                paramsv![],
+            )
+            .await?;
+        receive_imf(&alice, mdn.as_bytes(), false).await?;
+
+        let alice_msg = Message::load_from_db(&alice, alice_msg.id).await?;
+        assert_eq!(alice_msg.state, MessageState::OutMdnRcvd);
+        let target: String = alice
+            .sql
+            .query_get_value(
+                "SELECT target FROM imap WHERE rfc724_mid='Mr.12345678902@example.net';",
+                paramsv![],
+            )
+            .await?
+            .unwrap();
+        assert_eq!(target, "");
+
+        Ok(())
+    }
+
+    /// Tests that messages from broken senders reusing the same Message-ID are not dropped,
+    /// while real duplicates still are.
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_reused_message_id() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        t.set_config(Config::ShowEmails, Some("2")).await?;
+
+        let raw = |date: &str, text: &str| {
+            format!(
+                "From: notifications@example.net\n\
+                 To: alice@example.org\n\
+                 Subject: Notification\n\
+                 Message-ID: <static@example.net>\n\
+                 Date: {}\n\
+                 \n\
+                 {}\n",
+                date, text
+            )
+        };
+        let first = raw("Sun, 22 Mar 2020 22:37:57 +0000", "first notification");
+        let second = raw("Mon, 23 Mar 2020 10:00:00 +0000", "second notification");
+
+        receive_imf(&t, first.as_bytes(), false).await?;
+        let msg = t.get_last_msg().await;
+        assert_eq!(msg.rfc724_mid, "static@example.net");
+
+        receive_imf(&t, second.as_bytes(), false).await?;
+        let msg2 = t.get_last_msg().await;
+        assert_eq!(
+            msg2.text.as_deref(),
+            Some("Notification – second notification")
+        );
+        assert_ne!(msg2.rfc724_mid, "static@example.net");
+        assert_eq!(
+            msg2.param.get(Param::OriginalRfc724Mid),
+            Some("static@example.net")
+        );
+        assert_eq!(msg2.chat_id, msg.chat_id);
+        assert_eq!(msg.chat_id.get_msg_cnt(&t).await?, 2);
+
+        // Real duplicates are still dropped.
+        receive_imf(&t, first.as_bytes(), false).await?;
+        receive_imf(&t, second.as_bytes(), false).await?;
+        assert_eq!(msg.chat_id.get_msg_cnt(&t).await?, 2);
+
+        // A different date alone does not make a different message.
+        let resent = raw("Tue, 24 Mar 2020 10:00:00 +0000", "first notification");
+        receive_imf(&t, resent.as_bytes(), false).await?;
+        assert_eq!(msg.chat_id.get_msg_cnt(&t).await?, 2);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_catch_all_chat() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        t.set_config(Config::ShowEmails, Some("2")).await?;
+        let raw = |id: &str, from: &str, to: &str, envelope: &str| {
+            format!(
+                "{}\n\
+                 From: {}\n\
+                 To: {}\n\
+                 Subject: Order\n\
+                 Message-ID: <{}@example.net>\n\
+                 Date: Sun, 22 Mar 2020 22:37:57 +0000\n\
+                 Content-Type: text/plain; charset=utf-8\n\
+                 \n\
+                 Hello\n",
+                envelope, from, to, id
+            )
+        };
+
+        // Without the option, messages go to the chat with the sender.
+        receive_imf(
+            &t,
+            raw(
+                "0",
+                "bob@example.net",
+                "shop@example.org",
+                "Delivered-To: shop@example.org",
+            )
+            .as_bytes(),
+            false,
+        )
+        .await?;
+        let msg = t.get_last_msg().await;
+        assert_eq!(
+            Chat::load_from_db(&t, msg.chat_id).await?.typ,
+            Chattype::Single
+        );
+
+        t.set_config_bool(Config::CatchAllChat, true).await?;
+        receive_imf(
+            &t,
+            raw(
+                "1",
+                "bob@example.net",
+                "shop@example.org",
+                "Delivered-To: shop@example.org",
+            )
+            .as_bytes(),
+            false,
+        )
+        .await?;
+        let msg = t.get_last_msg().await;
+        let catch_all = Chat::load_from_db(&t, msg.chat_id).await?;
+        assert_eq!(catch_all.typ, Chattype::Mailinglist);
+        assert_eq!(catch_all.get_name(), "Catch-All");
+        assert!(!catch_all.is_contact_request());
+        assert_eq!(msg.get_delivered_to(), Some("shop@example.org".to_string()));
+
+        // The server may rewrite `Delivered-To` to the mailbox address.
+        receive_imf(
+            &t,
+            raw(
+                "2",
+                "claire@example.net",
+                "undisclosed-recipients:;",
+                "Delivered-To: alice@example.org\nX-Original-To: <news@EXAMPLE.org>",
+            )
+            .as_bytes(),
+            false,
+        )
+        .await?;
+        let msg = t.get_last_msg().await;
+        assert_eq!(msg.chat_id, catch_all.id);
+        assert_eq!(msg.get_delivered_to(), Some("news@example.org".to_string()));
+        assert_eq!(catch_all.id.get_msg_cnt(&t).await?, 2);
+
+        // Messages sent to a self address are not caught.
+        receive_imf(
+            &t,
+            raw(
+                "3",
+                "bob@example.net",
+                "alice@example.org, shop@example.org",
+                "Delivered-To: alice@example.org",
+            )
+            .as_bytes(),
+            false,
+        )
+        .await?;
+        let msg = t.get_last_msg().await;
+        assert_ne!(msg.chat_id, catch_all.id);
+
+        // Messages to other domains are not caught.
+        receive_imf(
+            &t,
+            raw(
+                "4",
+                "bob@example.net",
+                "list@example.com",
+                "Delivered-To: alice@example.org",
+            )
+            .as_bytes(),
+            false,
+        )
+        .await?;
+        let msg = t.get_last_msg().await;
+        assert_ne!(msg.chat_id, catch_all.id);
+
+        // Recipients set by the sender are not used.
+        receive_imf(
+            &t,
+            raw(
+                "5",
+                "bob@example.net",
+                "shop@example.org",
+                "Delivered-To: alice@example.org",
+            )
+            .as_bytes(),
+            false,
+        )
+        .await?;
+        let msg = t.get_last_msg().await;
+        assert_ne!(msg.chat_id, catch_all.id);
+        assert_eq!(catch_all.id.get_msg_cnt(&t).await?, 2);
+
+        // The catch-all chat is not found via `List-Id`.
+        receive_imf(
+            &t,
+            raw(
+                "6",
+                "bob@example.net",
+                "list@example.com",
+                "Delivered-To: alice@example.org\nList-Id: <#catch-all>",
+            )
+            .as_bytes(),
+            false,
+        )
+        .await?;
+        let msg = t.get_last_msg().await;
+        assert_ne!(msg.chat_id, catch_all.id);
+        assert_eq!(catch_all.id.get_msg_cnt(&t).await?, 2);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_content_language() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        t.set_config(Config::ShowEmails, Some("2")).await?;
+        receive_imf(
+            &t,
+            b"From: Bob <bob@example.net>\n\
+    To: alice@example.org\n\
+    Subject: Hallo\n\
+    Message-ID: <1@example.net>\n\
+    Content-Language: de\n\
+    Date: Sun, 22 Mar 2020 22:37:57 +0000\n\
+    \n\
+    Wie geht's?\n",
+            false,
+        )
+        .await?;
+        assert_eq!(t.get_last_msg().await.language(), Some("de".to_string()));
+
+        receive_imf(
+            &t,
+            b"From: Bob <bob@example.net>\n\
+    To: alice@example.org\n\
+    Subject: Hello\n\
+    Message-ID: <2@example.net>\n\
+    Date: Sun, 22 Mar 2020 22:38:57 +0000\n\
+    \n\
+    How are you?\n",
+            false,
+        )
+        .await?;
+        assert_eq!(t.get_last_msg().await.language(), None);
+
+        Ok(())
+    }
+
+    async fn recv_future_dated(t: &TestContext, from: &str, id: u32) -> Result<Message> {
+        let imf = format!(
+            "From: {from}\n\
+             To: alice@example.org\n\
+             Subject: hi\n\
+             Message-ID: <{id}@example.net>\n\
+             Date: Thu, 01 Jan 2099 00:00:00 +0000\n\
+             \n\
+             hello {id}\n"
+        );
+        receive_imf(t, imf.as_bytes(), false).await?;
+        Ok(t.get_last_msg().await)
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_future_dated_sender_clock_warning() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        t.set_config(Config::ShowEmails, Some("2")).await?;
+
+        let msg = recv_future_dated(&t, "bob@example.net", 1).await?;
+        assert!(msg.param.get_bool(Param::FutureDated).unwrap_or_default());
+        assert!(msg.get_timestamp() <= time());
+        let chat_id = msg.chat_id;
+        recv_future_dated(&t, "bob@example.net", 2).await?;
+        assert!(get_chat_msgs(&t, chat_id, DC_GCM_INFO_ONLY)
+            .await?
+            .is_empty());
+
+        // The third future-dated message within a day triggers the info message.
+        recv_future_dated(&t, "bob@example.net", 3).await?;
+        assert_eq!(get_chat_msgs(&t, chat_id, DC_GCM_INFO_ONLY).await?.len(), 1);
+        let info = t.get_last_msg_in(chat_id).await;
+        assert!(info.is_info());
+        assert!(info.get_text().unwrap().contains("bob@example.net"));
+
+        // The info message is not repeated.
+        recv_future_dated(&t, "bob@example.net", 4).await?;
+        recv_future_dated(&t, "bob@example.net", 5).await?;
+        assert_eq!(get_chat_msgs(&t, chat_id, DC_GCM_INFO_ONLY).await?.len(), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_future_dated_param_value() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        t.set_config(Config::ShowEmails, Some("2")).await?;
+
+        let chat_id = recv_future_dated(&t, "bob@example.net", 1).await?.chat_id;
+
+        // Other params containing `)=1` in their value are not counted.
+        for id in [2, 3] {
+            let msg = recv_msg_with_text(&t, "bob@example.net", id).await?;
+            t.sql
+                .execute(
+                    "UPDATE msgs SET param=? WHERE id=?",
+                    paramsv!["E=wrong clock )=1", msg.id],
+                )
+                .await?;
+        }
+        recv_future_dated(&t, "bob@example.net", 4).await?;
+        assert!(get_chat_msgs(&t, chat_id, DC_GCM_INFO_ONLY)
+            .await?
+            .is_empty());
+
+        Ok(())
+    }
+
+    async fn recv_msg_with_text(t: &TestContext, from: &str, id: u32) -> Result<Message> {
+        let imf = format!(
+            "From: {from}\n\
+             To: alice@example.org\n\
+             Subject: hi\n\
+             Message-ID: <{id}@example.net>\n\
+             Date: Sun, 22 Mar 2020 22:37:57 +0000\n\
+             \n\
+             hello {id}\n"
+        );
+        receive_imf(t, imf.as_bytes(), false).await?;
+        Ok(t.get_last_msg().await)
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_future_dated_local_clock_warning() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        t.set_config(Config::ShowEmails, Some("2")).await?;
+        for (i, from) in ["bob@example.net", "claire@example.org", "fiona@example.net"]
+            .iter()
+            .enumerate()
+        {
+            recv_future_dated(&t, from, i as u32).await?;
+        }
+        let device_chat_id = ChatId::get_for_contact(&t, ContactId::DEVICE).await?;
+        let msg = t.get_last_msg_in(device_chat_id).await;
+        assert!(msg.get_text().unwrap().contains("inaccurate"));
+
+        Ok(())
+    }
 }
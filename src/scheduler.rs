@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use anyhow::{bail, Context as _, Result};
 use async_channel::{self as channel, Receiver, Sender};
 use futures::{join, try_join};
@@ -7,7 +9,8 @@
 use crate::config::Config;
 use crate::context::Context;
 use crate::ephemeral::{self, delete_expired_imap_messages};
-use crate::imap::Imap;
+use crate::events::EventType;
+use crate::imap::{self, Imap};
 use crate::job;
 use crate::location;
 use crate::log::LogExt;
@@ -20,6 +23,9 @@
 
 pub(crate) mod connectivity;
 
+/// Time after which an unanswered ping is considered to indicate a lost network.
+const PING_TIMEOUT: Duration = Duration::from_secs(30);
+
 /// Job and connection scheduler.
 #[derive(Debug)]
 pub(crate) struct Scheduler {
@@ -56,6 +62,51 @@ pub async fn maybe_network_lost(&self) {
         connectivity::maybe_network_lost(self, lock).await;
     }
 
+    /// Measures the roundtrip time to the configured IMAP server in milliseconds.
+    ///
+    /// A NOOP command is sent over the existing inbox connection,
+    /// so IO must be started.
+    /// On success, [`EventType::PingResult`] is emitted.
+    /// If the server does not answer within 30 seconds or the connection fails,
+    /// the network is considered to be lost and reconnect is scheduled.
+    pub async fn ping(&self) -> Result<i64> {
+        self.ping_with_timeout(PING_TIMEOUT).await
+    }
+
+    pub(crate) async fn ping_with_timeout(&self, ping_timeout: Duration) -> Result<i64> {
+        let (reply_sender, reply_receiver) = channel::bounded(1);
+        {
+            // Do not hold the lock while waiting for the reply,
+            // the inbox loop may need it to make progress.
+            let lock = self.scheduler.read().await;
+            let scheduler = lock.as_ref().context("IO is not started")?;
+            scheduler
+                .ping_inbox(PingRequest {
+                    timeout: ping_timeout,
+                    reply_sender,
+                })
+                .await?;
+        }
+        let res = reply_receiver
+            .recv()
+            .await
+            .context("inbox loop stopped before answering the ping")?;
+        match res {
+            Ok(latency_ms) => {
+                info!(self, "IMAP roundtrip time is {} ms.", latency_ms);
+                self.emit_event(EventType::PingResult { latency_ms });
+                Ok(latency_ms)
+            }
+            Err(err) => {
+                warn!(self, "IMAP ping failed: {:#}", err);
+                if imap::is_network_error(&err) {
+                    self.maybe_network_lost().await;
+                }
+                Err(err)
+            }
+        }
+    }
+
     pub(crate) async fn interrupt_inbox(&self, info: InterruptInfo) {
         if let Some(scheduler) = &*self.scheduler.read().await {
             scheduler.interrupt_inbox(info).await;
@@ -88,6 +139,7 @@ async fn inbox_loop(ctx: Context, started: Sender<()>, inbox_handlers: ImapConne
     let ImapConnectionHandlers {
         mut connection,
         stop_receiver,
+        ping_receiver,
     } = inbox_handlers;
 
     let ctx1 = ctx.clone();
@@ -100,6 +152,11 @@ async fn inbox_loop(ctx: Context, started: Sender<()>, inbox_handlers: ImapConne
 
         let mut info = InterruptInfo::default();
         loop {
+            while let Ok(request) = ping_receiver.try_recv() {
+                let res = connection.ping(&ctx, request.timeout).await;
+                request.reply_sender.try_send(res).ok();
+            }
+
             let job = match job::load_next(&ctx, &info).await {
                 Err(err) => {
                     error!(ctx, "Failed loading job from the database: {:#}.", err);
@@ -279,6 +336,7 @@ async fn simple_imap_loop(
     let ImapConnectionHandlers {
         mut connection,
         stop_receiver,
+        ..
     } = inbox_handlers;
 
     let ctx1 = ctx.clone();
@@ -523,6 +581,11 @@ async fn interrupt_inbox(&self, info: InterruptInfo) {
         self.inbox.interrupt(info).await;
     }
 
+    /// Passes the ping request to the inbox loop and interrupts its IDLE.
+    async fn ping_inbox(&self, request: PingRequest) -> Result<()> {
+        self.inbox.ping(request).await
+    }
+
     async fn interrupt_mvbox(&self, info: InterruptInfo) {
         self.mvbox.interrupt(info).await;
     }
@@ -657,6 +720,8 @@ struct SmtpConnectionHandlers {
 #[derive(Debug)]
 pub(crate) struct ImapConnectionState {
     state: ConnectionState,
+    /// Channel to request a ping over the existing connection.
+    ping_sender: Sender<PingRequest>,
 }
 
 impl ImapConnectionState {
@@ -664,10 +729,12 @@ impl ImapConnectionState {
     async fn new(context: &Context) -> Result<(Self, ImapConnectionHandlers)> {
         let (stop_sender, stop_receiver) = channel::bounded(1);
         let (idle_interrupt_sender, idle_interrupt_receiver) = channel::bounded(1);
+        let (ping_sender, ping_receiver) = channel::unbounded();
 
         let handlers = ImapConnectionHandlers {
             connection: Imap::new_configured(context, idle_interrupt_receiver).await?,
             stop_receiver,
+            ping_receiver,
         };
 
         let state = ConnectionState {
@@ -676,7 +743,7 @@ async fn new(context: &Context) -> Result<(Self, ImapConnectionHandlers)> {
             connectivity: handlers.connection.connectivity.clone(),
         };
 
-        let conn = ImapConnectionState { state };
+        let conn = ImapConnectionState { state, ping_sender };
 
         Ok((conn, handlers))
     }
@@ -686,6 +753,16 @@ async fn interrupt(&self, info: InterruptInfo) {
         self.state.interrupt(info).await;
     }
 
+    /// Requests a ping and interrupts idle so the request is handled.
+    async fn ping(&self, request: PingRequest) -> Result<()> {
+        self.ping_sender
+            .send(request)
+            .await
+            .context("failed to ping, missing receiver")?;
+        self.interrupt(InterruptInfo::new(false)).await;
+        Ok(())
+    }
+
     /// Shutdown this connection completely.
     async fn stop(&self) -> Result<()> {
         self.state.stop().await?;
@@ -697,6 +774,14 @@ async fn stop(&self) -> Result<()> {
 struct ImapConnectionHandlers {
     connection: Imap,
     stop_receiver: Receiver<()>,
+    ping_receiver: Receiver<PingRequest>,
+}
+
+/// Request to measure the roundtrip time over an existing IMAP connection.
+#[derive(Debug)]
+struct PingRequest {
+    timeout: Duration,
+    reply_sender: Sender<Result<i64>>,
 }
 
 #[derive(Default, Debug)]
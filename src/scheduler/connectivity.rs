@@ -8,11 +8,10 @@
 use crate::quota::{
     QUOTA_ERROR_THRESHOLD_PERCENTAGE, QUOTA_MAX_AGE_SECONDS, QUOTA_WARN_THRESHOLD_PERCENTAGE,
 };
-use crate::tools::time;
+use crate::tools::{humanize_bytes_binary, time};
 use crate::{config::Config, scheduler::Scheduler, stock_str, tools};
 use crate::{context::Context, log::LogExt};
 use anyhow::{anyhow, Result};
-use humansize::{file_size_opts, FileSize};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, EnumProperty, PartialOrd, Ord)]
 pub enum Connectivity {
@@ -505,12 +504,8 @@ pub async fn get_connectivity_html(&self) -> Result<String> {
                                     // - the string is not longer than the other strings that way (minus title, plus units) -
                                     //   additional linebreaks on small displays are unlikely therefore
                                     // - most times, this is the only item anyway
-                                    let usage = (resource.usage * 1024)
-                                        .file_size(file_size_opts::BINARY)
-                                        .unwrap_or_default();
-                                    let limit = (resource.limit * 1024)
-                                        .file_size(file_size_opts::BINARY)
-                                        .unwrap_or_default();
+                                    let usage = humanize_bytes_binary(resource.usage * 1024);
+                                    let limit = humanize_bytes_binary(resource.limit * 1024);
                                     stock_str::part_of_total_used(self, usage, limit).await
                                 }
                             };
@@ -4,6 +4,7 @@
 
 use anyhow::{bail, Context as _, Error, Result};
 use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::Serialize;
 
 use crate::aheader::EncryptPreference;
 use crate::chat::{self, Chat, ChatId, ChatIdBlocked};
@@ -47,6 +48,41 @@ macro_rules! inviter_progress {
     };
 }
 
+/// Stage of a secure-join handshake, see [`EventType::SecurejoinProgress`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum SecurejoinStage {
+    /// Inviter received a vc-request or vg-request.
+    RequestReceived,
+
+    /// The key of the peer was verified.
+    ///
+    /// On the inviter side the vc-/vg-request-with-auth was checked,
+    /// on the joiner side the vc-/vg-request-with-auth was sent.
+    AuthVerified,
+
+    /// Inviter added the joiner to the group and sent vg-member-added.
+    MemberAddedSent,
+
+    /// The handshake finished successfully.
+    Completed,
+
+    /// The handshake failed.
+    Failed { reason: String },
+}
+
+fn emit_securejoin_progress(
+    context: &Context,
+    contact_id: ContactId,
+    chat_id: Option<ChatId>,
+    stage: SecurejoinStage,
+) {
+    context.emit_event(EventType::SecurejoinProgress {
+        contact_id,
+        chat_id,
+        stage,
+    });
+}
+
 /// Generates a Secure Join QR code.
 ///
 /// With `group` set to `None` this generates a setup-contact QR code, with `group` set to a
@@ -317,6 +353,7 @@ pub(crate) async fn handle_securejoin_handshake(
             info!(context, "Secure-join requested.",);
 
             inviter_progress!(context, contact_id, 300);
+            emit_securejoin_progress(context, contact_id, None, SecurejoinStage::RequestReceived);
 
             // for setup-contact, make Alice's one-to-one chat with Bob visible
             // (secure-join-information are shown in the group chat)
@@ -420,6 +457,7 @@ pub(crate) async fn handle_securejoin_handshake(
                 return Ok(HandshakeMessage::Ignore);
             }
             Contact::scaleup_origin_by_id(context, contact_id, Origin::SecurejoinInvited).await?;
+            chat::accept_verified_contact_request(context, contact_id).await?;
             info!(context, "Auth verified.",);
             context.emit_event(EventType::ContactsChanged(Some(contact_id)));
             inviter_progress!(context, contact_id, 600);
@@ -436,17 +474,31 @@ pub(crate) async fn handle_securejoin_handshake(
                 };
                 match chat::get_chat_id_by_grpid(context, field_grpid).await? {
                     Some((group_chat_id, _, _)) => {
+                        emit_securejoin_progress(
+                            context,
+                            contact_id,
+                            Some(group_chat_id),
+                            SecurejoinStage::AuthVerified,
+                        );
                         secure_connection_established(context, contact_id, group_chat_id).await?;
                         if let Err(err) =
                             chat::add_contact_to_chat_ex(context, group_chat_id, contact_id, true)
                                 .await
                         {
                             error!(context, "failed to add contact: {}", err);
+                        } else {
+                            emit_securejoin_progress(
+                                context,
+                                contact_id,
+                                Some(group_chat_id),
+                                SecurejoinStage::MemberAddedSent,
+                            );
                         }
                     }
                     None => bail!("Chat {} not found", &field_grpid),
                 }
             } else {
+                emit_securejoin_progress(context, contact_id, None, SecurejoinStage::AuthVerified);
                 // Alice -> Bob
                 secure_connection_established(
                     context,
@@ -464,6 +516,7 @@ pub(crate) async fn handle_securejoin_handshake(
                 .context("failed sending vc-contact-confirm message")?;
 
                 inviter_progress!(context, contact_id, 1000);
+                emit_securejoin_progress(context, contact_id, None, SecurejoinStage::Completed);
             }
             Ok(HandshakeMessage::Ignore) // "Done" would delete the message and break multi-device (the key from Autocrypt-header is needed)
         }
@@ -501,11 +554,19 @@ pub(crate) async fn handle_securejoin_handshake(
                         .get_header(HeaderDef::SecureJoinGroup)
                         .map(|s| s.as_str())
                         .unwrap_or_else(|| "");
-                    if let Err(err) = chat::get_chat_id_by_grpid(context, field_grpid).await {
-                        warn!(context, "Failed to lookup chat_id from grpid: {}", err);
-                        return Err(
-                            err.context(format!("Chat for group {} not found", &field_grpid))
-                        );
+                    match chat::get_chat_id_by_grpid(context, field_grpid).await {
+                        Ok(group_chat) => emit_securejoin_progress(
+                            context,
+                            contact_id,
+                            group_chat.map(|(chat_id, _, _)| chat_id),
+                            SecurejoinStage::Completed,
+                        ),
+                        Err(err) => {
+                            warn!(context, "Failed to lookup chat_id from grpid: {}", err);
+                            return Err(
+                                err.context(format!("Chat for group {} not found", &field_grpid))
+                            );
+                        }
                     }
                 }
                 Ok(HandshakeMessage::Ignore) // "Done" deletes the message and breaks multi-device
@@ -594,6 +655,19 @@ pub(crate) async fn observe_securejoin_on_other_device(
                 .await?;
                 return Ok(HandshakeMessage::Ignore);
             }
+            chat::accept_verified_contact_request(context, contact_id).await?;
+            let group_chat_id = match mime_message.get_header(HeaderDef::ChatGroupId) {
+                Some(grpid) => chat::get_chat_id_by_grpid(context, grpid)
+                    .await?
+                    .map(|(chat_id, _, _)| chat_id),
+                None => None,
+            };
+            emit_securejoin_progress(
+                context,
+                contact_id,
+                group_chat_id,
+                SecurejoinStage::Completed,
+            );
             Ok(if step.as_str() == "vg-member-added" {
                 HandshakeMessage::Propagate
             } else {
@@ -629,6 +703,14 @@ async fn could_not_establish_secure_connection(
         context,
         "StockMessage::ContactNotVerified posted to 1:1 chat ({})", details
     );
+    emit_securejoin_progress(
+        context,
+        contact_id,
+        None,
+        SecurejoinStage::Failed {
+            reason: details.to_string(),
+        },
+    );
     Ok(())
 }
 
@@ -1033,6 +1115,56 @@ async fn test_setup_contact_bob_knows_alice() -> Result<()> {
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_setup_contact_auto_accept_verified() -> Result<()> {
+        let mut tcm = TestContextManager::new().await;
+        let alice = tcm.alice().await;
+        let bob = tcm.bob().await;
+        alice
+            .set_config_bool(Config::AutoAcceptVerified, true)
+            .await?;
+
+        // Bob writes to Alice first, so Alice has a contact request from Bob.
+        let bob_chat = bob.create_chat(&alice).await;
+        let sent = bob.send_text(bob_chat.id, "Hi Alice").await;
+        let msg = alice.recv_msg(&sent).await;
+        let alice_chat_id = msg.chat_id;
+        let contact_bob_id = msg.from_id;
+        assert_eq!(
+            Chat::load_from_db(&alice, alice_chat_id).await?.blocked,
+            Blocked::Request
+        );
+
+        let qr = get_securejoin_qr(&alice, None).await?;
+        join_securejoin(&bob, &qr).await?;
+        alice.recv_msg(&bob.pop_sent_msg().await).await;
+        bob.recv_msg(&alice.pop_sent_msg().await).await;
+
+        // Alice receives vc-request-with-auth and verifies Bob.
+        alice.recv_msg(&bob.pop_sent_msg().await).await;
+        let contact_bob = Contact::load_from_db(&alice, contact_bob_id).await?;
+        assert_eq!(
+            contact_bob.is_verified(&alice).await?,
+            VerifiedStatus::BidirectVerified
+        );
+        assert_eq!(
+            Chat::load_from_db(&alice, alice_chat_id).await?.blocked,
+            Blocked::Not
+        );
+        alice
+            .evtracker
+            .get_info_contains("Accepting contact request")
+            .await;
+        alice
+            .evtracker
+            .get_matching(
+                |evt| matches!(evt, EventType::ChatModified(chat_id) if *chat_id == alice_chat_id),
+            )
+            .await;
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_setup_contact_concurrent_calls() -> Result<()> {
         let mut tcm = TestContextManager::new().await;
@@ -1064,6 +1196,90 @@ async fn test_setup_contact_concurrent_calls() -> Result<()> {
         Ok(())
     }
 
+    async fn next_securejoin_progress(
+        t: &TestContext,
+    ) -> (ContactId, Option<ChatId>, SecurejoinStage) {
+        match t
+            .evtracker
+            .get_matching(|evt| matches!(evt, EventType::SecurejoinProgress { .. }))
+            .await
+        {
+            EventType::SecurejoinProgress {
+                contact_id,
+                chat_id,
+                stage,
+            } => (contact_id, chat_id, stage),
+            _ => unreachable!(),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_secure_join_progress_events() -> Result<()> {
+        let mut tcm = TestContextManager::new().await;
+        let alice = tcm.alice().await;
+        let bob = tcm.bob().await;
+        let alice_bob_id = alice.add_or_lookup_contact(&bob).await.id;
+        let bob_alice_id = bob.add_or_lookup_contact(&alice).await.id;
+
+        let alice_chatid =
+            chat::create_group_chat(&alice.ctx, ProtectionStatus::Protected, "the chat").await?;
+        let qr = get_securejoin_qr(&alice.ctx, Some(alice_chatid)).await?;
+        let bob_chatid = join_securejoin(&bob.ctx, &qr).await?;
+
+        // vg-request
+        alice.recv_msg(&bob.pop_sent_msg().await).await;
+        assert_eq!(
+            next_securejoin_progress(&alice).await,
+            (alice_bob_id, None, SecurejoinStage::RequestReceived)
+        );
+
+        // vg-auth-required
+        bob.recv_msg(&alice.pop_sent_msg().await).await;
+        assert_eq!(
+            next_securejoin_progress(&bob).await,
+            (
+                bob_alice_id,
+                Some(bob_chatid),
+                SecurejoinStage::AuthVerified
+            )
+        );
+
+        // vg-request-with-auth
+        alice.recv_msg(&bob.pop_sent_msg().await).await;
+        assert_eq!(
+            next_securejoin_progress(&alice).await,
+            (
+                alice_bob_id,
+                Some(alice_chatid),
+                SecurejoinStage::AuthVerified
+            )
+        );
+        assert_eq!(
+            next_securejoin_progress(&alice).await,
+            (
+                alice_bob_id,
+                Some(alice_chatid),
+                SecurejoinStage::MemberAddedSent
+            )
+        );
+
+        // vg-member-added
+        bob.recv_msg(&alice.pop_sent_msg().await).await;
+        assert_eq!(
+            next_securejoin_progress(&bob).await,
+            (bob_alice_id, Some(bob_chatid), SecurejoinStage::Completed)
+        );
+
+        // vg-member-added-received
+        alice.recv_msg(&bob.pop_sent_msg().await).await;
+        assert_eq!(
+            next_securejoin_progress(&alice).await,
+            (alice_bob_id, Some(alice_chatid), SecurejoinStage::Completed)
+        );
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_secure_join() -> Result<()> {
         let mut tcm = TestContextManager::new().await;
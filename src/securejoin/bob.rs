@@ -16,7 +16,7 @@
 
 use super::bobstate::{BobHandshakeStage, BobState};
 use super::qrinvite::QrInvite;
-use super::HandshakeMessage;
+use super::{HandshakeMessage, SecurejoinStage};
 
 /// Starts the securejoin protocol with the QR `invite`.
 ///
@@ -52,6 +52,9 @@ pub(super) async fn start_protocol(context: &Context, invite: QrInvite) -> Resul
     }
     if matches!(stage, BobHandshakeStage::RequestWithAuthSent) {
         state.emit_progress(context, JoinerProgress::RequestWithAuthSent);
+        state
+            .emit_stage(context, SecurejoinStage::AuthVerified)
+            .await?;
     }
     match invite {
         QrInvite::Group { .. } => {
@@ -99,6 +102,9 @@ pub(super) async fn handle_auth_required(
                     chat::add_info_msg(context, chat_id, &msg, time()).await?;
                 }
                 bobstate.emit_progress(context, JoinerProgress::RequestWithAuthSent);
+                bobstate
+                    .emit_stage(context, SecurejoinStage::AuthVerified)
+                    .await?;
                 Ok(HandshakeMessage::Done)
             }
             None => Ok(HandshakeMessage::Ignore),
@@ -161,6 +167,17 @@ fn emit_progress(&self, context: &Context, progress: JoinerProgress) {
         });
     }
 
+    /// Emits [`EventType::SecurejoinProgress`] for the contact and group being joined.
+    async fn emit_stage(&self, context: &Context, stage: SecurejoinStage) -> Result<()> {
+        let chat_id = if self.is_join_group() {
+            Some(self.joining_chat_id(context).await?)
+        } else {
+            None
+        };
+        super::emit_securejoin_progress(context, self.invite().contact_id(), chat_id, stage);
+        Ok(())
+    }
+
     /// Returns the [`ChatId`] of the chat being joined.
     ///
     /// This is the chat in which you want to notify the user as well.
@@ -212,7 +229,13 @@ async fn notify_aborted(&self, context: &Context, why: &str) -> Result<()> {
             context,
             "StockMessage::ContactNotVerified posted to joining chat ({})", why
         );
-        Ok(())
+        self.emit_stage(
+            context,
+            SecurejoinStage::Failed {
+                reason: why.to_string(),
+            },
+        )
+        .await
     }
 
     /// Notifies the user that the SecureJoin peer is verified.
@@ -224,7 +247,7 @@ async fn notify_peer_verified(&self, context: &Context) -> Result<()> {
         let chat_id = self.joining_chat_id(context).await?;
         chat::add_info_msg(context, chat_id, &msg, time()).await?;
         context.emit_event(EventType::ChatModified(chat_id));
-        Ok(())
+        self.emit_stage(context, SecurejoinStage::Completed).await
     }
 }
 
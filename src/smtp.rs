@@ -6,6 +6,7 @@
 
 use anyhow::{bail, format_err, Context as _, Error, Result};
 use async_smtp::smtp::client::net::ClientTlsParameters;
+use async_smtp::smtp::extension::ClientId;
 use async_smtp::smtp::response::{Category, Code, Detail};
 use async_smtp::{smtp, EmailAddress, ServerAddress};
 use tokio::task;
@@ -191,6 +192,14 @@ pub async fn connect(
             client = client.use_socks5(socks5_config.to_async_smtp_socks5_config());
         }
 
+        if let Some(helo) = context
+            .get_config(Config::SmtpHelo)
+            .await?
+            .filter(|helo| !helo.is_empty())
+        {
+            client = client.hello_name(ClientId::Domain(helo));
+        }
+
         let mut trans = client.into_transport();
         trans.connect().await.context("SMTP failed to connect")?;
 
@@ -357,7 +366,9 @@ pub(crate) async fn smtp_send(
 
     if let SendResult::Failure(err) = &status {
         // We couldn't send the message, so mark it as failed
-        message::set_msg_failed(context, msg_id, &err.to_string()).await;
+        if !msg_id.is_unset() {
+            message::set_msg_failed(context, msg_id, &err.to_string()).await;
+        }
     }
     status
 }
@@ -407,7 +418,9 @@ pub(crate) async fn send_msg_to_smtp(
         )
         .await?;
     if retries > 6 {
-        message::set_msg_failed(context, msg_id, "Number of retries exceeded the limit.").await;
+        if !msg_id.is_unset() {
+            message::set_msg_failed(context, msg_id, "Number of retries exceeded the limit.").await;
+        }
         context
             .sql
             .execute("DELETE FROM smtp WHERE id=?", paramsv![rowid])
@@ -435,9 +448,11 @@ pub(crate) async fn send_msg_to_smtp(
 
     // If there is a msg-id and it does not exist in the db, cancel sending. this happens if
     // delete_msgs() was called before the generated mime was sent out.
-    if !message::exists(context, msg_id)
-        .await
-        .with_context(|| format!("failed to check message {} existence", msg_id))?
+    // Jobs without a msg-id, e.g. ephemeral webxdc updates, are not stored as messages.
+    if !msg_id.is_unset()
+        && !message::exists(context, msg_id)
+            .await
+            .with_context(|| format!("failed to check message {} existence", msg_id))?
     {
         info!(
             context,
@@ -469,7 +484,9 @@ pub(crate) async fn send_msg_to_smtp(
     match status {
         SendResult::Retry => Err(format_err!("Retry")),
         SendResult::Success => {
-            msg_id.set_delivered(context).await?;
+            if !msg_id.is_unset() {
+                msg_id.set_delivered(context).await?;
+            }
             Ok(())
         }
         SendResult::Failure(err) => Err(format_err!("{}", err)),
@@ -669,3 +686,54 @@ async fn send_mdn(context: &Context, smtp: &mut Smtp) -> Result<bool> {
         Ok(true)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::test_utils::TestContext;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_smtp_helo() -> Result<()> {
+        let t = TestContext::new_alice().await;
+        t.set_config(Config::SmtpHelo, Some("mail.example.org"))
+            .await?;
+
+        // Minimal SMTP server recording the EHLO command.
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let port = listener.local_addr()?.port();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await?;
+            let (reader, mut writer) = stream.into_split();
+            let mut lines = BufReader::new(reader).lines();
+            writer.write_all(b"220 localhost ESMTP\r\n").await?;
+            let ehlo = lines.next_line().await?.unwrap_or_default();
+            writer
+                .write_all(b"250-localhost\r\n250 AUTH PLAIN\r\n")
+                .await?;
+            lines.next_line().await?;
+            writer
+                .write_all(b"235 2.7.0 Authentication successful\r\n")
+                .await?;
+            anyhow::Ok(ehlo)
+        });
+
+        let lp = ServerLoginParam {
+            server: "127.0.0.1".to_string(),
+            user: "alice".to_string(),
+            password: "secret".to_string(),
+            port,
+            security: Socket::Plain,
+            oauth2: false,
+            certificate_checks: CertificateChecks::Automatic,
+        };
+        let mut smtp = Smtp::new();
+        smtp.connect(&t, &lp, &None, "alice@example.org", false)
+            .await?;
+
+        assert_eq!(server.await??, "EHLO mail.example.org");
+        Ok(())
+    }
+}
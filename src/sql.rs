@@ -10,12 +10,12 @@
 use rusqlite::{config::DbConfig, Connection, OpenFlags};
 use tokio::sync::RwLock;
 
-use crate::blob::BlobObject;
+use crate::blob::{self, BlobObject};
 use crate::chat::{add_device_msg, update_device_icon, update_saved_messages_icon};
 use crate::config::Config;
 use crate::constants::DC_CHAT_ID_TRASH;
 use crate::context::Context;
-use crate::ephemeral::start_ephemeral_timers;
+use crate::ephemeral::{delete_expired_messages, start_ephemeral_timers};
 use crate::log::LogExt;
 use crate::message::{Message, Viewtype};
 use crate::param::{Param, Params};
@@ -119,6 +119,8 @@ async fn close(&self) {
     /// Exports the database to a separate file with the given passphrase.
     ///
     /// Set passphrase to empty string to export the database unencrypted.
+    ///
+    /// [`Config::KeyTransferSetupCode`] is not exported.
     pub(crate) async fn export(&self, path: &Path, passphrase: String) -> Result<()> {
         let path_str = path
             .to_str()
@@ -132,7 +134,14 @@ pub(crate) async fn export(&self, path: &Path, passphrase: String) -> Result<()>
             .context("failed to attach backup database")?;
             let res = conn
                 .query_row("SELECT sqlcipher_export('backup')", [], |_row| Ok(()))
-                .context("failed to export to attached backup database");
+                .context("failed to export to attached backup database")
+                .and_then(|()| {
+                    conn.execute(
+                        "DELETE FROM backup.config WHERE keyname=?",
+                        paramsv![Config::KeyTransferSetupCode.as_ref()],
+                    )
+                    .context("failed to remove setup code from backup database")
+                });
             conn.execute("DETACH DATABASE backup", [])
                 .context("failed to detach backup database")?;
             res?;
@@ -341,6 +350,7 @@ pub async fn open(&self, context: &Context, passphrase: String) -> Result<()> {
         } else {
             info!(context, "Opened database {:?}.", self.dbfile);
             *self.is_encrypted.write().await = Some(passphrase_nonempty);
+            context.update_event_log_size().await?;
             Ok(())
         }
     }
@@ -366,6 +376,10 @@ pub async fn insert(&self, query: &str, params: impl rusqlite::Params) -> Result
     /// Prepares and executes the statement and maps a function over the resulting rows.
     /// Then executes the second function over the returned iterator and returns the
     /// result of that function.
+    ///
+    /// Callers usually collect the rows into a `Vec` in the second function,
+    /// which may use a lot of memory for queries returning many rows.
+    /// Use [`Sql::query_stream`] if the rows do not need to be collected.
     pub async fn query_map<T, F, G, H>(
         &self,
         sql: &str,
@@ -385,6 +399,29 @@ pub async fn query_map<T, F, G, H>(
         })
     }
 
+    /// Prepares and executes the statement and calls `f` for each resulting row.
+    ///
+    /// Unlike [`Sql::query_map`], rows are processed one at a time
+    /// and never buffered. Iteration stops at the first error returned by `f`.
+    pub async fn query_stream<F>(
+        &self,
+        sql: &str,
+        params: impl rusqlite::Params,
+        f: F,
+    ) -> Result<()>
+    where
+        F: FnMut(&rusqlite::Row) -> Result<()>,
+    {
+        let conn = self.get_conn().await?;
+        tokio::task::block_in_place(move || {
+            let mut stmt = conn.prepare(sql)?;
+            for res in stmt.query_and_then(params, f)? {
+                res?;
+            }
+            Ok(())
+        })
+    }
+
     pub async fn get_conn(
         &self,
     ) -> Result<r2d2::PooledConnection<r2d2_sqlite::SqliteConnectionManager>> {
@@ -643,6 +680,13 @@ pub async fn housekeeping(context: &Context) -> Result<()> {
         );
     }
 
+    if let Err(err) = delete_expired_messages(context, time()).await {
+        warn!(
+            context,
+            "Housekeeping: cannot delete expired messages: {}", err
+        );
+    }
+
     if let Err(err) = prune_tombstones(&context.sql).await {
         warn!(
             context,
@@ -686,11 +730,9 @@ pub async fn housekeeping(context: &Context) -> Result<()> {
     Ok(())
 }
 
-pub async fn remove_unused_files(context: &Context) -> Result<()> {
+/// Returns the names of all blobdir files referenced from the database.
+pub(crate) async fn get_files_in_use(context: &Context) -> Result<HashSet<String>> {
     let mut files_in_use = HashSet::new();
-    let mut unreferenced_count = 0;
-
-    info!(context, "Start housekeeping...");
     maybe_add_from_param(
         &context.sql,
         &mut files_in_use,
@@ -719,6 +761,13 @@ pub async fn remove_unused_files(context: &Context) -> Result<()> {
         Param::ProfileImage,
     )
     .await?;
+    maybe_add_from_param(
+        &context.sql,
+        &mut files_in_use,
+        "SELECT param FROM contacts;",
+        Param::UnconfirmedProfileImage,
+    )
+    .await?;
 
     context
         .sql
@@ -736,26 +785,51 @@ pub async fn remove_unused_files(context: &Context) -> Result<()> {
         .await
         .context("housekeeping: failed to SELECT value FROM config")?;
 
+    Ok(files_in_use)
+}
+
+/// Deletes the given blobdir files unless they are still referenced from the database.
+///
+/// `names` are file names relative to the blobdir.
+pub(crate) async fn remove_files_if_unused(context: &Context, names: &[String]) -> Result<()> {
+    if names.is_empty() {
+        return Ok(());
+    }
+    let files_in_use = get_files_in_use(context).await?;
+    for name in names {
+        if !is_blob_in_use(&files_in_use, name) {
+            delete_file(context, context.get_blobdir().join(name)).await;
+        }
+    }
+    Ok(())
+}
+
+/// Checks if a blobdir file or one of its derived files (previews, waveforms, ...) is in use.
+pub(crate) fn is_blob_in_use(files_in_use: &HashSet<String>, name: &str) -> bool {
+    is_file_in_use(files_in_use, None, name)
+        || is_file_in_use(files_in_use, Some(".increation"), name)
+        || is_file_in_use(files_in_use, Some(".waveform"), name)
+        || is_file_in_use(files_in_use, Some("-preview.jpg"), name)
+}
+
+pub async fn remove_unused_files(context: &Context) -> Result<()> {
+    let mut unreferenced_count = 0;
+
+    info!(context, "Start housekeeping...");
+    let files_in_use = get_files_in_use(context).await?;
+
     info!(context, "{} files in use.", files_in_use.len(),);
     /* go through directory and delete unused files */
-    let p = context.get_blobdir();
-    match tokio::fs::read_dir(p).await {
-        Ok(mut dir_handle) => {
+    match blob::read_blobdir(context).await {
+        Ok(entries) => {
             /* avoid deletion of files that are just created to build a message object */
             let diff = std::time::Duration::from_secs(60 * 60);
             let keep_files_newer_than = std::time::SystemTime::now()
                 .checked_sub(diff)
                 .unwrap_or(std::time::SystemTime::UNIX_EPOCH);
 
-            while let Ok(Some(entry)) = dir_handle.next_entry().await {
-                let name_f = entry.file_name();
-                let name_s = name_f.to_string_lossy();
-
-                if is_file_in_use(&files_in_use, None, &name_s)
-                    || is_file_in_use(&files_in_use, Some(".increation"), &name_s)
-                    || is_file_in_use(&files_in_use, Some(".waveform"), &name_s)
-                    || is_file_in_use(&files_in_use, Some("-preview.jpg"), &name_s)
-                {
+            for (name, entry) in entries {
+                if is_blob_in_use(&files_in_use, &name) {
                     continue;
                 }
 
@@ -776,34 +850,60 @@ pub async fn remove_unused_files(context: &Context) -> Result<()> {
                             context,
                             "Housekeeping: Keeping new unreferenced file #{}: {:?}",
                             unreferenced_count,
-                            entry.file_name(),
+                            name,
                         );
                         continue;
                     }
                 }
                 info!(
                     context,
-                    "Housekeeping: Deleting unreferenced file #{}: {:?}",
-                    unreferenced_count,
-                    entry.file_name()
+                    "Housekeeping: Deleting unreferenced file #{}: {:?}", unreferenced_count, name
                 );
                 let path = entry.path();
                 delete_file(context, path).await;
             }
         }
         Err(err) => {
-            warn!(
-                context,
-                "Housekeeping: Cannot open {}. ({})",
-                context.get_blobdir().display(),
-                err
-            );
+            warn!(context, "Housekeeping: Cannot read blobdir: {:#}", err);
         }
     }
+    blob::remove_empty_subdirs(context).await.ok_or_log_msg(
+        context,
+        "Housekeeping: Cannot remove empty blob subdirectories",
+    );
 
     Ok(())
 }
 
+/// Removes blobdir files that are not referenced from the database, regardless of their age.
+///
+/// This is used after importing a backup, when the blobdir may still contain files from the
+/// previous, unconfigured state of the account.  Files listed in `keep`, e.g. the files written
+/// by the import itself, are never removed.
+///
+/// Returns the number of bytes reclaimed.
+pub(crate) async fn remove_stray_files(context: &Context, keep: &HashSet<String>) -> Result<u64> {
+    let files_in_use = get_files_in_use(context).await?;
+    let mut reclaimed_bytes = 0;
+
+    for (name, entry) in blob::read_blobdir(context).await? {
+        if keep.contains(&name) || is_blob_in_use(&files_in_use, &name) {
+            continue;
+        }
+
+        let metadata = match entry.metadata().await {
+            Ok(metadata) if metadata.is_file() => metadata,
+            _ => continue,
+        };
+        info!(context, "Deleting stray file {:?}", name);
+        if delete_file(context, entry.path()).await {
+            reclaimed_bytes += metadata.len();
+        }
+    }
+
+    Ok(reclaimed_bytes)
+}
+
 #[allow(clippy::indexing_slicing)]
 fn is_file_in_use(files_in_use: &HashSet<String>, namespc_opt: Option<&str>, name: &str) -> bool {
     let name_to_check = if let Some(namespc) = namespc_opt {
@@ -881,6 +981,8 @@ mod tests {
     use async_channel as channel;
 
     use crate::config::Config;
+    use crate::constants::Chattype;
+    use crate::contact::ContactId;
     use crate::{test_utils::TestContext, EventType};
 
     use super::*;
@@ -926,6 +1028,41 @@ async fn test_col_exists() {
         assert!(!t.ctx.sql.col_exists("foobar", "foobar").await.unwrap());
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_query_stream() -> Result<()> {
+        let t = TestContext::new().await;
+        for keyname in ["stream_a", "stream_b", "stream_c"] {
+            t.sql.set_raw_config(keyname, Some("1")).await?;
+        }
+
+        let mut keynames = Vec::new();
+        t.sql
+            .query_stream(
+                "SELECT keyname FROM config WHERE keyname LIKE 'stream_%' ORDER BY keyname",
+                paramsv![],
+                |row| {
+                    keynames.push(row.get::<_, String>(0)?);
+                    Ok(())
+                },
+            )
+            .await?;
+        assert_eq!(keynames, vec!["stream_a", "stream_b", "stream_c"]);
+
+        // Errors returned by the callback stop the iteration.
+        let mut count = 0;
+        assert!(t
+            .sql
+            .query_stream("SELECT keyname FROM config", paramsv![], |_row| {
+                count += 1;
+                bail!("stop")
+            })
+            .await
+            .is_err());
+        assert_eq!(count, 1);
+
+        Ok(())
+    }
+
     /// Tests that auto_vacuum is enabled for new databases.
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_auto_vacuum() -> Result<()> {
@@ -1040,6 +1177,45 @@ async fn test_db_reopen() -> Result<()> {
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_migration_creator_id() -> Result<()> {
+        use tempfile::tempdir;
+
+        // The context is used only for logging.
+        let t = TestContext::new().await;
+
+        let dir = tempdir()?;
+        let dbfile = dir.path().join("testdb.sqlite");
+        let sql = Sql::new(dbfile);
+        sql.open(&t, "".to_string()).await?;
+        assert_eq!(sql.get_raw_config_int("dbversion").await?, Some(99));
+        assert!(sql.col_exists("chats", "creator_id").await?);
+
+        // Downgrade the database to the version before the creator was recorded.
+        sql.execute("ALTER TABLE chats DROP COLUMN creator_id;", paramsv![])
+            .await?;
+        sql.execute(
+            "INSERT INTO chats (type, name) VALUES (?, ?);",
+            paramsv![Chattype::Group, "old group"],
+        )
+        .await?;
+        sql.set_raw_config_int("dbversion", 98).await?;
+        sql.close().await;
+
+        sql.open(&t, "".to_string()).await?;
+        assert_eq!(sql.get_raw_config_int("dbversion").await?, Some(99));
+        let creator_id: Option<ContactId> = sql
+            .query_get_value(
+                "SELECT creator_id FROM chats WHERE name=?;",
+                paramsv!["old group"],
+            )
+            .await?
+            .flatten();
+        assert_eq!(creator_id, None);
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_migration_flags() -> Result<()> {
         let t = TestContext::new().await;
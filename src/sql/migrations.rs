@@ -650,6 +650,83 @@ pub async fn run(context: &Context, sql: &Sql) -> Result<(bool, bool, bool, bool
         )
         .await?;
     }
+    if dbversion < 92 {
+        info!(context, "[migration] v92");
+        sql.execute_migration(
+            "ALTER TABLE contacts ADD COLUMN last_seen_source INTEGER NOT NULL DEFAULT 0;",
+            92,
+        )
+        .await?;
+    }
+    if dbversion < 93 {
+        info!(context, "[migration] v93");
+        sql.execute_migration(
+            r#"CREATE TABLE msg_labels (
+              msg_id INTEGER NOT NULL,
+              label TEXT NOT NULL, -- one of the labels in Param::Labels
+              UNIQUE(msg_id, label)
+            );
+            CREATE INDEX msg_labels_index1 ON msg_labels (label);
+            CREATE TRIGGER msg_labels_delete AFTER DELETE ON msgs
+            BEGIN
+              DELETE FROM msg_labels WHERE msg_id=OLD.id;
+            END;
+            CREATE TRIGGER msg_labels_param_update AFTER UPDATE OF param ON msgs
+            WHEN instr(char(10) || NEW.param, char(10) || '0=')=0
+            BEGIN
+              DELETE FROM msg_labels WHERE msg_id=NEW.id;
+            END;"#,
+            93,
+        )
+        .await?;
+    }
+    if dbversion < 94 {
+        info!(context, "[migration] v94");
+        sql.execute_migration(
+            r#"CREATE TABLE folder_stats (
+              folder TEXT PRIMARY KEY,
+              uid_next INTEGER DEFAULT 0,
+              msg_count INTEGER DEFAULT 0,
+              last_seen_at INTEGER DEFAULT 0 -- timestamp of the last scan
+            );"#,
+            94,
+        )
+        .await?;
+    }
+    if dbversion < 95 {
+        info!(context, "[migration] v95");
+        sql.execute_migration(
+            "ALTER TABLE contacts ADD COLUMN status_html TEXT DEFAULT '';",
+            95,
+        )
+        .await?;
+    }
+    if dbversion < 96 {
+        info!(context, "[migration] v96");
+        sql.execute_migration("ALTER TABLE chats ADD COLUMN admin_contact_id INTEGER;", 96)
+            .await?;
+    }
+    if dbversion < 97 {
+        info!(context, "[migration] v97");
+        // Size of the attached file, NULL if not known yet.
+        sql.execute_migration("ALTER TABLE msgs ADD COLUMN blob_bytes INTEGER;", 97)
+            .await?;
+    }
+    if dbversion < 98 {
+        info!(context, "[migration] v98");
+        // Time at which the message was moved to the trash chat, 0 if not known.
+        sql.execute_migration(
+            "ALTER TABLE msgs ADD COLUMN timestamp_trashed INTEGER DEFAULT 0;",
+            98,
+        )
+        .await?;
+    }
+    if dbversion < 99 {
+        info!(context, "[migration] v99");
+        // NULL for groups created before the creator was recorded.
+        sql.execute_migration("ALTER TABLE chats ADD COLUMN creator_id INTEGER;", 99)
+            .await?;
+    }
 
     Ok((
         recalc_fingerprints,
@@ -0,0 +1,302 @@
+//! # Messaging statistics.
+//!
+//! Aggregated numbers about the messages, chats and contacts of an account,
+//! e.g. for a "year in review" feature.
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::chat::ChatId;
+use crate::constants::{Chattype, DC_CHAT_ID_LAST_SPECIAL, DC_GCL_VERIFIED_ONLY};
+use crate::contact::{Contact, ContactId};
+use crate::context::Context;
+use crate::message::Viewtype;
+use crate::param::{Param, Params};
+use crate::tools::get_filebytes;
+
+/// Condition selecting messages sent or received by the user
+/// in normal chats, excluding trashed, hidden and info messages.
+///
+/// Info messages are detected the same way as in [`crate::chat::get_chat_msgs`].
+/// GLOB is used instead of LIKE because it is case-sensitive.
+const COUNTED_MSGS: &str = "m.chat_id>? AND m.hidden=0
+    AND (m.from_id=? OR m.from_id>?) AND m.to_id!=?
+    AND m.param NOT GLOB '*S=*'";
+
+/// Messaging statistics of an account, see [`get_stats`].
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct Stats {
+    /// Number of sent and received messages per month, oldest month first.
+    pub msgs_by_month: Vec<MonthStats>,
+
+    /// Number of messages per chat, chat with most messages first.
+    pub msgs_by_chat: Vec<ChatStats>,
+
+    /// Size of attachments per viewtype.
+    pub attachment_bytes: Vec<AttachmentStats>,
+
+    /// Number of known contacts.
+    pub contacts: usize,
+
+    /// Number of verified contacts.
+    pub verified_contacts: usize,
+
+    /// Number of chats per chat type, not including contact requests.
+    pub chats_by_type: Vec<ChattypeStats>,
+}
+
+/// Number of messages in a month.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MonthStats {
+    /// Month in the format `YYYY-MM`, UTC.
+    pub month: String,
+
+    /// Number of messages sent by the user.
+    pub sent: usize,
+
+    /// Number of messages received by the user.
+    pub received: usize,
+}
+
+/// Number of messages in a chat.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChatStats {
+    pub chat_id: ChatId,
+    pub name: String,
+    pub msgs: usize,
+}
+
+/// Size of the attachments of a viewtype.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AttachmentStats {
+    pub viewtype: Viewtype,
+    pub bytes: u64,
+}
+
+/// Number of chats of a chat type.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChattypeStats {
+    pub chattype: Chattype,
+    pub chats: usize,
+}
+
+/// Computes messaging statistics of the account.
+pub async fn get_stats(context: &Context) -> Result<Stats> {
+    let msgs_by_month = context
+        .sql
+        .query_map(
+            &format!(
+                "SELECT strftime('%Y-%m', m.timestamp, 'unixepoch') AS month,
+                        SUM(m.from_id=?), SUM(m.from_id!=?)
+                 FROM msgs m
+                 WHERE {}
+                 GROUP BY month
+                 ORDER BY month",
+                COUNTED_MSGS
+            ),
+            paramsv![
+                ContactId::SELF,
+                ContactId::SELF,
+                DC_CHAT_ID_LAST_SPECIAL,
+                ContactId::SELF,
+                ContactId::LAST_SPECIAL,
+                ContactId::INFO
+            ],
+            |row| {
+                Ok(MonthStats {
+                    month: row.get(0)?,
+                    sent: row.get(1)?,
+                    received: row.get(2)?,
+                })
+            },
+            |rows| {
+                rows.collect::<std::result::Result<Vec<_>, _>>()
+                    .map_err(Into::into)
+            },
+        )
+        .await?;
+
+    let msgs_by_chat = context
+        .sql
+        .query_map(
+            &format!(
+                "SELECT c.id, c.name, COUNT(*) AS cnt
+                 FROM msgs m INNER JOIN chats c ON c.id=m.chat_id
+                 WHERE {}
+                 GROUP BY c.id
+                 ORDER BY cnt DESC, c.id",
+                COUNTED_MSGS
+            ),
+            paramsv![
+                DC_CHAT_ID_LAST_SPECIAL,
+                ContactId::SELF,
+                ContactId::LAST_SPECIAL,
+                ContactId::INFO
+            ],
+            |row| {
+                Ok(ChatStats {
+                    chat_id: row.get(0)?,
+                    name: row.get(1)?,
+                    msgs: row.get(2)?,
+                })
+            },
+            |rows| {
+                rows.collect::<std::result::Result<Vec<_>, _>>()
+                    .map_err(Into::into)
+            },
+        )
+        .await?;
+
+    let attachments = context
+        .sql
+        .query_map(
+            &format!("SELECT m.type, m.param FROM msgs m WHERE {}", COUNTED_MSGS),
+            paramsv![
+                DC_CHAT_ID_LAST_SPECIAL,
+                ContactId::SELF,
+                ContactId::LAST_SPECIAL,
+                ContactId::INFO
+            ],
+            |row| {
+                let viewtype: Viewtype = row.get(0)?;
+                let param: Params = row.get::<_, String>(1)?.parse().unwrap_or_default();
+                Ok((viewtype, param))
+            },
+            |rows| {
+                rows.collect::<std::result::Result<Vec<_>, _>>()
+                    .map_err(Into::into)
+            },
+        )
+        .await?;
+    let mut attachment_bytes: Vec<AttachmentStats> = Vec::new();
+    for (viewtype, param) in attachments {
+        let path = match param.get_path(Param::File, context) {
+            Ok(Some(path)) => path,
+            _ => continue,
+        };
+        // Messages stored by older versions do not have the size cached.
+        let bytes = match param.get_i64(Param::Bytes) {
+            Some(bytes) => u64::try_from(bytes).unwrap_or_default(),
+            None => get_filebytes(context, &path).await,
+        };
+        match attachment_bytes
+            .iter_mut()
+            .find(|stats| stats.viewtype == viewtype)
+        {
+            Some(stats) => stats.bytes += bytes,
+            None => attachment_bytes.push(AttachmentStats { viewtype, bytes }),
+        }
+    }
+
+    let contacts = Contact::get_all(context, 0, None).await?.len();
+    let verified_contacts = Contact::get_all(context, DC_GCL_VERIFIED_ONLY, None)
+        .await?
+        .len();
+
+    let chats_by_type = context
+        .sql
+        .query_map(
+            "SELECT type, COUNT(*) FROM chats WHERE id>? AND blocked=0 GROUP BY type ORDER BY type",
+            paramsv![DC_CHAT_ID_LAST_SPECIAL],
+            |row| {
+                Ok(ChattypeStats {
+                    chattype: row.get(0)?,
+                    chats: row.get(1)?,
+                })
+            },
+            |rows| {
+                rows.collect::<std::result::Result<Vec<_>, _>>()
+                    .map_err(Into::into)
+            },
+        )
+        .await?;
+
+    Ok(Stats {
+        msgs_by_month,
+        msgs_by_chat,
+        attachment_bytes,
+        contacts,
+        verified_contacts,
+        chats_by_type,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::chat::{self, get_chat_contacts};
+    use crate::message::{delete_msgs, Message};
+    use crate::test_utils::TestContext;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_get_stats() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let bob = TestContext::new_bob().await;
+        let alice_chat = alice.create_chat(&bob).await;
+        let bob_chat = bob.create_chat(&alice).await;
+
+        alice.send_text(alice_chat.id, "Hi Bob").await;
+        alice.send_text(alice_chat.id, "Are you there?").await;
+        let sent = bob.send_text(bob_chat.id, "Yes").await;
+        alice.recv_msg(&sent).await;
+
+        // Deleted messages are not counted.
+        let sent = alice.send_text(alice_chat.id, "Deleted").await;
+        delete_msgs(&alice, &[sent.sender_msg_id]).await?;
+
+        let file = alice.get_blobdir().join("file.txt");
+        tokio::fs::write(&file, b"hello").await?;
+        let mut msg = Message::new(Viewtype::File);
+        msg.set_file(file.to_str().unwrap(), None);
+        let sent = alice.send_msg(alice_chat.id, &mut msg).await;
+        let msg = Message::load_from_db(&alice, sent.sender_msg_id).await?;
+        assert_eq!(msg.param.get_i64(Param::Bytes), Some(5));
+
+        let group_id =
+            chat::create_group_chat(&alice, chat::ProtectionStatus::Unprotected, "grp").await?;
+        assert_eq!(get_chat_contacts(&alice, group_id).await?.len(), 1);
+
+        // Info messages are not counted.
+        let bob_id = alice.add_or_lookup_contact(&bob).await.id;
+        chat::add_contact_to_chat(&alice, group_id, bob_id).await?;
+        assert!(alice.get_last_msg_in(group_id).await.is_info());
+
+        let stats = get_stats(&alice).await?;
+        assert_eq!(stats.msgs_by_month.len(), 1);
+        assert_eq!(stats.msgs_by_month[0].sent, 3);
+        assert_eq!(stats.msgs_by_month[0].received, 1);
+        assert_eq!(
+            stats.msgs_by_chat,
+            vec![ChatStats {
+                chat_id: alice_chat.id,
+                name: alice_chat.name.clone(),
+                msgs: 4
+            }]
+        );
+        assert_eq!(
+            stats.attachment_bytes,
+            vec![AttachmentStats {
+                viewtype: Viewtype::File,
+                bytes: 5
+            }]
+        );
+        assert_eq!(stats.contacts, 1);
+        assert_eq!(stats.verified_contacts, 0);
+        assert_eq!(
+            stats.chats_by_type,
+            vec![
+                ChattypeStats {
+                    chattype: Chattype::Single,
+                    chats: 1
+                },
+                ChattypeStats {
+                    chattype: Chattype::Group,
+                    chats: 1
+                }
+            ]
+        );
+
+        Ok(())
+    }
+}
@@ -14,8 +14,7 @@
 use crate::context::Context;
 use crate::message::{Message, Viewtype};
 use crate::param::Param;
-use crate::tools::timestamp_to_str;
-use humansize::{file_size_opts, FileSize};
+use crate::tools::{humanize_bytes_binary, timestamp_to_str};
 
 /// Stock strings
 ///
@@ -340,6 +339,45 @@ pub enum StockMessage {
         fallback = "You changed your email address from %1$s to %2$s.\n\nIf you now send a message to a verified group, contacts there will automatically replace the old with your new address.\n\nIt's highly advised to set up your old email provider to forward all emails to your new email address. Otherwise you might miss messages of contacts who did not get your new address yet."
     ))]
     AeapExplanationAndLink = 123,
+
+    #[strum(props(fallback = "Unknown group"))]
+    UnknownGroup = 124,
+
+    #[strum(props(fallback = "Backup from %1$s imported: %2$s chats, %3$s messages."))]
+    BackupImported = 125,
+
+    #[strum(props(fallback = "Your encryption key changed from %1$s to %2$s."))]
+    SelfKeyChanged = 126,
+
+    #[strum(props(fallback = "Your encryption keys were exported to %1$s."))]
+    SelfKeysExported = 127,
+
+    #[strum(props(fallback = "Group image change could not be applied."))]
+    MsgGrpImgChangeFailed = 128,
+
+    #[strum(props(
+        fallback = "Your encryption key expires on %1$s. Import or generate a new key to stay reachable."
+    ))]
+    SelfKeyExpiresSoon = 129,
+
+    #[strum(props(
+        fallback = "[Some parts of this message are not shown because it is too complex.]"
+    ))]
+    MsgPartsTruncated = 130,
+
+    #[strum(props(fallback = "Contact"))]
+    Contact = 131,
+
+    #[strum(props(fallback = "Catch-All"))]
+    CatchAllChat = 132,
+
+    #[strum(props(fallback = "Attachment too large: %1$s"))]
+    AttachmentTooLarge = 133,
+
+    #[strum(props(
+        fallback = "⚠️ The clock of %1$s seems to be wrong, the times of their messages may be inaccurate."
+    ))]
+    SenderClockWrong = 134,
 }
 
 impl StockMessage {
@@ -599,6 +637,11 @@ pub(crate) async fn msg_grp_img_deleted(context: &Context, by_contact: ContactId
         .await
 }
 
+/// Stock string: `Group image change could not be applied.`.
+pub(crate) async fn msg_grp_img_change_failed(context: &Context) -> String {
+    translated(context, StockMessage::MsgGrpImgChangeFailed).await
+}
+
 /// Stock string: `End-to-end encryption preferred.`.
 pub(crate) async fn e2e_preferred(context: &Context) -> String {
     translated(context, StockMessage::E2ePreferred).await
@@ -995,9 +1038,7 @@ pub(crate) async fn quota_exceeding(context: &Context, highest_usage: u64) -> St
 
 /// Stock string: `%1$s message` with placeholder replaced by human-readable size.
 pub(crate) async fn partial_download_msg_body(context: &Context, org_bytes: u32) -> String {
-    let size = org_bytes
-        .file_size(file_size_opts::BINARY)
-        .unwrap_or_default();
+    let size = humanize_bytes_binary(org_bytes.into());
     translated(context, StockMessage::PartialDownloadMsgBody)
         .await
         .replace1(size)
@@ -1120,6 +1161,81 @@ pub(crate) async fn aeap_explanation_and_link(
         .replace2(new_addr)
 }
 
+/// Stock string: `Unknown group`.
+pub(crate) async fn unknown_group(context: &Context) -> String {
+    translated(context, StockMessage::UnknownGroup).await
+}
+
+/// Stock string: `Backup from %1$s imported: %2$s chats, %3$s messages.`.
+pub(crate) async fn backup_imported(
+    context: &Context,
+    backup_date: impl AsRef<str>,
+    chat_cnt: usize,
+    msg_cnt: usize,
+) -> String {
+    translated(context, StockMessage::BackupImported)
+        .await
+        .replace1(backup_date)
+        .replace2(chat_cnt.to_string())
+        .replace3(msg_cnt.to_string())
+}
+
+/// Stock string: `Your encryption key changed from %1$s to %2$s.`.
+pub(crate) async fn self_key_changed(
+    context: &Context,
+    old_fingerprint: impl AsRef<str>,
+    new_fingerprint: impl AsRef<str>,
+) -> String {
+    translated(context, StockMessage::SelfKeyChanged)
+        .await
+        .replace1(old_fingerprint)
+        .replace2(new_fingerprint)
+}
+
+/// Stock string: `Your encryption keys were exported to %1$s.`.
+pub(crate) async fn self_keys_exported(context: &Context, dir: impl AsRef<str>) -> String {
+    translated(context, StockMessage::SelfKeysExported)
+        .await
+        .replace1(dir)
+}
+
+/// Stock string: `Your encryption key expires on %1$s. Import or generate a new key to stay reachable.`.
+pub(crate) async fn self_key_expires_soon(context: &Context, date: impl AsRef<str>) -> String {
+    translated(context, StockMessage::SelfKeyExpiresSoon)
+        .await
+        .replace1(date)
+}
+
+/// Stock string: `[Some parts of this message are not shown because it is too complex.]`.
+pub(crate) async fn msg_parts_truncated(context: &Context) -> String {
+    translated(context, StockMessage::MsgPartsTruncated).await
+}
+
+/// Stock string: `Contact`.
+pub(crate) async fn contact(context: &Context) -> String {
+    translated(context, StockMessage::Contact).await
+}
+
+/// Stock string: `Catch-All`.
+pub(crate) async fn catch_all_chat(context: &Context) -> String {
+    translated(context, StockMessage::CatchAllChat).await
+}
+
+/// Stock string: `Attachment too large: %1$s`.
+pub(crate) async fn attachment_too_large(context: &Context, bytes: u64) -> String {
+    let size = humanize_bytes_binary(bytes);
+    translated(context, StockMessage::AttachmentTooLarge)
+        .await
+        .replace1(size)
+}
+
+/// Stock string: `⚠️ The clock of %1$s seems to be wrong, ...`.
+pub(crate) async fn sender_clock_wrong(context: &Context, name: impl AsRef<str>) -> String {
+    translated(context, StockMessage::SenderClockWrong)
+        .await
+        .replace1(name)
+}
+
 impl Context {
     /// Set the stock string for the [StockMessage].
     ///
@@ -1323,7 +1439,7 @@ async fn test_quota_exceeding_stock_str() -> anyhow::Result<()> {
     async fn test_partial_download_msg_body() -> anyhow::Result<()> {
         let t = TestContext::new().await;
         let str = partial_download_msg_body(&t, 1024 * 1024).await;
-        assert_eq!(str, "1 MiB message");
+        assert_eq!(str, "1.0 MiB message");
         Ok(())
     }
 
@@ -81,15 +81,9 @@ pub async fn new(
             }
         };
 
-        let mut text = msg.get_summary_text(context).await;
-
-        if text.is_empty() && msg.quoted_text().is_some() {
-            text = stock_str::reply_noun(context).await
-        }
-
         Self {
             prefix,
-            text,
+            text: msg.summary_text(context).await,
             timestamp: msg.get_timestamp(),
             state: msg.state,
         }
@@ -102,8 +96,33 @@ pub fn truncated_text(&self, approx_chars: usize) -> Cow<str> {
 }
 
 impl Message {
+    /// Returns the text summarizing the message, e.g. for the chatlist or notifications.
+    ///
+    /// For system messages such as "Member Bob added by Alice.",
+    /// this is the text describing the change,
+    /// which also replaces the original text of received system messages.
+    /// For other messages, the text is prefixed by the viewtype, e.g. "Image – Hello".
+    pub async fn summary_text(&self, context: &Context) -> String {
+        let text = if self.is_info() {
+            self.text
+                .as_deref()
+                .unwrap_or_default()
+                .split_whitespace()
+                .collect::<Vec<&str>>()
+                .join(" ")
+        } else {
+            self.get_summary_text(context).await
+        };
+
+        if text.is_empty() && self.quoted_text().is_some() {
+            stock_str::reply_noun(context).await
+        } else {
+            text
+        }
+    }
+
     /// Returns a summary text.
-    async fn get_summary_text(&self, context: &Context) -> String {
+    pub(crate) async fn get_summary_text(&self, context: &Context) -> String {
         let mut append_text = true;
         let prefix = match self.viewtype {
             Viewtype::Image => stock_str::image(context).await,
@@ -137,6 +156,7 @@ async fn get_summary_text(&self, context: &Context) -> String {
                 append_text = false;
                 stock_str::videochat_invitation(context).await
             }
+            Viewtype::Vcard => stock_str::contact(context).await,
             Viewtype::Webxdc => {
                 append_text = true;
                 self.get_webxdc_info(context)
@@ -187,6 +207,7 @@ async fn get_summary_text(&self, context: &Context) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::chat::{self, ProtectionStatus};
     use crate::test_utils as test;
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -320,4 +341,42 @@ async fn test_get_summary_text() {
             "Autocrypt Setup Message" // file name is not added for autocrypt setup messages
         );
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_summary_text() -> anyhow::Result<()> {
+        let alice = test::TestContext::new_alice().await;
+        let bob = test::TestContext::new_bob().await;
+
+        let alice_chat = alice.create_chat(&bob).await;
+        let sent = alice.send_text(alice_chat.id, " Hello \n Bob ").await;
+        let msg = bob.recv_msg(&sent).await;
+        assert_eq!(msg.summary_text(&bob).await, "Hello Bob");
+
+        let group_id =
+            chat::create_group_chat(&alice, ProtectionStatus::Unprotected, "Group").await?;
+        let bob_id = alice.add_or_lookup_contact(&bob).await.id;
+        chat::add_contact_to_chat(&alice, group_id, bob_id).await?;
+        let sent = alice.send_text(group_id, "Welcome").await;
+        bob.recv_msg(&sent).await;
+        let fiona = test::TestContext::new_fiona().await;
+        let fiona_id = alice.add_or_lookup_contact(&fiona).await.id;
+        chat::add_contact_to_chat(&alice, group_id, fiona_id).await?;
+        let sent = alice.pop_sent_msg().await;
+
+        let msg = alice.get_last_msg_in(group_id).await;
+        assert!(msg.is_info());
+        assert_eq!(
+            msg.summary_text(&alice).await,
+            "Member fiona@example.net added by me."
+        );
+
+        // Received system messages show the text substituted for the original one.
+        let msg = bob.recv_msg(&sent).await;
+        assert!(msg.is_info());
+        assert_eq!(
+            msg.summary_text(&bob).await,
+            "Member fiona@example.net added by alice@example.org."
+        );
+        Ok(())
+    }
 }
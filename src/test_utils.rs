@@ -26,7 +26,7 @@
 use crate::context::Context;
 use crate::events::{Event, EventType, Events};
 use crate::key::{self, DcKey, KeyPair, KeyPairUse};
-use crate::message::{update_msg_state, Message, MessageState, MsgId, Viewtype};
+use crate::message::{Message, MessageState, MsgId, Viewtype};
 use crate::mimeparser::MimeMessage;
 use crate::receive_imf::receive_imf;
 use crate::tools::EmailAddress;
@@ -403,9 +403,12 @@ pub async fn pop_sent_msg(&self) -> SentMessage {
             .execute("DELETE FROM jobs WHERE id=?;", paramsv![rowid])
             .await
             .expect("failed to remove job");
-        update_msg_state(&self.ctx, msg_id, MessageState::OutDelivered)
-            .await
-            .expect("failed to update message state");
+        if !msg_id.is_unset() {
+            msg_id
+                .update_state(&self.ctx, MessageState::OutDelivered)
+                .await
+                .expect("failed to update message state");
+        }
         SentMessage {
             payload,
             sender_msg_id: msg_id,
@@ -23,7 +23,7 @@
 use crate::constants::{DC_ELLIPSIS, DC_OUTDATED_WARNING_DAYS};
 use crate::context::Context;
 use crate::events::EventType;
-use crate::message::{Message, Viewtype};
+use crate::message::{self, Message, Viewtype};
 use crate::provider::get_provider_update_timestamp;
 use crate::stock_str;
 
@@ -66,6 +66,51 @@ pub fn duration_to_str(duration: Duration) -> String {
     format!("{}h {}m {}s", h, m, s)
 }
 
+/// Formats a number of bytes for display using SI prefixes,
+/// e.g. `823 B`, `1.2 KB`, `45 MB` or `2.3 GB`.
+///
+/// Values below 10 of a unit are shown with one decimal place,
+/// larger values are rounded to whole units.
+/// See [`humanize_bytes_binary`] for a variant using powers of 1024.
+pub fn humanize_bytes(bytes: u64) -> String {
+    humanize(bytes, 1000, &["B", "KB", "MB", "GB", "TB", "PB", "EB"])
+}
+
+/// Formats a number of bytes for display using binary prefixes,
+/// e.g. `823 B`, `1.2 KiB`, `45 MiB` or `2.3 GiB`.
+///
+/// Same as [`humanize_bytes`], but using powers of 1024.
+pub fn humanize_bytes_binary(bytes: u64) -> String {
+    humanize(
+        bytes,
+        1024,
+        &["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"],
+    )
+}
+
+fn humanize(bytes: u64, base: u64, units: &[&str]) -> String {
+    // Integer arithmetic avoids inconsistent rounding of floats at the boundaries.
+    let bytes = u128::from(bytes);
+    let base = u128::from(base);
+    let mut divisor = 1;
+    let mut unit = "";
+    for (i, next_unit) in units.iter().enumerate() {
+        unit = next_unit;
+        // Switch to the next unit only if the value would be rounded up to `base`.
+        if i + 1 == units.len() || (bytes + divisor / 2) / divisor < base {
+            break;
+        }
+        divisor *= base;
+    }
+
+    let tenths = (bytes * 10 + divisor / 2) / divisor;
+    if divisor == 1 || tenths >= 100 {
+        format!("{} {}", (bytes + divisor / 2) / divisor, unit)
+    } else {
+        format!("{}.{} {}", tenths / 10, tenths % 10, unit)
+    }
+}
+
 pub(crate) fn gm2local_offset() -> i64 {
     /* returns the offset that must be _added_ to an UTC/GMT-time to create the localtime.
     the function may return negative values. */
@@ -273,6 +318,92 @@ pub fn get_filemeta(buf: &[u8]) -> Result<(u32, u32), Error> {
     Ok(dimensions)
 }
 
+/// Content types we know a file extension for.
+const CONTENT_TYPE_EXTENSIONS: &[(&str, &str)] = &[
+    ("application/pdf", "pdf"),
+    ("application/webxdc+zip", "xdc"),
+    ("application/zip", "zip"),
+    ("audio/mpeg", "mp3"),
+    ("audio/ogg", "ogg"),
+    ("image/gif", "gif"),
+    ("image/jpeg", "jpg"),
+    ("image/png", "png"),
+    ("image/webp", "webp"),
+    ("text/html", "html"),
+    ("text/plain", "txt"),
+    ("text/vcard", "vcf"),
+    ("video/mp4", "mp4"),
+    ("video/webm", "webm"),
+];
+
+/// Leading bytes of file formats that can be recognized without an extension.
+const CONTENT_TYPE_MAGIC: &[(&[u8], &str)] = &[
+    (b"\xFF\xD8\xFF", "image/jpeg"),
+    (b"\x89PNG", "image/png"),
+    (b"%PDF", "application/pdf"),
+    (UTF8_BOM, "text/plain"),
+];
+
+/// Byte order mark of UTF-8 encoded text.
+pub(crate) const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Byte order marks of UTF-16 encoded text, big endian and little endian.
+const UTF16_BOMS: [&[u8]; 2] = [b"\xFE\xFF", b"\xFF\xFE"];
+
+/// Strips a UTF-8 or UTF-16 byte order mark from the start of `bytes`.
+///
+/// Some clients prepend a byte order mark to text attachments,
+/// which would otherwise be shown as garbage like `ï»¿`.
+pub fn strip_bom(bytes: &[u8]) -> &[u8] {
+    std::iter::once(UTF8_BOM)
+        .chain(UTF16_BOMS)
+        .find_map(|bom| bytes.strip_prefix(bom))
+        .unwrap_or(bytes)
+}
+
+/// Returns the content type of an attachment.
+///
+/// The declared `header_ct` is honoured if it is a specific type known to us,
+/// i.e. not `application/octet-stream`.  Otherwise the type is guessed from
+/// the extension of `filename` and finally sniffed from the first bytes of
+/// `data`.  Falls back to `application/octet-stream`.
+pub fn detect_content_type_from_bytes(
+    header_ct: &str,
+    filename: &str,
+    data: &[u8],
+) -> &'static str {
+    let declared = header_ct
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+    if let Some((ct, _)) = CONTENT_TYPE_EXTENSIONS
+        .iter()
+        .find(|(ct, _)| *ct == declared)
+    {
+        return ct;
+    }
+
+    if let Some((_, ct)) = message::guess_msgtype_from_suffix(Path::new(filename)) {
+        return ct;
+    }
+
+    let head = data.get(..16).unwrap_or(data);
+    CONTENT_TYPE_MAGIC
+        .iter()
+        .find(|(magic, _)| head.starts_with(magic))
+        .map_or("application/octet-stream", |(_, ct)| *ct)
+}
+
+/// Returns the file extension, without leading dot, for a content type.
+pub(crate) fn get_extension_for_content_type(content_type: &str) -> Option<&'static str> {
+    CONTENT_TYPE_EXTENSIONS
+        .iter()
+        .find(|(ct, _)| *ct == content_type)
+        .map(|(_, ext)| *ext)
+}
+
 /// Expand paths relative to $BLOBDIR into absolute paths.
 ///
 /// If `path` starts with "$BLOBDIR", replaces it with the blobdir path.
@@ -512,6 +643,63 @@ fn from_str(input: &str) -> Result<EmailAddress> {
     }
 }
 
+/// Maximum length of an email address, see RFC 5321, section 4.5.3.1.3.
+const MAX_EMAIL_ADDR_LEN: usize = 254;
+
+/// Maximum length of a domain label, see RFC 1035, section 2.3.4.
+const MAX_DOMAIN_LABEL_LEN: usize = 63;
+
+/// Checks if `addr` is a valid email address.
+///
+/// Unlike parsing an [`EmailAddress`], this requires exactly one `@`,
+/// a local part without empty dot-separated atoms
+/// and a domain with at least two labels, e.g. `example.org`.
+/// Whitespace, control characters and `<`, `>` are not allowed,
+/// so quoted local parts and domain literals are rejected.
+/// Internationalized domains may be given as UTF-8 or as punycode.
+pub fn is_valid_email_addr(addr: &str) -> bool {
+    if addr.is_empty() || addr.len() > MAX_EMAIL_ADDR_LEN {
+        return false;
+    }
+    if addr
+        .chars()
+        .any(|c| c.is_whitespace() || c.is_control() || c == '<' || c == '>')
+    {
+        return false;
+    }
+    let (local, domain) = match addr.split_once('@') {
+        Some(parts) => parts,
+        None => return false,
+    };
+    !domain.contains('@')
+        && local.split('.').all(|atom| !atom.is_empty())
+        && is_valid_domain(domain)
+}
+
+fn is_valid_domain(domain: &str) -> bool {
+    let labels: Vec<&str> = domain.split('.').collect();
+    labels.len() >= 2 && labels.iter().all(|label| is_valid_domain_label(label))
+}
+
+fn is_valid_domain_label(label: &str) -> bool {
+    if label.is_empty()
+        || label.len() > MAX_DOMAIN_LABEL_LEN
+        || label.starts_with('-')
+        || label.ends_with('-')
+    {
+        return false;
+    }
+    let is_punycode = label
+        .get(..4)
+        .map_or(false, |prefix| prefix.eq_ignore_ascii_case("xn--"));
+    if is_punycode {
+        // ASCII-encoded label of an internationalized domain name.
+        label.len() > 4 && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    } else {
+        label.chars().all(|c| c.is_alphanumeric() || c == '-')
+    }
+}
+
 impl rusqlite::types::ToSql for EmailAddress {
     fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput> {
         let val = rusqlite::types::Value::Text(self.to_string());
@@ -811,6 +999,91 @@ fn test_create_outgoing_rfc724_mid() {
         );
     }
 
+    #[test]
+    fn test_is_valid_email_addr() {
+        let valid = [
+            "user@domain.tld",
+            "u@d.tt",
+            "u@d.t",
+            "first.last@example.org",
+            "first.last+tag@example.org",
+            "user+mailbox/department=shipping@example.com",
+            "!#$%&'*+-/=?^_`.{|}~@example.com",
+            "customer/department=shipping@example.com",
+            "$A12345@example.com",
+            "_somename@example.com",
+            "x@example.com",
+            "1234567890@example.com",
+            "user@sub.domain.example.co.uk",
+            "user@my-domain.example",
+            "user@123.example",
+            "User.Name@Example.ORG",
+            "user%example.org@example.net",
+            "user-@example.org",
+            "-user@example.org",
+            "user@xn--mnchen-3ya.de",
+            "user@XN--MNCHEN-3YA.DE",
+            "user@münchen.de",
+            "пользователь@пример.рф",
+            "用户@例子.广告",
+            "δοκιμή@παράδειγμα.δοκιμή",
+            "ñoño@example.es",
+            "user@a.b",
+            "alice@d-e-f.example",
+            "very.common@example.com",
+            "disposable.style.email.with+symbol@example.com",
+        ];
+        let long_local = "a".repeat(242);
+        let long_label = "a".repeat(64);
+        let invalid = [
+            "",
+            "uuu",
+            "dd.tt",
+            "@d.tt",
+            "u@",
+            "u@d",
+            "tt.dd@uu",
+            "user@localhost",
+            "u@d.",
+            "u@.tt",
+            "u@d..tt",
+            "a@b@example.com",
+            "<da@d.tt",
+            "sk <@d.tt>",
+            "as@sd.de>",
+            "ask dkl@dd.tt",
+            "user@exa mple.com",
+            "user\t@example.com",
+            "user\n@example.com",
+            "user@example.com\r",
+            "user\u{0}@example.com",
+            ".user@example.com",
+            "user.@example.com",
+            "us..er@example.com",
+            "\"john doe\"@example.com",
+            "user@[192.168.0.1]",
+            "user@-example.com",
+            "user@example-.com",
+            "user@exam_ple.com",
+            "user@xn--.com",
+        ];
+        assert_eq!(valid.len(), 30);
+        assert_eq!(invalid.len(), 30);
+        for addr in &valid {
+            assert!(is_valid_email_addr(addr), "{:?} should be valid", addr);
+        }
+        for addr in &invalid {
+            assert!(!is_valid_email_addr(addr), "{:?} should be invalid", addr);
+        }
+
+        assert!(is_valid_email_addr(&format!("{}@example.com", long_local)));
+        assert!(!is_valid_email_addr(&format!(
+            "{}a@example.com",
+            long_local
+        )));
+        assert!(!is_valid_email_addr(&format!("user@{}.com", long_label)));
+    }
+
     #[test]
     fn test_emailaddress_parse() {
         assert_eq!("".parse::<EmailAddress>().is_ok(), false);
@@ -977,6 +1250,49 @@ fn test_duration_to_str() {
         );
     }
 
+    #[test]
+    fn test_humanize_bytes() {
+        for (bytes, expected) in [
+            (0, "0 B"),
+            (823, "823 B"),
+            (999, "999 B"),
+            (1000, "1.0 KB"),
+            (1049, "1.0 KB"),
+            (1050, "1.1 KB"),
+            (1234, "1.2 KB"),
+            (9949, "9.9 KB"),
+            (9950, "10 KB"),
+            (999_499, "999 KB"),
+            (999_500, "1.0 MB"),
+            (45_000_000, "45 MB"),
+            (2_300_000_000, "2.3 GB"),
+            (999_999_999_999, "1.0 TB"),
+            (u64::MAX, "18 EB"),
+        ] {
+            assert_eq!(humanize_bytes(bytes), expected, "{} bytes", bytes);
+        }
+    }
+
+    #[test]
+    fn test_humanize_bytes_binary() {
+        for (bytes, expected) in [
+            (0, "0 B"),
+            (1000, "1000 B"),
+            (1023, "1023 B"),
+            (1024, "1.0 KiB"),
+            (1536, "1.5 KiB"),
+            (10188, "9.9 KiB"),
+            (10189, "10 KiB"),
+            (1_048_063, "1023 KiB"),
+            (1_048_064, "1.0 MiB"),
+            (45 * 1024 * 1024, "45 MiB"),
+            (2_469_606_195, "2.3 GiB"),
+            (u64::MAX, "16 EiB"),
+        ] {
+            assert_eq!(humanize_bytes_binary(bytes), expected, "{} bytes", bytes);
+        }
+    }
+
     #[test]
     fn test_get_filemeta() {
         let (w, h) = get_filemeta(test_utils::AVATAR_900x900_BYTES).unwrap();
@@ -994,6 +1310,62 @@ fn test_get_filemeta() {
         assert_eq!(h, 50);
     }
 
+    #[test]
+    fn test_detect_content_type_from_bytes() {
+        let jpeg = include_bytes!("../test-data/image/avatar1000x1000.jpg");
+
+        // A specific declared type is honoured.
+        assert_eq!(
+            detect_content_type_from_bytes("image/png; name=x", "foo.jpg", jpeg),
+            "image/png"
+        );
+
+        // Generic declared type falls back to the extension.
+        assert_eq!(
+            detect_content_type_from_bytes("application/octet-stream", "foo.pdf", jpeg),
+            "application/pdf"
+        );
+
+        // No usable header or extension, sniff the data.
+        assert_eq!(
+            detect_content_type_from_bytes("application/octet-stream", "foo", jpeg),
+            "image/jpeg"
+        );
+        assert_eq!(
+            detect_content_type_from_bytes("", "foo", b"\x89PNG\r\n\x1a\n"),
+            "image/png"
+        );
+        assert_eq!(
+            detect_content_type_from_bytes("", "", b"%PDF-1.4"),
+            "application/pdf"
+        );
+        assert_eq!(
+            detect_content_type_from_bytes("", "foo", b"hello"),
+            "application/octet-stream"
+        );
+        assert_eq!(
+            detect_content_type_from_bytes("", "foo", b""),
+            "application/octet-stream"
+        );
+        assert_eq!(
+            detect_content_type_from_bytes("", "foo", b"\xEF\xBB\xBFhello"),
+            "text/plain"
+        );
+    }
+
+    #[test]
+    fn test_strip_bom() {
+        assert_eq!(strip_bom(b"\xEF\xBB\xBFhello"), b"hello");
+        assert_eq!(strip_bom(b"\xFE\xFF\x00h\x00i"), b"\x00h\x00i");
+        assert_eq!(strip_bom(b"\xFF\xFEh\x00i\x00"), b"h\x00i\x00");
+
+        // Only a single leading BOM is stripped.
+        assert_eq!(strip_bom(b"\xEF\xBB\xBF\xEF\xBB\xBF"), b"\xEF\xBB\xBF");
+        assert_eq!(strip_bom(b"hello\xEF\xBB\xBF"), b"hello\xEF\xBB\xBF");
+        assert_eq!(strip_bom(b"\xEF\xBB"), b"\xEF\xBB");
+        assert_eq!(strip_bom(b""), b"");
+    }
+
     #[test]
     fn test_improve_single_line_input() {
         assert_eq!(improve_single_line_input("Hi\naiae "), "Hi aiae");
@@ -3,7 +3,7 @@
 use std::convert::TryFrom;
 use std::path::PathBuf;
 
-use anyhow::{anyhow, bail, ensure, format_err, Result};
+use anyhow::{anyhow, bail, ensure, format_err, Context as _, Result};
 use deltachat_derive::FromSql;
 use lettre_email::mime;
 use lettre_email::PartBuilder;
@@ -387,6 +387,78 @@ pub async fn send_webxdc_status_update(
         Ok(())
     }
 
+    /// Sends an ephemeral status update for an webxdc instance.
+    ///
+    /// Unlike [`Context::send_webxdc_status_update`], the update is sent immediately
+    /// and neither stored on this device nor by the recipients,
+    /// who only get a `WebxdcEphemeralUpdate` event with the payload.
+    /// This is meant for transient state, e.g. cursor positions, that is not needed later.
+    /// Only the `payload` of the update is used.
+    pub async fn send_webxdc_ephemeral_update(
+        &self,
+        instance_msg_id: MsgId,
+        update_str: &str,
+    ) -> Result<()> {
+        let instance = Message::load_from_db(self, instance_msg_id).await?;
+        ensure!(
+            instance.viewtype == Viewtype::Webxdc,
+            "send_webxdc_ephemeral_update: is no webxdc message"
+        );
+        ensure!(
+            !matches!(
+                instance.state,
+                MessageState::Undefined | MessageState::OutPreparing | MessageState::OutDraft
+            ),
+            "send_webxdc_ephemeral_update: instance is not sent yet"
+        );
+
+        let chat = Chat::load_from_db(self, instance.chat_id).await?;
+        ensure!(chat.can_send(self).await?, "cannot send to {}", chat.id);
+
+        let item: StatusUpdateItem = serde_json::from_str(update_str.trim())
+            .context("send_webxdc_ephemeral_update: no valid update item")?;
+        let item = StatusUpdateItem {
+            payload: item.payload,
+            info: None,
+            document: None,
+            summary: None,
+        };
+        let json = format!(r#"{{"updates":[{}]}}"#, serde_json::to_string(&item)?);
+
+        let mut status_update = Message {
+            chat_id: instance.chat_id,
+            viewtype: Viewtype::Text,
+            hidden: true,
+            ..Default::default()
+        };
+        status_update
+            .param
+            .set_cmd(SystemMessage::WebxdcStatusUpdate);
+        status_update.param.set(Param::Arg, json);
+        status_update.param.set_int(Param::Arg2, 1);
+        status_update.set_quote(self, Some(&instance)).await?;
+        status_update.param.remove(Param::GuaranteeE2ee);
+        let row_id = chat::prepare_send_msg(self, instance.chat_id, &mut status_update).await?;
+        let msg_id = status_update.id;
+
+        // Detach the queued SMTP job from the message and trash the message at once,
+        // so that the SMTP loop never sees a job of a trashed message and cancels it.
+        // The trashed row only remains to recognize the update if it comes back via BCC-self.
+        self.sql
+            .transaction(move |transaction| {
+                transaction.execute(
+                    "UPDATE smtp SET msg_id=? WHERE msg_id=?",
+                    paramsv![MsgId::new(0), msg_id],
+                )?;
+                msg_id.trash_in_transaction(transaction)
+            })
+            .await?;
+        if row_id.is_some() {
+            self.interrupt_smtp(InterruptInfo::new(false)).await;
+        }
+        Ok(())
+    }
+
     /// Pops one record of queued webxdc status updates.
     /// This function exists to make the sqlite statement testable.
     async fn pop_smtp_status_update(
@@ -472,20 +544,8 @@ pub(crate) async fn receive_status_update(
         msg_id: MsgId,
         json: &str,
     ) -> Result<()> {
-        let msg = Message::load_from_db(self, msg_id).await?;
-        let (timestamp, mut instance, can_info_msg) = if msg.viewtype == Viewtype::Webxdc {
-            (msg.timestamp_sort, msg, false)
-        } else if let Some(parent) = msg.parent(self).await? {
-            if parent.viewtype == Viewtype::Webxdc {
-                (msg.timestamp_sort, parent, true)
-            } else if parent.download_state() != DownloadState::Done {
-                (msg.timestamp_sort, parent, false)
-            } else {
-                bail!("receive_status_update: message is not the child of a webxdc message.")
-            }
-        } else {
-            bail!("receive_status_update: status message has no parent.")
-        };
+        let (timestamp, mut instance, can_info_msg) =
+            self.get_status_update_instance(msg_id).await?;
 
         let updates: StatusUpdates = serde_json::from_str(json)?;
         for update_item in updates.updates {
@@ -502,6 +562,50 @@ pub(crate) async fn receive_status_update(
         Ok(())
     }
 
+    /// Receives status updates sent by [`Context::send_webxdc_ephemeral_update`].
+    ///
+    /// The updates are not stored, only a `WebxdcEphemeralUpdate` event is emitted for each of them.
+    pub(crate) async fn receive_ephemeral_status_update(
+        &self,
+        msg_id: MsgId,
+        json: &str,
+    ) -> Result<()> {
+        let (_, instance, _) = self.get_status_update_instance(msg_id).await?;
+        if instance.viewtype != Viewtype::Webxdc {
+            return Ok(());
+        }
+
+        let updates: StatusUpdates = serde_json::from_str(json)?;
+        for update_item in updates.updates {
+            self.emit_event(EventType::WebxdcEphemeralUpdate {
+                msg_id: instance.id,
+                payload: serde_json::to_string(&update_item.payload)?,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Returns the timestamp, the instance and whether info messages may be added
+    /// for the message `msg_id` carrying status updates.
+    async fn get_status_update_instance(&self, msg_id: MsgId) -> Result<(i64, Message, bool)> {
+        let msg = Message::load_from_db(self, msg_id).await?;
+        let res = if msg.viewtype == Viewtype::Webxdc {
+            (msg.timestamp_sort, msg, false)
+        } else if let Some(parent) = msg.parent(self).await? {
+            if parent.viewtype == Viewtype::Webxdc {
+                (msg.timestamp_sort, parent, true)
+            } else if parent.download_state() != DownloadState::Done {
+                (msg.timestamp_sort, parent, false)
+            } else {
+                bail!("receive_status_update: message is not the child of a webxdc message.")
+            }
+        } else {
+            bail!("receive_status_update: status message has no parent.")
+        };
+        Ok(res)
+    }
+
     /// Returns status updates as an JSON-array, ready to be consumed by a webxdc.
     ///
     /// Example: `[{"serial":1, "max_serial":3, "payload":"any update data"},
@@ -1436,6 +1540,73 @@ async fn test_send_webxdc_status_update() -> Result<()> {
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_send_webxdc_ephemeral_update() -> Result<()> {
+        let alice = TestContext::new_alice().await;
+        let bob = TestContext::new_bob().await;
+        let alice_chat = alice.create_chat(&bob).await;
+        let alice_instance = send_webxdc_instance(&alice, alice_chat.id).await?;
+        let bob_instance = bob.recv_msg(&alice.pop_sent_msg().await).await;
+
+        alice
+            .send_webxdc_ephemeral_update(alice_instance.id, r#"{"payload": {"x": 1}}"#)
+            .await?;
+        let sent = alice.pop_sent_msg().await;
+        assert!(sent.payload().contains("report-type=status-update"));
+        assert!(sent.sender_msg_id.is_unset());
+        assert_eq!(
+            alice
+                .get_webxdc_status_updates(alice_instance.id, StatusUpdateSerial(0))
+                .await?,
+            "[]"
+        );
+        assert_eq!(
+            alice
+                .sql
+                .count(
+                    "SELECT COUNT(*) FROM msgs WHERE chat_id=?",
+                    paramsv![alice_chat.id]
+                )
+                .await?,
+            1
+        );
+
+        bob.recv_msg_opt(&sent).await;
+        let event = bob
+            .evtracker
+            .get_matching(|evt| matches!(evt, EventType::WebxdcEphemeralUpdate { .. }))
+            .await;
+        assert_eq!(
+            event,
+            EventType::WebxdcEphemeralUpdate {
+                msg_id: bob_instance.id,
+                payload: r#"{"x":1}"#.to_string(),
+            }
+        );
+        assert_eq!(
+            bob.get_webxdc_status_updates(bob_instance.id, StatusUpdateSerial(0))
+                .await?,
+            "[]"
+        );
+        assert_eq!(bob_instance.chat_id.get_msg_cnt(&bob).await?, 1);
+
+        // Drafts cannot get ephemeral updates.
+        let mut draft = create_webxdc_instance(
+            &alice,
+            "minimal.xdc",
+            include_bytes!("../test-data/webxdc/minimal.xdc"),
+        )
+        .await?;
+        alice_chat.id.set_draft(&alice, Some(&mut draft)).await?;
+        let draft = alice_chat.id.get_draft(&alice).await?.unwrap();
+        assert!(alice
+            .send_webxdc_ephemeral_update(draft.id, r#"{"payload": 1}"#)
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_render_webxdc_status_update_object() -> Result<()> {
         let t = TestContext::new_alice().await;